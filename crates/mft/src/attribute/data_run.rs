@@ -53,7 +53,8 @@ pub fn decode_data_runs(runs: &[u8]) -> Option<Vec<DataRun>> {
         let run_type;
         if offset_size != 0 {
             // offset_size of 0 == sparse cluster
-            if let Some(last) = out.last() {
+            // Offsets are relative to the previous allocated run, sparse runs have no LCN.
+            if let Some(last) = out.iter().rev().find(|r| r.run_type == RunType::Standard) {
                 let rel_offset = decode_run_svalue(&mut it, offset_size)?;
                 abs_offset = (last.lcn_offset as i64 + rel_offset) as u64;
             } else {
@@ -89,4 +90,33 @@ mod tests {
         );
         assert_eq!(decode_run_svalue(&mut vec![0xE0].into_iter(), 2), None);
     }
+
+    #[test]
+    fn test_decode_data_runs_after_sparse_run() {
+        // 16 clusters at 0x1000, 4 sparse clusters, then 8 clusters at 0x800 (-0x800 from the
+        // first run, not from the sparse one)
+        let runs = [
+            0x21, 0x10, 0x00, 0x10, 0x01, 0x04, 0x21, 0x08, 0x00, 0xF8, 0x00,
+        ];
+        assert_eq!(
+            decode_data_runs(&runs),
+            Some(vec![
+                DataRun {
+                    lcn_offset: 0x1000,
+                    lcn_length: 16,
+                    run_type:   RunType::Standard,
+                },
+                DataRun {
+                    lcn_offset: 0,
+                    lcn_length: 4,
+                    run_type:   RunType::Sparse,
+                },
+                DataRun {
+                    lcn_offset: 0x800,
+                    lcn_length: 8,
+                    run_type:   RunType::Standard,
+                },
+            ])
+        );
+    }
 }
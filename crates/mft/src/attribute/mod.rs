@@ -53,9 +53,17 @@ impl MftAttributeContent {
         resident: &ResidentHeader,
     ) -> Result<Self> {
         match header.type_code {
-            MftAttributeType::StandardInformation => Ok(MftAttributeContent::AttrX10(
-                StandardInfoAttr::from_reader(stream)?,
-            )),
+            MftAttributeType::StandardInformation => {
+                // Pre-NTFS 3.0 volumes write a shorter attribute without the owner, security id,
+                // quota and USN. Zero-pad it instead of reading those from the next attribute.
+                let content_size = resident.data_size as usize;
+                let mut attribute_buffer = vec![0; content_size.max(x10::STANDARD_INFO_V3_SIZE)];
+                stream.read_exact(&mut attribute_buffer[..content_size])?;
+
+                Ok(MftAttributeContent::AttrX10(StandardInfoAttr::from_reader(
+                    &mut Cursor::new(attribute_buffer),
+                )?))
+            },
             MftAttributeType::AttributeList => {
                 // An attribute list is a buffer of attribute entries which are varying sizes if
                 // the attributes contain names. Thus, we must know when to stop reading. To
@@ -245,6 +253,71 @@ bitflags! {
 
 impl_serialize_for_bitflags! {FileAttributeFlags}
 
+impl FileAttributeFlags {
+    /// Letters used by [`FileAttributeFlags::to_compact_string`], in column order.
+    const COMPACT_LETTERS: [(char, FileAttributeFlags); 8] = [
+        ('R', FileAttributeFlags::FILE_ATTRIBUTE_READONLY),
+        ('H', FileAttributeFlags::FILE_ATTRIBUTE_HIDDEN),
+        ('S', FileAttributeFlags::FILE_ATTRIBUTE_SYSTEM),
+        ('A', FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE),
+        ('C', FileAttributeFlags::FILE_ATTRIBUTE_COMPRESSED),
+        ('E', FileAttributeFlags::FILE_ATTRIBUTE_ENCRYPTED),
+        ('P', FileAttributeFlags::FILE_ATTRIBUTE_SPARSE_FILE),
+        ('L', FileAttributeFlags::FILE_ATTRIBUTE_REPARSE_POINT),
+    ];
+
+    pub fn is_read_only(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_READONLY)
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_HIDDEN)
+    }
+
+    pub fn is_system(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_SYSTEM)
+    }
+
+    pub fn is_archive(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE)
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_COMPRESSED)
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_ENCRYPTED)
+    }
+
+    pub fn is_sparse(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_SPARSE_FILE)
+    }
+
+    pub fn is_reparse_point(&self) -> bool {
+        self.contains(FileAttributeFlags::FILE_ATTRIBUTE_REPARSE_POINT)
+    }
+
+    /// Renders the common attributes as a fixed-width string in `attrib` style, one column per
+    /// flag and `-` when unset: `R`ead-only, `H`idden, `S`ystem, `A`rchive, `C`ompressed,
+    /// `E`ncrypted, s`P`arse and reparse point (`L`ink).
+    ///
+    /// ```
+    /// use mft::attribute::FileAttributeFlags;
+    ///
+    /// let flags = FileAttributeFlags::FILE_ATTRIBUTE_HIDDEN
+    ///     | FileAttributeFlags::FILE_ATTRIBUTE_SYSTEM
+    ///     | FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE;
+    /// assert_eq!(flags.to_compact_string(), "-HSA----");
+    /// ```
+    pub fn to_compact_string(&self) -> String {
+        Self::COMPACT_LETTERS
+            .iter()
+            .map(|(letter, flag)| if self.contains(*flag) { *letter } else { '-' })
+            .collect()
+    }
+}
+
 bitflags! {
     #[derive(Default, Clone, Copy, Debug, Eq, PartialEq)]
     pub struct AttributeDataFlags: u16 {
@@ -11,6 +11,10 @@ use crate::{
     err::{Error, Result},
 };
 
+/// Size of the `$STANDARD_INFORMATION` attribute written since NTFS 3.0. Older volumes store a
+/// 48-byte attribute that ends after `class_id`.
+pub const STANDARD_INFO_V3_SIZE: usize = 72;
+
 #[derive(Serialize, Debug, Clone)]
 pub struct StandardInfoAttr {
     pub created:      DateTime<Utc>,
@@ -19,12 +23,17 @@ pub struct StandardInfoAttr {
     pub accessed:     DateTime<Utc>,
     /// DOS File Permissions
     pub file_flags:   FileAttributeFlags,
+    /// Maximum number of file versions, 0 when versioning is disabled.
     pub max_version:  u32,
     pub version:      u32,
     pub class_id:     u32,
+    /// Index into the `$Quota` owner table. The fields below are 0 on pre-NTFS 3.0 volumes.
     pub owner_id:     u32,
+    /// Key of the file's security descriptor in the `$Secure` `$SII` index.
     pub security_id:  u32,
+    /// Bytes charged to the owner's quota.
     pub quota:        u64,
+    /// Update sequence number of the file's last `$UsnJrnl` record.
     pub usn:          u64,
 }
 
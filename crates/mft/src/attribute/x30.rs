@@ -13,6 +13,9 @@ use crate::{
     err::{Error, Result},
 };
 
+/// The namespace of a `$FILE_NAME` attribute. A file with a long name usually carries two
+/// attributes: a `Win32` long name and a `DOS` 8.3 short name (or a single `Win32AndDos` one
+/// when the long name already fits 8.3).
 #[derive(FromPrimitive, Serialize, Clone, Debug, PartialOrd, PartialEq)]
 #[repr(u8)]
 pub enum FileNamespace {
@@ -22,6 +25,13 @@ pub enum FileNamespace {
     Win32AndDos = 3,
 }
 
+impl FileNamespace {
+    /// Whether this is an 8.3 short name that duplicates a separate long name.
+    pub fn is_short_name_only(&self) -> bool {
+        *self == FileNamespace::DOS
+    }
+}
+
 #[derive(Serialize, Clone, Debug, PartialEq)]
 pub struct FileNameAttr {
     pub parent:        MftReference,
@@ -29,8 +39,8 @@ pub struct FileNameAttr {
     pub modified:      DateTime<Utc>,
     pub mft_modified:  DateTime<Utc>,
     pub accessed:      DateTime<Utc>,
-    pub logical_size:  u64,
     pub physical_size: u64,
+    pub logical_size:  u64,
     pub flags:         FileAttributeFlags,
     pub reparse_value: u32,
     pub name_length:   u8,
@@ -64,8 +74,8 @@ impl FileNameAttr {
     /// assert_eq!(attribute.modified.timestamp(), 1370144608);
     /// assert_eq!(attribute.mft_modified.timestamp(), 1370144608);
     /// assert_eq!(attribute.accessed.timestamp(), 1370144608);
-    /// assert_eq!(attribute.logical_size, 67108864);
     /// assert_eq!(attribute.physical_size, 67108864);
+    /// assert_eq!(attribute.logical_size, 67108864);
     /// assert_eq!(attribute.flags.bits(), 6);
     /// assert_eq!(attribute.reparse_value, 0);
     /// assert_eq!(attribute.name_length, 8);
@@ -89,8 +99,9 @@ impl FileNameAttr {
             .map_err(Error::failed_to_read_windows_time)?
             .to_datetime();
 
-        let logical_size = stream.read_u64::<LittleEndian>()?;
+        // the allocated size comes before the real size
         let physical_size = stream.read_u64::<LittleEndian>()?;
+        let logical_size = stream.read_u64::<LittleEndian>()?;
         let flags = FileAttributeFlags::from_bits_truncate(stream.read_u32::<LittleEndian>()?);
         let reparse_value = stream.read_u32::<LittleEndian>()?;
         let name_length = stream.read_u8()?;
@@ -112,8 +123,8 @@ impl FileNameAttr {
             modified,
             mft_modified,
             accessed,
-            logical_size,
             physical_size,
+            logical_size,
             flags,
             reparse_value,
             name_length,
@@ -122,3 +133,38 @@ impl FileNameAttr {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::{FileNameAttr, FileNamespace};
+    use crate::tests::builder::{MftRecordBuilder, Timestamps};
+
+    #[test]
+    fn test_is_short_name_only() {
+        let times = Timestamps::all(Utc.with_ymd_and_hms(2021, 3, 4, 5, 6, 7).unwrap());
+        let entry = MftRecordBuilder::new(42)
+            .file_name((5, 5), "PROGRA~1", FileNamespace::DOS, times)
+            .file_name((5, 5), "Program Files", FileNamespace::Win32, times)
+            .build_entry();
+
+        let names: Vec<FileNameAttr> = entry
+            .iter_attributes()
+            .filter_map(Result::ok)
+            .filter_map(|a| a.data.into_file_name())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert_eq!(names[0].name, "PROGRA~1");
+        assert!(names[0].namespace.is_short_name_only());
+        assert!(!names[1].namespace.is_short_name_only());
+        assert!(!FileNamespace::Win32AndDos.is_short_name_only());
+        assert!(!FileNamespace::POSIX.is_short_name_only());
+
+        // the long name wins over the DOS one
+        assert_eq!(
+            entry.find_best_name_attribute().unwrap().name,
+            "Program Files"
+        );
+    }
+}
@@ -10,15 +10,19 @@ use std::{
     str::FromStr,
 };
 
-use anyhow::{Context, Error, Result, anyhow};
+use anyhow::{Context, Result, anyhow};
 use clap::{Arg, ArgAction, ArgMatches};
 use dialoguer::Confirm;
 use indoc::indoc;
 use log::Level;
 use mft::{
-    MftEntry, attribute::MftAttributeType, csv::FlatMftEntryWithName, entry::ZERO_HEADER,
-    mft::MftParser,
+    MftEntry,
+    attribute::MftAttributeType,
+    csv::{FlatMftEntryWithName, ResidentDigests, ResidentHashes},
+    err::Error,
+    mft::{MftParser, PathPrefix},
 };
+use serde::Serialize;
 
 #[derive(Debug, PartialOrd, PartialEq)]
 #[allow(clippy::upper_case_acronyms)]
@@ -48,7 +52,7 @@ impl Ranges {
 }
 
 impl FromStr for Ranges {
-    type Err = Error;
+    type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
         let mut ranges = vec![];
@@ -129,6 +133,15 @@ mod tests {
     }
 }
 
+/// JSON output of an entry with its resident `$DATA` digests appended.
+#[derive(Serialize)]
+struct JsonEntryWithDigests<'a> {
+    #[serde(flatten)]
+    entry:   &'a MftEntry,
+    #[serde(flatten)]
+    digests: ResidentDigests,
+}
+
 struct MftDump {
     filepath:            PathBuf,
     // We use an option here to be able to move the output out of mftdump from a mutable reference.
@@ -137,6 +150,8 @@ struct MftDump {
     verbosity_level:     Option<Level>,
     output_format:       OutputFormat,
     ranges:              Option<Ranges>,
+    path_prefix:         Option<PathPrefix>,
+    hashes:              ResidentHashes,
 }
 
 impl MftDump {
@@ -192,6 +207,23 @@ impl MftDump {
             None => None,
         };
 
+        let path_prefix = matches
+            .get_one::<String>("path-prefix")
+            .map(|p| PathPrefix::new(p));
+
+        let hashes = matches
+            .get_many::<String>("hash")
+            .into_iter()
+            .flatten()
+            .fold(ResidentHashes::empty(), |acc, h| {
+                acc | match h.as_str() {
+                    "md5" => ResidentHashes::MD5,
+                    "sha1" => ResidentHashes::SHA1,
+                    "sha256" => ResidentHashes::SHA256,
+                    _ => unreachable!("Validated with clap possible values"),
+                }
+            });
+
         Ok(MftDump {
             filepath: PathBuf::from(input),
             output,
@@ -199,6 +231,8 @@ impl MftDump {
             verbosity_level,
             output_format,
             ranges,
+            path_prefix,
+            hashes,
         })
     }
 
@@ -298,13 +332,23 @@ impl MftDump {
             None => Box::new(0..number_of_entries as usize) as Box<dyn Iterator<Item = usize>>,
         };
 
+        let mut baad_records = 0_u64;
+        let mut unused_records = 0_u64;
         for i in entries {
             let entry = parser.get_entry(i as u64);
 
             let entry = match entry {
-                Ok(entry) => match &entry.header.signature {
-                    ZERO_HEADER => continue,
-                    _ => entry,
+                Ok(entry) if entry.header.is_unused() => {
+                    unused_records += 1;
+                    continue;
+                },
+                Ok(entry) => entry,
+                Err(error @ Error::TruncatedEntry { entry_number, .. }) => {
+                    eprintln!(
+                        "{error} - the image ends here, last intact entry is {}",
+                        entry_number.saturating_sub(1)
+                    );
+                    continue;
                 },
                 Err(error) => {
                     eprintln!("{error}");
@@ -312,6 +356,23 @@ impl MftDump {
                 },
             };
 
+            if entry.header.is_baad() {
+                baad_records += 1;
+            }
+
+            // Resolving the path is cheap after the first few entries, since the parser caches
+            // the paths of parent directories.
+            if let Some(prefix) = &self.path_prefix {
+                match parser.entry_matches_prefix(&entry, prefix) {
+                    Ok(true) => {},
+                    Ok(false) => continue,
+                    Err(error) => {
+                        eprintln!("Skipping entry {i}, its path could not be resolved: {error}");
+                        continue;
+                    },
+                }
+            }
+
             if let Some(data_streams_dir) = &self.data_streams_output
                 && let Ok(Some(path)) = parser.get_full_path_for_entry(&entry)
             {
@@ -369,6 +430,16 @@ impl MftDump {
             }
         }
 
+        if unused_records > 0 {
+            log::info!("Skipped {unused_records} unused (zeroed) record(s)");
+        }
+        if baad_records > 0 {
+            eprintln!(
+                "Found {baad_records} BAAD record(s), NTFS marked these as corrupt (see the \
+                 `signature` field)"
+            );
+        }
+
         Ok(())
     }
 
@@ -386,30 +457,41 @@ impl MftDump {
     }
 
     pub fn print_json_entry(&mut self, entry: &MftEntry) -> Result<()> {
+        let json_str = if self.hashes.is_empty() {
+            self.to_json(entry)
+        } else {
+            self.to_json(&JsonEntryWithDigests {
+                entry,
+                digests: ResidentDigests::from_entry(entry, self.hashes),
+            })
+        };
+
         let out = self
             .output
             .as_mut()
             .expect("CSV Flow cannot occur, so `Mftdump` should still Own `output`");
 
-        let json_str = if self.output_format == OutputFormat::JSON {
-            serde_json::to_vec_pretty(&entry).expect("It should be valid UTF-8")
-        } else {
-            serde_json::to_vec(&entry).expect("It should be valid UTF-8")
-        };
-
         out.write_all(&json_str)?;
         out.write_all(b"\n")?;
 
         Ok(())
     }
 
+    fn to_json(&self, value: &impl Serialize) -> Vec<u8> {
+        if self.output_format == OutputFormat::JSON {
+            serde_json::to_vec_pretty(value).expect("It should be valid UTF-8")
+        } else {
+            serde_json::to_vec(value).expect("It should be valid UTF-8")
+        }
+    }
+
     pub fn print_csv_entry<W: Write>(
         &self,
         entry: &MftEntry,
         parser: &mut MftParser<impl Read + Seek>,
         writer: &mut csv::Writer<W>,
     ) -> Result<()> {
-        let flat_entry = FlatMftEntryWithName::from_entry(entry, parser);
+        let flat_entry = FlatMftEntryWithName::from_entry_with_hashes(entry, parser, self.hashes);
 
         writer.serialize(flat_entry)?;
 
@@ -470,6 +552,29 @@ fn main() -> Result<()> {
                      entries 1-15, and 30"
                 )),
         )
+        .arg(
+            Arg::new("path-prefix")
+                .long("path-prefix")
+                .action(ArgAction::Set)
+                .help(indoc!(
+                    "Only dumps entries whose resolved full path starts with the given prefix \
+                     (case-insensitive), for example `\\Users\\victim\\`"
+                )),
+        )
+        .arg(
+            Arg::new("hash")
+                .long("hash")
+                .action(ArgAction::Append)
+                .value_delimiter(',')
+                .value_parser(clap::builder::PossibleValuesParser::new([
+                    "md5", "sha1", "sha256",
+                ]))
+                .help(indoc!(
+                    "Hashes the resident unnamed $DATA stream of each entry with the given \
+                     algorithm(s), for example `--hash md5,sha256`. Entries whose data is not \
+                     resident get empty hashes."
+                )),
+        )
         .arg(
             Arg::new("output-target")
                 .long("output")
@@ -3,12 +3,16 @@ use std::{
     path::PathBuf,
 };
 
+use bitflags::bitflags;
 use chrono::{DateTime, Utc};
 use serde::Serialize;
+use sha2::Digest;
 
 use crate::{
     MftAttribute, MftEntry, MftParser,
-    attribute::{FileAttributeFlags, MftAttributeType, header::ResidentialHeader},
+    attribute::{
+        FileAttributeFlags, MftAttributeType, header::ResidentialHeader, x30::FileNamespace,
+    },
     entry::EntryFlags,
 };
 
@@ -33,7 +37,13 @@ pub struct FlatMftEntryWithName {
 
     /// The size of the file, if available, from the X80 attribute.
     /// Will be 0 if no $DATA attribute is found.
-    pub file_size: u64,
+    pub file_size:     u64,
+    /// The real size of the unnamed `$DATA` stream (falling back to the `$FILE_NAME` copy).
+    /// Empty for directories.
+    pub logical_size:  Option<u64>,
+    /// The space allocated on disk for the unnamed `$DATA` stream (falling back to the
+    /// `$FILE_NAME` copy). Empty for directories.
+    pub physical_size: Option<u64>,
 
     /// Indicates whether the record is a directory.
     pub is_a_directory: bool,
@@ -45,22 +55,101 @@ pub struct FlatMftEntryWithName {
 
     /// All of these fields are present for entries that have an 0x10 attribute.
     pub standard_info_flags:         Option<FileAttributeFlags>,
+    /// `standard_info_flags` as `RHSACEPL` letters, see [`FileAttributeFlags::to_compact_string`].
+    pub standard_info_attributes:    Option<String>,
     pub standard_info_last_modified: Option<DateTime<Utc>>,
     pub standard_info_last_access:   Option<DateTime<Utc>>,
     pub standard_info_created:       Option<DateTime<Utc>>,
+    pub standard_info_max_version:   Option<u32>,
+    pub standard_info_class_id:      Option<u32>,
+    pub standard_info_owner_id:      Option<u32>,
+    /// Joins against the `$Secure` stream.
+    pub standard_info_security_id:   Option<u32>,
+    /// Links the entry to its last `$UsnJrnl` record.
+    pub standard_info_usn:           Option<u64>,
     /// All of these fields are present for entries that have an 0x30 attribute.
     pub file_name_flags:             Option<FileAttributeFlags>,
+    pub file_name_attributes:        Option<String>,
     pub file_name_last_modified:     Option<DateTime<Utc>>,
     pub file_name_last_access:       Option<DateTime<Utc>>,
     pub file_name_created:           Option<DateTime<Utc>>,
+    /// `DOS` marks an 8.3 short name, `Win32`/`Win32AndDos` a long name.
+    pub file_name_namespace:         Option<FileNamespace>,
 
     pub full_path: PathBuf,
+
+    /// Digests of the unnamed `$DATA` stream, filled only for the algorithms selected in
+    /// [`ResidentHashes`] and only when the stream is resident.
+    pub resident_md5:    Option<String>,
+    pub resident_sha1:   Option<String>,
+    pub resident_sha256: Option<String>,
+}
+
+bitflags! {
+    /// Digests to compute over an entry's resident unnamed `$DATA` bytes.
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+    pub struct ResidentHashes: u8 {
+        const MD5    = 0x01;
+        const SHA1   = 0x02;
+        const SHA256 = 0x04;
+    }
+}
+
+/// Lowercase hex digests of an entry's resident unnamed `$DATA` stream.
+///
+/// Non-resident streams leave every digest empty, since their content isn't stored in the MFT.
+#[derive(Serialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct ResidentDigests {
+    pub resident_md5:    Option<String>,
+    pub resident_sha1:   Option<String>,
+    pub resident_sha256: Option<String>,
+}
+
+impl ResidentDigests {
+    pub fn from_entry(entry: &MftEntry, hashes: ResidentHashes) -> ResidentDigests {
+        if hashes.is_empty() {
+            return ResidentDigests::default();
+        }
+
+        let Some(data) = entry
+            .iter_attributes_matching(Some(vec![MftAttributeType::DATA]))
+            .filter_map(Result::ok)
+            .find(|a| a.header.name.is_empty())
+            .filter(|a| matches!(a.header.residential_header, ResidentialHeader::Resident(_)))
+            .and_then(|a| a.data.into_data())
+        else {
+            return ResidentDigests::default();
+        };
+
+        let bytes = data.data();
+        ResidentDigests {
+            resident_md5:    hashes
+                .contains(ResidentHashes::MD5)
+                .then(|| format!("{:x}", md5::Md5::digest(bytes))),
+            resident_sha1:   hashes
+                .contains(ResidentHashes::SHA1)
+                .then(|| format!("{:x}", sha1::Sha1::digest(bytes))),
+            resident_sha256: hashes
+                .contains(ResidentHashes::SHA256)
+                .then(|| format!("{:x}", sha2::Sha256::digest(bytes))),
+        }
+    }
 }
 
 impl FlatMftEntryWithName {
     pub fn from_entry(
         entry: &MftEntry,
         parser: &mut MftParser<impl Read + Seek>,
+    ) -> FlatMftEntryWithName {
+        Self::from_entry_with_hashes(entry, parser, ResidentHashes::empty())
+    }
+
+    /// Like [`FlatMftEntryWithName::from_entry`], also filling the `Resident*` digest columns
+    /// selected in `hashes`.
+    pub fn from_entry_with_hashes(
+        entry: &MftEntry,
+        parser: &mut MftParser<impl Read + Seek>,
+        hashes: ResidentHashes,
     ) -> FlatMftEntryWithName {
         let entry_attributes: Vec<MftAttribute> = entry
             .iter_attributes_matching(Some(vec![
@@ -85,6 +174,29 @@ impl FlatMftEntryWithName {
             .iter()
             .find(|a| a.header.type_code == MftAttributeType::DATA);
 
+        // Directories have no data stream of their own; following Sleuthkit, leave both sizes
+        // empty instead of reporting the index allocation.
+        let (logical_size, physical_size) = if entry.is_dir() {
+            (None, None)
+        } else {
+            match entry_attributes
+                .iter()
+                .find(|a| a.header.type_code == MftAttributeType::DATA && a.header.name.is_empty())
+                .map(|a| &a.header.residential_header)
+            {
+                Some(ResidentialHeader::Resident(r)) => {
+                    (Some(u64::from(r.data_size)), Some(u64::from(r.data_size)))
+                },
+                Some(ResidentialHeader::NonResident(nr)) => {
+                    (Some(nr.file_size), Some(nr.allocated_length))
+                },
+                None => (
+                    file_name.as_ref().map(|f| f.logical_size),
+                    file_name.as_ref().map(|f| f.physical_size),
+                ),
+            }
+        };
+
         let file_size = match data_attr {
             Some(attr) => match &attr.header.residential_header {
                 ResidentialHeader::Resident(r) => u64::from(r.data_size),
@@ -97,10 +209,11 @@ impl FlatMftEntryWithName {
             .iter()
             .any(|a| a.header.type_code == MftAttributeType::DATA && !a.header.name.is_empty());
 
+        let digests = ResidentDigests::from_entry(entry, hashes);
+
         FlatMftEntryWithName {
             entry_id: entry.header.record_number,
-            signature: String::from_utf8(entry.header.signature.to_ascii_uppercase())
-                .expect("It should be either FILE or BAAD (valid utf-8)"),
+            signature: entry.header.signature_str().into_owned(),
             sequence: entry.header.sequence,
             hard_link_count: entry.header.hard_link_count,
             flags: entry.header.flags,
@@ -112,18 +225,33 @@ impl FlatMftEntryWithName {
             is_deleted: !entry.header.flags.contains(EntryFlags::ALLOCATED),
             has_alternate_data_streams: has_ads,
             standard_info_flags: standard_info.as_ref().map(|i| i.file_flags),
+            standard_info_attributes: standard_info
+                .as_ref()
+                .map(|i| i.file_flags.to_compact_string()),
             standard_info_last_modified: standard_info.as_ref().map(|i| i.modified),
             standard_info_last_access: standard_info.as_ref().map(|i| i.accessed),
             standard_info_created: standard_info.as_ref().map(|i| i.created),
+            standard_info_max_version: standard_info.as_ref().map(|i| i.max_version),
+            standard_info_class_id: standard_info.as_ref().map(|i| i.class_id),
+            standard_info_owner_id: standard_info.as_ref().map(|i| i.owner_id),
+            standard_info_security_id: standard_info.as_ref().map(|i| i.security_id),
+            standard_info_usn: standard_info.as_ref().map(|i| i.usn),
             file_name_flags: file_name.as_ref().map(|i| i.flags),
+            file_name_attributes: file_name.as_ref().map(|i| i.flags.to_compact_string()),
             file_name_last_modified: file_name.as_ref().map(|i| i.modified),
             file_name_last_access: file_name.as_ref().map(|i| i.accessed),
             file_name_created: file_name.as_ref().map(|i| i.created),
+            file_name_namespace: file_name.as_ref().map(|i| i.namespace.clone()),
             file_size,
+            logical_size,
+            physical_size,
             full_path: parser
                 .get_full_path_for_entry(entry)
                 .expect("I/O Err")
                 .unwrap_or_default(),
+            resident_md5: digests.resident_md5,
+            resident_sha1: digests.resident_sha1,
+            resident_sha256: digests.resident_sha256,
         }
     }
 }
@@ -1,4 +1,7 @@
-use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::{
+    borrow::Cow,
+    io::{Cursor, Read, Seek, SeekFrom},
+};
 
 use bitflags::bitflags;
 use byteorder::{LittleEndian, ReadBytesExt};
@@ -56,6 +59,7 @@ pub struct EntryHeader {
     /// MULTI_SECTOR_HEADER
     /// The signature. This value is a convenience to the user.
     /// This is either "BAAD", "FILE", or "\x00\x00\x00\x00"
+    #[serde(serialize_with = "serialize_signature")]
     pub signature: [u8; 4],
     /// The offset to the update sequence array, from the start of this structure.
     /// The update sequence array must end before the last USHORT value in the first sector.
@@ -95,8 +99,19 @@ bitflags! {
 
 impl_serialize_for_bitflags! {EntryFlags}
 
+fn serialize_signature<S>(
+    signature: &[u8; 4],
+    serializer: S,
+) -> ::std::result::Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    serializer.serialize_str(&String::from_utf8_lossy(signature))
+}
+
 impl EntryHeader {
-    /// Reads an entry from a stream, will error if the entry is empty (zeroes)
+    /// Reads an entry from a stream. A zeroed signature marks a slot that was never used, for
+    /// which an otherwise empty header is returned.
     /// Since the entry id is not present in the header, it should be provided by the caller.
     pub fn from_reader<R: Read>(reader: &mut R, entry_id: u64) -> Result<EntryHeader> {
         let mut signature = [0; 4];
@@ -111,7 +126,10 @@ impl EntryHeader {
         }
 
         if signature == *ZERO_HEADER {
-            return Ok(Self::zero());
+            return Ok(EntryHeader {
+                record_number: entry_id,
+                ..Self::zero()
+            });
         }
 
         let usa_offset = reader.read_u16::<LittleEndian>()?;
@@ -150,6 +168,22 @@ impl EntryHeader {
         self.signature == *FILE_HEADER
     }
 
+    /// Whether NTFS marked this record `BAAD`, which it does when a multi-sector transfer
+    /// fails its fixup check (usually found and stamped by chkdsk).
+    pub fn is_baad(&self) -> bool {
+        self.signature == *BAAD_HEADER
+    }
+
+    /// Whether this slot was never used, i.e. its signature is zeroed.
+    pub fn is_unused(&self) -> bool {
+        self.signature == *ZERO_HEADER
+    }
+
+    /// The signature as text, e.g. `FILE` or `BAAD`.
+    pub fn signature_str(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.signature)
+    }
+
     pub fn zero() -> Self {
         EntryHeader {
             signature: *ZERO_HEADER,
@@ -357,6 +391,14 @@ impl MftEntry {
                 // Check if the header is resident, and if it is, read the attribute content.
                 let attribute_content = match header.residential_header {
                     ResidentialHeader::Resident(ref resident) => {
+                        // The value is quadword aligned, so after a name it doesn't necessarily
+                        // start where the header ends.
+                        if let Err(e) = cursor.seek(SeekFrom::Start(
+                            header.start_offset + u64::from(resident.data_offset),
+                        )) {
+                            return Some(Err(e.into()));
+                        }
+
                         match MftAttributeContent::from_stream_resident(
                             &mut cursor,
                             &header,
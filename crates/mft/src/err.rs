@@ -23,6 +23,35 @@ pub enum Error {
         bad_sig
     )]
     InvalidEntrySignature { bad_sig: Vec<u8> },
+    #[error(
+        "Entry {} is out of range, the MFT only holds {} entries",
+        entry_number,
+        entry_count
+    )]
+    EntryOutOfRange {
+        entry_number: u64,
+        entry_count:  u64,
+    },
+    #[error(
+        "Entry {} is truncated: expected {} bytes but only {} were available",
+        entry_number,
+        expected,
+        got
+    )]
+    TruncatedEntry {
+        entry_number: u64,
+        expected:     usize,
+        got:          usize,
+    },
+    #[error(
+        "Entry {} is not a FILE record (signature: {:x?})",
+        entry_number,
+        signature
+    )]
+    NotAFileRecord {
+        entry_number: u64,
+        signature:    Vec<u8>,
+    },
     #[error("Unknown `AttributeType`: {:04X}", attribute_type)]
     UnknownAttributeType { attribute_type: u32 },
     #[error("Unknown collation type {}", collation_type)]
@@ -1,6 +1,6 @@
 use std::{
     fs::{self, File},
-    io::{BufReader, Cursor, Read, Seek, SeekFrom},
+    io::{BufReader, Cursor, ErrorKind, Read, Seek, SeekFrom},
     num::NonZeroUsize,
     path::{Path, PathBuf},
 };
@@ -14,6 +14,64 @@ use crate::{
     err::{Error, Result},
 };
 
+/// A record yielded by [`MftParser::iter_records`].
+#[derive(Debug, Clone)]
+pub enum MftRecord {
+    /// An intact `FILE` record.
+    File(MftEntry),
+    /// A record NTFS stamped `BAAD` after finding it corrupt. Its attributes are parsed on a
+    /// best-effort basis, since no fixups were applied.
+    Baad(MftEntry),
+    /// A zeroed slot that was never used. Only the header's `record_number` is meaningful.
+    Unused(MftEntry),
+}
+
+impl MftRecord {
+    pub fn entry(&self) -> &MftEntry {
+        match self {
+            MftRecord::File(entry) | MftRecord::Baad(entry) | MftRecord::Unused(entry) => entry,
+        }
+    }
+
+    pub fn into_entry(self) -> MftEntry {
+        match self {
+            MftRecord::File(entry) | MftRecord::Baad(entry) | MftRecord::Unused(entry) => entry,
+        }
+    }
+
+    pub fn is_unused(&self) -> bool {
+        matches!(self, MftRecord::Unused(_))
+    }
+
+    pub fn is_baad(&self) -> bool {
+        matches!(self, MftRecord::Baad(_))
+    }
+}
+
+/// A prefix to filter entries by their resolved full path. Matching ignores case, treats `\`
+/// and `/` alike and ignores leading separators, so `\Users\victim\` matches
+/// `Users/Victim/NTUSER.DAT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathPrefix(String);
+
+impl PathPrefix {
+    pub fn new(prefix: &str) -> Self {
+        PathPrefix(Self::normalized(prefix))
+    }
+
+    pub fn matches(&self, path: &str) -> bool {
+        Self::normalized(path).starts_with(&self.0)
+    }
+
+    fn normalized(path: &str) -> String {
+        path.replace('\\', "/")
+            .trim_start_matches('/')
+            .to_lowercase()
+    }
+}
+
+const DEFAULT_ENTRY_SIZE: u32 = 1024;
+
 pub struct MftParser<T: Read + Seek> {
     data:          T,
     /// Entry size is present in the volume header, but this is not available to us.
@@ -51,6 +109,12 @@ impl<T: Read + Seek> MftParser<T> {
     pub fn from_read_seek(mut data: T, size: Option<u64>) -> Result<Self> {
         // We use the first entry to guess the entry size for all the other records.
         let first_entry = EntryHeader::from_reader(&mut data, 0)?;
+        // A zeroed first slot (e.g. a carved or partially wiped MFT) carries no size, so fall
+        // back to the size every modern NTFS volume uses.
+        let entry_size = match first_entry.total_entry_size {
+            0 => DEFAULT_ENTRY_SIZE,
+            size => size,
+        };
 
         let size = match size {
             Some(sz) => sz,
@@ -61,14 +125,16 @@ impl<T: Read + Seek> MftParser<T> {
 
         Ok(Self {
             data,
-            entry_size: first_entry.total_entry_size,
+            entry_size,
             size,
             entries_cache: LruCache::new(NonZeroUsize::new(1000).expect("1000 > 0")),
         })
     }
 
+    /// Number of entries in the MFT. A trailing partial record (truncated image) is counted, so
+    /// iterating surfaces it as [`Error::TruncatedEntry`] instead of silently dropping it.
     pub fn get_entry_count(&self) -> u64 {
-        self.size / u64::from(self.entry_size)
+        self.size.div_ceil(u64::from(self.entry_size))
     }
 
     /// Reads an entry from the MFT by entry number.
@@ -79,11 +145,61 @@ impl<T: Read + Seek> MftParser<T> {
             .seek(SeekFrom::Start(entry_number * u64::from(self.entry_size)))?;
         let mut entry_buffer = vec![0; self.entry_size as usize];
 
-        self.data.read_exact(&mut entry_buffer)?;
+        // Read until the buffer is full or the image ends, so a short read can be reported
+        // together with the entry it cut off.
+        let mut got = 0;
+        while got < entry_buffer.len() {
+            match self.data.read(&mut entry_buffer[got..]) {
+                Ok(0) => break,
+                Ok(n) => got += n,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+        if got < entry_buffer.len() {
+            return Err(Error::TruncatedEntry {
+                entry_number,
+                expected: entry_buffer.len(),
+                got,
+            });
+        }
 
         MftEntry::from_buffer(entry_buffer, entry_number)
     }
 
+    /// Reads the entry at `entry_number`, requiring it to be an intact `FILE` record.
+    ///
+    /// Unlike [`MftParser::get_entry`], zeroed, `BAAD` and garbled records are all rejected with
+    /// [`Error::NotAFileRecord`], which makes this the right call when following parent or base
+    /// references.
+    pub fn get_file_record(&mut self, entry_number: u64) -> Result<MftEntry> {
+        let entry_count = self.get_entry_count();
+        if entry_number >= entry_count {
+            return Err(Error::EntryOutOfRange {
+                entry_number,
+                entry_count,
+            });
+        }
+
+        let entry = match self.get_entry(entry_number) {
+            Err(Error::InvalidEntrySignature { bad_sig }) => {
+                return Err(Error::NotAFileRecord {
+                    entry_number,
+                    signature: bad_sig,
+                });
+            },
+            res => res?,
+        };
+        if !entry.header.is_valid() {
+            return Err(Error::NotAFileRecord {
+                entry_number,
+                signature: entry.header.signature.to_vec(),
+            });
+        }
+
+        Ok(entry)
+    }
+
     /// Iterates over all the entries in the MFT.
     pub fn iter_entries(&mut self) -> impl Iterator<Item = Result<MftEntry>> + '_ {
         let total_entries = self.get_entry_count();
@@ -91,6 +207,36 @@ impl<T: Read + Seek> MftParser<T> {
         (0..total_entries).map(move |i| self.get_entry(i))
     }
 
+    /// Iterates over all the records of the MFT, classifying each one. Zeroed slots come back as
+    /// [`MftRecord::Unused`] rather than errors, so a sparse MFT can be listed in full.
+    ///
+    /// `BAAD` records are yielded as [`MftRecord::Baad`] when `include_baad` is set, and as
+    /// [`Error::NotAFileRecord`] otherwise.
+    pub fn iter_records(
+        &mut self,
+        include_baad: bool,
+    ) -> impl Iterator<Item = Result<MftRecord>> + '_ {
+        let total_entries = self.get_entry_count();
+
+        (0..total_entries).filter_map(move |i| match self.get_entry(i) {
+            Ok(entry) if entry.header.is_valid() => Some(Ok(MftRecord::File(entry))),
+            Ok(entry) if entry.header.is_baad() => Some(if include_baad {
+                Ok(MftRecord::Baad(entry))
+            } else {
+                Err(Error::NotAFileRecord {
+                    entry_number: i,
+                    signature:    entry.header.signature.to_vec(),
+                })
+            }),
+            Ok(entry) if entry.header.is_unused() => Some(Ok(MftRecord::Unused(entry))),
+            Ok(entry) => Some(Err(Error::NotAFileRecord {
+                entry_number: i,
+                signature:    entry.header.signature.to_vec(),
+            })),
+            Err(e) => Some(Err(e)),
+        })
+    }
+
     fn inner_get_entry(&mut self, parent_entry_id: u64, entry_name: Option<&str>) -> PathBuf {
         let cached_entry = self.entries_cache.get(&parent_entry_id);
 
@@ -122,6 +268,13 @@ impl<T: Read + Seek> MftParser<T> {
         }
     }
 
+    /// Whether the resolved full path of `entry` starts with `prefix`. Parent paths are cached,
+    /// so filtering a whole MFT this way stays cheap after the first few entries.
+    pub fn entry_matches_prefix(&mut self, entry: &MftEntry, prefix: &PathPrefix) -> Result<bool> {
+        let path = self.get_full_path_for_entry(entry)?.unwrap_or_default();
+        Ok(prefix.matches(&path.to_string_lossy()))
+    }
+
     /// Gets the full path for an entry.
     /// Caches computations.
     pub fn get_full_path_for_entry(&mut self, entry: &MftEntry) -> Result<Option<PathBuf>> {
@@ -167,7 +320,18 @@ impl<T: Read + Seek> MftParser<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::{MftEntry, MftParser, tests::fixtures::mft_sample};
+    use chrono::{TimeZone, Utc};
+
+    use super::{MftRecord, PathPrefix};
+    use crate::{
+        MftEntry, MftParser,
+        attribute::x30::FileNamespace,
+        err::Error,
+        tests::{
+            builder::{MftRecordBuilder, Timestamps, parser_from_records},
+            fixtures::mft_sample,
+        },
+    };
 
     // entrypoint for clion profiler.
     #[test]
@@ -215,4 +379,119 @@ mod tests {
         let e = parser.get_entry(5).unwrap();
         parser.get_full_path_for_entry(&e).unwrap();
     }
+
+    #[test]
+    fn test_truncated_entry_is_reported() {
+        let data = std::fs::read(mft_sample()).unwrap();
+        let mut parser = MftParser::from_buffer(data[..1024 * 3 + 100].to_vec()).unwrap();
+
+        assert_eq!(parser.get_entry_count(), 4);
+        assert!(matches!(
+            parser.get_entry(3),
+            Err(Error::TruncatedEntry {
+                entry_number: 3,
+                expected:     1024,
+                got:          100,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_get_file_record() {
+        let sample = mft_sample();
+        let mut parser = MftParser::from_path(sample).unwrap();
+
+        let e = parser.get_file_record(5).unwrap();
+        assert_eq!(e.header.record_number, 5);
+
+        let count = parser.get_entry_count();
+        assert!(matches!(
+            parser.get_file_record(count),
+            Err(Error::EntryOutOfRange { entry_number, entry_count })
+                if entry_number == count && entry_count == count
+        ));
+    }
+
+    #[test]
+    fn test_get_file_record_rejects_other_signatures() {
+        let mut parser = parser_from_records([
+            (0, MftRecordBuilder::new(0).build()),
+            (1, MftRecordBuilder::new(1).signature(b"BAAD").build()),
+            (2, MftRecordBuilder::new(2).signature(b"RCRD").build()),
+            (4, MftRecordBuilder::new(4).build()),
+        ]);
+
+        assert!(parser.get_file_record(4).is_ok());
+        // slot 3 is a zeroed gap
+        for (entry, expected) in [(1, *b"BAAD"), (2, *b"RCRD"), (3, [0; 4])] {
+            match parser.get_file_record(entry) {
+                Err(Error::NotAFileRecord {
+                    entry_number,
+                    signature,
+                }) => {
+                    assert_eq!(entry_number, entry);
+                    assert_eq!(signature, expected);
+                },
+                other => panic!("entry {entry}: expected NotAFileRecord, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_entry_matches_prefix() {
+        let times = Timestamps::all(Utc.with_ymd_and_hms(2022, 1, 2, 3, 4, 5).unwrap());
+        let named = |record: u64, parent: u64, name: &str, directory: bool| {
+            let builder = MftRecordBuilder::new(record);
+            let builder = if directory {
+                builder.directory()
+            } else {
+                builder
+            };
+            builder
+                .file_name((parent, 1), name, FileNamespace::Win32, times)
+                .build()
+        };
+        let mut parser = parser_from_records([
+            (0, MftRecordBuilder::new(0).build()),
+            (6, named(6, 5, "Users", true)),
+            (7, named(7, 6, "Victim", true)),
+            (8, named(8, 7, "NTUSER.DAT", false)),
+            (9, named(9, 5, "Windows", true)),
+        ]);
+
+        let prefix = PathPrefix::new(r"\Users\victim\");
+        let ntuser = parser.get_entry(8).unwrap();
+        let windows = parser.get_entry(9).unwrap();
+        assert!(parser.entry_matches_prefix(&ntuser, &prefix).unwrap());
+        assert!(!parser.entry_matches_prefix(&windows, &prefix).unwrap());
+
+        assert!(PathPrefix::new("/users").matches(r"Users\Public"));
+        assert!(!PathPrefix::new("users/victim").matches("Users/Public"));
+    }
+
+    #[test]
+    fn test_iter_records_reports_unused_slots() {
+        let mut parser = parser_from_records([
+            (0, MftRecordBuilder::new(0).build()),
+            (3, MftRecordBuilder::new(3).build()),
+        ]);
+
+        let records: Vec<MftRecord> = parser.iter_records(false).map(Result::unwrap).collect();
+        let unused: Vec<u64> = records
+            .iter()
+            .filter(|r| r.is_unused())
+            .map(|r| r.entry().header.record_number)
+            .collect();
+        assert_eq!(records.len(), 4);
+        assert_eq!(unused, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_parser_accepts_a_zeroed_first_slot() {
+        let mut parser = parser_from_records([(1, MftRecordBuilder::new(1).build())]);
+
+        assert_eq!(parser.get_entry_count(), 2);
+        assert!(parser.get_entry(0).unwrap().header.is_unused());
+        assert!(parser.get_entry(1).unwrap().header.is_valid());
+    }
 }
@@ -0,0 +1,579 @@
+//! Assembles synthetic MFT records for tests, so parsing features can be exercised on targeted
+//! entries (alternate data streams, timestomped `$STANDARD_INFORMATION`, non-resident `$DATA`,
+//! ...) without hunting for them in a real `$MFT`.
+//!
+//! Records are laid out the way NTFS 3.1 writes them: a 48-byte header followed by the update
+//! sequence array, quadword-aligned attributes and the `$END` marker, with the fixups applied
+//! to the last two bytes of every 512-byte stride.
+
+use std::io::Cursor;
+
+use chrono::{DateTime, Utc};
+
+use crate::{
+    MftEntry, MftParser,
+    attribute::{FileAttributeFlags, MftAttributeType, x30::FileNamespace},
+    entry::{EntryFlags, FILE_HEADER},
+};
+
+const ENTRY_SIZE: usize = 1024;
+const SECTOR_SIZE: usize = 512;
+const USA_OFFSET: usize = 0x30;
+const UPDATE_SEQUENCE: [u8; 2] = [0x01, 0x00];
+const END_MARKER: u32 = 0xFFFF_FFFF;
+
+/// The four MACB timestamps shared by `$STANDARD_INFORMATION` and `$FILE_NAME`.
+#[derive(Debug, Clone, Copy)]
+pub struct Timestamps {
+    pub created:      DateTime<Utc>,
+    pub modified:     DateTime<Utc>,
+    pub mft_modified: DateTime<Utc>,
+    pub accessed:     DateTime<Utc>,
+}
+
+impl Timestamps {
+    pub fn all(at: DateTime<Utc>) -> Timestamps {
+        Timestamps {
+            created:      at,
+            modified:     at,
+            mft_modified: at,
+            accessed:     at,
+        }
+    }
+}
+
+/// A cluster run of a non-resident attribute, `None` for a sparse run.
+pub type Run = (u64, Option<u64>);
+
+pub struct MftRecordBuilder {
+    signature:      [u8; 4],
+    record_number:  u64,
+    sequence:       u16,
+    flags:          EntryFlags,
+    base_reference: (u64, u16),
+    attributes:     Vec<Vec<u8>>,
+}
+
+impl MftRecordBuilder {
+    /// An allocated, empty `FILE` record with sequence number 1.
+    pub fn new(record_number: u64) -> MftRecordBuilder {
+        MftRecordBuilder {
+            signature: *FILE_HEADER,
+            record_number,
+            sequence: 1,
+            flags: EntryFlags::ALLOCATED,
+            base_reference: (0, 0),
+            attributes: vec![],
+        }
+    }
+
+    pub fn signature(mut self, signature: &[u8; 4]) -> Self {
+        self.signature = *signature;
+        self
+    }
+
+    pub fn sequence(mut self, sequence: u16) -> Self {
+        self.sequence = sequence;
+        self
+    }
+
+    pub fn directory(mut self) -> Self {
+        self.flags |= EntryFlags::INDEX_PRESENT;
+        self
+    }
+
+    pub fn deleted(mut self) -> Self {
+        self.flags.remove(EntryFlags::ALLOCATED);
+        self
+    }
+
+    /// Marks this record as an extension of the given base record.
+    pub fn base_reference(mut self, entry: u64, sequence: u16) -> Self {
+        self.base_reference = (entry, sequence);
+        self
+    }
+
+    pub fn standard_info(self, times: Timestamps, flags: FileAttributeFlags) -> Self {
+        let mut value = Vec::with_capacity(72);
+        push_timestamps(&mut value, &times);
+        value.extend_from_slice(&flags.bits().to_le_bytes());
+        // max_version, version, class_id, owner_id
+        value.extend_from_slice(&[0; 16]);
+        value.extend_from_slice(&0x100_u32.to_le_bytes()); // security_id
+        value.extend_from_slice(&0_u64.to_le_bytes()); // quota
+        value.extend_from_slice(&0x1000_u64.to_le_bytes()); // usn
+
+        self.resident(MftAttributeType::StandardInformation, "", &value)
+    }
+
+    /// Adds a `$FILE_NAME` in `parent` (entry, sequence). Both sizes are left at 0, like NTFS
+    /// does for most names that aren't kept in an index.
+    pub fn file_name(
+        self,
+        parent: (u64, u16),
+        name: &str,
+        namespace: FileNamespace,
+        times: Timestamps,
+    ) -> Self {
+        let utf16: Vec<u16> = name.encode_utf16().collect();
+        let mut value = Vec::with_capacity(66 + utf16.len() * 2);
+        value.extend_from_slice(&mft_reference(parent.0, parent.1).to_le_bytes());
+        push_timestamps(&mut value, &times);
+        value.extend_from_slice(&0_u64.to_le_bytes()); // allocated size
+        value.extend_from_slice(&0_u64.to_le_bytes()); // real size
+        let flags = if self.flags.contains(EntryFlags::INDEX_PRESENT) {
+            FileAttributeFlags::FILE_ATTRIBUTE_IS_DIRECTORY
+        } else {
+            FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE
+        };
+        value.extend_from_slice(&flags.bits().to_le_bytes());
+        value.extend_from_slice(&0_u32.to_le_bytes()); // reparse value
+        value.push(utf16.len() as u8);
+        value.push(namespace as u8);
+        utf16
+            .iter()
+            .for_each(|c| value.extend_from_slice(&c.to_le_bytes()));
+
+        self.resident(MftAttributeType::FileName, "", &value)
+    }
+
+    /// Adds a resident `$DATA` stream, `name` is empty for the default stream.
+    pub fn resident_data(self, name: &str, data: &[u8]) -> Self {
+        self.resident(MftAttributeType::DATA, name, data)
+    }
+
+    /// Adds a non-resident `$DATA` stream of `file_size` bytes stored in `runs`.
+    pub fn non_resident_data(
+        mut self,
+        name: &str,
+        runs: &[Run],
+        file_size: u64,
+        cluster_size: u64,
+    ) -> Self {
+        let clusters: u64 = runs.iter().map(|(length, _)| length).sum();
+        let name = utf16_bytes(name);
+        let name_offset = 0x40;
+        let runs_offset = align8(name_offset + name.len());
+        let mut run_list = encode_runs(runs);
+        run_list.resize(align8(run_list.len()), 0);
+
+        let mut record = self.attribute_header(
+            MftAttributeType::DATA,
+            1,
+            runs_offset + run_list.len(),
+            &name,
+            name_offset,
+        );
+        record.extend_from_slice(&0_u64.to_le_bytes()); // first VCN
+        record.extend_from_slice(&clusters.saturating_sub(1).to_le_bytes()); // last VCN
+        record.extend_from_slice(&(runs_offset as u16).to_le_bytes());
+        record.extend_from_slice(&0_u16.to_le_bytes()); // compression unit
+        record.extend_from_slice(&0_u32.to_le_bytes()); // padding
+        record.extend_from_slice(&(clusters * cluster_size).to_le_bytes());
+        record.extend_from_slice(&file_size.to_le_bytes());
+        record.extend_from_slice(&file_size.to_le_bytes()); // valid data length
+        record.extend_from_slice(&name);
+        record.resize(runs_offset, 0);
+        record.extend_from_slice(&run_list);
+
+        self.attributes.push(record);
+        self
+    }
+
+    /// The raw record as it's stored on disk, with the fixups applied.
+    pub fn build(self) -> Vec<u8> {
+        let usa_size = ENTRY_SIZE / SECTOR_SIZE + 1;
+        let first_attribute_offset = align8(USA_OFFSET + usa_size * 2);
+
+        let attributes_size: usize = self.attributes.iter().map(Vec::len).sum();
+        let used_size = align8(first_attribute_offset + attributes_size + 4);
+        assert!(
+            used_size <= ENTRY_SIZE,
+            "attributes don't fit in a {ENTRY_SIZE} byte record"
+        );
+
+        let mut buffer = vec![0_u8; ENTRY_SIZE];
+        let mut offset = first_attribute_offset;
+        for attribute in &self.attributes {
+            buffer[offset..offset + attribute.len()].copy_from_slice(attribute);
+            offset += attribute.len();
+        }
+        buffer[offset..offset + 4].copy_from_slice(&END_MARKER.to_le_bytes());
+
+        buffer[0..4].copy_from_slice(&self.signature);
+        buffer[4..6].copy_from_slice(&(USA_OFFSET as u16).to_le_bytes());
+        buffer[6..8].copy_from_slice(&(usa_size as u16).to_le_bytes());
+        // 8..16 is the $LogFile sequence number
+        buffer[16..18].copy_from_slice(&self.sequence.to_le_bytes());
+        buffer[18..20].copy_from_slice(&1_u16.to_le_bytes()); // hard link count
+        buffer[20..22].copy_from_slice(&(first_attribute_offset as u16).to_le_bytes());
+        buffer[22..24].copy_from_slice(&self.flags.bits().to_le_bytes());
+        buffer[24..28].copy_from_slice(&(used_size as u32).to_le_bytes());
+        buffer[28..32].copy_from_slice(&(ENTRY_SIZE as u32).to_le_bytes());
+        let (base_entry, base_sequence) = self.base_reference;
+        buffer[32..40].copy_from_slice(&mft_reference(base_entry, base_sequence).to_le_bytes());
+        buffer[40..42].copy_from_slice(&(self.attributes.len() as u16).to_le_bytes());
+        buffer[44..48].copy_from_slice(&(self.record_number as u32).to_le_bytes());
+
+        // Move the last two bytes of every sector into the update sequence array, and stamp the
+        // update sequence number in their place.
+        buffer[USA_OFFSET..USA_OFFSET + 2].copy_from_slice(&UPDATE_SEQUENCE);
+        for sector in 0..ENTRY_SIZE / SECTOR_SIZE {
+            let end = (sector + 1) * SECTOR_SIZE;
+            let fixup = USA_OFFSET + 2 + sector * 2;
+            let original = [buffer[end - 2], buffer[end - 1]];
+            buffer[fixup..fixup + 2].copy_from_slice(&original);
+            buffer[end - 2..end].copy_from_slice(&UPDATE_SEQUENCE);
+        }
+
+        buffer
+    }
+
+    pub fn build_entry(self) -> MftEntry {
+        let record_number = self.record_number;
+        MftEntry::from_buffer(self.build(), record_number).expect("built a valid record")
+    }
+
+    fn resident(mut self, type_code: MftAttributeType, name: &str, value: &[u8]) -> Self {
+        let name = utf16_bytes(name);
+        let name_offset = 0x18;
+        let value_offset = align8(name_offset + name.len());
+        let length = align8(value_offset + value.len());
+
+        let mut record = self.attribute_header(type_code, 0, length, &name, name_offset);
+        record.extend_from_slice(&(value.len() as u32).to_le_bytes());
+        record.extend_from_slice(&(value_offset as u16).to_le_bytes());
+        record.push(0); // indexed flag
+        record.push(0); // padding
+        record.extend_from_slice(&name);
+        record.resize(value_offset, 0);
+        record.extend_from_slice(value);
+        record.resize(length, 0);
+
+        self.attributes.push(record);
+        self
+    }
+
+    /// The 16 bytes shared by resident and non-resident attribute records.
+    fn attribute_header(
+        &self,
+        type_code: MftAttributeType,
+        form_code: u8,
+        length: usize,
+        name: &[u8],
+        name_offset: usize,
+    ) -> Vec<u8> {
+        let mut header = Vec::with_capacity(length);
+        header.extend_from_slice(&(type_code as u32).to_le_bytes());
+        header.extend_from_slice(&(length as u32).to_le_bytes());
+        header.push(form_code);
+        header.push((name.len() / 2) as u8);
+        header.extend_from_slice(&(name_offset as u16).to_le_bytes());
+        header.extend_from_slice(&0_u16.to_le_bytes()); // data flags
+        header.extend_from_slice(&(self.attributes.len() as u16).to_le_bytes()); // instance
+        header
+    }
+}
+
+/// A parser over `records`, placed at their record numbers. Gaps are zeroed entries.
+pub fn parser_from_records(
+    records: impl IntoIterator<Item = (u64, Vec<u8>)>,
+) -> MftParser<Cursor<Vec<u8>>> {
+    let mut buffer = vec![];
+    for (record_number, record) in records {
+        let start = record_number as usize * ENTRY_SIZE;
+        if buffer.len() < start + ENTRY_SIZE {
+            buffer.resize(start + ENTRY_SIZE, 0);
+        }
+        buffer[start..start + ENTRY_SIZE].copy_from_slice(&record);
+    }
+
+    MftParser::from_buffer(buffer).expect("a valid or zeroed first record")
+}
+
+fn align8(n: usize) -> usize {
+    n.div_ceil(8) * 8
+}
+
+fn utf16_bytes(s: &str) -> Vec<u8> {
+    s.encode_utf16().flat_map(u16::to_le_bytes).collect()
+}
+
+fn mft_reference(entry: u64, sequence: u16) -> u64 {
+    (entry & 0xFFFF_FFFF_FFFF) | (u64::from(sequence) << 48)
+}
+
+fn filetime(at: DateTime<Utc>) -> u64 {
+    const UNIX_EPOCH_AS_FILETIME_SECONDS: i64 = 11_644_473_600;
+    let seconds = (at.timestamp() + UNIX_EPOCH_AS_FILETIME_SECONDS) as u64;
+    seconds * 10_000_000 + u64::from(at.timestamp_subsec_nanos() / 100)
+}
+
+fn push_timestamps(value: &mut Vec<u8>, times: &Timestamps) {
+    for at in [
+        times.created,
+        times.modified,
+        times.mft_modified,
+        times.accessed,
+    ] {
+        value.extend_from_slice(&filetime(at).to_le_bytes());
+    }
+}
+
+/// Encodes `runs` as NTFS mapping pairs, each holding the run length and the offset from the
+/// previous run's first cluster.
+fn encode_runs(runs: &[Run]) -> Vec<u8> {
+    fn minimal_le(value: i64, signed: bool) -> Vec<u8> {
+        let bytes = value.to_le_bytes();
+        let mut len = 8;
+        while len > 1 {
+            let top = bytes[len - 1];
+            let next_sign = bytes[len - 2] & 0x80 != 0;
+            let redundant = if signed {
+                (top == 0x00 && !next_sign) || (top == 0xFF && next_sign)
+            } else {
+                top == 0
+            };
+            if !redundant {
+                break;
+            }
+            len -= 1;
+        }
+        bytes[..len].to_vec()
+    }
+
+    let mut out = vec![];
+    let mut previous_lcn = 0_i64;
+    for (length, lcn) in runs {
+        let length = minimal_le(*length as i64, false);
+        let offset = match lcn {
+            Some(lcn) => {
+                let delta = *lcn as i64 - previous_lcn;
+                previous_lcn = *lcn as i64;
+                minimal_le(delta, true)
+            },
+            None => vec![],
+        };
+        out.push(((offset.len() as u8) << 4) | length.len() as u8);
+        out.extend_from_slice(&length);
+        out.extend_from_slice(&offset);
+    }
+    out.push(0);
+    out
+}
+
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::{
+        attribute::{
+            data_run::{DataRun, RunType},
+            header::ResidentialHeader,
+        },
+        csv::{FlatMftEntryWithName, ResidentDigests, ResidentHashes},
+    };
+
+    fn at(year: i32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(year, 6, 1, 12, 30, 15).unwrap()
+    }
+
+    fn root() -> Vec<u8> {
+        MftRecordBuilder::new(0)
+            .standard_info(Timestamps::all(at(2020)), FileAttributeFlags::empty())
+            .file_name(
+                (5, 5),
+                "$MFT",
+                FileNamespace::Win32AndDos,
+                Timestamps::all(at(2020)),
+            )
+            .build()
+    }
+
+    #[test]
+    fn test_builder_round_trips_a_resident_file() {
+        let entry = MftRecordBuilder::new(42)
+            .sequence(3)
+            .standard_info(
+                Timestamps::all(at(2021)),
+                FileAttributeFlags::FILE_ATTRIBUTE_HIDDEN
+                    | FileAttributeFlags::FILE_ATTRIBUTE_SYSTEM,
+            )
+            .file_name(
+                (5, 5),
+                "notes.txt",
+                FileNamespace::Win32,
+                Timestamps::all(at(2021)),
+            )
+            .resident_data("", b"hello")
+            .build_entry();
+
+        assert!(entry.header.is_valid());
+        assert_eq!(entry.valid_fixup, Some(true));
+        assert_eq!(entry.header.sequence, 3);
+        assert!(entry.is_allocated());
+        assert!(!entry.is_dir());
+
+        let name = entry.find_best_name_attribute().unwrap();
+        assert_eq!(name.name, "notes.txt");
+        assert_eq!(name.parent.entry, 5);
+        assert_eq!(name.created, at(2021));
+
+        let standard_info = entry
+            .iter_attributes()
+            .filter_map(Result::ok)
+            .find_map(|a| a.data.into_standard_info())
+            .unwrap();
+        assert_eq!(standard_info.file_flags.to_compact_string(), "-HS-----");
+        assert_eq!(standard_info.security_id, 0x100);
+        assert_eq!(standard_info.usn, 0x1000);
+
+        let data = entry
+            .iter_attributes()
+            .filter_map(Result::ok)
+            .find_map(|a| a.data.into_data())
+            .unwrap();
+        assert_eq!(data.data(), b"hello");
+    }
+
+    #[test]
+    fn test_builder_alternate_data_stream() {
+        let zone_identifier = b"[ZoneTransfer]\r\nZoneId=3\r\n";
+        let record = MftRecordBuilder::new(1)
+            .standard_info(Timestamps::all(at(2022)), FileAttributeFlags::empty())
+            .file_name(
+                (5, 5),
+                "setup.exe",
+                FileNamespace::Win32,
+                Timestamps::all(at(2022)),
+            )
+            .resident_data("", b"MZ")
+            .resident_data("Zone.Identifier", zone_identifier)
+            .build();
+        let mut parser = parser_from_records([(0, root()), (1, record)]);
+        let entry = parser.get_entry(1).unwrap();
+
+        let streams: Vec<(String, Vec<u8>)> = entry
+            .iter_attributes()
+            .filter_map(Result::ok)
+            .filter(|a| a.header.type_code == MftAttributeType::DATA)
+            .map(|a| {
+                (
+                    a.header.name.clone(),
+                    a.data.into_data().unwrap().data().to_vec(),
+                )
+            })
+            .collect();
+        assert_eq!(streams[0], (String::new(), b"MZ".to_vec()));
+        assert_eq!(
+            streams[1],
+            ("Zone.Identifier".to_string(), zone_identifier.to_vec())
+        );
+
+        let flat = FlatMftEntryWithName::from_entry(&entry, &mut parser);
+        assert!(flat.has_alternate_data_streams);
+        assert_eq!(flat.full_path.to_str(), Some("setup.exe"));
+    }
+
+    #[test]
+    fn test_builder_timestomped_standard_info() {
+        let file_name_times = Timestamps::all(at(2023));
+        let stomped = Timestamps {
+            created: at(2009),
+            modified: at(2009),
+            ..file_name_times
+        };
+        let record = MftRecordBuilder::new(1)
+            .standard_info(stomped, FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE)
+            .file_name((5, 5), "payload.dll", FileNamespace::Win32, file_name_times)
+            .build();
+        let mut parser = parser_from_records([(0, root()), (1, record)]);
+        let entry = parser.get_entry(1).unwrap();
+
+        let flat = FlatMftEntryWithName::from_entry(&entry, &mut parser);
+        assert_eq!(flat.standard_info_created, Some(at(2009)));
+        assert_eq!(flat.file_name_created, Some(at(2023)));
+        assert!(flat.standard_info_created < flat.file_name_created);
+    }
+
+    #[test]
+    fn test_builder_non_resident_data() {
+        let entry = MftRecordBuilder::new(7)
+            .standard_info(Timestamps::all(at(2024)), FileAttributeFlags::empty())
+            .file_name(
+                (5, 5),
+                "big.bin",
+                FileNamespace::Win32,
+                Timestamps::all(at(2024)),
+            )
+            .non_resident_data(
+                "",
+                &[(16, Some(0x1000)), (4, None), (8, Some(0x800))],
+                100_000,
+                4096,
+            )
+            .build_entry();
+
+        let data = entry
+            .iter_attributes()
+            .filter_map(Result::ok)
+            .find(|a| a.header.type_code == MftAttributeType::DATA)
+            .unwrap();
+        match &data.header.residential_header {
+            ResidentialHeader::NonResident(nr) => {
+                assert_eq!(nr.file_size, 100_000);
+                assert_eq!(nr.allocated_length, 28 * 4096);
+                assert_eq!(nr.vnc_last, 27);
+            },
+            ResidentialHeader::Resident(_) => panic!("expected non-resident $DATA"),
+        }
+
+        let runs = data.data.into_data_runs().unwrap().data_runs;
+        assert_eq!(
+            runs,
+            vec![
+                DataRun {
+                    lcn_offset: 0x1000,
+                    lcn_length: 16,
+                    run_type:   RunType::Standard,
+                },
+                DataRun {
+                    lcn_offset: 0,
+                    lcn_length: 4,
+                    run_type:   RunType::Sparse,
+                },
+                DataRun {
+                    lcn_offset: 0x800,
+                    lcn_length: 8,
+                    run_type:   RunType::Standard,
+                },
+            ]
+        );
+
+        let digests = ResidentDigests::from_entry(&entry, ResidentHashes::all());
+        assert_eq!(digests, ResidentDigests::default());
+    }
+
+    #[test]
+    fn test_builder_deleted_directory_and_baad_records() {
+        let directory = MftRecordBuilder::new(9)
+            .directory()
+            .deleted()
+            .base_reference(3, 2)
+            .file_name(
+                (5, 5),
+                "Temp",
+                FileNamespace::Win32,
+                Timestamps::all(at(2019)),
+            )
+            .build_entry();
+        assert!(directory.is_dir());
+        assert_eq!(directory.header.base_reference.entry, 3);
+        assert_eq!(directory.header.base_reference.sequence, 2);
+        assert!(!directory.is_allocated());
+
+        let baad = MftRecordBuilder::new(10).signature(b"BAAD").build_entry();
+        assert!(baad.header.is_baad());
+        assert_eq!(baad.valid_fixup, None);
+    }
+}
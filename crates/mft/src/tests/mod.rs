@@ -1 +1,2 @@
+pub mod builder;
 pub mod fixtures;
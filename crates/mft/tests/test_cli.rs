@@ -77,3 +77,108 @@ fn test_it_exports_resident_streams() {
 
     assert_eq!(fs::read_dir(d.path()).unwrap().count(), 2142)
 }
+
+#[test]
+fn test_it_filters_by_path_prefix() {
+    let sample = mft_sample();
+    let mut cmd = Command::cargo_bin("mft_cmd").expect("failed to find binary");
+    cmd.args(&[
+        "-o",
+        "csv",
+        "--path-prefix",
+        "\\$EXTEND\\",
+        sample.to_str().unwrap(),
+    ]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+
+    let mut rdr = csv::Reader::from_reader(output.stdout.as_slice());
+    let path_col = rdr
+        .headers()
+        .unwrap()
+        .iter()
+        .position(|h| h == "FullPath")
+        .expect("FullPath column");
+
+    let mut rows = 0;
+    for record in rdr.records() {
+        let record = record.unwrap();
+        assert!(record[path_col].to_lowercase().starts_with("$extend/"));
+        rows += 1;
+    }
+    assert!(rows > 0, "Expected entries under $Extend");
+}
+
+#[test]
+fn test_it_hashes_resident_data() {
+    let sample = mft_sample();
+    let mut cmd = Command::cargo_bin("mft_cmd").expect("failed to find binary");
+    cmd.args(&[
+        "-o",
+        "csv",
+        "--hash",
+        "md5,sha256",
+        sample.to_str().unwrap(),
+    ]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+
+    let mut rdr = csv::Reader::from_reader(output.stdout.as_slice());
+    let headers = rdr.headers().unwrap().clone();
+    let col = |name: &str| {
+        headers
+            .iter()
+            .position(|h| h == name)
+            .unwrap_or_else(|| panic!("{name} column"))
+    };
+    let (id_col, md5_col, sha1_col, sha256_col) = (
+        col("EntryId"),
+        col("ResidentMd5"),
+        col("ResidentSha1"),
+        col("ResidentSha256"),
+    );
+
+    let mut hashed = 0;
+    for record in rdr.records() {
+        let record = record.unwrap();
+        assert!(record[sha1_col].is_empty(), "SHA-1 was not requested");
+        // Entry 0 is `$MFT` itself, whose data is never resident.
+        if &record[id_col] == "0" {
+            assert!(record[md5_col].is_empty());
+            assert!(record[sha256_col].is_empty());
+        }
+        if !record[md5_col].is_empty() {
+            assert_eq!(record[md5_col].len(), 32);
+            assert_eq!(record[sha256_col].len(), 64);
+            hashed += 1;
+        }
+    }
+    assert!(hashed > 0, "Expected some entries with resident data");
+}
+
+#[test]
+fn test_it_exports_standard_info_ids() {
+    let sample = mft_sample();
+    let mut cmd = Command::cargo_bin("mft_cmd").expect("failed to find binary");
+    cmd.args(&["-o", "csv", sample.to_str().unwrap()]);
+
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+
+    let mut rdr = csv::Reader::from_reader(output.stdout.as_slice());
+    let headers = rdr.headers().unwrap().clone();
+    let security_col = headers
+        .iter()
+        .position(|h| h == "StandardInfoSecurityId")
+        .expect("StandardInfoSecurityId column");
+    assert!(headers.iter().any(|h| h == "StandardInfoUsn"));
+
+    let with_security_id = rdr
+        .records()
+        .map(Result::unwrap)
+        .filter(|r| !r[security_col].is_empty() && &r[security_col] != "0")
+        .count();
+    assert!(with_security_id > 0, "Expected entries with a security id");
+}
@@ -8,7 +8,7 @@ use mft::{
         x90::{IndexCollationRules, IndexEntryFlags, IndexEntryHeader},
     },
     entry::MftEntry,
-    mft::MftParser,
+    mft::{MftParser, MftRecord},
 };
 use serde_json;
 use winstructs::{ntfs::mft_reference::MftReference, timestamp::WinTimestamp};
@@ -72,8 +72,8 @@ fn test_entry_index_root() {
                         modified:      created,
                         mft_modified:  mft_modified,
                         accessed:      mft_modified,
-                        logical_size:  4096,
-                        physical_size: 1484,
+                        physical_size: 4096,
+                        logical_size:  1484,
                         flags:         FileAttributeFlags::FILE_ATTRIBUTE_ARCHIVE,
                         reparse_value: 0,
                         name_length:   22,
@@ -87,3 +87,42 @@ fn test_entry_index_root() {
         }
     }
 }
+
+#[test]
+fn test_iter_records_surfaces_baad_entries() {
+    let mut buffer = std::fs::read(mft_sample()).unwrap();
+    let entry_size = MftParser::from_buffer(buffer.clone())
+        .unwrap()
+        .get_entry(0)
+        .unwrap()
+        .header
+        .total_entry_size as usize;
+    buffer.truncate(entry_size * 4);
+    buffer[entry_size * 2..entry_size * 2 + 4].copy_from_slice(b"BAAD");
+
+    let mut parser = MftParser::from_buffer(buffer).unwrap();
+
+    let entry = parser.get_entry(2).unwrap();
+    assert!(entry.header.is_baad());
+    assert!(!entry.header.is_valid());
+    assert_eq!(entry.header.signature_str(), "BAAD");
+
+    let json = serde_json::to_value(&entry).unwrap();
+    assert_eq!(json["header"]["signature"], "BAAD");
+
+    let records: Vec<MftRecord> = parser.iter_records(true).map(Result::unwrap).collect();
+    assert_eq!(records.len(), 4);
+    assert!(records[2].is_baad());
+    assert_eq!(records[2].entry().header.record_number, 2);
+    assert!(!records[0].is_baad());
+
+    let errors: Vec<_> = parser.iter_records(false).filter_map(Result::err).collect();
+    assert_eq!(errors.len(), 1);
+    assert!(matches!(
+        errors[0],
+        mft::err::Error::NotAFileRecord {
+            entry_number: 2,
+            ..
+        }
+    ));
+}
@@ -4,7 +4,7 @@ use std::{
     path::{Path, PathBuf},
     sync::{
         OnceLock,
-        atomic::{AtomicBool, Ordering},
+        atomic::{AtomicBool, AtomicU8, AtomicUsize, Ordering},
     },
 };
 
@@ -25,11 +25,14 @@ pub const DEFAULT_RDR_BUFFER_CAPACITY: usize = 128 * (1 << 10);
 pub const DEFAULT_WTR_BUFFER_CAPACITY: usize = 512 * (1 << 10);
 
 // number of rows for qsv_sniffer to sample
-const DEFAULT_SNIFFER_SAMPLE: usize = 100;
+pub(crate) const DEFAULT_SNIFFER_SAMPLE: usize = 100;
 
 // file size at which we warn user that a large file has not been indexed
 const NO_INDEX_WARNING_FILESIZE: u64 = 100 * (1 << 20); // 100MB
 
+// UTF-8 byte order mark, as written by Excel at the start of exported CSVs
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
 // so we don't have to keep checking if the index has been created
 static AUTO_INDEXED: AtomicBool = AtomicBool::new(false);
 
@@ -37,6 +40,72 @@ pub static TEMP_FILE_DIR: OnceLock<PathBuf> = OnceLock::new();
 
 pub static POLARS_FLOAT_PRECISION: OnceLock<Option<usize>> = OnceLock::new();
 
+// share of memory mem_file_check leaves free, unless overridden
+const DEFAULT_FREEMEMORY_HEADROOM_PCT: u8 = 20;
+
+// headroom set through set_memory_headroom_pct; 0 means not set
+static MEMORY_HEADROOM_PCT: AtomicU8 = AtomicU8::new(0);
+
+/// Override QSV_FREEMEMORY_HEADROOM_PCT for this process, clamped to 10-90%.
+/// Passing 0 drops the override. Returns the effective headroom.
+pub fn set_memory_headroom_pct(pct: u8) -> u8 {
+    let pct = if pct == 0 { 0 } else { pct.clamp(10, 90) };
+    MEMORY_HEADROOM_PCT.store(pct, Ordering::Relaxed);
+    memory_headroom_pct()
+}
+
+/// The percent of memory non-streaming operations leave free: the value set with
+/// set_memory_headroom_pct, else QSV_FREEMEMORY_HEADROOM_PCT, else 20%. Always within 10-90%,
+/// except that QSV_FREEMEMORY_HEADROOM_PCT=0 gives 0, which disables the memory check.
+pub fn memory_headroom_pct() -> u8 {
+    effective_headroom_pct(
+        MEMORY_HEADROOM_PCT.load(Ordering::Relaxed),
+        env::var("QSV_FREEMEMORY_HEADROOM_PCT").ok().as_deref(),
+    )
+}
+
+/// The headroom for an override (0 = not set) and a QSV_FREEMEMORY_HEADROOM_PCT value.
+fn effective_headroom_pct(set_pct: u8, env_pct: Option<&str>) -> u8 {
+    if set_pct != 0 {
+        return set_pct.clamp(10, 90);
+    }
+    match env_pct.and_then(|v| v.parse::<u8>().ok()) {
+        Some(0) => 0,
+        Some(pct) => pct.clamp(10, 90),
+        None => DEFAULT_FREEMEMORY_HEADROOM_PCT,
+    }
+}
+
+// buffer capacities set through set_default_buffer_capacities; 0 means not set
+static RDR_BUFFER_CAPACITY: AtomicUsize = AtomicUsize::new(0);
+static WTR_BUFFER_CAPACITY: AtomicUsize = AtomicUsize::new(0);
+
+/// Override the reader and writer buffer capacities new Configs start with, e.g. larger
+/// buffers for evidence on a network share. 0 drops the respective override.
+pub fn set_default_buffer_capacities(rdr: usize, wtr: usize) {
+    RDR_BUFFER_CAPACITY.store(rdr, Ordering::Relaxed);
+    WTR_BUFFER_CAPACITY.store(wtr, Ordering::Relaxed);
+}
+
+/// Capacity new Configs start with: the set_default_buffer_capacities override, else
+/// `env_var`, else `default`.
+fn default_buffer_capacity(overridden: &AtomicUsize, env_var: &str, default: usize) -> u32 {
+    let capacity = match overridden.load(Ordering::Relaxed) {
+        0 => env::var(env_var)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default),
+        capacity => capacity,
+    };
+    buffer_capacity(capacity, default)
+}
+
+/// `capacity` as stored on Config; 0 falls back to `default`.
+fn buffer_capacity(capacity: usize, default: usize) -> u32 {
+    let capacity = if capacity == 0 { default } else { capacity };
+    u32::try_from(capacity).unwrap_or(u32::MAX)
+}
+
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub enum SpecialFormat {
     Avro,
@@ -50,6 +119,17 @@ pub enum SpecialFormat {
     Mft,
     Unknown,
 }
+/// Freshness of the `.idx` sidecar of a CSV, see [`Config::index_state`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IndexState {
+    /// no index file, or the input is stdin
+    #[default]
+    None,
+    Fresh,
+    /// the CSV was modified after the index was written
+    Stale,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Delimiter(pub u8);
 
@@ -113,6 +193,10 @@ pub struct Config {
     prefer_dmy:            bool,
     pub comment:           Option<u8>,
     snappy:                bool, // flag to enable snappy compression/decompression
+    bom:                   bool, // flag to emit a UTF-8 BOM when writing
+    null_values:           Vec<Vec<u8>>, // field values treated as empty, besides ""
+    record_transform:      Option<fn(&mut csv::ByteRecord)>, // applied to records as they're read
+    dedup_headers:         bool, // read repeated header names as name_2, name_3, ...
     pub read_buffer:       u32,
     pub write_buffer:      u32,
     pub skip_format_check: bool,
@@ -126,6 +210,18 @@ impl<T: io::Seek + io::Read> SeekRead for T {}
 
 #[bon::bon]
 impl Config {
+    /// Build the configuration for reading `path` (stdin when `None` or `-`).
+    ///
+    /// Defaults come from these environment variables:
+    /// - `WAKA_DEFAULT_DELIMITER`: delimiter when the extension doesn't imply one.
+    /// - `QSV_SNIFF_DELIMITER`, `QSV_SNIFF_PREAMBLE`: sniff the delimiter and preamble rows.
+    /// - `WAKA_SKIP_FORMAT_CHECK`: accept files with unknown extensions.
+    /// - `QSV_COMMENT_CHAR`: skip lines starting with this character.
+    /// - `QSV_NO_HEADERS`: treat the first row as data.
+    /// - `QSV_AUTOINDEX_SIZE`: index files of at least this many bytes automatically.
+    /// - `QSV_PREFER_DMY`: read ambiguous dates as day-month-year.
+    /// - `QSV_NULL_VALUES`: comma-separated field values treated as empty, e.g. `NULL,N/A,\N`.
+    /// - `QSV_RDR_BUFFER_CAPACITY`, `QSV_WTR_BUFFER_CAPACITY`: I/O buffer sizes in bytes.
     #[builder]
     pub fn new(#[builder(into)] path: Option<String>) -> Config {
         let default_delim = match env::var("WAKA_DEFAULT_DELIMITER") {
@@ -258,16 +354,29 @@ impl Config {
             prefer_dmy: util::get_envvar_flag("QSV_PREFER_DMY"),
             comment,
             snappy,
-            read_buffer: std::env::var("QSV_RDR_BUFFER_CAPACITY")
-                .unwrap_or_else(|_| DEFAULT_RDR_BUFFER_CAPACITY.to_string())
-                .parse()
-                .unwrap_or(DEFAULT_RDR_BUFFER_CAPACITY as u32),
-            write_buffer: std::env::var("QSV_WTR_BUFFER_CAPACITY")
-                .unwrap_or_else(|_| DEFAULT_WTR_BUFFER_CAPACITY.to_string())
-                .parse()
-                .unwrap_or(DEFAULT_WTR_BUFFER_CAPACITY as u32),
+            bom: false,
+            null_values: std::env::var("QSV_NULL_VALUES")
+                .map(|v| {
+                    v.split(',')
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.as_bytes().to_vec())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            read_buffer: default_buffer_capacity(
+                &RDR_BUFFER_CAPACITY,
+                "QSV_RDR_BUFFER_CAPACITY",
+                DEFAULT_RDR_BUFFER_CAPACITY,
+            ),
+            write_buffer: default_buffer_capacity(
+                &WTR_BUFFER_CAPACITY,
+                "QSV_WTR_BUFFER_CAPACITY",
+                DEFAULT_WTR_BUFFER_CAPACITY,
+            ),
             format_error,
             skip_format_check,
+            record_transform: None,
+            dedup_headers: false,
         }
     }
 
@@ -304,6 +413,132 @@ impl Config {
         self
     }
 
+    /// Emit a UTF-8 BOM at the start of the output, for Excel compatibility.
+    /// QSV_OUTPUT_BOM also turns this on.
+    pub const fn with_bom(mut self, yes: bool) -> Config {
+        self.bom = yes;
+        self
+    }
+
+    /// Treat fields equal to `value` (e.g. `NULL`, `N/A` or `\N`) as empty, replacing any
+    /// values set before. QSV_NULL_VALUES sets a comma-separated list by default.
+    pub fn null_value(mut self, value: Option<String>) -> Config {
+        self.null_values = value.into_iter().map(String::into_bytes).collect();
+        self
+    }
+
+    /// Like `null_value`, for several markers at once.
+    pub fn null_values(mut self, values: impl IntoIterator<Item = String>) -> Config {
+        self.null_values = values.into_iter().map(String::into_bytes).collect();
+        self
+    }
+
+    pub fn get_null_values(&self) -> &[Vec<u8>] {
+        &self.null_values
+    }
+
+    /// Is `field` empty or one of the configured null markers?
+    #[inline]
+    pub fn is_null(&self, field: &[u8]) -> bool {
+        field.is_empty() || self.null_values.iter().any(|n| n.as_slice() == field)
+    }
+
+    /// Apply `transform` to every record read through `read_byte_record`, `read_record`,
+    /// `byte_records` or `records`, so normalization like trimming or case-folding is set up once
+    /// for all readers of this Config rather than by wrapping each record iterator. The
+    /// csv::Reader returned by `reader`/`indexed` can't run the hook itself, so records must be
+    /// read through these methods. Replaces any earlier transform.
+    pub const fn with_record_transform(mut self, transform: fn(&mut csv::ByteRecord)) -> Config {
+        self.record_transform = Some(transform);
+        self
+    }
+
+    /// Read the next record of `rdr` into `record`, applying the record transform, if any.
+    #[inline]
+    pub fn read_byte_record<R: io::Read>(
+        &self,
+        rdr: &mut csv::Reader<R>,
+        record: &mut csv::ByteRecord,
+    ) -> csv::Result<bool> {
+        let more = rdr.read_byte_record(record)?;
+        if more && let Some(transform) = self.record_transform {
+            // a transform that rebuilds the record drops its position, which joins seek back to
+            let pos = record.position().cloned();
+            transform(record);
+            record.set_position(pos);
+        }
+        Ok(more)
+    }
+
+    /// Read the next record of `rdr` into `record`, applying the record transform, if any.
+    #[inline]
+    pub fn read_record<R: io::Read>(
+        &self,
+        rdr: &mut csv::Reader<R>,
+        record: &mut csv::StringRecord,
+    ) -> csv::Result<bool> {
+        let more = rdr.read_record(record)?;
+        if more && let Some(transform) = self.record_transform {
+            let mut byte_record = std::mem::take(record).into_byte_record();
+            transform(&mut byte_record);
+            *record = csv::StringRecord::from_byte_record_lossy(byte_record);
+        }
+        Ok(more)
+    }
+
+    /// The remaining records of `rdr`, with the record transform, if any, applied.
+    pub fn byte_records<'r, R: io::Read>(
+        &self,
+        rdr: &'r mut csv::Reader<R>,
+    ) -> impl Iterator<Item = csv::Result<csv::ByteRecord>> + 'r {
+        let transform = self.record_transform;
+        rdr.byte_records().map(move |r| {
+            r.map(|mut record| {
+                if let Some(transform) = transform {
+                    transform(&mut record);
+                }
+                record
+            })
+        })
+    }
+
+    /// Like `byte_records`, decoding each record as UTF-8. Fields the transform leaves as
+    /// invalid UTF-8 are decoded lossily.
+    pub fn records<'r, R: io::Read>(
+        &self,
+        rdr: &'r mut csv::Reader<R>,
+    ) -> impl Iterator<Item = csv::Result<csv::StringRecord>> + 'r {
+        let transform = self.record_transform;
+        rdr.records().map(move |r| {
+            r.map(|record| match transform {
+                Some(transform) => {
+                    let mut record = record.into_byte_record();
+                    transform(&mut record);
+                    csv::StringRecord::from_byte_record_lossy(record)
+                },
+                None => record,
+            })
+        })
+    }
+
+    /// Make repeated header names unique as the headers are read: the first occurrence keeps
+    /// its name and later ones become `name_2`, `name_3`, ..., so selecting by name and JSON
+    /// keys can tell the columns apart. Has no effect with `no_headers`.
+    pub const fn dedup_headers(mut self, yes: bool) -> Config {
+        self.dedup_headers = yes;
+        self
+    }
+
+    /// Header names that occur more than once, with the indices of all their occurrences.
+    /// Empty with `no_headers`.
+    pub fn duplicate_headers(&self) -> anyhow::Result<Vec<(String, Vec<usize>)>> {
+        if self.no_headers {
+            return Ok(Vec::new());
+        }
+        let mut rdr = self.clone().dedup_headers(false).reader()?;
+        Ok(util::duplicate_headers(rdr.byte_headers()?))
+    }
+
     pub const fn skip_format_check(mut self, yes: bool) -> Config {
         self.skip_format_check = yes;
         self
@@ -353,20 +588,36 @@ impl Config {
         self
     }
 
-    pub fn set_read_buffer(mut self, buffer: usize) -> Config {
-        self.read_buffer = buffer
-            .try_into()
-            .unwrap_or(DEFAULT_RDR_BUFFER_CAPACITY as u32);
+    /// Buffer capacity of the CSV readers made from this config. Larger buffers help on
+    /// network filesystems. 0 falls back to DEFAULT_RDR_BUFFER_CAPACITY.
+    pub fn rdr_buffer_capacity(mut self, capacity: usize) -> Config {
+        self.read_buffer = buffer_capacity(capacity, DEFAULT_RDR_BUFFER_CAPACITY);
         self
     }
 
-    pub fn set_write_buffer(mut self, buffer: usize) -> Config {
-        self.write_buffer = buffer
-            .try_into()
-            .unwrap_or(DEFAULT_WTR_BUFFER_CAPACITY as u32);
+    /// Buffer capacity of the CSV writers made from this config.
+    /// 0 falls back to DEFAULT_WTR_BUFFER_CAPACITY.
+    pub fn wtr_buffer_capacity(mut self, capacity: usize) -> Config {
+        self.write_buffer = buffer_capacity(capacity, DEFAULT_WTR_BUFFER_CAPACITY);
         self
     }
 
+    pub const fn get_rdr_buffer_capacity(&self) -> usize {
+        self.read_buffer as usize
+    }
+
+    pub const fn get_wtr_buffer_capacity(&self) -> usize {
+        self.write_buffer as usize
+    }
+
+    pub fn set_read_buffer(self, buffer: usize) -> Config {
+        self.rdr_buffer_capacity(buffer)
+    }
+
+    pub fn set_write_buffer(self, buffer: usize) -> Config {
+        self.wtr_buffer_capacity(buffer)
+    }
+
     #[allow(clippy::missing_const_for_fn)]
     pub fn select(mut self, sel_cols: SelectColumns) -> Config {
         self.select_columns = Some(sel_cols);
@@ -444,7 +695,7 @@ impl Config {
                 self.format_error.clone().unwrap(),
             ))
         } else {
-            Ok(self.from_reader(self.io_reader()?))
+            Ok(self.from_reader(skip_utf8_bom(self.io_reader()?)?))
         }
     }
 
@@ -608,12 +859,59 @@ impl Config {
             info!("index stale... autoindexing...");
             self.autoindex_file();
             idx_file = fs::File::open(&idx_path_work)?;
+            // a stale index must not be used, as its offsets no longer match the data
+            let (idx_modified, _) = util::file_metadata(&idx_file.metadata()?);
+            if data_modified > idx_modified {
+                warn!(
+                    "{} is stale and could not be recreated. Falling back to reading without an \
+                     index.",
+                    idx_path_work.display()
+                );
+                return Ok(None);
+            }
         }
 
-        let csv_rdr = self.from_reader(csv_file);
+        let mut csv_rdr = self.from_reader(csv_file);
+        // index offsets are absolute, so step over a BOM instead of wrapping the file
+        if starts_with_utf8_bom(csv_rdr.get_mut())? {
+            let mut pos = csv::Position::new();
+            pos.set_byte(UTF8_BOM.len() as u64);
+            csv_rdr.seek_raw(io::SeekFrom::Start(pos.byte()), pos)?;
+            // a raw seek hands the header row back as a record, and any headers read
+            // before it still carry the BOM, so read them again from here
+            if !self.no_headers {
+                let mut headers = csv::ByteRecord::new();
+                csv_rdr.read_byte_record(&mut headers)?;
+                csv_rdr.set_byte_headers(headers);
+                self.dedup_reader_headers(&mut csv_rdr);
+            }
+        }
         Ok(Some((csv_rdr, idx_file)))
     }
 
+    /// Report whether the CSV has an index and if it is still up to date, comparing the
+    /// modification times of both files the same way `index_files` does. Unlike `indexed`,
+    /// this never creates or recreates an index.
+    pub fn index_state(&self) -> IndexState {
+        let Some(path) = &self.path else {
+            return IndexState::None;
+        };
+        let idx_path = self
+            .idx_path
+            .clone()
+            .unwrap_or_else(|| util::idx_path(path));
+        let (Ok(data_md), Ok(idx_md)) = (fs::metadata(path), fs::metadata(idx_path)) else {
+            return IndexState::None;
+        };
+        let (data_modified, _) = util::file_metadata(&data_md);
+        let (idx_modified, _) = util::file_metadata(&idx_md);
+        if data_modified > idx_modified {
+            IndexState::Stale
+        } else {
+            IndexState::Fresh
+        }
+    }
+
     /// Check if the index file exists and is newer than the CSV file.
     /// If so, return the index file.
     /// If not, return None.
@@ -649,7 +947,7 @@ impl Config {
 
     #[allow(clippy::wrong_self_convention)]
     pub fn from_reader<R: Read>(&self, rdr: R) -> csv::Reader<R> {
-        csv::ReaderBuilder::new()
+        let mut rdr = csv::ReaderBuilder::new()
             .flexible(self.flexible)
             .delimiter(self.delimiter)
             .has_headers(!self.no_headers)
@@ -659,7 +957,25 @@ impl Config {
             .buffer_capacity(self.read_buffer as usize)
             .comment(self.comment)
             .trim(self.trim)
-            .from_reader(rdr)
+            .from_reader(rdr);
+        self.dedup_reader_headers(&mut rdr);
+        rdr
+    }
+
+    fn dedup_reader_headers<R: Read>(&self, rdr: &mut csv::Reader<R>) {
+        if self.dedup_headers && !self.no_headers {
+            let deduped = rdr.headers().ok().and_then(|headers| {
+                let names = util::dedup_header_names(headers);
+                names
+                    .iter()
+                    .zip(headers)
+                    .any(|(name, header)| name != header)
+                    .then(|| csv::StringRecord::from(names))
+            });
+            if let Some(headers) = deduped {
+                rdr.set_headers(headers);
+            }
+        }
     }
 
     pub fn io_writer(&self) -> io::Result<Box<dyn io::Write + 'static>> {
@@ -682,8 +998,8 @@ impl Config {
 
     #[allow(clippy::wrong_self_convention)]
     pub fn from_writer<W: io::Write>(&self, mut wtr: W) -> csv::Writer<W> {
-        if util::get_envvar_flag("QSV_OUTPUT_BOM") {
-            wtr.write_all("\u{FEFF}".as_bytes()).unwrap();
+        if self.bom || util::get_envvar_flag("QSV_OUTPUT_BOM") {
+            wtr.write_all(UTF8_BOM).unwrap();
         }
 
         csv::WriterBuilder::new()
@@ -699,6 +1015,31 @@ impl Config {
     }
 }
 
+/// Skip a leading UTF-8 BOM so it doesn't end up in the first header name.
+/// Anything else read while checking is put back in front of the stream.
+pub(crate) fn skip_utf8_bom(
+    mut rdr: Box<dyn io::Read + Send + 'static>,
+) -> io::Result<Box<dyn io::Read + Send + 'static>> {
+    let mut prefix = Vec::with_capacity(UTF8_BOM.len());
+    (&mut rdr)
+        .take(UTF8_BOM.len() as u64)
+        .read_to_end(&mut prefix)?;
+    if prefix == UTF8_BOM {
+        prefix.clear();
+    }
+    Ok(Box::new(io::Cursor::new(prefix).chain(rdr)))
+}
+
+/// Check a seekable stream for a leading UTF-8 BOM, leaving it rewound to the start.
+fn starts_with_utf8_bom<R: io::Read + io::Seek>(rdr: &mut R) -> io::Result<bool> {
+    let mut prefix = Vec::with_capacity(UTF8_BOM.len());
+    (&mut *rdr)
+        .take(UTF8_BOM.len() as u64)
+        .read_to_end(&mut prefix)?;
+    rdr.seek(io::SeekFrom::Start(0))?;
+    Ok(prefix == UTF8_BOM)
+}
+
 /// Determines the delimiter and compression status based on the file extension.
 ///
 /// # Arguments
@@ -837,6 +1178,82 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_reader_strips_utf8_bom() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("excel.csv");
+        fs::write(&path, b"\xEF\xBB\xBFid,name\n1,a\n").unwrap();
+
+        let mut rdr = Config::builder()
+            .path(path.to_string_lossy())
+            .build()
+            .reader()
+            .unwrap();
+        assert_eq!(rdr.headers().unwrap(), vec!["id", "name"]);
+        assert_eq!(rdr.records().count(), 1);
+    }
+
+    #[test]
+    fn test_indexed_strips_utf8_bom() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("excel.csv");
+        fs::write(&path, b"\xEF\xBB\xBFid,name\n1,a\n2,b\n").unwrap();
+        crate::index::run(&path.to_string_lossy(), None).unwrap();
+
+        let mut idx = Config::builder()
+            .path(path.to_string_lossy())
+            .build()
+            .indexed()
+            .unwrap()
+            .unwrap();
+        assert_eq!(idx.byte_headers().unwrap(), vec!["id", "name"]);
+        idx.seek(1).unwrap();
+        let mut rec = csv::ByteRecord::new();
+        assert!(idx.read_byte_record(&mut rec).unwrap());
+        assert_eq!(rec, vec!["2", "b"]);
+
+        // without a seek, a fresh reader starts at the first data row
+        let mut idx = Config::builder()
+            .path(path.to_string_lossy())
+            .build()
+            .indexed()
+            .unwrap()
+            .unwrap();
+        let rows = idx.byte_records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rows, vec![vec!["1", "a"], vec!["2", "b"]]);
+
+        fs::write(&path, b"\xEF\xBB\xBFid,id\n1,a\n").unwrap();
+        crate::index::run(&path.to_string_lossy(), None).unwrap();
+        let mut idx = Config::builder()
+            .path(path.to_string_lossy())
+            .build()
+            .dedup_headers(true)
+            .indexed()
+            .unwrap()
+            .unwrap();
+        assert_eq!(idx.headers().unwrap(), vec!["id", "id_2"]);
+        let rows = idx.byte_records().collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rows, vec![vec!["1", "a"]]);
+    }
+
+    #[test]
+    fn test_skip_utf8_bom_keeps_short_input() {
+        let mut out = String::new();
+        skip_utf8_bom(Box::new(io::Cursor::new(b"a\n".to_vec())))
+            .unwrap()
+            .read_to_string(&mut out)
+            .unwrap();
+        assert_eq!(out, "a\n");
+    }
+
+    #[test]
+    fn test_writer_with_bom() {
+        let mut wtr = Config::builder().build().with_bom(true).from_writer(vec![]);
+        wtr.write_record(["id", "name"]).unwrap();
+        let out = wtr.into_inner().unwrap();
+        assert_eq!(out, b"\xEF\xBB\xBFid,name\n");
+    }
+
     #[test]
     fn test_csv_extension() {
         let path = PathBuf::from("test.csv");
@@ -901,4 +1318,176 @@ mod tests {
         assert_eq!(delim, default_delim);
         assert!(!snappy);
     }
+
+    #[test]
+    fn test_config_delimiter_from_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_for = |name: &str, contents: &str| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            Config::builder().path(path.to_string_lossy()).build()
+        };
+
+        assert_eq!(config_for("data.tsv", "a\tb\n").get_delimiter(), b'\t');
+        assert_eq!(config_for("data.tab", "a\tb\n").get_delimiter(), b'\t');
+        assert_eq!(config_for("DATA.TSV", "a\tb\n").get_delimiter(), b'\t');
+        assert_eq!(config_for("data.ssv", "a;b\n").get_delimiter(), b';');
+        assert_eq!(config_for("data.csv", "a,b\n").get_delimiter(), b',');
+
+        let mut rdr = config_for("people.tsv", "name\tage\nalice\t30\n")
+            .reader()
+            .unwrap();
+        assert_eq!(rdr.headers().unwrap(), vec!["name", "age"]);
+
+        // no override keeps the extension's delimiter, an explicit one wins
+        let conf = config_for("override.tsv", "a|b\n");
+        assert_eq!(conf.clone().delimiter(None).get_delimiter(), b'\t');
+        assert_eq!(conf.delimiter(Some(Delimiter(b'|'))).get_delimiter(), b'|');
+    }
+
+    #[test]
+    fn test_record_transform() {
+        fn trim_fields(record: &mut csv::ByteRecord) {
+            record.trim();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("padded.csv");
+        fs::write(&path, "a,b\n 1 ,x  \n2,  y\n").unwrap();
+
+        let conf = Config::builder()
+            .path(path.to_string_lossy())
+            .build()
+            .with_record_transform(trim_fields);
+        let mut rdr = conf.reader().unwrap();
+        let mut record = csv::ByteRecord::new();
+        assert!(conf.read_byte_record(&mut rdr, &mut record).unwrap());
+        assert_eq!(record, vec!["1", "x"]);
+        let rest: Vec<_> = conf
+            .byte_records(&mut rdr)
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(rest, vec![csv::ByteRecord::from(vec!["2", "y"])]);
+
+        let mut rdr = conf.reader().unwrap();
+        let rows: Vec<_> = conf.records(&mut rdr).collect::<Result<_, _>>().unwrap();
+        assert_eq!(rows, vec![vec!["1", "x"], vec!["2", "y"]]);
+
+        let mut rdr = conf.reader().unwrap();
+        let mut row = csv::StringRecord::new();
+        assert!(conf.read_record(&mut rdr, &mut row).unwrap());
+        assert_eq!(row, vec!["1", "x"]);
+
+        // and through an index
+        crate::index::run(&path.to_string_lossy(), None).unwrap();
+        let mut idx = conf.indexed().unwrap().unwrap();
+        idx.seek(1).unwrap();
+        assert!(conf.read_byte_record(&mut idx, &mut record).unwrap());
+        assert_eq!(record, vec!["2", "y"]);
+    }
+
+    #[test]
+    fn test_dedup_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("dupes.csv");
+        fs::write(&path, "id,name,id,id\n1,a,2,3\n").unwrap();
+
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        assert_eq!(
+            conf.duplicate_headers().unwrap(),
+            vec![("id".to_string(), vec![0, 2, 3])]
+        );
+        assert_eq!(
+            conf.reader().unwrap().headers().unwrap(),
+            vec!["id", "name", "id", "id"]
+        );
+
+        let conf = conf.dedup_headers(true);
+        let mut rdr = conf.reader().unwrap();
+        assert_eq!(
+            rdr.byte_headers().unwrap(),
+            vec!["id", "name", "id_2", "id_3"]
+        );
+        let mut record = csv::ByteRecord::new();
+        assert!(rdr.read_byte_record(&mut record).unwrap());
+        assert_eq!(record, vec!["1", "a", "2", "3"]);
+        // the check still sees the original names
+        assert_eq!(conf.duplicate_headers().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_index_state() {
+        use filetime::{FileTime, set_file_mtime};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("data.csv");
+        fs::write(&path, b"id\n1\n2\n").unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        assert_eq!(conf.index_state(), IndexState::None);
+
+        let idx = util::idx_path(&path);
+        fs::write(&idx, b"").unwrap();
+        set_file_mtime(&path, FileTime::from_unix_time(1_000, 0)).unwrap();
+        set_file_mtime(&idx, FileTime::from_unix_time(2_000, 0)).unwrap();
+        assert_eq!(conf.index_state(), IndexState::Fresh);
+
+        set_file_mtime(&path, FileTime::from_unix_time(3_000, 0)).unwrap();
+        assert_eq!(conf.index_state(), IndexState::Stale);
+    }
+
+    #[test]
+    fn test_null_value_markers() {
+        let conf = Config::builder()
+            .build()
+            .null_value(Some("NULL".to_string()));
+        assert!(conf.is_null(b""));
+        assert!(conf.is_null(b"NULL"));
+        assert!(!conf.is_null(b"null"));
+        assert!(!conf.is_null(b"N/A"));
+
+        let conf = conf.null_values(["N/A".to_string(), "\\N".to_string()]);
+        assert!(conf.is_null(b"N/A"));
+        assert!(conf.is_null(b"\\N"));
+        assert!(!conf.is_null(b"NULL"));
+
+        let conf = conf.null_value(None);
+        assert!(conf.get_null_values().is_empty());
+        assert!(conf.is_null(b""));
+    }
+
+    #[test]
+    fn test_effective_headroom_pct() {
+        // an override wins over the environment, clamped to 10-90%
+        assert_eq!(effective_headroom_pct(5, Some("50")), 10);
+        assert_eq!(effective_headroom_pct(95, None), 90);
+        assert_eq!(effective_headroom_pct(35, Some("0")), 35);
+
+        assert_eq!(effective_headroom_pct(0, Some("50")), 50);
+        assert_eq!(effective_headroom_pct(0, Some("99")), 90);
+        assert_eq!(effective_headroom_pct(0, Some("0")), 0);
+        assert_eq!(
+            effective_headroom_pct(0, Some("lots")),
+            DEFAULT_FREEMEMORY_HEADROOM_PCT
+        );
+        assert_eq!(
+            effective_headroom_pct(0, None),
+            DEFAULT_FREEMEMORY_HEADROOM_PCT
+        );
+    }
+
+    #[test]
+    fn test_buffer_capacity() {
+        let conf = Config::builder()
+            .build()
+            .rdr_buffer_capacity(4 << 20)
+            .wtr_buffer_capacity(8 << 20);
+        assert_eq!(conf.get_rdr_buffer_capacity(), 4 << 20);
+        assert_eq!(conf.get_wtr_buffer_capacity(), 8 << 20);
+
+        let conf = conf.rdr_buffer_capacity(0).wtr_buffer_capacity(0);
+        assert_eq!(conf.get_rdr_buffer_capacity(), DEFAULT_RDR_BUFFER_CAPACITY);
+        assert_eq!(conf.get_wtr_buffer_capacity(), DEFAULT_WTR_BUFFER_CAPACITY);
+
+        assert_eq!(buffer_capacity(usize::MAX, 1), u32::MAX);
+    }
 }
@@ -238,9 +238,10 @@ pub fn match_indices_single_col_values(
     casei: bool,
     selected: &[String],
 ) -> anyhow::Result<Vec<u64>> {
-    use crate::config::Config;
     use std::collections::HashSet;
 
+    use crate::config::Config;
+
     if selected.is_empty() {
         return Ok(Vec::new());
     }
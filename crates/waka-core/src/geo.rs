@@ -0,0 +1,842 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    io::Write,
+    path::Path,
+};
+
+use anyhow::anyhow;
+use foldhash::fast::RandomState;
+
+use crate::{config::Config, regex_oncelock, util};
+
+/// mean Earth radius in km (IUGG), used by the great-circle helpers below
+const EARTH_RADIUS_KM: f64 = 6_371.008_8;
+
+/// great-circle distance in km between two WGS 84 (lat, long) points,
+/// using the haversine formula. Computed in f64 to avoid precision loss at short distances.
+#[inline]
+pub fn haversine_km(a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (lat1, lat2) = (f64::from(a.0).to_radians(), f64::from(b.0).to_radians());
+    let dlat = lat2 - lat1;
+    let dlong = (f64::from(b.1) - f64::from(a.1)).to_radians();
+
+    let h = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlong / 2.0).sin().powi(2);
+    (2.0 * EARTH_RADIUS_KM * h.sqrt().min(1.0).asin()) as f32
+}
+
+/// initial bearing (forward azimuth) in degrees from point a to point b,
+/// normalized to 0..360 where 0 is true north and 90 is east
+#[inline]
+pub fn initial_bearing_deg(a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (lat1, lat2) = (f64::from(a.0).to_radians(), f64::from(b.0).to_radians());
+    let dlong = (f64::from(b.1) - f64::from(a.1)).to_radians();
+
+    let y = dlong.sin() * lat2.cos();
+    let x = lat1.cos() * lat2.sin() - lat1.sin() * lat2.cos() * dlong.cos();
+    (y.atan2(x).to_degrees().rem_euclid(360.0)) as f32
+}
+
+/// parse a WGS 84 "(lat, long)" or "lat, long" coordinate from cell.
+/// Returns None if there's no coordinate or it's out of range.
+pub fn parse_location(cell: &str) -> Option<(f32, f32)> {
+    let loccaps = regex_oncelock!(r"(?-u)([+-]?(?:\d+\.?\d*|\.\d+)),\s*([+-]?(?:\d+\.?\d*|\.\d+))")
+        .captures(cell)?;
+    let lat = loccaps[1].parse::<f32>().ok()?;
+    let long = loccaps[2].parse::<f32>().ok()?;
+    ((-90.0..=90.0).contains(&lat) && (-180.0..=180.0).contains(&long)).then_some((lat, long))
+}
+
+/// append the great-circle distance (km) from the coordinate in `column` to `reference` as a
+/// `distance_km` column, and whether it's within `radius_km` as `within_radius`, streaming the
+/// result to `output` (stdout if None). This is the "which events happened near the scene"
+/// query. Rows without a valid coordinate get `invalid_result` as the distance and an empty flag.
+///
+/// Returns the number of rows within the radius.
+pub fn distance_to_reference(
+    conf: &Config,
+    column: usize,
+    reference: (f32, f32),
+    radius_km: f32,
+    invalid_result: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<u64> {
+    if !(radius_km.is_finite() && radius_km >= 0.0) {
+        return Err(anyhow!("radius must be a non-negative number of km"));
+    }
+    let mut rdr = conf.reader()?;
+    let mut headers = rdr.headers()?.clone();
+    if column >= headers.len() {
+        return Err(anyhow!(
+            "column {column} is out of range, the file has {} columns",
+            headers.len()
+        ));
+    }
+
+    let mut wtr = util::csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    if !conf.no_headers {
+        headers.push_field("distance_km");
+        headers.push_field("within_radius");
+        wtr.write_record(&headers)?;
+    }
+
+    let jobs = util::njobs(None);
+    // a requested batch size of 1 lets optimal_batch_size spread the rows over the jobs
+    let batch_size = util::optimal_batch_size(conf, 1, jobs);
+    let batches = util::par_record_batches(conf, &mut rdr, batch_size, jobs, |record| {
+        let mut record = record.clone();
+        let mut inside = false;
+        if let Some(location) = record.get(column).and_then(parse_location) {
+            let distance = haversine_km(reference, location);
+            inside = distance <= radius_km;
+            record.push_field(&format!("{distance:.3}"));
+            record.push_field(if inside { "true" } else { "false" });
+        } else {
+            record.push_field(invalid_result);
+            record.push_field("");
+        }
+        (record, inside)
+    })?;
+
+    let mut within = 0_u64;
+    for batch in batches {
+        for (record, inside) in batch? {
+            within += u64::from(inside);
+            wtr.write_record(&record)?;
+        }
+    }
+    wtr.flush()?;
+    Ok(within)
+}
+
+/// canonical admin1 codes (e.g. US.NY) and the names they go by, used to normalize a column of
+/// mixed admin1 codes and names. GeoNames' `admin1CodesASCII.txt` - the source of the admin1
+/// divisions in a geocode index - can be loaded with `from_geonames_file`.
+#[derive(Debug, Default, Clone)]
+pub struct Admin1Table {
+    codes: HashSet<String, RandomState>,
+    // lowercased admin1 name -> admin1 codes with that name
+    names: HashMap<String, Vec<String>, RandomState>,
+}
+
+impl Admin1Table {
+    /// a table of (code, name) admin1 divisions, restricted to `countries` if set
+    pub fn from_divisions<'a>(
+        divisions: impl IntoIterator<Item = (&'a str, &'a str)>,
+        countries: Option<&[String]>,
+    ) -> Self {
+        let mut table = Admin1Table::default();
+        for (code, name) in divisions {
+            if let Some(countries) = countries
+                && !countries
+                    .iter()
+                    .any(|c| c.eq_ignore_ascii_case(admin1_country(code)))
+            {
+                continue;
+            }
+            table.codes.insert(code.to_string());
+            let codes = table.names.entry(name.to_lowercase()).or_default();
+            if !codes.iter().any(|c| c == code) {
+                codes.push(code.to_string());
+            }
+        }
+        table
+    }
+
+    /// load GeoNames' tab-separated `admin1CodesASCII.txt` (code, name, ASCII name, geonameid).
+    /// Both the name and its ASCII form resolve to the code.
+    pub fn from_geonames_file(path: &Path, countries: Option<&[String]>) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let divisions = contents
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| {
+                let mut fields = line.split('\t');
+                match (fields.next(), fields.next(), fields.next()) {
+                    (Some(code), Some(name), ascii_name) => {
+                        Ok([Some((code, name)), ascii_name.map(|ascii| (code, ascii))])
+                    },
+                    _ => Err(anyhow!("not an admin1 codes line: {line}")),
+                }
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        Ok(Self::from_divisions(
+            divisions.into_iter().flatten().flatten(),
+            countries,
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.codes.is_empty()
+    }
+
+    /// resolve cell to its canonical admin1 code. cell can be a full code (US.NY), a bare code
+    /// (NY) if countries is set, or an unambiguous, case-insensitive admin1 name.
+    pub fn resolve(&self, cell: &str, countries: Option<&[String]>) -> Option<String> {
+        let cell = cell.trim();
+        let upper = cell.to_ascii_uppercase();
+        if regex_oncelock!(r"^[A-Z]{2}\.[A-Z0-9]{1,8}$").is_match(&upper) {
+            return self.codes.contains(&upper).then_some(upper);
+        }
+
+        // a bare code, qualified with each of the country filters
+        if let Some(countries) = countries {
+            let mut matches = countries
+                .iter()
+                .map(|country| format!("{}.{upper}", country.to_ascii_uppercase()))
+                .filter(|code| self.codes.contains(code));
+            if let Some(code) = matches.next() {
+                return matches.next().is_none().then_some(code);
+            }
+        }
+
+        // an admin1 name - only resolve it if it's unambiguous
+        match self.names.get(&cell.to_lowercase()).map(Vec::as_slice) {
+            Some([code]) => Some(code.clone()),
+            _ => None,
+        }
+    }
+}
+
+/// the country code prefix of an admin1 code, e.g. US.NY -> US
+#[inline]
+fn admin1_country(code: &str) -> &str {
+    code.split_once('.').map_or(code, |(country, _)| country)
+}
+
+/// replace each admin1 code or name in `column` with its canonical code from `table` (see
+/// `Admin1Table::resolve`), streaming the result to `output` (stdout if None). Values that don't
+/// resolve are replaced with `invalid_result`, or left as-is if it's empty.
+///
+/// Returns the number of values that didn't resolve.
+pub fn normalize_admin1(
+    conf: &Config,
+    column: usize,
+    table: &Admin1Table,
+    countries: Option<&[String]>,
+    invalid_result: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<u64> {
+    let mut rdr = conf.reader()?;
+    let headers = rdr.headers()?.clone();
+    if column >= headers.len() {
+        return Err(anyhow!(
+            "column {column} is out of range, the file has {} columns",
+            headers.len()
+        ));
+    }
+
+    let mut wtr = util::csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    if !conf.no_headers {
+        wtr.write_record(&headers)?;
+    }
+
+    let mut unresolved = 0_u64;
+    let mut record = csv::StringRecord::new();
+    while conf.read_record(&mut rdr, &mut record)? {
+        let Some(cell) = record.get(column) else {
+            wtr.write_record(&record)?;
+            continue;
+        };
+        let normalized = match table.resolve(cell, countries) {
+            Some(code) => code,
+            None => {
+                unresolved += 1;
+                if invalid_result.is_empty() {
+                    cell.to_string()
+                } else {
+                    invalid_result.to_string()
+                }
+            },
+        };
+        wtr.write_record(record.iter().enumerate().map(|(i, field)| {
+            if i == column {
+                normalized.as_str()
+            } else {
+                field
+            }
+        }))?;
+    }
+    wtr.flush()?;
+    Ok(unresolved)
+}
+
+/// a populated place from a GeoNames gazetteer dump
+#[derive(Debug, Clone, PartialEq)]
+pub struct Place {
+    pub geonameid:     u32,
+    pub name:          String,
+    pub location:      (f32, f32),
+    /// ISO 3166-1 alpha-2 country code
+    pub country:       String,
+    /// admin1 code qualified with its country (e.g. US.NY) as in `Admin1Table`, empty if unknown
+    pub admin1:        String,
+    pub population:    u64,
+    /// GeoNames feature class, P for the populated places kept by `Places::from_geonames_file`
+    pub feature_class: String,
+    /// GeoNames feature code telling the kind of place apart, e.g. PPLC (a capital), PPLX (a
+    /// section of a populated place) or PPL
+    pub feature_code:  String,
+}
+
+/// the populated places of a GeoNames gazetteer dump, for "which city is this coordinate in"
+/// (reverse) lookups. The `cities*.txt` dumps and `allCountries.txt` can be loaded with
+/// `from_geonames_file`.
+#[derive(Debug, Default, Clone)]
+pub struct Places {
+    places: Vec<Place>,
+}
+
+impl Places {
+    /// a table of `places`, restricted to `countries` if set
+    pub fn from_places(
+        places: impl IntoIterator<Item = Place>,
+        countries: Option<&[String]>,
+    ) -> Self {
+        let places = places
+            .into_iter()
+            .filter(|place| {
+                countries.is_none_or(|countries| {
+                    countries
+                        .iter()
+                        .any(|c| c.eq_ignore_ascii_case(&place.country))
+                })
+            })
+            .collect();
+        Places { places }
+    }
+
+    /// load a tab-separated GeoNames gazetteer dump (geonameid, name, asciiname, alternatenames,
+    /// latitude, longitude, feature class, feature code, country code, cc2, admin1 code, admin2
+    /// code, admin3 code, admin4 code, population, ...). Only populated places (feature class P)
+    /// are kept.
+    pub fn from_geonames_file(path: &Path, countries: Option<&[String]>) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut places = Vec::new();
+        for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 15 {
+                return Err(anyhow!("not a GeoNames gazetteer line: {line}"));
+            }
+            if fields[6] != "P" {
+                continue;
+            }
+            let parse_err = || anyhow!("not a GeoNames gazetteer line: {line}");
+            let admin1 = if fields[10].is_empty() {
+                String::new()
+            } else {
+                format!("{}.{}", fields[8], fields[10])
+            };
+            places.push(Place {
+                geonameid: fields[0].parse().map_err(|_| parse_err())?,
+                name: fields[1].to_string(),
+                location: (
+                    fields[4].parse().map_err(|_| parse_err())?,
+                    fields[5].parse().map_err(|_| parse_err())?,
+                ),
+                country: fields[8].to_string(),
+                admin1,
+                population: if fields[14].is_empty() {
+                    0
+                } else {
+                    fields[14].parse().map_err(|_| parse_err())?
+                },
+                feature_class: fields[6].to_string(),
+                feature_code: fields[7].to_string(),
+            });
+        }
+        Ok(Self::from_places(places, countries))
+    }
+
+    pub fn len(&self) -> usize {
+        self.places.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.places.is_empty()
+    }
+
+    /// the place nearest to `location` with a population of at least `min_population`, and its
+    /// great-circle distance in km
+    pub fn nearest(&self, location: (f32, f32), min_population: u64) -> Option<(&Place, f32)> {
+        self.places
+            .iter()
+            .filter(|place| place.population >= min_population)
+            .map(|place| (place, haversine_km(location, place.location)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+}
+
+/// append the nearest place in `places` to the coordinate in `column` as `place`, `country`,
+/// `admin1`, `feature_class`, `feature_code` and `distance_km` columns, streaming the result to
+/// `output` (stdout if None). Places with fewer than `min_population` people are skipped, so a
+/// threshold gives the nearest significant city rather than whatever hamlet happens to be closest.
+/// Rows without a valid coordinate or a large enough place get `invalid_result` as the place and
+/// empty columns otherwise.
+///
+/// Returns the number of rows that didn't resolve to a place.
+pub fn reverse_geocode(
+    conf: &Config,
+    column: usize,
+    places: &Places,
+    min_population: u64,
+    invalid_result: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<u64> {
+    let mut rdr = conf.reader()?;
+    let mut headers = rdr.headers()?.clone();
+    if column >= headers.len() {
+        return Err(anyhow!(
+            "column {column} is out of range, the file has {} columns",
+            headers.len()
+        ));
+    }
+
+    let mut wtr = util::csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    if !conf.no_headers {
+        for name in [
+            "place",
+            "country",
+            "admin1",
+            "feature_class",
+            "feature_code",
+            "distance_km",
+        ] {
+            headers.push_field(name);
+        }
+        wtr.write_record(&headers)?;
+    }
+
+    let jobs = util::njobs(None);
+    let batch_size = util::optimal_batch_size(conf, 1, jobs);
+    let batches = util::par_record_batches(conf, &mut rdr, batch_size, jobs, |record| {
+        let mut record = record.clone();
+        let nearest = record
+            .get(column)
+            .and_then(parse_location)
+            .and_then(|location| places.nearest(location, min_population));
+        match nearest {
+            Some((place, distance)) => {
+                record.push_field(&place.name);
+                record.push_field(&place.country);
+                record.push_field(&place.admin1);
+                record.push_field(&place.feature_class);
+                record.push_field(&place.feature_code);
+                record.push_field(&format!("{distance:.3}"));
+            },
+            None => {
+                record.push_field(invalid_result);
+                for _ in 0..5 {
+                    record.push_field("");
+                }
+            },
+        }
+        (record, nearest.is_some())
+    })?;
+
+    let mut unresolved = 0_u64;
+    for batch in batches {
+        for (record, resolved) in batch? {
+            unresolved += u64::from(!resolved);
+            wtr.write_record(&record)?;
+        }
+    }
+    wtr.flush()?;
+    Ok(unresolved)
+}
+
+/// a country from GeoNames' `countryInfo.txt`
+#[derive(Debug, Clone, PartialEq, serde::Serialize)]
+pub struct Country {
+    pub iso2:          String,
+    pub iso3:          String,
+    pub fips:          String,
+    pub name:          String,
+    pub continent:     String,
+    pub currency_code: String,
+    pub currency_name: String,
+    pub phone:         String,
+    pub tld:           String,
+    /// comma-separated language tags, e.g. en-GB,cy-GB,gd
+    pub languages:     String,
+    /// comma-separated ISO 3166-1 alpha-2 codes of the bordering countries
+    pub neighbours:    String,
+}
+
+/// the country reference table of GeoNames' `countryInfo.txt`, in file order
+#[derive(Debug, Default, Clone)]
+pub struct Countries {
+    countries: Vec<Country>,
+}
+
+impl Countries {
+    /// load GeoNames' tab-separated `countryInfo.txt` (ISO, ISO3, ISO-Numeric, fips, Country,
+    /// Capital, Area, Population, Continent, tld, CurrencyCode, CurrencyName, Phone, Postal Code
+    /// Format, Postal Code Regex, Languages, geonameid, neighbours, EquivalentFipsCode). Lines
+    /// starting with # are comments.
+    pub fn from_geonames_file(path: &Path) -> anyhow::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut countries = Vec::new();
+        for line in contents
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        {
+            let fields: Vec<&str> = line.split('\t').collect();
+            if fields.len() < 18 {
+                return Err(anyhow!("not a GeoNames country info line: {line}"));
+            }
+            countries.push(Country {
+                iso2:          fields[0].to_string(),
+                iso3:          fields[1].to_string(),
+                fips:          fields[3].to_string(),
+                name:          fields[4].to_string(),
+                continent:     fields[8].to_string(),
+                currency_code: fields[10].to_string(),
+                currency_name: fields[11].to_string(),
+                phone:         fields[12].to_string(),
+                tld:           fields[9].to_string(),
+                languages:     fields[15].to_string(),
+                neighbours:    fields[17].to_string(),
+            });
+        }
+        Ok(Countries { countries })
+    }
+
+    pub fn len(&self) -> usize {
+        self.countries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.countries.is_empty()
+    }
+
+    /// the country with the ISO 3166-1 alpha-2 or alpha-3 code `code`, in any case
+    pub fn get(&self, code: &str) -> Option<&Country> {
+        let code = code.trim();
+        self.countries.iter().find(|country| {
+            country.iso2.eq_ignore_ascii_case(code) || country.iso3.eq_ignore_ascii_case(code)
+        })
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Country> {
+        self.countries.iter()
+    }
+}
+
+/// how `dump_countries` writes the country table
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CountryDumpFormat {
+    Csv,
+    /// a JSON array of country objects
+    Json,
+}
+
+/// write every country in `countries` to `output` (stdout if None), e.g. to join against
+/// offline. Returns the number of countries written.
+pub fn dump_countries(
+    countries: &Countries,
+    format: CountryDumpFormat,
+    output: Option<&Path>,
+) -> anyhow::Result<u64> {
+    match format {
+        CountryDumpFormat::Csv => {
+            let mut wtr = util::csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+            for country in countries.iter() {
+                wtr.serialize(country)?;
+            }
+            wtr.flush()?;
+        },
+        CountryDumpFormat::Json => {
+            let output = output.map(|p| p.display().to_string());
+            let mut wtr = Config::builder().maybe_path(output).build().io_writer()?;
+            serde_json::to_writer_pretty(&mut wtr, &countries.countries)?;
+            writeln!(wtr)?;
+            wtr.flush()?;
+        },
+    }
+    Ok(countries.len() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    const NEW_YORK: (f32, f32) = (40.7128, -74.0060);
+    const LONDON: (f32, f32) = (51.5074, -0.1278);
+    const PARIS: (f32, f32) = (48.8566, 2.3522);
+    const SYDNEY: (f32, f32) = (-33.8688, 151.2093);
+    const TOKYO: (f32, f32) = (35.6762, 139.6503);
+
+    fn assert_close(actual: f32, expected: f32, tolerance: f32) {
+        assert!(
+            (actual - expected).abs() <= tolerance,
+            "expected {expected} ± {tolerance}, got {actual}"
+        );
+    }
+
+    #[test]
+    fn test_haversine_km() {
+        assert_close(haversine_km(NEW_YORK, LONDON), 5570.2, 1.0);
+        assert_close(haversine_km(LONDON, PARIS), 343.6, 0.5);
+        assert_close(haversine_km(SYDNEY, TOKYO), 7825.8, 1.0);
+    }
+
+    #[test]
+    fn test_haversine_km_symmetric_and_zero() {
+        assert_close(
+            haversine_km(NEW_YORK, LONDON),
+            haversine_km(LONDON, NEW_YORK),
+            0.01,
+        );
+        assert_close(haversine_km(PARIS, PARIS), 0.0, f32::EPSILON);
+    }
+
+    #[test]
+    fn test_initial_bearing_deg() {
+        assert_close(initial_bearing_deg(NEW_YORK, LONDON), 51.2, 0.1);
+        assert_close(initial_bearing_deg(LONDON, PARIS), 148.1, 0.1);
+        assert_close(initial_bearing_deg(PARIS, LONDON), 330.0, 0.1);
+        assert_close(initial_bearing_deg(SYDNEY, TOKYO), 350.0, 0.1);
+    }
+
+    #[test]
+    fn test_parse_location() {
+        assert_eq!(parse_location("40.7128, -74.0060"), Some(NEW_YORK));
+        assert_eq!(parse_location("(51.5074,-0.1278)"), Some(LONDON));
+        assert_eq!(
+            parse_location("The incident was at 48.8566, 2.3522 yesterday"),
+            Some(PARIS)
+        );
+        assert_eq!(parse_location("91.0, 0.0"), None);
+        assert_eq!(parse_location("0.0, 181.0"), None);
+        assert_eq!(parse_location("no coordinate here"), None);
+    }
+
+    #[test]
+    fn test_distance_to_reference() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("events.csv");
+        fs::write(
+            &input,
+            "event,location\nbriefing,\"51.5074, -0.1278\"\nmeeting,\"48.8566, \
+             2.3522\"\nunknown,\n",
+        )
+        .unwrap();
+        let output = dir.path().join("distances.csv");
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+
+        let within = distance_to_reference(&conf, 1, LONDON, 100.0, "n/a", Some(&output)).unwrap();
+        assert_eq!(within, 1);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "event,location,distance_km,within_radius\nbriefing,\"51.5074, \
+             -0.1278\",0.000,true\nmeeting,\"48.8566, 2.3522\",343.556,false\nunknown,,n/a,\n"
+        );
+
+        assert!(distance_to_reference(&conf, 2, LONDON, 100.0, "", Some(&output)).is_err());
+        assert!(distance_to_reference(&conf, 1, LONDON, -1.0, "", Some(&output)).is_err());
+    }
+
+    #[test]
+    fn test_admin1_table_resolve() {
+        let divisions = [
+            ("US.NY", "New York"),
+            ("US.GA", "Georgia"),
+            ("BR.07", "Distrito Federal"),
+            ("MX.09", "Distrito Federal"),
+        ];
+        let table = Admin1Table::from_divisions(divisions, None);
+        assert_eq!(table.len(), 4);
+        assert_eq!(table.resolve("us.ny", None).as_deref(), Some("US.NY"));
+        assert_eq!(table.resolve(" new YORK ", None).as_deref(), Some("US.NY"));
+        assert_eq!(table.resolve("US.ZZ", None), None);
+        // a bare code needs a country, and an ambiguous name doesn't resolve
+        assert_eq!(table.resolve("GA", None), None);
+        let us = ["US".to_string()];
+        assert_eq!(table.resolve("GA", Some(&us)).as_deref(), Some("US.GA"));
+        assert_eq!(table.resolve("Distrito Federal", None), None);
+
+        let mexico = ["mx".to_string()];
+        let table = Admin1Table::from_divisions(divisions, Some(&mexico));
+        assert_eq!(table.len(), 1);
+        assert_eq!(
+            table.resolve("distrito federal", Some(&mexico)).as_deref(),
+            Some("MX.09")
+        );
+    }
+
+    #[test]
+    fn test_normalize_admin1() {
+        let dir = tempfile::tempdir().unwrap();
+        let admin1_codes = dir.path().join("admin1CodesASCII.txt");
+        fs::write(
+            &admin1_codes,
+            "US.NY\tNew York\tNew York\t5128638\nMX.09\tCiudad de México\tCiudad de \
+             Mexico\t3527646\n",
+        )
+        .unwrap();
+        let table = Admin1Table::from_geonames_file(&admin1_codes, None).unwrap();
+        assert_eq!(
+            table.resolve("Ciudad de Mexico", None).as_deref(),
+            Some("MX.09")
+        );
+
+        let input = dir.path().join("states.csv");
+        fs::write(&input, "id,state\n1,ny\n2,New York\n3,mx.09\n4,Atlantis\n").unwrap();
+        let output = dir.path().join("normalized.csv");
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        let us = ["US".to_string()];
+        let unresolved =
+            normalize_admin1(&conf, 1, &table, Some(&us), "<invalid>", Some(&output)).unwrap();
+        assert_eq!(unresolved, 1);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "id,state\n1,US.NY\n2,US.NY\n3,MX.09\n4,<invalid>\n"
+        );
+    }
+
+    /// a GeoNames gazetteer dump with two places near the scene, one far away and a river
+    fn write_gazetteer(dir: &Path) -> std::path::PathBuf {
+        // the dump is tab-separated, | keeps the columns readable here
+        let places = [
+            "2643743|London|London||51.50853|-0.12574|P|PPLC|GB||ENG|GLA|||8961989||25||",
+            "9999001|Little Hamlet|Little Hamlet||51.5112|-0.1318|P|PPL|GB||ENG||||120||30||",
+            "2988507|Paris|Paris||48.85341|2.3488|P|PPLC|FR||11|75|751|75056|2138551||42||",
+            "2635978|River Thames|River Thames||51.5102|-0.1302|H|STM|GB||ENG||||0||5||",
+        ];
+        let path = dir.join("cities.txt");
+        fs::write(&path, places.join("\n").replace('|', "\t")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_places_nearest() {
+        let dir = tempfile::tempdir().unwrap();
+        let gazetteer = write_gazetteer(dir.path());
+        let places = Places::from_geonames_file(&gazetteer, None).unwrap();
+        // the river isn't a populated place
+        assert_eq!(places.len(), 3);
+
+        let scene = (51.5101, -0.1301);
+        let (place, distance) = places.nearest(scene, 0).unwrap();
+        assert_eq!(place.name, "Little Hamlet");
+        assert_close(distance, 0.170, 0.001);
+        let (place, distance) = places.nearest(scene, 1000).unwrap();
+        assert_eq!(
+            (place.name.as_str(), place.admin1.as_str(), place.population),
+            ("London", "GB.ENG", 8_961_989)
+        );
+        // a capital rather than just any populated place
+        assert_eq!(
+            (place.feature_class.as_str(), place.feature_code.as_str()),
+            ("P", "PPLC")
+        );
+        assert_close(distance, 0.349, 0.001);
+        assert!(places.nearest(scene, 10_000_000).is_none());
+
+        let france = ["fr".to_string()];
+        let places = Places::from_geonames_file(&gazetteer, Some(&france)).unwrap();
+        assert_eq!(places.len(), 1);
+        assert_eq!(places.nearest(scene, 0).unwrap().0.name, "Paris");
+    }
+
+    #[test]
+    fn test_reverse_geocode_min_population() {
+        let dir = tempfile::tempdir().unwrap();
+        let places = Places::from_geonames_file(&write_gazetteer(dir.path()), None).unwrap();
+        let input = dir.path().join("events.csv");
+        fs::write(
+            &input,
+            "event,location\nbriefing,\"51.5101, -0.1301\"\nmeeting,\"48.8566, \
+             2.3522\"\nunknown,\n",
+        )
+        .unwrap();
+        let output = dir.path().join("places.csv");
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+
+        let unresolved =
+            reverse_geocode(&conf, 1, &places, 1000, "<invalid>", Some(&output)).unwrap();
+        assert_eq!(unresolved, 1);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "event,location,place,country,admin1,feature_class,feature_code,distance_km\nbriefing,\
+             \"51.5101, -0.1301\",London,GB,GB.ENG,P,PPLC,0.349\nmeeting,\"48.8566, \
+             2.3522\",Paris,FR,FR.11,P,PPLC,0.434\nunknown,,<invalid>,,,,,\n"
+        );
+
+        assert!(reverse_geocode(&conf, 2, &places, 0, "", Some(&output)).is_err());
+    }
+
+    /// two lines of GeoNames' countryInfo.txt under its comment header
+    fn write_country_info(dir: &Path) -> std::path::PathBuf {
+        // the file is tab-separated, | keeps the columns readable here
+        let lines = [
+            "#ISO|ISO3|ISO-Numeric|fips|Country|Capital|Area(in sq \
+             km)|Population|Continent|tld|CurrencyCode|CurrencyName|Phone|Postal Code \
+             Format|Postal Code Regex|Languages|geonameid|neighbours|EquivalentFipsCode",
+            "GB|GBR|826|UK|United Kingdom|London|244820|66488991|EU|.uk|GBP|Pound|44|@# \
+             #@@|^(.*)$|en-GB,cy-GB,gd|2635167|IE|",
+            "NZ|NZL|554|NZ|New \
+             Zealand|Wellington|268680|4885500|OC|.nz|NZD|Dollar|64|####|^(\\d{4})$|en-NZ,\
+             mi|2186224||",
+        ];
+        let path = dir.join("countryInfo.txt");
+        fs::write(&path, lines.join("\n").replace('|', "\t")).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_countries_from_geonames_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let countries = Countries::from_geonames_file(&write_country_info(dir.path())).unwrap();
+        assert_eq!(countries.len(), 2);
+        let gb = countries.get("gbr").unwrap();
+        assert_eq!(
+            (gb.iso2.as_str(), gb.fips.as_str(), gb.tld.as_str()),
+            ("GB", "UK", ".uk")
+        );
+        assert_eq!(gb.languages, "en-GB,cy-GB,gd");
+        assert_eq!(countries.get("NZ").unwrap().neighbours, "");
+        assert!(countries.get("FR").is_none());
+    }
+
+    #[test]
+    fn test_dump_countries() {
+        let dir = tempfile::tempdir().unwrap();
+        let countries = Countries::from_geonames_file(&write_country_info(dir.path())).unwrap();
+        let output = dir.path().join("countries.csv");
+        assert_eq!(
+            dump_countries(&countries, CountryDumpFormat::Csv, Some(&output)).unwrap(),
+            2
+        );
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "iso2,iso3,fips,name,continent,currency_code,currency_name,phone,tld,languages,\
+             neighbours\nGB,GBR,UK,United \
+             Kingdom,EU,GBP,Pound,44,.uk,\"en-GB,cy-GB,gd\",IE\nNZ,NZL,NZ,New \
+             Zealand,OC,NZD,Dollar,64,.nz,\"en-NZ,mi\",\n"
+        );
+
+        let output = dir.path().join("countries.json");
+        dump_countries(&countries, CountryDumpFormat::Json, Some(&output)).unwrap();
+        let json: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(json[1]["iso3"], "NZL");
+        assert_eq!(json[0]["neighbours"], "IE");
+        assert_eq!(json.as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_initial_bearing_deg_cardinal() {
+        assert_close(initial_bearing_deg((0.0, 0.0), (10.0, 0.0)), 0.0, 0.01);
+        assert_close(initial_bearing_deg((0.0, 0.0), (0.0, 10.0)), 90.0, 0.01);
+        assert_close(initial_bearing_deg((10.0, 0.0), (0.0, 0.0)), 180.0, 0.01);
+        assert_close(initial_bearing_deg((0.0, 10.0), (0.0, 0.0)), 270.0, 0.01);
+    }
+}
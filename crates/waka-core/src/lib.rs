@@ -3,6 +3,7 @@ pub mod config;
 pub mod count;
 pub mod dedup;
 pub mod exclude;
+pub mod geo;
 pub mod index;
 pub mod joinp;
 pub mod lookup;
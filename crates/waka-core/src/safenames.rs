@@ -11,12 +11,13 @@ use crate::{
 
 #[derive(Deserialize)]
 struct Args {
-    arg_input:      Option<String>,
-    flag_mode:      String,
-    flag_reserved:  String,
-    flag_prefix:    String,
-    flag_output:    Option<String>,
-    flag_delimiter: Option<Delimiter>,
+    arg_input:       Option<String>,
+    flag_mode:       String,
+    flag_reserved:   String,
+    flag_prefix:     String,
+    flag_snake_case: bool,
+    flag_output:     Option<String>,
+    flag_delimiter:  Option<Delimiter>,
 }
 
 #[derive(PartialEq)]
@@ -78,11 +79,13 @@ pub fn run(argv: &[&str]) -> CliResult<()> {
 
     let (safe_headers, changed_count) = util::safe_header_names(
         &noquote_headers,
-        true,
-        safenames_mode == SafeNameMode::Conditional,
-        Some(reserved_names_vec).as_ref(),
-        &args.flag_prefix,
-        false,
+        &util::SafeNameOptions::builder()
+            .check_first_char(true)
+            .conditional(safenames_mode == SafeNameMode::Conditional)
+            .reserved_names(&reserved_names_vec)
+            .unsafe_prefix(&args.flag_prefix)
+            .snake_case(args.flag_snake_case)
+            .build(),
     );
     if let SafeNameMode::Conditional | SafeNameMode::Always = safenames_mode {
         headers.clear();
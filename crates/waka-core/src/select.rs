@@ -18,11 +18,12 @@ pub struct SelectColumns {
 }
 
 impl SelectColumns {
+    /// Parse a selection such as `1-10,!3`. A `!` in front of a later selector removes its
+    /// columns from the selection, while a `!` in front of the whole selection inverts it,
+    /// so `!b,d` keeps every column except b and d.
     pub fn parse(mut s: &str) -> anyhow::Result<SelectColumns> {
-        let is_empty = s.is_empty();
-        let bytes = s.as_bytes();
-        let invert = if !is_empty && bytes[0] == b'!' {
-            s = &s[1..];
+        let invert = if let Some(rest) = s.strip_prefix('!') {
+            s = rest;
             true
         } else {
             false
@@ -48,19 +49,22 @@ impl SelectColumns {
         }
 
         let mut map = vec![];
+        let mut excluded: HashSet<usize> = HashSet::default();
         for sel in &self.selectors {
-            let idxs = sel.indices(first_record, use_names);
-            map.extend(idxs?);
+            let idxs = sel.indices(first_record, use_names)?;
+            // an inverted selection drops every selector, with or without its own `!`
+            if self.invert || matches!(sel, Selector::Exclude(_)) {
+                excluded.extend(idxs);
+            } else {
+                map.extend(idxs);
+            }
         }
+        // an inverted selection keeps the remaining columns in file order
         if self.invert {
-            let set: HashSet<_> = map.into_iter().collect();
-            let mut map = vec![];
-            for i in 0..first_record.len() {
-                if !set.contains(&i) {
-                    map.push(i);
-                }
-            }
-            return Ok(Selection(map));
+            map = (0..first_record.len()).collect();
+        }
+        if !excluded.is_empty() {
+            map.retain(|i| !excluded.contains(i));
         }
         Ok(Selection(map))
     }
@@ -106,55 +110,96 @@ impl SelectorParser {
     }
 
     fn parse(&mut self) -> anyhow::Result<Vec<Selector>> {
-        if (self.chars.first(), self.chars.last()) == (Some(&'/'), Some(&'/')) {
-            if self.chars.len() == 2 {
-                return Err(anyhow!(
-                    "Empty regex: {}",
-                    self.chars.iter().collect::<String>()
-                ));
-            }
-            let re: String = self.chars[1..(self.chars.len() - 1)].iter().collect();
-            let Ok(regex) = Regex::new(&re) else {
-                return Err(anyhow!("Invalid regex: {re}"));
-            };
-            return Ok(vec![Selector::Regex(regex)]);
-        }
-
         let mut sels = vec![];
         loop {
             if self.cur().is_none() {
                 break;
             }
-            let f1: OneSelector = if self.cur() == Some('-') {
-                OneSelector::Start
-            } else {
-                self.parse_one()?
-            };
-            let f2: Option<OneSelector> = if self.cur() == Some('-') {
+            // `!sel` removes the columns of `sel` from the selection, e.g. `1-10,!3`
+            let exclude = self.cur() == Some('!');
+            if exclude {
                 self.bump();
-                Some(if self.is_end_of_selector() {
-                    OneSelector::End
-                } else {
-                    self.parse_one()?
-                })
-            } else {
-                None
-            };
-            if !self.is_end_of_selector() {
-                return Err(anyhow!(
-                    "Expected end of field but got '{}' instead.",
-                    self.cur().unwrap()
-                ));
             }
-            sels.push(match f2 {
-                Some(end) => Selector::Range(f1, end),
-                None => Selector::One(f1),
+            let sel = self.parse_selector()?;
+            sels.push(if exclude {
+                Selector::Exclude(Box::new(sel))
+            } else {
+                sel
             });
             self.bump();
         }
         Ok(sels)
     }
 
+    /// Parse one comma-separated selector: a column, a range or a regex.
+    fn parse_selector(&mut self) -> anyhow::Result<Selector> {
+        // `/pattern/` selects every column whose name matches, e.g. `id,/_time$/`
+        if self.cur() == Some('/') {
+            return self.parse_regex();
+        }
+        let f1: OneSelector = if self.cur() == Some('-') {
+            OneSelector::Start
+        } else {
+            self.parse_one()?
+        };
+        let f2: Option<OneSelector> = if self.cur() == Some('-') {
+            self.bump();
+            Some(if self.is_end_of_selector() {
+                OneSelector::End
+            } else {
+                self.parse_one()?
+            })
+        } else {
+            None
+        };
+        if !self.is_end_of_selector() {
+            return Err(anyhow!(
+                "Expected end of field but got '{}' instead.",
+                self.cur().unwrap()
+            ));
+        }
+        Ok(match f2 {
+            Some(end) => Selector::Range(f1, end),
+            None => Selector::One(f1),
+        })
+    }
+
+    /// Parse a `/pattern/` selector. The regex ends at the first unescaped `/` that is
+    /// followed by `,` or the end of input, so patterns may contain `/` and `,`.
+    fn parse_regex(&mut self) -> anyhow::Result<Selector> {
+        assert_eq!(self.cur().unwrap(), '/');
+        let start = self.pos;
+        self.bump();
+
+        let mut re = String::new();
+        let mut escaped = false;
+        loop {
+            match self.cur() {
+                None => {
+                    return Err(anyhow!("Unclosed regex, missing closing /."));
+                },
+                Some('/') if !escaped && self.peek().is_none_or(|c| c == ',') => {
+                    self.bump();
+                    break;
+                },
+                Some(c) => {
+                    escaped = c == '\\' && !escaped;
+                    re.push(c);
+                    self.bump();
+                },
+            }
+        }
+        if re.is_empty() {
+            return Err(anyhow!(
+                "Empty regex: {}",
+                self.chars[start..self.pos].iter().collect::<String>()
+            ));
+        }
+        Regex::new(&re)
+            .map(Selector::Regex)
+            .map_err(|err| anyhow!("Invalid regex: {re}: {err}"))
+    }
+
     fn parse_one(&mut self) -> anyhow::Result<OneSelector> {
         let name = if self.cur() == Some('"') {
             self.bump();
@@ -243,6 +288,10 @@ impl SelectorParser {
         self.chars.get(self.pos).copied()
     }
 
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos + 1).copied()
+    }
+
     fn is_end_of_field(&self) -> bool {
         self.cur().is_none_or(|c| c == ',' || c == '-')
     }
@@ -263,6 +312,7 @@ pub enum Selector {
     One(OneSelector),
     Range(OneSelector, OneSelector),
     Regex(Regex),
+    Exclude(Box<Selector>),
 }
 
 #[derive(Clone)]
@@ -312,6 +362,7 @@ impl Selector {
                 }
                 Ok(inds)
             },
+            Selector::Exclude(ref sel) => sel.indices(first_record, use_names),
         }
     }
 }
@@ -377,6 +428,7 @@ impl fmt::Debug for Selector {
             Selector::One(ref sel) => sel.fmt(f),
             Selector::Range(ref s, ref e) => write!(f, "Range({s:?}, {e:?})"),
             Selector::Regex(ref re) => re.fmt(f),
+            Selector::Exclude(ref sel) => write!(f, "Not({sel:?})"),
         }
     }
 }
@@ -514,3 +566,60 @@ impl ops::Deref for NormalSelection {
         &self.0
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(sel: &str, headers: &[&str]) -> anyhow::Result<Vec<usize>> {
+        let headers = csv::ByteRecord::from(headers.to_vec());
+        let selection = SelectColumns::parse(sel)?.selection(&headers, true)?;
+        Ok(selection.to_vec())
+    }
+
+    #[test]
+    fn test_regex_selector() {
+        let headers = ["id", "created_time", "name", "modified_time"];
+        assert_eq!(select("/_time$/", &headers).unwrap(), vec![1, 3]);
+        assert_eq!(select("id,/_time$/", &headers).unwrap(), vec![0, 1, 3]);
+        assert_eq!(select("/^(id|name)$/,4", &headers).unwrap(), vec![0, 2, 3]);
+        // commas and slashes inside the pattern
+        assert_eq!(select("/e{1,2}d_/", &headers).unwrap(), vec![1, 3]);
+        assert_eq!(select("/a\\/b/", &["a/b", "c"]).unwrap(), vec![0]);
+    }
+
+    #[test]
+    fn test_regex_selector_errors() {
+        let headers = ["id", "name"];
+        let err = |sel: &str| select(sel, &headers).unwrap_err().to_string();
+        assert_eq!(err("//"), "Empty regex: //");
+        assert!(err("/(/").starts_with("Invalid regex: ("));
+        assert_eq!(err("id,/name"), "Unclosed regex, missing closing /.");
+        assert_eq!(
+            err("/_time$/"),
+            "Selector regex '_time$' does not match any columns in the CSV header."
+        );
+    }
+
+    #[test]
+    fn test_exclude_selector() {
+        let headers = ["a", "b", "c", "d", "e"];
+        assert_eq!(select("1-_,!c", &headers).unwrap(), vec![0, 1, 3, 4]);
+        assert_eq!(select("a-d,!2-3", &headers).unwrap(), vec![0, 3]);
+        // order of the kept columns follows the positive selectors
+        assert_eq!(select("e,a,c,!a", &headers).unwrap(), vec![4, 2]);
+        assert_eq!(select("/[a-c]/,!/b/", &headers).unwrap(), vec![0, 2]);
+        // a leading `!` inverts the whole selection
+        assert_eq!(select("!b,d", &headers).unwrap(), vec![0, 2, 4]);
+        assert_eq!(select("!", &headers).unwrap(), Vec::<usize>::new());
+        assert!(select("a,!z", &headers).is_err());
+    }
+
+    #[test]
+    fn test_only_exclusions() {
+        let headers = ["a", "b", "c", "d"];
+        assert_eq!(select("!a", &headers).unwrap(), vec![1, 2, 3]);
+        assert_eq!(select("!a,!b", &headers).unwrap(), vec![2, 3]);
+        assert_eq!(select("!2-3,!d", &headers).unwrap(), vec![0]);
+    }
+}
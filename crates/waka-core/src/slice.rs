@@ -30,7 +30,7 @@ pub fn page(conf: &Config, start: usize, len: usize) -> anyhow::Result<Vec<csv::
     if let Some(mut idx) = conf.indexed()? {
         idx.seek(start as u64)?;
         let mut out: Vec<csv::ByteRecord> = Vec::with_capacity(len.min(1024));
-        for r in idx.byte_records().take(len) {
+        for r in conf.byte_records(&mut idx).take(len) {
             out.push(r?);
         }
         return Ok(out);
@@ -42,7 +42,7 @@ pub fn page(conf: &Config, start: usize, len: usize) -> anyhow::Result<Vec<csv::
     let _ = rdr.headers();
 
     let mut out: Vec<csv::ByteRecord> = Vec::with_capacity(len.min(1024));
-    for r in rdr.byte_records().skip(start).take(len) {
+    for r in conf.byte_records(&mut rdr).skip(start).take(len) {
         out.push(r?);
     }
     Ok(out)
@@ -98,6 +98,8 @@ impl Args {
                 self.flag_no_headers,
                 &headers,
                 records,
+                self.rconfig().get_null_values(),
+                false,
             )
         } else {
             let mut wtr = self.wconfig().writer()?;
@@ -149,6 +151,8 @@ impl Args {
                 self.flag_no_headers,
                 &headers,
                 records.into_iter(),
+                self.rconfig().get_null_values(),
+                false,
             )
         } else {
             let mut wtr = self.wconfig().writer()?;
@@ -388,8 +388,11 @@ pub fn run(args: Args) -> anyhow::Result<()> {
 /// - `reverse`: descending order
 /// - `ignore_case`: case-insensitive (where applicable)
 /// - `faster`: use Rayon `par_sort_unstable_by` (non-allocating comparator)
+///
+/// `delimiter` overrides the delimiter detected for `path`.
 pub fn sort_indices_single_col(
     path: &str,
+    delimiter: Option<Delimiter>,
     col_index: usize,
     numeric: bool,
     natural: bool,
@@ -399,7 +402,7 @@ pub fn sort_indices_single_col(
 ) -> anyhow::Result<Vec<u64>> {
     use rayon::slice::ParallelSliceMut;
 
-    let rconfig = Config::builder().path(path).build();
+    let rconfig = Config::builder().path(path).build().delimiter(delimiter);
     let mut rdr = rconfig.reader()?;
 
     // Collect all data rows (header is excluded by `records()`/`byte_records()`).
@@ -1291,6 +1291,8 @@ impl Args {
         // and hopefully the compiler will optimize this and use registers in the hot loop
         let infer_boolean = self.flag_infer_boolean;
         let prefer_dmy = self.flag_prefer_dmy;
+        // fields matching a configured null marker are counted as empty (TNull)
+        let null_values = self.rconfig().get_null_values().to_vec();
 
         let mut i;
         #[allow(unused_assignments)]
@@ -1301,7 +1303,11 @@ impl Args {
             // we know we don't need to bounds check
             unsafe {
                 current_row = row.unwrap_unchecked();
-                for field in sel.select(&current_row) {
+                for mut field in sel.select(&current_row) {
+                    if !null_values.is_empty() && null_values.iter().any(|n| n.as_slice() == field)
+                    {
+                        field = b"";
+                    }
                     stats.get_unchecked_mut(i).add(
                         field,
                         *infer_date_flags.get_unchecked(i),
@@ -3,7 +3,7 @@ use std::os::unix::process::ExitStatusExt;
 use std::{
     borrow::Cow,
     cmp::min,
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     env,
     fs::{self, File},
     io::{BufRead, BufReader, BufWriter, Read, Write},
@@ -14,10 +14,13 @@ use std::{
 };
 
 use anyhow::anyhow;
+use bon::Builder;
 use csv::ByteRecord;
 use docopt::Docopt;
 use filetime::FileTime;
+use indicatif::ProgressBar;
 use polars::prelude::Schema;
+use qsv_sniffer::{SampleSize, Sniffer, metadata::Quote};
 use reqwest::Client;
 use serde::de::{Deserialize, DeserializeOwned, Deserializer, Error};
 use sysinfo::System;
@@ -43,13 +46,30 @@ macro_rules! regex_oncelock {
     }};
 }
 
-// leave at least 20% of the available memory free
-const DEFAULT_FREEMEMORY_HEADROOM_PCT: u8 = 20;
-
 const DEFAULT_BATCH_SIZE: usize = 50_000;
 
 const DEFAULT_STATSCACHE_MODE: &str = "auto";
 
+// default maximum length of the names produced by safe_header_names
+pub const DEFAULT_SAFE_NAME_MAX_LEN: usize = 60;
+
+// maximum number of output files split_by_column keeps open at once
+pub const SPLIT_BY_COLUMN_MAX_WRITERS: usize = 500;
+
+// 13-16 digit runs, optionally grouped by 4; only masked when they pass the Luhn check
+const CARD_NUMBER_PATTERN: &str = r"\b\d{4}[ -]?\d{4}[ -]?\d{4}[ -]?\d{1,4}\b";
+
+/// the patterns redact masks unless told not to: email addresses, US SSNs and payment card
+/// numbers
+pub const DEFAULT_REDACT_PATTERNS: &[&str] = &[
+    r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}",
+    r"\b\d{3}-\d{2}-\d{4}\b",
+    CARD_NUMBER_PATTERN,
+];
+
+// what redact replaces each match with
+const REDACT_MASK: &[u8] = b"***";
+
 static ROW_COUNT: OnceLock<Option<u64>> = OnceLock::new();
 
 static JOBS_TO_USE: OnceLock<usize> = OnceLock::new();
@@ -205,6 +225,24 @@ pub fn njobs(flag_jobs: Option<usize>) -> usize {
     *njobs_result
 }
 
+/// Create a CSV writer for `output` (stdout if None) with the given quote style.
+/// If `delim` is None, the delimiter is inferred from the output file extension
+/// (e.g. .tsv, .ssv) as with any other Config writer. "sink" and snappy (.sz)
+/// outputs are supported too.
+pub fn csv_writer(
+    output: Option<&Path>,
+    delim: Option<Delimiter>,
+    quote_style: csv::QuoteStyle,
+) -> std::io::Result<csv::Writer<Box<dyn std::io::Write + 'static>>> {
+    let output = output.map(|p| p.display().to_string());
+    Config::builder()
+        .maybe_path(output)
+        .build()
+        .delimiter(delim)
+        .quote_style(quote_style)
+        .writer()
+}
+
 pub fn timeout_secs(timeout: u16) -> anyhow::Result<u64> {
     let timeout = match env::var("QSV_TIMEOUT") {
         Ok(val) => val.parse::<u16>().unwrap_or(30_u16),
@@ -249,6 +287,7 @@ pub fn version() -> String {
     let total_mem = sys.total_memory();
     let free_swap = sys.free_swap();
     let max_file_size = mem_file_check(Path::new(""), true, false).unwrap_or(0) as u64;
+    let headroom_pct = config::memory_headroom_pct();
 
     let malloc_kind = "mimalloc";
 
@@ -266,8 +305,8 @@ pub fn version() -> String {
         if pre.is_empty() {
             format!(
                 "{qsvtype} {maj}.{min}.{pat}-{malloc_kind}-{enabled_features}{maxjobs}-{numcpus};\
-                 {max_file_size}-{free_swap}-{avail_mem}-{total_mem} ({TARGET} compiled with Rust \
-                 {rustversion})",
+                 {max_file_size}-{free_swap}-{avail_mem}-{total_mem}-{headroom_pct}% ({TARGET} \
+                 compiled with Rust {rustversion})",
                 maxjobs = max_jobs(),
                 numcpus = num_cpus(),
             )
@@ -275,8 +314,8 @@ pub fn version() -> String {
             format!(
                 "{qsvtype} {maj}.{min}.\
                  {pat}-{pre}-{malloc_kind}-{enabled_features}{maxjobs}-{numcpus};\
-                 {max_file_size}-{free_swap}-{avail_mem}-{total_mem} ({TARGET} compiled with Rust \
-                 {rustversion})",
+                 {max_file_size}-{free_swap}-{avail_mem}-{total_mem}-{headroom_pct}% ({TARGET} \
+                 compiled with Rust {rustversion})",
                 maxjobs = max_jobs(),
                 numcpus = num_cpus(),
             )
@@ -336,6 +375,42 @@ pub fn count_rows(conf: &Config) -> anyhow::Result<u64> {
         .ok_or_else(|| anyhow!("Unable to get row count"))
 }
 
+/// Row count of a CSV that also tells a header-only file apart from an empty one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowCount {
+    /// number of data rows, excluding the header row
+    pub data_rows:  u64,
+    /// whether the file has a (non-empty) header row. Always false with --no-headers.
+    pub has_header: bool,
+}
+
+/// Count rows like count_rows, but also report whether a header row is present,
+/// so a 1-line header-only CSV can be told apart from a truly empty file.
+/// Unlike count_rows, the result is not cached in ROW_COUNT, so this can be called
+/// for different files in long-running processes like the GUI.
+pub fn count_rows_detail(conf: &Config) -> anyhow::Result<RowCount> {
+    let data_rows = match conf.indexed().unwrap_or(None) {
+        Some(idx) => idx.count(),
+        None => {
+            count_rows_with_best_method(conf).ok_or_else(|| anyhow!("Unable to get row count"))?
+        },
+    };
+
+    let has_header = if conf.no_headers {
+        false
+    } else if data_rows > 0 {
+        true
+    } else {
+        let mut rdr = conf.clone().skip_format_check(true).reader()?;
+        !rdr.byte_headers()?.is_empty()
+    };
+
+    Ok(RowCount {
+        data_rows,
+        has_header,
+    })
+}
+
 fn count_rows_with_best_method(conf: &Config) -> Option<u64> {
     if !conf.no_headers {
         // Try polars first for files with headers
@@ -504,10 +579,8 @@ pub fn mem_file_check(
 
     let conservative_memcheck_work = get_envvar_flag("WAKA_MEMORY_CHECK") || conservative_memcheck;
 
-    let mut mem_pct = env::var("QSV_FREEMEMORY_HEADROOM_PCT")
-        .unwrap_or_else(|_| DEFAULT_FREEMEMORY_HEADROOM_PCT.to_string())
-        .parse::<u8>()
-        .unwrap_or(DEFAULT_FREEMEMORY_HEADROOM_PCT);
+    // clamped to 10-90%: less headroom isn't safe, more is too memory-restrictive
+    let mem_pct = config::memory_headroom_pct();
 
     // if QSV_FREEMEMORY_HEADROOM_PCT is 0, we skip the memory check
     if mem_pct == 0 {
@@ -520,10 +593,6 @@ pub fn mem_file_check(
     let free_swap = sys.free_swap();
     let total_mem = sys.total_memory();
 
-    // for safety, we don't want to go below 10% memory headroom
-    // nor above 90% memory headroom as its too memory-restrictive
-    mem_pct = mem_pct.clamp(10, 90);
-
     #[allow(clippy::cast_precision_loss)]
     let max_avail_mem = if conservative_memcheck_work {
         ((avail_mem + free_swap) as f32 * ((100 - mem_pct) as f32 / 100.0_f32)) as u64
@@ -623,6 +692,322 @@ pub fn range(start: Idx, end: Idx, len: Idx, index: Idx) -> anyhow::Result<(usiz
     }
 }
 
+/// Return the last `n` data records of the CSV, in file order.
+/// If an index exists, seek straight to record `count - n`. Otherwise, stream
+/// the whole file, keeping only the last `n` records in a ring buffer.
+pub fn tail_records(conf: &Config, n: usize) -> anyhow::Result<Vec<csv::ByteRecord>> {
+    use std::collections::VecDeque;
+
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    if let Some(mut idx) = conf.indexed()? {
+        let count = idx.count();
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        idx.seek(count.saturating_sub(n as u64))?;
+        let mut out: Vec<csv::ByteRecord> = Vec::with_capacity(n.min(count as usize));
+        for r in conf.byte_records(&mut idx) {
+            out.push(r?);
+        }
+        return Ok(out);
+    }
+
+    let mut rdr = conf.reader()?;
+    // Ensure we are positioned after the header row for data rows.
+    let _ = rdr.headers();
+
+    let mut ring: VecDeque<csv::ByteRecord> = VecDeque::with_capacity(n.min(1024));
+    for r in conf.byte_records(&mut rdr) {
+        if ring.len() == n {
+            ring.pop_front();
+        }
+        ring.push_back(r?);
+    }
+    Ok(ring.into())
+}
+
+/// Find the data records whose field count differs from the header's, as
+/// `(row, fields)` pairs where `row` is the 0-based data row (header excluded).
+/// With `no_headers`, records are compared against the first record instead.
+pub fn find_ragged_rows(conf: &Config) -> anyhow::Result<Vec<(u64, usize)>> {
+    let mut rdr = conf.clone().flexible(true).reader()?;
+    let mut expected = if conf.no_headers {
+        None
+    } else {
+        Some(rdr.byte_headers()?.len())
+    };
+
+    let mut ragged = Vec::new();
+    let mut record = csv::ByteRecord::new();
+    let mut row = 0_u64;
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        let fields = record.len();
+        if *expected.get_or_insert(fields) != fields {
+            ragged.push((row, fields));
+        }
+        row += 1;
+    }
+    Ok(ragged)
+}
+
+/// How many individual issues `validate_csv` keeps; the per-kind counts in
+/// `CsvReport` keep going past this.
+pub const CSV_REPORT_MAX_ISSUES: usize = 1000;
+
+/// A kind of RFC 4180 conformance problem found by `validate_csv`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsvIssueKind {
+    /// A quote inside an unquoted field, a quoted field with trailing text
+    /// after its closing quote, or a quote that is never closed.
+    UnescapedQuote,
+    /// A line break in an unquoted field, which splits one row into two
+    /// short records.
+    UnquotedNewline,
+    /// The record's field count differs from the header's.
+    FieldCount { expected: usize, found: usize },
+    /// The record is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl std::fmt::Display for CsvIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CsvIssueKind::UnescapedQuote => write!(f, "unescaped quote"),
+            CsvIssueKind::UnquotedNewline => write!(f, "line break in an unquoted field"),
+            CsvIssueKind::FieldCount { expected, found } => {
+                write!(f, "expected {expected} fields, found {found}")
+            },
+            CsvIssueKind::InvalidUtf8 => write!(f, "invalid UTF-8"),
+        }
+    }
+}
+
+/// One problem found by `validate_csv`. `line` is the 1-based line the
+/// offending record starts on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CsvIssue {
+    pub line: u64,
+    pub kind: CsvIssueKind,
+}
+
+/// Result of `validate_csv`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CsvReport {
+    /// records read, header included
+    pub records:                u64,
+    /// whether the sample checked by `isutf8_file` is UTF-8
+    pub utf8_sample:            bool,
+    /// the first `CSV_REPORT_MAX_ISSUES` issues in file order
+    pub issues:                 Vec<CsvIssue>,
+    pub unescaped_quotes:       u64,
+    pub unquoted_newlines:      u64,
+    pub field_count_mismatches: u64,
+    pub invalid_utf8:           u64,
+}
+
+impl CsvReport {
+    /// Total number of issues, including those past `CSV_REPORT_MAX_ISSUES`.
+    pub const fn issue_count(&self) -> u64 {
+        self.unescaped_quotes
+            + self.unquoted_newlines
+            + self.field_count_mismatches
+            + self.invalid_utf8
+    }
+
+    pub const fn is_valid(&self) -> bool {
+        self.issue_count() == 0
+    }
+
+    /// Whether `issues` stops short of `issue_count`.
+    pub fn truncated(&self) -> bool {
+        (self.issues.len() as u64) < self.issue_count()
+    }
+
+    fn push(&mut self, line: u64, kind: CsvIssueKind) {
+        let count = match kind {
+            CsvIssueKind::UnescapedQuote => &mut self.unescaped_quotes,
+            CsvIssueKind::UnquotedNewline => &mut self.unquoted_newlines,
+            CsvIssueKind::FieldCount { .. } => &mut self.field_count_mismatches,
+            CsvIssueKind::InvalidUtf8 => &mut self.invalid_utf8,
+        };
+        *count += 1;
+        if self.issues.len() < CSV_REPORT_MAX_ISSUES {
+            self.issues.push(CsvIssue { line, kind });
+        }
+    }
+}
+
+/// Check the raw bytes of one record for RFC 4180 quoting: a quote may only
+/// open a field, `""` escapes a quote inside a quoted field, and a closing
+/// quote must be followed by a delimiter or line end. Comment lines are skipped.
+fn has_unescaped_quote(raw: &[u8], delim: u8, quote: u8, comment: Option<u8>) -> bool {
+    let mut i = 0;
+    let mut in_quotes = false;
+    let mut field_start = true;
+    let mut line_start = true;
+    while let Some(&b) = raw.get(i) {
+        i += 1;
+        if in_quotes {
+            if b == quote {
+                if raw.get(i) == Some(&quote) {
+                    i += 1;
+                    continue;
+                }
+                in_quotes = false;
+                if !matches!(raw.get(i), None | Some(b'\r' | b'\n')) && raw.get(i) != Some(&delim) {
+                    return true;
+                }
+            }
+            continue;
+        }
+        if line_start && Some(b) == comment {
+            while let Some(&c) = raw.get(i) {
+                i += 1;
+                if c == b'\n' {
+                    break;
+                }
+            }
+            continue;
+        }
+        line_start = b == b'\n';
+        if b == quote {
+            if !field_start {
+                return true;
+            }
+            in_quotes = true;
+            field_start = false;
+            continue;
+        }
+        field_start = b == delim || b == b'\n' || b == b'\r';
+    }
+    in_quotes
+}
+
+/// Check a CSV file for RFC 4180 conformance: unescaped quotes, line breaks
+/// in unquoted fields, inconsistent field counts and invalid UTF-8, each
+/// reported with the line it starts on.
+///
+/// Field counts are compared against the header, or the first record with
+/// `no_headers`. A short record directly followed by another short record
+/// whose fields add up to one more than expected is reported as a single
+/// `UnquotedNewline` rather than two field count mismatches.
+pub fn validate_csv(conf: &Config) -> anyhow::Result<CsvReport> {
+    let Some(path) = conf.path.as_deref() else {
+        return Err(anyhow!("Cannot validate <stdin>"));
+    };
+    let mut report = CsvReport {
+        utf8_sample: isutf8_file(path)?,
+        ..CsvReport::default()
+    };
+
+    // read the header as an ordinary record so every record has a position,
+    // and walk the raw bytes alongside to check quoting
+    let mut rdr = conf.clone().flexible(true).no_headers(true).reader()?;
+    let mut src = BufReader::new(config::skip_utf8_bom(conf.io_reader()?)?);
+    let (delim, quote, comment) = (conf.get_delimiter(), conf.quote, conf.comment);
+
+    let mut record = csv::ByteRecord::new();
+    let mut raw = Vec::new();
+    // line and start byte of the previous record, whose raw bytes end where
+    // the current one starts
+    let mut prev: Option<(u64, u64)> = None;
+    let mut expected = None;
+    // a short record that may be the first half of a row split by a line break
+    let mut short: Option<(u64, usize)> = None;
+    let mut check_quotes =
+        |report: &mut CsvReport, line: u64, len: Option<u64>| -> anyhow::Result<()> {
+            raw.clear();
+            match len {
+                Some(len) => (&mut src).take(len).read_to_end(&mut raw)?,
+                None => src.read_to_end(&mut raw)?,
+            };
+            if has_unescaped_quote(&raw, delim, quote, comment) {
+                report.push(line, CsvIssueKind::UnescapedQuote);
+            }
+            Ok(())
+        };
+
+    while rdr.read_byte_record(&mut record)? {
+        let pos = record
+            .position()
+            .cloned()
+            .unwrap_or_else(csv::Position::new);
+        let line = pos.line();
+        if let Some((prev_line, prev_byte)) = prev {
+            check_quotes(&mut report, prev_line, Some(pos.byte() - prev_byte))?;
+        } else if pos.byte() > 0 {
+            // leading comment lines
+            check_quotes(&mut report, 1, Some(pos.byte()))?;
+        }
+        prev = Some((line, pos.byte()));
+        report.records += 1;
+
+        if record
+            .iter()
+            .any(|field| simdutf8::basic::from_utf8(field).is_err())
+        {
+            report.push(line, CsvIssueKind::InvalidUtf8);
+        }
+
+        let found = record.len();
+        let expected = *expected.get_or_insert(found);
+        if found == expected {
+            if let Some((short_line, short_found)) = short.take() {
+                report.push(
+                    short_line,
+                    CsvIssueKind::FieldCount {
+                        expected,
+                        found: short_found,
+                    },
+                );
+            }
+            continue;
+        }
+        match short.take() {
+            Some((short_line, short_found))
+                if found < expected && short_found + found - 1 == expected =>
+            {
+                report.push(short_line, CsvIssueKind::UnquotedNewline);
+            },
+            pending => {
+                if let Some((short_line, short_found)) = pending {
+                    report.push(
+                        short_line,
+                        CsvIssueKind::FieldCount {
+                            expected,
+                            found: short_found,
+                        },
+                    );
+                }
+                if found < expected {
+                    short = Some((line, found));
+                } else {
+                    report.push(line, CsvIssueKind::FieldCount { expected, found });
+                }
+            },
+        }
+    }
+    if let Some((short_line, short_found)) = short
+        && let Some(expected) = expected
+    {
+        report.push(
+            short_line,
+            CsvIssueKind::FieldCount {
+                expected,
+                found: short_found,
+            },
+        );
+    }
+    if let Some((prev_line, _)) = prev {
+        check_quotes(&mut report, prev_line, None)?;
+    }
+    report.issues.sort_by_key(|issue| issue.line);
+    Ok(report)
+}
+
 /// Represents a filename template of the form `"{}.csv"`, where `"{}"` is
 /// the place to insert the part of the filename generated by `qsv`.
 #[derive(Clone)]
@@ -647,6 +1032,19 @@ impl FilenameTemplate {
         path: P,
         unique_value: &str,
     ) -> std::io::Result<csv::Writer<Box<dyn std::io::Write + 'static>>>
+    where
+        P: AsRef<Path>,
+    {
+        self.writer_with_quote_style(path, unique_value, csv::QuoteStyle::Necessary)
+    }
+
+    /// Same as `writer`, but quoting fields according to `quote_style`.
+    pub fn writer_with_quote_style<P>(
+        &self,
+        path: P,
+        unique_value: &str,
+        quote_style: csv::QuoteStyle,
+    ) -> std::io::Result<csv::Writer<Box<dyn std::io::Write + 'static>>>
     where
         P: AsRef<Path>,
     {
@@ -655,11 +1053,7 @@ impl FilenameTemplate {
         if let Some(parent) = full_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
-        let spath = Some(full_path.display().to_string());
-        Config::builder()
-            .maybe_path(spath.as_ref())
-            .build()
-            .writer()
+        csv_writer(Some(&full_path), None, quote_style)
     }
 }
 
@@ -754,19 +1148,46 @@ pub fn init_logger() -> anyhow::Result<(String, Option<tracing_appender::non_blo
     Ok((qsv_args, guard_opt))
 }
 
+/// How `safe_header_names` turns header names into safe ones.
+#[derive(Clone, Copy, Debug, Builder)]
+pub struct SafeNameOptions<'a> {
+    #[builder(default)]
+    pub check_first_char: bool,
+    #[builder(default)]
+    pub conditional:      bool,
+    pub reserved_names:   Option<&'a [String]>,
+    #[builder(default)]
+    pub unsafe_prefix:    &'a str,
+    #[builder(default)]
+    pub keep_case:        bool,
+    #[builder(default = DEFAULT_SAFE_NAME_MAX_LEN)]
+    pub max_len:          usize,
+    #[builder(default)]
+    pub snake_case:       bool,
+}
+
 pub fn safe_header_names(
     headers: &csv::StringRecord,
-    check_first_char: bool,
-    conditional: bool,
-    reserved_names: Option<&Vec<String>>,
-    unsafe_prefix: &str,
-    keep_case: bool,
+    options: &SafeNameOptions,
 ) -> (Vec<String>, u16) {
+    let SafeNameOptions {
+        check_first_char,
+        conditional,
+        reserved_names,
+        unsafe_prefix,
+        keep_case,
+        max_len,
+        snake_case,
+    } = *options;
+
     // Create "safe" var/key names - to support dynfmt/url-template, valid python vars & db-safe
     // column names. Fold to lowercase if keep_case is false. Trim leading & trailing whitespace.
     // Replace whitespace/non-alphanumeric) with _. If name starts with a number & check_first_char
     // is true, prepend the unsafe_prefix. If a column with the same name already exists,
-    // append a sequence suffix (e.g. _n). Names are limited to 60 characters in length.
+    // append a sequence suffix (e.g. _n). Names are limited to max_len characters in length
+    // (DEFAULT_SAFE_NAME_MAX_LEN is 60), truncated on a char boundary.
+    // If snake_case is true, underscores are first inserted at camelCase/PascalCase word
+    // boundaries, so SomeColumnName becomes some_column_name.
     // Empty names are replaced with unsafe_prefix as well.
 
     // If conditional = true & reserved_names is none, only rename the header if its not safe
@@ -797,14 +1218,21 @@ pub fn safe_header_names(
         } else {
             false
         };
-        safe_name = if conditional && is_safe_name(header_name) && !reserved_found {
+        let snake_name = snake_case.then(|| camel_to_snake(header_name));
+        // a name that snake_case would change isn't safe as-is
+        let snake_unchanged = snake_name.as_deref().is_none_or(|s| s == header_name);
+        safe_name = if conditional
+            && is_safe_name(header_name, max_len)
+            && snake_unchanged
+            && !reserved_found
+        {
             header_name.to_string()
         } else {
             safename_always = if header_name.is_empty() {
                 prefix.to_string()
             } else {
                 safename_regex
-                    .replace_all(header_name.trim(), "_")
+                    .replace_all(snake_name.as_deref().unwrap_or(header_name).trim(), "_")
                     .to_string()
             };
             if check_first_char && safename_always.as_bytes()[0].is_ascii_digit() {
@@ -821,7 +1249,7 @@ pub fn safe_header_names(
             final_candidate = safename_candidate[..safename_candidate
                 .chars()
                 .map(char::len_utf8)
-                .take(60)
+                .take(max_len)
                 .sum()]
                 .to_string();
 
@@ -836,12 +1264,7 @@ pub fn safe_header_names(
             }
             final_candidate
         };
-        let mut sequence_suffix = 2_u16;
-        let mut candidate_name = safe_name.clone();
-        while name_vec.contains(&candidate_name) {
-            candidate_name = format!("{safe_name}_{sequence_suffix}");
-            sequence_suffix += 1;
-        }
+        let candidate_name = unique_name(&name_vec, &safe_name);
         if candidate_name.ne(header_name) {
             changed_count += 1;
         }
@@ -851,11 +1274,76 @@ pub fn safe_header_names(
     (name_vec, changed_count)
 }
 
+/// `name` if it is not taken yet, else the first free `name_2`, `name_3`, ...
+fn unique_name(taken: &[String], name: &str) -> String {
+    let mut sequence_suffix = 2_u16;
+    let mut candidate_name = name.to_string();
+    while taken.contains(&candidate_name) {
+        candidate_name = format!("{name}_{sequence_suffix}");
+        sequence_suffix += 1;
+    }
+    candidate_name
+}
+
+/// Header names that occur more than once, with the 0-based indices of all their
+/// occurrences, in order of first occurrence.
+pub fn duplicate_headers(headers: &csv::ByteRecord) -> Vec<(String, Vec<usize>)> {
+    let mut seen: HashMap<&[u8], usize> = HashMap::with_capacity(headers.len());
+    let mut occurrences: Vec<(&[u8], Vec<usize>)> = Vec::new();
+    for (i, name) in headers.iter().enumerate() {
+        match seen.get(name) {
+            Some(&slot) => occurrences[slot].1.push(i),
+            None => {
+                seen.insert(name, occurrences.len());
+                occurrences.push((name, vec![i]));
+            },
+        }
+    }
+    occurrences
+        .into_iter()
+        .filter(|(_, indices)| indices.len() > 1)
+        .map(|(name, indices)| (String::from_utf8_lossy(name).into_owned(), indices))
+        .collect()
+}
+
+/// Header names with repeats made unique the way `safe_header_names` does it: the first
+/// occurrence keeps its name and later ones get `_2`, `_3`, ... Unlike `safe_header_names`,
+/// names are otherwise left as they are.
+pub fn dedup_header_names<'a>(headers: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for name in headers {
+        let name = unique_name(&names, name);
+        names.push(name);
+    }
+    names
+}
+
+/// insert underscores at camelCase/PascalCase word boundaries,
+/// e.g. SomeColumnName -> Some_Column_Name, HTTPServerID -> HTTP_Server_ID
+fn camel_to_snake(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 8);
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && c.is_uppercase() {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_uppercase() && next_is_lower)
+            {
+                snake.push('_');
+            }
+        }
+        snake.push(c);
+    }
+    snake
+}
+
 #[inline]
-pub fn is_safe_name(header_name: &str) -> bool {
+pub fn is_safe_name(header_name: &str, max_len: usize) -> bool {
     if header_name.trim().is_empty()
         || header_name.trim_start_matches('_').is_empty()
-        || header_name.len() > 60
+        || header_name.len() > max_len
     {
         return false;
     }
@@ -1266,6 +1754,83 @@ pub fn isutf8_file(path: &Path) -> anyhow::Result<bool> {
     Ok(simdutf8::basic::from_utf8(&buffer).is_ok())
 }
 
+/// Dialect and shape of a CSV file, as reported by sniff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialect {
+    /// field delimiter
+    pub delimiter:     u8,
+    /// quote character, None if the sniffed sample has no quoted fields
+    pub quote:         Option<u8>,
+    /// whether the first row after the preamble is a header row
+    pub has_headers:   bool,
+    /// rows before the header (or first record), e.g. a title or comments
+    pub preamble_rows: usize,
+    /// number of data rows, excluding the preamble and header rows
+    pub record_count:  u64,
+    /// number of fields of the header (or first record)
+    pub column_count:  usize,
+    /// whether the start of the file is valid UTF-8, see isutf8_file
+    pub is_utf8:       bool,
+    /// false if sniffing failed (e.g. a single column file), in which case the delimiter,
+    /// quote and has_headers are the defaults for the file's extension
+    pub sniffed:       bool,
+}
+
+/// One-shot report of a CSV file's dialect - delimiter, quote, headers and preamble as
+/// sniffed from a sample - plus its record count, column count and UTF-8 validity.
+pub fn sniff(path: &Path) -> anyhow::Result<CsvDialect> {
+    let is_utf8 = isutf8_file(path)?;
+    let conf = Config::builder().path(path.to_string_lossy()).build();
+
+    let sniffed = Sniffer::new()
+        .sample_size(SampleSize::Records(config::DEFAULT_SNIFFER_SAMPLE))
+        .sniff_path(path);
+    let (delimiter, quote, has_headers, preamble_rows) = match &sniffed {
+        Ok(metadata) => (
+            metadata.dialect.delimiter,
+            match metadata.dialect.quote {
+                Quote::Some(quote) => Some(quote),
+                Quote::None => None,
+            },
+            metadata.dialect.header.has_header_row,
+            metadata.dialect.header.num_preamble_rows,
+        ),
+        Err(e) => {
+            tracing::warn!("sniff error for {}: {e}", path.display());
+            (conf.get_delimiter(), Some(b'"'), true, 0)
+        },
+    };
+
+    // preamble rows may have a different number of fields than the records
+    let conf = conf
+        .delimiter(Some(Delimiter(delimiter)))
+        .no_headers(!has_headers)
+        .flexible(true)
+        .skip_format_check(true);
+    let record_count = count_rows_detail(&conf)?
+        .data_rows
+        .saturating_sub(preamble_rows as u64);
+    let column_count = conf
+        .clone()
+        .no_headers(true)
+        .reader()?
+        .into_byte_records()
+        .nth(preamble_rows)
+        .transpose()?
+        .map_or(0, |record| record.len());
+
+    Ok(CsvDialect {
+        delimiter,
+        quote,
+        has_headers,
+        preamble_rows,
+        record_count,
+        column_count,
+        is_utf8,
+        sniffed: sniffed.is_ok(),
+    })
+}
+
 // check if a file is supported by process_input
 fn is_supported_file(path: &Path) -> bool {
     // If QSV_SKIP_FORMAT_CHECK is set, consider all files as supported
@@ -1284,6 +1849,54 @@ fn is_supported_file(path: &Path) -> bool {
     }
 }
 
+/// does `path` have the ".infile-list" extension (case-insensitive)?
+fn is_infile_list(path: &Path) -> bool {
+    path.extension()
+        .and_then(std::ffi::OsStr::to_str)
+        .map(str::to_lowercase)
+        == Some("infile-list".to_string())
+}
+
+/// read an ".infile-list" file. Each non-empty line that doesn't start with '#' is a file path;
+/// paths that don't exist are warn logged and left out.
+fn read_infile_list(input_path: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let mut input_file = std::fs::File::open(input_path)?;
+    let mut input_file_contents = String::new();
+    let mut canonical_invalid_path = PathBuf::new();
+    let mut invalid_files = 0_u32;
+    input_file.read_to_string(&mut input_file_contents)?;
+    let infile_list_vec = input_file_contents
+        .lines()
+        .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
+        .map(PathBuf::from)
+        .filter_map(|path| {
+            if path.exists() {
+                Some(path)
+            } else {
+                // note that we're warn logging if files do not exist for
+                // each line in the infile-list file
+                // even though we're returning an error on the FIRST file that
+                // doesn't exist in the next section. This is because
+                // we want to log ALL the invalid file paths in the infile-list
+                // file, not just the first one.
+                invalid_files += 1;
+                canonical_invalid_path = path.canonicalize().unwrap_or_default();
+                tracing::warn!(
+                    ".infile-list file '{}': '{}' does not exist",
+                    path.display(),
+                    canonical_invalid_path.display()
+                );
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+    tracing::info!(
+        ".infile-list file parsed. Filecount - valid:{} invalid:{invalid_files}",
+        infile_list_vec.len()
+    );
+    Ok(infile_list_vec)
+}
+
 /// Process the input files and return a vector of paths to the input files.
 ///
 /// If the input is empty, try to copy stdin to a file named stdin in the passed temp directory.
@@ -1315,47 +1928,8 @@ pub fn process_input(
         } else if input_path.is_file() {
             // if the input is a file and has the extension "infile-list" case-insensitive,
             // read the file. Each line is a file path
-            if input_path
-                .extension()
-                .and_then(std::ffi::OsStr::to_str)
-                .map(str::to_lowercase)
-                == Some("infile-list".to_string())
-            {
-                let mut input_file = std::fs::File::open(input_path)?;
-                let mut input_file_contents = String::new();
-                let mut canonical_invalid_path = PathBuf::new();
-                let mut invalid_files = 0_u32;
-                input_file.read_to_string(&mut input_file_contents)?;
-                let infile_list_vec = input_file_contents
-                    .lines()
-                    .filter(|line| !line.trim().is_empty() && !line.starts_with('#'))
-                    .map(PathBuf::from)
-                    .filter_map(|path| {
-                        if path.exists() {
-                            Some(path)
-                        } else {
-                            // note that we're warn logging if files do not exist for
-                            // each line in the infile-list file
-                            // even though we're returning an error on the FIRST file that
-                            // doesn't exist in the next section. This is because
-                            // we want to log ALL the invalid file paths in the infile-list
-                            // file, not just the first one.
-                            invalid_files += 1;
-                            canonical_invalid_path = path.canonicalize().unwrap_or_default();
-                            tracing::warn!(
-                                ".infile-list file '{}': '{}' does not exist",
-                                path.display(),
-                                canonical_invalid_path.display()
-                            );
-                            None
-                        }
-                    })
-                    .collect::<Vec<_>>();
-                tracing::info!(
-                    ".infile-list file parsed. Filecount - valid:{} invalid:{invalid_files}",
-                    infile_list_vec.len()
-                );
-                infile_list_vec
+            if is_infile_list(input_path) {
+                read_infile_list(input_path)?
             } else {
                 // if the input is not an ".infile-list" file, add the file to the input
                 arg_input
@@ -1484,6 +2058,103 @@ pub fn process_input(
     Ok(processed_input)
 }
 
+/// What process_input would do with an input file. See process_input_plan.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessDecision {
+    /// the file is passed through as-is
+    Included,
+    /// the file is skipped as it's not a supported format
+    SkippedUnsupported,
+    /// the file is skipped as it's a system file (e.g. .DS_Store, __MACOSX/) in a zip archive
+    SkippedSystemFile,
+    /// the file is snappy compressed and would be decompressed into the temp directory
+    Decompressed,
+}
+
+/// Preview what process_input would do with arg_input, without reading stdin, decompressing
+/// or extracting anything.
+///
+/// Each input file is returned with its decision. Decompressed files and zip archive entries are
+/// returned with the path they would be given in tmpdir; stdin is returned as "-".
+/// Like process_input, an input file that doesn't exist is an error.
+pub fn process_input_plan(
+    arg_input: Vec<PathBuf>,
+    tmpdir: &tempfile::TempDir,
+) -> anyhow::Result<Vec<(PathBuf, ProcessDecision)>> {
+    let mut plan = Vec::with_capacity(arg_input.len());
+
+    if arg_input.len() == 1 && arg_input[0].is_dir() {
+        // only the supported files in a directory are processed
+        for entry in std::fs::read_dir(&arg_input[0])? {
+            let path = entry?.path();
+            let decision = if is_supported_file(&path) {
+                ProcessDecision::Included
+            } else {
+                ProcessDecision::SkippedUnsupported
+            };
+            plan.push((path, decision));
+        }
+        return Ok(plan);
+    }
+
+    let work_input =
+        if arg_input.len() == 1 && arg_input[0].is_file() && is_infile_list(&arg_input[0]) {
+            read_infile_list(&arg_input[0])?
+        } else {
+            arg_input
+        };
+
+    for path in work_input {
+        if path == PathBuf::from("-") {
+            plan.push((path, ProcessDecision::Included));
+            continue;
+        } else if !path.exists() {
+            return Err(anyhow!("Input file '{}' does not exist", path.display()));
+        }
+
+        let ext = path.extension().and_then(std::ffi::OsStr::to_str);
+        if ext == Some("sz") {
+            // safety: we know the path has a filename
+            let original_filename = path.with_extension("").file_name().unwrap().to_owned();
+            plan.push((
+                tmpdir.path().join(original_filename),
+                ProcessDecision::Decompressed,
+            ));
+        } else if ext.map(str::to_lowercase) == Some("zip".to_string()) {
+            // safety: we know the path has a filename
+            let zip_filename = path
+                .file_name()
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .replace(".zip", "");
+            let zip_extract_dir = tmpdir.path().join(&zip_filename);
+
+            // only the central directory is read; nothing is extracted
+            let mut archive = zip::ZipArchive::new(std::fs::File::open(&path)?)?;
+            for i in 0..archive.len() {
+                let entry_path = archive.by_index_raw(i)?.name().to_string();
+                if entry_path.ends_with('/') {
+                    continue;
+                }
+                let file_path = zip_extract_dir.join(&entry_path);
+                let decision = if !root_dir_common_filter(Path::new(&entry_path)) {
+                    ProcessDecision::SkippedSystemFile
+                } else if is_supported_file(&file_path) {
+                    ProcessDecision::Included
+                } else {
+                    ProcessDecision::SkippedUnsupported
+                };
+                plan.push((file_path, decision));
+            }
+        } else {
+            plan.push((path, ProcessDecision::Included));
+        }
+    }
+
+    Ok(plan)
+}
+
 #[inline]
 pub fn replace_column_value(
     record: &csv::StringRecord,
@@ -1514,6 +2185,23 @@ pub fn format_systemtime(time: SystemTime, format_specifier: &str) -> String {
     format!("{datetime}", datetime = datetime.format(format_specifier))
 }
 
+/// parse a date/datetime string the way `stats` infers dates, returning Unix milliseconds.
+/// Returns None if the string is not a recognizable date.
+#[inline]
+pub fn parse_timestamp_ms(s: &str, prefer_dmy: bool) -> Option<i64> {
+    qsv_dateparser::parse_with_preference(s.trim(), prefer_dmy)
+        .ok()
+        .map(|dt| dt.timestamp_millis())
+}
+
+/// format Unix milliseconds as an RFC3339 UTC timestamp, e.g. "2023-01-15T10:30:00.250+00:00"
+#[inline]
+pub fn timestamp_ms_to_rfc3339(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp)
+        .unwrap_or_default()
+        .to_rfc3339()
+}
+
 pub fn create_json_writer(
     output: Option<&String>,
     buffer_capacity: usize,
@@ -1543,12 +2231,16 @@ pub fn create_json_writer(
     Ok(writer)
 }
 
-/// iterate over the CSV ByteRecords and write them to the JSON file
+/// iterate over the CSV ByteRecords and write them to the JSON file.
+/// Empty fields and fields equal to one of `null_values` are written as JSON null.
+/// If `pretty` is set, each record and field goes on its own indented line.
 pub fn write_json(
     output: Option<&String>,
     no_headers: bool,
     headers: &csv::ByteRecord,
     records: impl Iterator<Item = csv::ByteRecord>,
+    null_values: &[Vec<u8>],
+    pretty: bool,
 ) -> anyhow::Result<()> {
     let mut json_wtr = create_json_writer(output, config::DEFAULT_WTR_BUFFER_CAPACITY * 4)?;
 
@@ -1566,6 +2258,12 @@ pub fn write_json(
         })
         .collect();
 
+    let (record_start, field_start, key_sep, record_end) = if pretty {
+        ("\n  {", "\n    ", ": ", "\n  }")
+    } else {
+        ("{", "", ":", "}")
+    };
+
     // Write the opening bracket for the JSON array
     write!(json_wtr, "[")?;
     let mut is_first = true;
@@ -1582,9 +2280,11 @@ pub fn write_json(
             // Write a comma before each record except the first one
             write!(json_wtr, ",")?;
         }
-        write!(json_wtr, "{{")?;
+        write!(json_wtr, "{record_start}")?;
         for (idx, b) in record.iter().enumerate() {
-            temp_val = if let Ok(val) = simdutf8::basic::from_utf8(b) {
+            temp_val = if null_values.iter().any(|n| n.as_slice() == b) {
+                String::new()
+            } else if let Ok(val) = simdutf8::basic::from_utf8(b) {
                 val.to_owned()
             } else {
                 String::from_utf8_lossy(b).to_string()
@@ -1603,7 +2303,7 @@ pub fn write_json(
                 unsafe {
                     write!(
                         &mut json_wtr,
-                        r#""{key}":{value},"#,
+                        r#"{field_start}"{key}"{key_sep}{value},"#,
                         key = header_vec.get_unchecked(idx),
                         value = temp_val
                     )?;
@@ -1613,21 +2313,76 @@ pub fn write_json(
                 unsafe {
                     write!(
                         &mut json_wtr,
-                        r#""{key}":{value}"#,
+                        r#"{field_start}"{key}"{key_sep}{value}"#,
                         key = header_vec.get_unchecked(idx),
                         value = temp_val
                     )?;
                 }
             }
         }
-        write!(json_wtr, "}}")?;
+        write!(json_wtr, "{record_end}")?;
     }
     // Write the closing bracket for the JSON array
+    if pretty && !is_first {
+        writeln!(json_wtr)?;
+    }
     writeln!(json_wtr, "]")?;
 
     Ok(json_wtr.flush()?)
 }
 
+/// write_json for the data rows `start..start + len` (0-based, header excluded) of conf.
+/// If conf has an index, it seeks straight to `start`. Otherwise, the rows before `start`
+/// are skipped by reading through them. Either way, records are streamed to output
+/// rather than collected, so memory stays bounded however large the range is.
+pub fn write_json_range(
+    conf: &Config,
+    output: Option<&String>,
+    start: u64,
+    len: usize,
+    pretty: bool,
+) -> anyhow::Result<()> {
+    // write_json takes plain records, so the first read error stops the stream and is
+    // returned after the JSON is closed
+    let mut read_error = None;
+    if let Some(mut idx) = conf.indexed()? {
+        let headers = idx.byte_headers()?.clone();
+        idx.seek(start)?;
+        let records = conf
+            .byte_records(&mut idx)
+            .take(len)
+            .map_while(|r| r.map_err(|e| read_error = Some(e)).ok());
+        write_json(
+            output,
+            conf.no_headers,
+            &headers,
+            records,
+            conf.get_null_values(),
+            pretty,
+        )?;
+    } else {
+        let mut rdr = conf.reader()?;
+        let headers = rdr.byte_headers()?.clone();
+        let records = conf
+            .byte_records(&mut rdr)
+            .skip(start as usize)
+            .take(len)
+            .map_while(|r| r.map_err(|e| read_error = Some(e)).ok());
+        write_json(
+            output,
+            conf.no_headers,
+            &headers,
+            records,
+            conf.get_null_values(),
+            pretty,
+        )?;
+    }
+    match read_error {
+        Some(e) => Err(e.into()),
+        None => Ok(()),
+    }
+}
+
 /// write a single csv::ByteRecord to a JSON record writer
 /// if no_headers is true, the column index (0-based) is used as the key
 /// if no_headers is false, the header is used as the key
@@ -1701,19 +2456,562 @@ pub fn write_json_record<W: std::io::Write>(
     Ok(write!(json_wtr, "}}")?)
 }
 
-/// get stats records from stats.csv.data.jsonl file, or if its invalid, by running the stats
-/// command returns tuple (`csv_fields`, `csv_stats`, `stats_col_index_map`)
-pub fn get_stats_records(
-    args: &SchemaArgs,
-    requested_mode: StatsMode,
-) -> anyhow::Result<(ByteRecord, Vec<StatsData>, HashMap<String, String>)> {
-    const DATASET_STATS_PREFIX: &str = r#"{"field":"qsv__"#;
+/// Lines of a JSONL file sampled for the CSV header when it is opened as a special format.
+const JSONL_HEADER_SAMPLE_LINES: usize = 1_000;
+
+/// How `jsonl_to_csv` writes nested JSON objects.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum JsonNesting {
+    /// one column per leaf, named with dotted keys, e.g. `process.parent.pid`
+    #[default]
+    Flatten,
+    /// one column per top-level key, nested objects written as JSON text
+    Stringify,
+}
 
-    let env_mode = env::var("QSV_STATSCACHE_MODE")
-        .unwrap_or_else(|_| DEFAULT_STATSCACHE_MODE.to_string())
-        .to_ascii_lowercase();
+/// flatten the JSON value of column `prefix` into (column, cell) pairs.
+/// Arrays are always written as JSON text; null becomes an empty cell.
+fn flatten_json_value(
+    prefix: &str,
+    value: serde_json::Value,
+    nesting: JsonNesting,
+    out: &mut Vec<(String, String)>,
+) {
+    match value {
+        serde_json::Value::Object(map) if nesting == JsonNesting::Flatten && !map.is_empty() => {
+            for (k, v) in map {
+                flatten_json_value(&format!("{prefix}.{k}"), v, nesting, out);
+            }
+        },
+        serde_json::Value::Null => out.push((prefix.to_string(), String::new())),
+        serde_json::Value::String(s) => out.push((prefix.to_string(), s)),
+        other => out.push((prefix.to_string(), other.to_string())),
+    }
+}
 
-    if !["auto", "force", "none"].contains(&env_mode.as_str()) {
+/// convert a JSON Lines file to CSV without loading it all in memory.
+///
+/// The header is the union of the keys found in the first `sample_lines` objects, in the
+/// order they were first seen; keys that only show up later are dropped (and logged).
+/// Blank lines are skipped. Returns the number of rows written.
+pub fn jsonl_to_csv(
+    input: &Path,
+    output: &Path,
+    nesting: JsonNesting,
+    sample_lines: usize,
+) -> anyhow::Result<u64> {
+    let mut rdr = BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, File::open(input)?);
+    let mut line = String::new();
+    let mut line_no = 0_u64;
+
+    // parse the next non-blank line into its flattened fields
+    let mut next_row = |line: &mut String| -> anyhow::Result<Option<Vec<(String, String)>>> {
+        loop {
+            line.clear();
+            if rdr.read_line(line)? == 0 {
+                return Ok(None);
+            }
+            line_no += 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let value: serde_json::Value = serde_json::from_str(line)
+                .map_err(|e| anyhow!("line {line_no}: invalid JSON: {e}"))?;
+            let serde_json::Value::Object(map) = value else {
+                return Err(anyhow!("line {line_no}: expected a JSON object"));
+            };
+            let mut fields = Vec::with_capacity(map.len());
+            for (k, v) in map {
+                flatten_json_value(&k, v, nesting, &mut fields);
+            }
+            return Ok(Some(fields));
+        }
+    };
+
+    // sample the first rows to settle the header
+    let mut sampled = Vec::with_capacity(sample_lines.min(10_000));
+    let mut headers: Vec<String> = Vec::new();
+    let mut col_index: HashMap<String, usize> = HashMap::new();
+    while sampled.len() < sample_lines.max(1) {
+        let Some(fields) = next_row(&mut line)? else {
+            break;
+        };
+        for (k, _) in &fields {
+            if !col_index.contains_key(k) {
+                col_index.insert(k.clone(), headers.len());
+                headers.push(k.clone());
+            }
+        }
+        sampled.push(fields);
+    }
+
+    let mut wtr = csv_writer(Some(output), None, csv::QuoteStyle::Necessary)?;
+    wtr.write_record(&headers)?;
+    let mut record = vec![String::new(); headers.len()];
+    let mut dropped_keys = 0_u64;
+    let mut rows = 0_u64;
+    let mut write_row = |fields: Vec<(String, String)>| -> anyhow::Result<()> {
+        record.iter_mut().for_each(String::clear);
+        for (k, v) in fields {
+            match col_index.get(&k) {
+                Some(&i) => record[i] = v,
+                None => dropped_keys += 1,
+            }
+        }
+        wtr.write_record(&record)?;
+        rows += 1;
+        Ok(())
+    };
+    for fields in sampled {
+        write_row(fields)?;
+    }
+    while let Some(fields) = next_row(&mut line)? {
+        write_row(fields)?;
+    }
+    wtr.flush()?;
+
+    if dropped_keys > 0 {
+        tracing::warn!(
+            "{dropped_keys} values dropped: their keys were not in the first {sample_lines} lines \
+             used for the header"
+        );
+    }
+    Ok(rows)
+}
+
+/// draw `n` random data rows from a CSV, returned in file order.
+///
+/// With a fresh index, `n` row numbers are picked up front and read with random seeks;
+/// otherwise the file is streamed once using reservoir sampling (Algorithm R), keeping
+/// at most `n` records in memory. The same `seed` always yields the same sample.
+/// When the file has `n` rows or fewer, all of them are returned.
+pub fn sample_records(
+    conf: &Config,
+    n: usize,
+    seed: Option<u64>,
+) -> anyhow::Result<Vec<ByteRecord>> {
+    use rand::{Rng, SeedableRng, rngs::StdRng};
+
+    let mut rng = match seed {
+        Some(s) => StdRng::seed_from_u64(s), // DevSkim: ignore DS148264
+        None => StdRng::from_os_rng(),
+    };
+    if n == 0 {
+        return Ok(Vec::new());
+    }
+
+    if let Some(mut idx) = conf.indexed()? {
+        let count = idx.count() as usize;
+        let mut rows = rand::seq::index::sample(&mut rng, count, n.min(count)).into_vec();
+        // seek forward only
+        rows.sort_unstable();
+        let mut sample = Vec::with_capacity(rows.len());
+        for row in rows {
+            idx.seek(row as u64)?;
+            let mut record = ByteRecord::new();
+            if conf.read_byte_record(&mut idx, &mut record)? {
+                sample.push(record);
+            }
+        }
+        return Ok(sample);
+    }
+
+    let mut rdr = conf.reader()?;
+    let mut reservoir: Vec<(usize, ByteRecord)> = Vec::with_capacity(n);
+    let mut record = ByteRecord::new();
+    let mut i = 0_usize;
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        if i < n {
+            reservoir.push((i, record.clone()));
+        } else {
+            let j = rng.random_range(0..=i);
+            if j < n {
+                reservoir[j] = (i, record.clone());
+            }
+        }
+        i += 1;
+    }
+    reservoir.sort_unstable_by_key(|(row, _)| *row);
+    Ok(reservoir.into_iter().map(|(_, record)| record).collect())
+}
+
+/// transpose a CSV, so its rows become columns and vice versa, writing the result to `output`
+/// (stdout if None). The header row is transposed like any other row, becoming the first
+/// column. Ragged records are padded with empty fields.
+///
+/// The whole file is read into memory, so files that don't fit per mem_file_check are
+/// refused with an error instead.
+pub fn transpose(conf: &Config, output: Option<&Path>) -> anyhow::Result<()> {
+    if let Some(path) = &conf.path {
+        mem_file_check(path, false, false)?;
+    }
+
+    let conf = conf.clone().no_headers(true).flexible(true);
+    let mut rdr = conf.reader()?;
+    let rows = conf.byte_records(&mut rdr).collect::<Result<Vec<_>, _>>()?;
+    let ncols = rows.iter().map(ByteRecord::len).max().unwrap_or(0);
+
+    let mut wtr = csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    let mut record = ByteRecord::with_capacity(1024, rows.len());
+    for i in 0..ncols {
+        record.clear();
+        for row in &rows {
+            record.push_field(row.get(i).unwrap_or_default());
+        }
+        wtr.write_byte_record(&record)?;
+    }
+    Ok(wtr.flush()?)
+}
+
+/// split a CSV into files of at most `rows_per_file` data rows each, written to `output_dir`
+/// with names generated by `template` from the 0-based row number each chunk starts at
+/// (e.g. `{}.csv` gives `0.csv`, `1000.csv`, ...). Unless `conf.no_headers` is set, every
+/// chunk gets a copy of the header row.
+///
+/// The input is streamed, so this works on files of any size. An input with no data rows
+/// produces no files. Returns the number of files written.
+pub fn split_by_rows(
+    conf: &Config,
+    rows_per_file: usize,
+    template: &FilenameTemplate,
+    output_dir: &Path,
+) -> anyhow::Result<usize> {
+    if rows_per_file == 0 {
+        anyhow::bail!("rows_per_file must be greater than 0.");
+    }
+
+    let mut rdr = conf.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let mut wtr: Option<csv::Writer<Box<dyn std::io::Write + 'static>>> = None;
+    let mut nfiles = 0_usize;
+    let mut row = 0_usize;
+    let mut record = ByteRecord::new();
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        if row.is_multiple_of(rows_per_file) {
+            if let Some(mut prev) = wtr.take() {
+                prev.flush()?;
+            }
+            let mut next = template.writer(output_dir, &row.to_string())?;
+            if !conf.no_headers {
+                next.write_byte_record(&headers)?;
+            }
+            wtr = Some(next);
+            nfiles += 1;
+        }
+        if let Some(w) = wtr.as_mut() {
+            w.write_byte_record(&record)?;
+        }
+        row += 1;
+    }
+    if let Some(mut last) = wtr {
+        last.flush()?;
+    }
+    Ok(nfiles)
+}
+
+/// split a CSV into one file per distinct value of column `column`, written to `output_dir`
+/// with names generated by `template` from a filename-safe form of the value (see
+/// safe_header_names). Values that sanitize to the same name get a `_2`, `_3`, ... suffix and
+/// an empty value is named `empty`. Unless `conf.no_headers` is set, every file starts with
+/// the header row.
+///
+/// Records are appended to their value's file as they stream, so every writer stays open
+/// until the end. More than SPLIT_BY_COLUMN_MAX_WRITERS distinct values is an error.
+/// Returns the number of files written.
+pub fn split_by_column(
+    conf: &Config,
+    column: usize,
+    template: &FilenameTemplate,
+    output_dir: &Path,
+) -> anyhow::Result<usize> {
+    let mut rdr = conf.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    if column >= headers.len() {
+        return Err(anyhow!(
+            "split column {column} out of range ({} columns)",
+            headers.len()
+        ));
+    }
+
+    let mut writers: HashMap<Vec<u8>, csv::Writer<Box<dyn std::io::Write + 'static>>> =
+        HashMap::new();
+    let mut used_names: HashSet<String> = HashSet::new();
+    let mut record = ByteRecord::new();
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        let value = record.get(column).unwrap_or_default();
+        if !writers.contains_key(value) {
+            if writers.len() >= SPLIT_BY_COLUMN_MAX_WRITERS {
+                return Err(anyhow!(
+                    "column {column} has more than {SPLIT_BY_COLUMN_MAX_WRITERS} distinct values, \
+                     too many to split into one file each"
+                ));
+            }
+            let value_str = String::from_utf8_lossy(value);
+            let base = if value_str.trim().is_empty() {
+                "empty".to_string()
+            } else {
+                let (names, _) = safe_header_names(
+                    &csv::StringRecord::from(vec![value_str.as_ref()]),
+                    &SafeNameOptions::builder().keep_case(true).build(),
+                );
+                names.into_iter().next().unwrap_or_default()
+            };
+            let mut name = base.clone();
+            let mut sequence_suffix = 2_u32;
+            while used_names.contains(&name) {
+                name = format!("{base}_{sequence_suffix}");
+                sequence_suffix += 1;
+            }
+            let mut wtr = template.writer(output_dir, &name)?;
+            if !conf.no_headers {
+                wtr.write_byte_record(&headers)?;
+            }
+            used_names.insert(name);
+            writers.insert(value.to_vec(), wtr);
+        }
+        if let Some(wtr) = writers.get_mut(value) {
+            wtr.write_byte_record(&record)?;
+        }
+    }
+
+    let nfiles = writers.len();
+    for wtr in writers.values_mut() {
+        wtr.flush()?;
+    }
+    Ok(nfiles)
+}
+
+/// stack several CSVs into one output CSV, reconciling their headers.
+///
+/// The output header is the union of the input headers in first-seen order; columns an
+/// input lacks are left empty. A header repeated within one file maps to its own column
+/// per occurrence. When `source_column` is given, a column by that name is appended holding
+/// the path of the file each row came from. Returns the number of rows written.
+pub fn merge_csvs(
+    inputs: &[Config],
+    output: &Path,
+    source_column: Option<&str>,
+) -> anyhow::Result<u64> {
+    // (header name, occurrence within its file) -> output column
+    let mut col_index: HashMap<(Vec<u8>, usize), usize> = HashMap::new();
+    let mut headers: Vec<Vec<u8>> = Vec::new();
+    let mut mappings = Vec::with_capacity(inputs.len());
+    for conf in inputs {
+        let mut rdr = conf.reader()?;
+        let mut seen: HashMap<&[u8], usize> = HashMap::new();
+        let mut mapping = Vec::new();
+        for name in rdr.byte_headers()? {
+            let nth = seen.entry(name).or_default();
+            let key = (name.to_vec(), *nth);
+            *nth += 1;
+            let col = *col_index.entry(key).or_insert_with(|| {
+                headers.push(name.to_vec());
+                headers.len() - 1
+            });
+            mapping.push(col);
+        }
+        mappings.push(mapping);
+    }
+    if let Some(name) = source_column {
+        if headers.iter().any(|h| h == name.as_bytes()) {
+            return Err(anyhow!(
+                "cannot add provenance column \"{name}\": an input already has a column by that \
+                 name"
+            ));
+        }
+        headers.push(name.as_bytes().to_vec());
+    }
+
+    let mut wtr = csv_writer(Some(output), None, csv::QuoteStyle::Necessary)?;
+    wtr.write_byte_record(&ByteRecord::from(headers.clone()))?;
+    let ncols = headers.len();
+    let mut record = ByteRecord::new();
+    let mut out = ByteRecord::with_capacity(0, ncols);
+    let mut rows = 0_u64;
+    for (conf, mapping) in inputs.iter().zip(&mappings) {
+        let source = conf
+            .path
+            .as_ref()
+            .map_or_else(|| "<stdin>".to_string(), |p| p.display().to_string());
+        // output column -> field of this input
+        let mut fields: Vec<Option<usize>> = vec![None; ncols];
+        for (field, &col) in mapping.iter().enumerate() {
+            fields[col] = Some(field);
+        }
+        let mut rdr = conf.reader()?;
+        while conf.read_byte_record(&mut rdr, &mut record)? {
+            out.clear();
+            for (col, field) in fields.iter().enumerate() {
+                match field {
+                    Some(i) => out.push_field(record.get(*i).unwrap_or_default()),
+                    None if source_column.is_some() && col == ncols - 1 => {
+                        out.push_field(source.as_bytes());
+                    },
+                    None => out.push_field(b""),
+                }
+            }
+            wtr.write_byte_record(&out)?;
+            rows += 1;
+        }
+    }
+    wtr.flush()?;
+    Ok(rows)
+}
+
+/// How `hash_join` treats left rows without a match on the right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JoinKind {
+    /// only rows whose key is found on both sides
+    #[default]
+    Inner,
+    /// every left row, with empty right columns when there is no match
+    Left,
+}
+
+/// join two CSVs on a key column each, streaming the result to `output`.
+///
+/// The right file is scanned once to build a hash index from key to record positions, so
+/// only its keys are held in memory; the left file is then streamed and matching right rows
+/// are read back by seeking. Keys are compared byte for byte and empty keys never match.
+/// The output has all left columns followed by the right columns minus its key; right
+/// headers that clash with a left one get a `_right` suffix. Returns the rows written.
+pub fn hash_join(
+    left: &Config,
+    left_key: usize,
+    right: &Config,
+    right_key: usize,
+    kind: JoinKind,
+    output: &Path,
+) -> anyhow::Result<u64> {
+    if right.is_snappy() {
+        return Err(anyhow!(
+            "the right side of a join cannot be snappy-compressed, decompress it first"
+        ));
+    }
+
+    let mut right_rdr = right.reader_file()?;
+    let right_headers = right_rdr.byte_headers()?.clone();
+    if right_key >= right_headers.len() {
+        return Err(anyhow!(
+            "right key column {right_key} out of range ({} columns)",
+            right_headers.len()
+        ));
+    }
+    let mut index: HashMap<Vec<u8>, Vec<csv::Position>> = HashMap::new();
+    let mut record = ByteRecord::new();
+    while right.read_byte_record(&mut right_rdr, &mut record)? {
+        let Some(key) = record.get(right_key).filter(|k| !k.is_empty()) else {
+            continue;
+        };
+        if let Some(pos) = record.position() {
+            index.entry(key.to_vec()).or_default().push(pos.clone());
+        }
+    }
+
+    let mut left_rdr = left.reader()?;
+    let left_headers = left_rdr.byte_headers()?.clone();
+    if left_key >= left_headers.len() {
+        return Err(anyhow!(
+            "left key column {left_key} out of range ({} columns)",
+            left_headers.len()
+        ));
+    }
+    let right_cols: Vec<usize> = (0..right_headers.len())
+        .filter(|&i| i != right_key)
+        .collect();
+
+    let mut wtr = csv_writer(Some(output), None, csv::QuoteStyle::Necessary)?;
+    let mut header = left_headers.clone();
+    for &i in &right_cols {
+        let name = &right_headers[i];
+        if left_headers.iter().any(|h| h == name) {
+            let mut renamed = name.to_vec();
+            renamed.extend_from_slice(b"_right");
+            header.push_field(&renamed);
+        } else {
+            header.push_field(name);
+        }
+    }
+    wtr.write_byte_record(&header)?;
+
+    let mut left_rec = ByteRecord::new();
+    let mut right_rec = ByteRecord::new();
+    let mut out = ByteRecord::new();
+    let mut rows = 0_u64;
+    while left.read_byte_record(&mut left_rdr, &mut left_rec)? {
+        let matches = left_rec
+            .get(left_key)
+            .and_then(|k| index.get(k))
+            .map_or(&[][..], Vec::as_slice);
+        if matches.is_empty() {
+            if kind == JoinKind::Left {
+                out.clone_from(&left_rec);
+                for _ in &right_cols {
+                    out.push_field(b"");
+                }
+                wtr.write_byte_record(&out)?;
+                rows += 1;
+            }
+            continue;
+        }
+        for pos in matches {
+            right_rdr.seek(pos.clone())?;
+            if !right.read_byte_record(&mut right_rdr, &mut right_rec)? {
+                continue;
+            }
+            out.clone_from(&left_rec);
+            for &i in &right_cols {
+                out.push_field(right_rec.get(i).unwrap_or_default());
+            }
+            wtr.write_byte_record(&out)?;
+            rows += 1;
+        }
+    }
+    wtr.flush()?;
+    Ok(rows)
+}
+
+/// A stats.csv.data.jsonl record that couldn't be parsed and was left out of the stats.
+#[derive(Debug, Clone)]
+pub struct StatsParseWarning {
+    /// 1-based line number in the stats jsonl file
+    pub line:  usize,
+    pub error: String,
+}
+
+/// get stats records from stats.csv.data.jsonl file, or if its invalid, by running the stats
+/// command returns tuple (`csv_fields`, `csv_stats`, `stats_col_index_map`).
+/// Stats records that can't be parsed are logged as warnings and left out; use
+/// get_stats_records_with_warnings to get them.
+pub fn get_stats_records(
+    args: &SchemaArgs,
+    requested_mode: StatsMode,
+) -> anyhow::Result<(ByteRecord, Vec<StatsData>, HashMap<String, String>)> {
+    let (csv_fields, csv_stats, dataset_stats, warnings) =
+        get_stats_records_with_warnings(args, requested_mode)?;
+    for w in &warnings {
+        tracing::warn!("error parsing stats line {}: {}", w.line, w.error);
+    }
+    Ok((csv_fields, csv_stats, dataset_stats))
+}
+
+/// like get_stats_records, but also returns the stats records that couldn't be parsed.
+/// A stats cache with unparseable records is regenerated, so warnings can only come from a
+/// freshly generated stats jsonl file.
+pub fn get_stats_records_with_warnings(
+    args: &SchemaArgs,
+    requested_mode: StatsMode,
+) -> anyhow::Result<(
+    ByteRecord,
+    Vec<StatsData>,
+    HashMap<String, String>,
+    Vec<StatsParseWarning>,
+)> {
+    let env_mode = env::var("QSV_STATSCACHE_MODE")
+        .unwrap_or_else(|_| DEFAULT_STATSCACHE_MODE.to_string())
+        .to_ascii_lowercase();
+
+    if !["auto", "force", "none"].contains(&env_mode.as_str()) {
         return Err(anyhow!(
             "Invalid QSV_STATSCACHE_MODE value: {env_mode}. Must be one of: auto, force, none"
         ));
@@ -1729,7 +3027,7 @@ pub fn get_stats_records(
     {
         // if stdin or StatsMode::None,
         // we're just doing frequency old school w/o cardinality
-        return Ok((ByteRecord::new(), Vec::new(), HashMap::new()));
+        return Ok((ByteRecord::new(), Vec::new(), HashMap::new(), Vec::new()));
     }
 
     let input_path = args
@@ -1767,7 +3065,7 @@ pub fn get_stats_records(
         // if the stats.data file is not current,
         // we're also doing frequency old school w/o cardinality
         // unless env_mode auto overrides
-        return Ok((ByteRecord::new(), Vec::new(), HashMap::new()));
+        return Ok((ByteRecord::new(), Vec::new(), HashMap::new(), Vec::new()));
     }
 
     // get the headers from the input file
@@ -1776,44 +3074,27 @@ pub fn get_stats_records(
     drop(rdr);
 
     let mut stats_data_loaded = false;
+    let mut parse_warnings = Vec::new();
     let mut csv_stats: Vec<StatsData> = Vec::with_capacity(csv_fields.len());
     let mut dataset_stats: HashMap<String, String> = HashMap::with_capacity(4);
 
     // if stats_data file exists and is current, use it
     if stats_data_current && !args.flag_force {
-        let statsdatajson_rdr =
-            BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, File::open(statsdata_path)?);
-
-        let mut curr_line: String;
-        let mut s_slice: Vec<u8>;
-
-        for line in statsdatajson_rdr.lines() {
-            curr_line = line?;
-            s_slice = curr_line.as_bytes().to_vec();
-            if curr_line.starts_with(DATASET_STATS_PREFIX) {
-                // Parse dataset stats record
-                let v: serde_json::Value = simd_json::serde::from_slice(&mut s_slice)
-                    .map_err(|e| anyhow!("Failed to parse dataset stats JSON: {e}"))?;
-                let field = &v["field"];
-                let value = v["qsv__value"].clone();
-
-                dataset_stats.insert(
-                    field
-                        .as_str()
-                        .unwrap_or_default()
-                        .trim_matches('"')
-                        .to_string(),
-                    value.to_string(),
-                );
-            } else {
-                // Parse regular stats record
-                match simd_json::from_slice::<StatsData>(&mut s_slice) {
-                    Ok(stats) => csv_stats.push(stats),
-                    Err(e) => eprintln!("error parsing stats: {e}"),
-                }
+        let warnings = read_stats_jsonl(&statsdata_path, &mut csv_stats, &mut dataset_stats)?;
+        if warnings.is_empty() {
+            stats_data_loaded = !csv_stats.is_empty();
+        } else {
+            // a partially corrupt stats cache would give incomplete stats, regenerate it instead
+            for w in &warnings {
+                tracing::warn!("{}:{}: {}", statsdata_path.display(), w.line, w.error);
             }
+            tracing::warn!(
+                "{} unparseable stats record(s) in cache. Regenerating stats jsonl.",
+                warnings.len()
+            );
+            csv_stats.clear();
+            dataset_stats.clear();
         }
-        stats_data_loaded = !csv_stats.is_empty();
     }
 
     // otherwise, run stats command to generate stats.csv.data.jsonl file
@@ -1952,37 +3233,7 @@ pub fn get_stats_records(
         // create a stats data jsonl from the output of the stats command
         csv_to_jsonl(&tempfile_path, &STATSDATA_TYPES_MAP, statsdatajson_path)?;
 
-        let statsdatajson_rdr =
-            BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, File::open(statsdatajson_path)?);
-
-        let mut curr_line: String;
-        let mut s_slice: Vec<u8>;
-        for line in statsdatajson_rdr.lines() {
-            curr_line = line?;
-            s_slice = curr_line.as_bytes().to_vec();
-            if curr_line.starts_with(DATASET_STATS_PREFIX) {
-                // Parse dataset stats record
-                let v: serde_json::Value = simd_json::serde::from_slice(&mut s_slice)
-                    .map_err(|e| anyhow!("Failed to parse dataset stats JSONL: {e}"))?;
-                let field = &v["field"];
-                let value = v["qsv__value"].clone();
-
-                dataset_stats.insert(
-                    field
-                        .as_str()
-                        .unwrap_or_default()
-                        .trim_matches('"')
-                        .to_string(),
-                    value.to_string(),
-                );
-            } else {
-                // Parse regular stats record
-                match simd_json::from_slice::<StatsData>(&mut s_slice) {
-                    Ok(stats) => csv_stats.push(stats),
-                    Err(e) => eprintln!("error parsing stats: {e}"),
-                }
-            }
-        }
+        parse_warnings = read_stats_jsonl(statsdatajson_path, &mut csv_stats, &mut dataset_stats)?;
     }
 
     // ensure csv_fields and csv_stats have the same length
@@ -1991,9 +3242,58 @@ pub fn get_stats_records(
         csv_fields.iter().take(csv_stats.len()).collect(),
         csv_stats,
         dataset_stats,
+        parse_warnings,
     ))
 }
 
+/// read a stats.csv.data.jsonl file, appending the column stats to csv_stats and the dataset
+/// stats to dataset_stats. Column stats records that can't be parsed are skipped and returned as
+/// warnings; an unparseable dataset stats record is an error.
+fn read_stats_jsonl(
+    path: &Path,
+    csv_stats: &mut Vec<StatsData>,
+    dataset_stats: &mut HashMap<String, String>,
+) -> anyhow::Result<Vec<StatsParseWarning>> {
+    const DATASET_STATS_PREFIX: &str = r#"{"field":"qsv__"#;
+
+    let statsdatajson_rdr =
+        BufReader::with_capacity(DEFAULT_RDR_BUFFER_CAPACITY, File::open(path)?);
+    let mut warnings = Vec::new();
+
+    let mut curr_line: String;
+    let mut s_slice: Vec<u8>;
+    for (idx, line) in statsdatajson_rdr.lines().enumerate() {
+        curr_line = line?;
+        s_slice = curr_line.as_bytes().to_vec();
+        if curr_line.starts_with(DATASET_STATS_PREFIX) {
+            // Parse dataset stats record
+            let v: serde_json::Value = simd_json::serde::from_slice(&mut s_slice)
+                .map_err(|e| anyhow!("Failed to parse dataset stats JSONL: {e}"))?;
+            let field = &v["field"];
+            let value = v["qsv__value"].clone();
+
+            dataset_stats.insert(
+                field
+                    .as_str()
+                    .unwrap_or_default()
+                    .trim_matches('"')
+                    .to_string(),
+                value.to_string(),
+            );
+        } else {
+            // Parse regular stats record
+            match simd_json::from_slice::<StatsData>(&mut s_slice) {
+                Ok(stats) => csv_stats.push(stats),
+                Err(e) => warnings.push(StatsParseWarning {
+                    line:  idx + 1,
+                    error: e.to_string(),
+                }),
+            }
+        }
+    }
+    Ok(warnings)
+}
+
 pub fn csv_to_jsonl(
     input_csv: &str,
     csv_types: &phf::Map<&'static str, JsonTypes>,
@@ -2071,6 +3371,30 @@ pub fn csv_to_jsonl(
     Ok(writer.flush()?)
 }
 
+/// which rule optimal_batch_size applied to pick the batch size
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchSizeBranch {
+    /// the requested batch size was below DEFAULT_BATCH_SIZE, so it was raised to it
+    RaisedToDefault,
+    /// the row count is unknown (not indexed and not counted), so DEFAULT_BATCH_SIZE is used
+    RowCountUnknown,
+    /// batching is disabled (batch size 0), all rows are handled in one batch
+    Disabled,
+    /// the rows are split evenly across the jobs
+    Optimal,
+    /// the requested batch size is used as is
+    Requested,
+}
+
+/// the inputs and outcome of an optimal batch size decision
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchSizeExplanation {
+    pub num_rows:   Option<usize>,
+    pub num_jobs:   usize,
+    pub batch_size: usize,
+    pub branch:     BatchSizeBranch,
+}
+
 /// get the optimal batch size
 /// if CSV is not indexed and ROW_COUNT is not set, return DEFAULT_BATCH_SIZE
 /// if batch_size is 0, return the number of rows in the CSV, effectively disabling batching
@@ -2080,8 +3404,33 @@ pub fn csv_to_jsonl(
 /// failing everything above, return the requested batch_size
 #[inline]
 pub fn optimal_batch_size(rconfig: &Config, batch_size: usize, num_jobs: usize) -> usize {
+    let explanation = optimal_batch_size_explained(rconfig, batch_size, num_jobs);
+    tracing::debug!(
+        "optimal batch size: {batch} (requested: {batch_size}, num_rows: {rows:?}, num_jobs: \
+         {num_jobs}, branch: {branch:?})",
+        batch = explanation.batch_size,
+        rows = explanation.num_rows,
+        branch = explanation.branch,
+    );
+    explanation.batch_size
+}
+
+/// same as optimal_batch_size, but also returns the row count, number of jobs
+/// and the branch taken, for diagnostics
+pub fn optimal_batch_size_explained(
+    rconfig: &Config,
+    batch_size: usize,
+    num_jobs: usize,
+) -> BatchSizeExplanation {
+    let explain = |num_rows, batch_size, branch| BatchSizeExplanation {
+        num_rows,
+        num_jobs,
+        batch_size,
+        branch,
+    };
+
     if batch_size > 1 && batch_size < DEFAULT_BATCH_SIZE {
-        return DEFAULT_BATCH_SIZE;
+        return explain(None, DEFAULT_BATCH_SIZE, BatchSizeBranch::RaisedToDefault);
     }
 
     let num_rows = match ROW_COUNT.get() {
@@ -2089,28 +3438,122 @@ pub fn optimal_batch_size(rconfig: &Config, batch_size: usize, num_jobs: usize)
         None => match rconfig.indexed() {
             Ok(Some(idx)) => idx.count() as usize,
             _ => {
-                return DEFAULT_BATCH_SIZE;
+                return explain(None, DEFAULT_BATCH_SIZE, BatchSizeBranch::RowCountUnknown);
             },
         },
     };
 
     if batch_size == 0 {
         // disable batching, handle all rows in one batch
-        num_rows
+        explain(Some(num_rows), num_rows, BatchSizeBranch::Disabled)
     } else if (num_rows > DEFAULT_BATCH_SIZE && (batch_size == DEFAULT_BATCH_SIZE))
         || batch_size == 1
     {
         // the optimal batch size is the number of rows divided by the number of jobs
-        if num_rows.is_multiple_of(num_jobs) {
+        let optimal_size = if num_rows.is_multiple_of(num_jobs) {
             // there is no remainder as num_rows is divisible by num_jobs
             num_rows / num_jobs
         } else {
             // there is a remainder, we add 1 to the batch size
             // this is to ensure that all rows are processed
             (num_rows / num_jobs) + 1
-        }
+        };
+        explain(Some(num_rows), optimal_size, BatchSizeBranch::Optimal)
+    } else {
+        explain(Some(num_rows), batch_size, BatchSizeBranch::Requested)
+    }
+}
+
+/// Iterator over the results of par_record_batches, one Vec per batch, in input order.
+pub struct ParRecordBatches<'r, R, F> {
+    conf:       &'r Config,
+    rdr:        &'r mut csv::Reader<R>,
+    batch:      Vec<csv::StringRecord>,
+    batch_size: usize,
+    pool:       Option<rayon::ThreadPool>,
+    f:          F,
+    done:       bool,
+}
+
+/// Read `rdr` in batches of `batch_size` records and map `f` over each batch in parallel.
+/// Records are read through `conf`, so its record transform, if any, is applied.
+/// Each item yielded is the mapped batch, with results in the same order as the input records.
+/// If `jobs` is 0 or matches the global rayon pool size, the global pool is used,
+/// otherwise a dedicated pool with `jobs` threads is created.
+pub fn par_record_batches<'r, R, T, F>(
+    conf: &'r Config,
+    rdr: &'r mut csv::Reader<R>,
+    batch_size: usize,
+    jobs: usize,
+    f: F,
+) -> anyhow::Result<ParRecordBatches<'r, R, F>>
+where
+    R: Read,
+    T: Send,
+    F: Fn(&csv::StringRecord) -> T + Sync,
+{
+    let pool = if jobs == 0 || jobs == rayon::current_num_threads() {
+        None
     } else {
-        batch_size
+        Some(rayon::ThreadPoolBuilder::new().num_threads(jobs).build()?)
+    };
+    let batch_size = batch_size.max(1);
+
+    Ok(ParRecordBatches {
+        conf,
+        rdr,
+        batch: Vec::with_capacity(batch_size),
+        batch_size,
+        pool,
+        f,
+        done: false,
+    })
+}
+
+impl<R, T, F> Iterator for ParRecordBatches<'_, R, F>
+where
+    R: Read,
+    T: Send,
+    F: Fn(&csv::StringRecord) -> T + Sync,
+{
+    type Item = csv::Result<Vec<T>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use rayon::prelude::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+
+        if self.done {
+            return None;
+        }
+
+        self.batch.clear();
+        let mut record = csv::StringRecord::new();
+        for _ in 0..self.batch_size {
+            match self.conf.read_record(self.rdr, &mut record) {
+                Ok(true) => self.batch.push(std::mem::take(&mut record)),
+                Ok(false) => {
+                    self.done = true;
+                    break;
+                },
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                },
+            }
+        }
+
+        if self.batch.is_empty() {
+            return None;
+        }
+
+        // collect_into_vec preserves the original record order
+        let mut results = Vec::with_capacity(self.batch.len());
+        let (batch, f) = (&self.batch, &self.f);
+        let process = || batch.par_iter().map(f).collect_into_vec(&mut results);
+        match &self.pool {
+            Some(pool) => pool.install(process),
+            None => process(),
+        }
+        Some(Ok(results))
     }
 }
 
@@ -2131,6 +3574,21 @@ pub fn expand_tilde(path: impl AsRef<Path>) -> Option<PathBuf> {
     Some(expanded)
 }
 
+/// Returns the pschema.json path that [`convert_special_format`] looks for when loading `path`.
+///
+/// Only the input file prefix is used, so data.csv, data.tsv.gz, data.parquet and data.ssv all
+/// share a schema file named data.pschema.json.
+pub fn pschema_path(path: &Path) -> PathBuf {
+    // TODO: replace this with std::path::file_prefix once its stabilized
+    // https://github.com/rust-lang/rust/pull/129114
+    let fileprefix = path
+        .file_name()
+        .and_then(|fname| fname.to_str())
+        .map(|s| s.split('.').next().unwrap_or(""))
+        .unwrap_or_default();
+    path.with_file_name(format!("{fileprefix}.pschema.json"))
+}
+
 /// Loads a Polars schema from a pschema.json file if it exists.
 ///
 /// # Arguments
@@ -2141,17 +3599,7 @@ pub fn expand_tilde(path: impl AsRef<Path>) -> Option<PathBuf> {
 ///
 /// * `Option<Arc<Schema>>` - The loaded schema if the file exists and can be parsed, None otherwise
 fn load_schema_from_file(path: &Path) -> Result<Option<Arc<Schema>>, Box<dyn std::error::Error>> {
-    // Use only the input file prefix to create the schema file path
-    // e.g. data.tsv.gz, data.parquet, data.ssv should look for a schema file
-    // named data.pschema.json
-    // TODO: replace this with std::path::file_prefix once its stabilized
-    // https://github.com/rust-lang/rust/pull/129114
-    let fileprefix = path
-        .file_name()
-        .and_then(|fname| fname.to_str())
-        .map(|s| s.split('.').next().unwrap_or(""))
-        .unwrap_or_default();
-    let schema_file = path.with_file_name(format!("{fileprefix}.pschema.json"));
+    let schema_file = pschema_path(path);
 
     if schema_file.exists() {
         // Load the schema from the pschema.json file
@@ -2166,8 +3614,82 @@ fn load_schema_from_file(path: &Path) -> Result<Option<Arc<Schema>>, Box<dyn std
     }
 }
 
+/// A cell whose value doesn't parse as the type its column is declared as in a schema
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// 0-based data row (header excluded)
+    pub row:      u64,
+    pub column:   String,
+    /// the declared polars type, e.g. "i64"
+    pub expected: String,
+    pub value:    String,
+}
+
+/// Does value parse as dtype? Types without a textual check (strings, lists, ...) accept
+/// anything.
+fn value_matches_dtype(value: &str, dtype: &polars::datatypes::DataType, prefer_dmy: bool) -> bool {
+    use polars::datatypes::DataType;
+
+    match dtype {
+        DataType::Int8 => value.parse::<i8>().is_ok(),
+        DataType::Int16 => value.parse::<i16>().is_ok(),
+        DataType::Int32 => value.parse::<i32>().is_ok(),
+        DataType::Int64 => value.parse::<i64>().is_ok(),
+        DataType::UInt8 => value.parse::<u8>().is_ok(),
+        DataType::UInt16 => value.parse::<u16>().is_ok(),
+        DataType::UInt32 => value.parse::<u32>().is_ok(),
+        DataType::UInt64 => value.parse::<u64>().is_ok(),
+        DataType::Float32 | DataType::Float64 | DataType::Decimal(..) => {
+            value.parse::<f64>().is_ok()
+        },
+        DataType::Boolean => {
+            value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false")
+        },
+        DataType::Date | DataType::Datetime(..) => parse_timestamp_ms(value, prefer_dmy).is_some(),
+        _ => true,
+    }
+}
+
+/// Check every cell of the CSV against the type its column is declared as in schema
+/// (e.g. loaded from a .pschema.json written by infer_polars_schema), returning the cells
+/// that don't parse as that type.
+///
+/// Columns are matched by header name; columns the schema doesn't declare aren't checked.
+/// Empty and null cells (see Config::is_null) are valid for any type.
+pub fn validate_against_schema(conf: &Config, schema: &Schema) -> anyhow::Result<Vec<Violation>> {
+    let mut rdr = conf.clone().flexible(true).reader()?;
+    let headers = rdr.headers()?.clone();
+    let dtypes: Vec<_> = headers.iter().map(|name| schema.get(name)).collect();
+    let prefer_dmy = conf.get_dmy_preference();
+
+    let mut violations = Vec::new();
+    let mut record = csv::StringRecord::new();
+    let mut row = 0_u64;
+    while conf.read_record(&mut rdr, &mut record)? {
+        for (col, value) in record.iter().enumerate() {
+            let Some(Some(dtype)) = dtypes.get(col) else {
+                continue;
+            };
+            if conf.is_null(value.as_bytes())
+                || value_matches_dtype(value.trim(), dtype, prefer_dmy)
+            {
+                continue;
+            }
+            violations.push(Violation {
+                row,
+                column: headers[col].to_string(),
+                expected: dtype.to_string(),
+                value: value.to_string(),
+            });
+        }
+        row += 1;
+    }
+    Ok(violations)
+}
+
 /// Converts files in special formats (Parquet, Avro, Arrow IPC, JSONL, JSON, or compressed CSV)
-/// into a standard delimited text file. The output file extension will be:
+/// into a standard delimited text file. JSONL is streamed with `jsonl_to_csv`, the other
+/// formats are loaded through polars. The output file extension will be:
 /// - .tsv for tab-delimited
 /// - .ssv for semicolon-delimited
 /// - .csv for comma-delimited
@@ -2190,15 +3712,19 @@ pub fn convert_special_format(
     use polars::{
         io::avro::AvroReader,
         prelude::{
-            CsvParseOptions, CsvReadOptions, CsvWriter, IpcReader, JsonLineReader, JsonReader,
-            ParquetReader, SerReader, SerWriter,
+            CsvParseOptions, CsvReadOptions, CsvWriter, IpcReader, JsonReader, ParquetReader,
+            SerReader, SerWriter,
         },
     };
 
     // Check if there's a pschema.json file with the same filestem
     // the Polars schema will be used in parsing
-    // JSON/JSONL and compressed CSV files only
-    let schema = if let SpecialFormat::Avro | SpecialFormat::Parquet | SpecialFormat::Ipc = format {
+    // JSON and compressed CSV files only
+    let schema = if let SpecialFormat::Avro
+    | SpecialFormat::Parquet
+    | SpecialFormat::Ipc
+    | SpecialFormat::Jsonl = format
+    {
         None
     } else {
         load_schema_from_file(path)?
@@ -2211,12 +3737,15 @@ pub fn convert_special_format(
         SpecialFormat::Parquet => ParquetReader::new(BufReader::new(File::open(path)?)).finish()?,
         SpecialFormat::Ipc => IpcReader::new(BufReader::new(File::open(path)?)).finish()?,
         SpecialFormat::Jsonl => {
-            let df = JsonLineReader::new(BufReader::new(File::open(path)?));
-            if let Some(schema) = schema {
-                df.with_schema(schema).finish()?
-            } else {
-                df.finish()?
-            }
+            // JSONL logs can be far bigger than memory, so they are streamed line by line
+            let temp_file = special_format_temp_file(".csv")?;
+            jsonl_to_csv(
+                path,
+                temp_file.path(),
+                JsonNesting::Flatten,
+                JSONL_HEADER_SAMPLE_LINES,
+            )?;
+            return Ok(temp_file.into_temp_path().keep()?);
         },
         SpecialFormat::Json => {
             let df = JsonReader::new(BufReader::new(File::open(path)?));
@@ -2289,16 +3818,7 @@ pub fn convert_special_format(
         SpecialFormat::Unknown => return Err("Unknown format".into()),
     };
 
-    // Get or initialize temp directory that persists until program exit
-    // safety: we know that the tempfile::TempDir::new() will not ordinarily fail
-    // otherwise, we have a bigger problem
-    let temp_dir =
-        crate::config::TEMP_FILE_DIR.get_or_init(|| tempfile::TempDir::new().unwrap().keep());
-
-    // Create temp file with appropriate extension
-    let mut temp_file = tempfile::Builder::new()
-        .suffix(extension)
-        .tempfile_in(temp_dir)?;
+    let mut temp_file = special_format_temp_file(extension)?;
 
     // Get QSV_POLARS_FORMAT_FLOAT_PRECISION env var
     let precision = crate::config::POLARS_FLOAT_PRECISION.get_or_init(|| {
@@ -2320,12 +3840,50 @@ pub fn convert_special_format(
     Ok(path)
 }
 
-pub fn infer_polars_schema(
-    delimiter: Option<config::Delimiter>,
-    debuglog_flag: bool,
+/// Create the temp file a special format is converted into, with the given extension.
+fn special_format_temp_file(extension: &str) -> std::io::Result<tempfile::NamedTempFile> {
+    // Get or initialize temp directory that persists until program exit
+    // safety: we know that the tempfile::TempDir::new() will not ordinarily fail
+    // otherwise, we have a bigger problem
+    let temp_dir =
+        crate::config::TEMP_FILE_DIR.get_or_init(|| tempfile::TempDir::new().unwrap().keep());
+
+    tempfile::Builder::new()
+        .suffix(extension)
+        .tempfile_in(temp_dir)
+}
+
+/// Column type as inferred from the stats of a CSV, see infer_column_types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InferredType {
+    Int,
+    Float,
+    Bool,
+    Date,
+    DateTime,
+    String,
+}
+
+impl InferredType {
+    /// map a stats `type` (e.g. "Integer") to an InferredType. Anything else (String, NULL)
+    /// is a String.
+    pub fn from_stats_type(stats_type: &str) -> Self {
+        match stats_type {
+            "Integer" => InferredType::Int,
+            "Float" => InferredType::Float,
+            "Boolean" => InferredType::Bool,
+            "Date" => InferredType::Date,
+            "DateTime" => InferredType::DateTime,
+            _ => InferredType::String,
+        }
+    }
+}
+
+/// run (or reuse the cache of) stats on table to get its headers and per-column stats
+fn infer_column_stats(
+    delimiter: Option<config::Delimiter>,
     table: &Path,
-    schema_file: &std::path::PathBuf,
-) -> anyhow::Result<bool> {
+) -> anyhow::Result<(ByteRecord, Vec<StatsData>)> {
     let schema_args = SchemaArgs {
         flag_enum_threshold:  0,
         flag_ignore_case:     false,
@@ -2344,6 +3902,36 @@ pub fn infer_polars_schema(
         flag_memcheck:        false,
     };
     let (csv_fields, csv_stats, _) = get_stats_records(&schema_args, StatsMode::PolarsSchema)?;
+    Ok((csv_fields, csv_stats))
+}
+
+/// Infer the type of each column of the CSV, as `(header, type)` pairs in column order.
+/// This is the same inference infer_polars_schema uses, without the polars-specific sizing
+/// of integer and float types. Stdin is not supported, as stats need a file.
+pub fn infer_column_types(conf: &Config) -> anyhow::Result<Vec<(String, InferredType)>> {
+    let Some(table) = &conf.path else {
+        return Err(anyhow!("Cannot infer column types of stdin."));
+    };
+    let (csv_fields, csv_stats) = infer_column_stats(Some(Delimiter(conf.get_delimiter())), table)?;
+    Ok(csv_fields
+        .iter()
+        .zip(&csv_stats)
+        .map(|(field, stat)| {
+            (
+                String::from_utf8_lossy(field).into_owned(),
+                InferredType::from_stats_type(&stat.r#type),
+            )
+        })
+        .collect())
+}
+
+pub fn infer_polars_schema(
+    delimiter: Option<config::Delimiter>,
+    debuglog_flag: bool,
+    table: &Path,
+    schema_file: &std::path::PathBuf,
+) -> anyhow::Result<bool> {
+    let (csv_fields, csv_stats) = infer_column_stats(delimiter, table)?;
     let mut schema = polars::prelude::Schema::with_capacity(csv_stats.len());
     for (idx, stat) in csv_stats.iter().enumerate() {
         // safety: we know that the get(idx) will not be None as we are using an iterator
@@ -2352,11 +3940,10 @@ pub fn infer_polars_schema(
                 simdutf8::basic::from_utf8(csv_fields.get(idx).unwrap()).unwrap(),
             ),
             {
-                let datatype = &stat.r#type;
                 #[allow(clippy::match_same_arms)]
-                match datatype.as_str() {
-                    "String" => polars::datatypes::DataType::String,
-                    "Integer" => {
+                match InferredType::from_stats_type(&stat.r#type) {
+                    InferredType::String => polars::datatypes::DataType::String,
+                    InferredType::Int => {
                         // safety: integer types are guaranteed to have a min and max
                         let min = stat.min.as_ref().unwrap();
                         let max = stat.max.as_ref().unwrap();
@@ -2390,7 +3977,7 @@ pub fn infer_polars_schema(
                             polars::datatypes::DataType::Int64
                         }
                     },
-                    "Float" => {
+                    InferredType::Float => {
                         // safety: float types are guaranteed to have a min and max
                         let min = stat.min.as_ref().unwrap();
                         let max = stat.max.as_ref().unwrap();
@@ -2415,9 +4002,10 @@ pub fn infer_polars_schema(
                             polars::datatypes::DataType::Float32
                         }
                     },
-                    "Boolean" => polars::datatypes::DataType::Boolean,
-                    "Date" => polars::datatypes::DataType::Date,
-                    _ => polars::datatypes::DataType::String,
+                    InferredType::Bool => polars::datatypes::DataType::Boolean,
+                    InferredType::Date => polars::datatypes::DataType::Date,
+                    // the schema leaves datetimes to polars' own parsing
+                    InferredType::DateTime => polars::datatypes::DataType::String,
                 }
             },
         );
@@ -2497,3 +4085,1524 @@ pub async fn download_file(
 
     Ok(file.flush()?)
 }
+
+/// Hash algorithms supported by hash_record and hash_file.
+/// MD5 and SHA-1 are only here to verify legacy evidence manifests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgo {
+    Md5,
+    Sha1,
+    Sha256,
+    Blake3,
+}
+
+impl str::FromStr for HashAlgo {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "md5" => Ok(HashAlgo::Md5),
+            "sha1" | "sha-1" => Ok(HashAlgo::Sha1),
+            "sha256" | "sha-256" => Ok(HashAlgo::Sha256),
+            "blake3" => Ok(HashAlgo::Blake3),
+            _ => Err(anyhow!(
+                "Unsupported hash algorithm: {s}. Must be one of: md5, sha1, sha256, blake3"
+            )),
+        }
+    }
+}
+
+/// incremental hasher for any HashAlgo
+enum Hasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    Blake3(Box<blake3::Hasher>),
+}
+
+impl Hasher {
+    fn new(algo: HashAlgo) -> Self {
+        use sha2::Digest;
+
+        match algo {
+            HashAlgo::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgo::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgo::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgo::Blake3 => Hasher::Blake3(Box::new(blake3::Hasher::new())),
+        }
+    }
+
+    #[inline]
+    fn update(&mut self, bytes: &[u8]) {
+        use sha2::Digest;
+
+        match self {
+            Hasher::Md5(h) => h.update(bytes),
+            Hasher::Sha1(h) => h.update(bytes),
+            Hasher::Sha256(h) => h.update(bytes),
+            Hasher::Blake3(h) => {
+                h.update(bytes);
+            },
+        }
+    }
+
+    /// the digest as lowercase hex
+    fn finalize_hex(self) -> String {
+        use sha2::Digest;
+
+        match self {
+            Hasher::Md5(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha1(h) => format!("{:x}", h.finalize()),
+            Hasher::Sha256(h) => format!("{:x}", h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+        }
+    }
+}
+
+/// deterministic content hash of a CSV record, as a lowercase hex digest.
+/// Each field is hashed prefixed with its length as a little-endian u64, so the hash only
+/// depends on the field values - not the delimiter or quoting of the source file - and field
+/// boundaries are unambiguous (e.g. ["a,b"] and ["a", "b"] hash differently).
+pub fn hash_record(record: &ByteRecord, algo: HashAlgo) -> String {
+    let mut hasher = Hasher::new(algo);
+    for field in record {
+        hasher.update(&(field.len() as u64).to_le_bytes());
+        hasher.update(field);
+    }
+    hasher.finalize_hex()
+}
+
+/// hash a whole file in a streaming, buffered loop, returning the lowercase hex digest so it
+/// compares directly against the output of other hashing tools.
+/// If progress is set, its length is set to the file size and it's advanced by bytes read.
+pub fn hash_file(
+    path: &Path,
+    algo: HashAlgo,
+    progress: Option<&ProgressBar>,
+) -> anyhow::Result<String> {
+    let mut file = File::open(path)?;
+    if let Some(progress) = progress {
+        progress.set_length(file.metadata()?.len());
+    }
+
+    let mut hasher = Hasher::new(algo);
+    let mut buffer = vec![0_u8; DEFAULT_RDR_BUFFER_CAPACITY * 8];
+    loop {
+        let bytes_read = match file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        hasher.update(&buffer[..bytes_read]);
+        if let Some(progress) = progress {
+            progress.inc(bytes_read as u64);
+        }
+    }
+    Ok(hasher.finalize_hex())
+}
+
+/// append a column per named capture group of `pattern`, filled from its first match in
+/// `column` (left empty where it does not match), streaming the result to `output` (stdout
+/// if None). The new columns are named after their groups, with a `_2`, `_3`, ... suffix if
+/// the name is already taken.
+///
+/// Returns the new column names and the number of records that matched.
+pub fn extract_regex_columns(
+    conf: &Config,
+    column: usize,
+    pattern: &str,
+    output: Option<&Path>,
+) -> anyhow::Result<(Vec<String>, u64)> {
+    let re = regex::bytes::Regex::new(pattern).map_err(|e| anyhow!("invalid pattern: {e}"))?;
+    let groups: Vec<(usize, &str)> = re
+        .capture_names()
+        .enumerate()
+        .filter_map(|(i, name)| name.map(|name| (i, name)))
+        .collect();
+    if groups.is_empty() {
+        return Err(anyhow!(
+            "pattern has no named capture groups, e.g. (?P<port>\\d+)"
+        ));
+    }
+
+    let mut rdr = conf.reader()?;
+    let mut headers = rdr.byte_headers()?.clone();
+    if column >= headers.len() {
+        return Err(anyhow!(
+            "extract column {column} out of range ({} columns)",
+            headers.len()
+        ));
+    }
+
+    let mut names: Vec<String> = if conf.no_headers {
+        Vec::new()
+    } else {
+        headers
+            .iter()
+            .map(|h| String::from_utf8_lossy(h).into_owned())
+            .collect()
+    };
+    let existing = names.len();
+    for &(_, group) in &groups {
+        let name = unique_name(&names, group);
+        names.push(name);
+    }
+    let new_names = names.split_off(existing);
+
+    let mut wtr = csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    if !conf.no_headers {
+        for name in &new_names {
+            headers.push_field(name.as_bytes());
+        }
+        wtr.write_byte_record(&headers)?;
+    }
+
+    let mut matched = 0_u64;
+    let mut record = ByteRecord::new();
+    let mut out_record = ByteRecord::new();
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        let caps = record.get(column).and_then(|field| re.captures(field));
+        if caps.is_some() {
+            matched += 1;
+        }
+        out_record.clone_from(&record);
+        for &(i, _) in &groups {
+            let value = caps.as_ref().and_then(|caps| caps.get(i));
+            out_record.push_field(value.map_or(&b""[..], |m| m.as_bytes()));
+        }
+        wtr.write_byte_record(&out_record)?;
+    }
+    wtr.flush()?;
+
+    Ok((new_names, matched))
+}
+
+/// A piece of a `FieldTemplate`
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Field(usize),
+}
+
+/// a template such as `{hostname}:{port}` or `{first} {last}` that derives a value from the
+/// fields of a record, using the same `{key}` placeholders as geocode's dynfmt format strings.
+/// A key is a header name, the safe name of a header with spaces or punctuation (see
+/// `safe_header_names`, compared case-insensitively) or a 0-based column index. Text outside
+/// placeholders is copied as-is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldTemplate {
+    parts: Vec<TemplatePart>,
+}
+
+impl FieldTemplate {
+    /// Resolve the placeholders of `template` against `headers`.
+    pub fn new(template: &str, headers: &[&str]) -> anyhow::Result<Self> {
+        let key_re = regex_oncelock!(r"\{(?P<key>\w+)\}");
+        let (safe_names, _) = safe_header_names(
+            &csv::StringRecord::from(headers.to_vec()),
+            &SafeNameOptions::builder().keep_case(true).build(),
+        );
+
+        let mut parts = Vec::new();
+        let mut last = 0;
+        for caps in key_re.captures_iter(template) {
+            let (placeholder, key) = (caps.get(0).unwrap(), &caps["key"]);
+            let column = headers
+                .iter()
+                .position(|&h| h == key)
+                .or_else(|| safe_names.iter().position(|s| s.eq_ignore_ascii_case(key)))
+                .or_else(|| key.parse::<usize>().ok().filter(|&i| i < headers.len()))
+                .ok_or_else(|| anyhow!("unknown column \"{key}\" in template"))?;
+            if placeholder.start() > last {
+                parts.push(TemplatePart::Literal(
+                    template[last..placeholder.start()].to_string(),
+                ));
+            }
+            parts.push(TemplatePart::Field(column));
+            last = placeholder.end();
+        }
+        if last < template.len() {
+            parts.push(TemplatePart::Literal(template[last..].to_string()));
+        }
+        Ok(Self { parts })
+    }
+
+    /// The template filled in from a record, `field` giving the value of a column (missing
+    /// fields are left empty).
+    pub fn render<'a>(&self, field: impl Fn(usize) -> Option<&'a [u8]>) -> String {
+        let mut out = String::new();
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => out.push_str(text),
+                TemplatePart::Field(column) => {
+                    out.push_str(&String::from_utf8_lossy(field(*column).unwrap_or_default()));
+                },
+            }
+        }
+        out
+    }
+}
+
+/// does the card number `s` pass the Luhn checksum? Separators are skipped.
+fn luhn_valid(s: &[u8]) -> bool {
+    let mut sum = 0_u32;
+    let mut double = false;
+    for &b in s.iter().rev().filter(|b| b.is_ascii_digit()) {
+        let mut digit = u32::from(b - b'0');
+        if double {
+            digit *= 2;
+            if digit > 9 {
+                digit -= 9;
+            }
+        }
+        sum += digit;
+        double = !double;
+    }
+    sum % 10 == 0
+}
+
+/// mask every match of the user-supplied `patterns`, and of DEFAULT_REDACT_PATTERNS when
+/// `default_patterns` is set, within `columns` with `***`, streaming the result to `output`
+/// (stdout if None). Other columns, and the header row, are copied unchanged. Digit runs
+/// that look like card numbers but fail the Luhn check (timestamps, IDs) are left alone.
+///
+/// Returns the number of redactions made in each of `columns`, in the same order, keyed by
+/// column name (or 0-based index with no_headers).
+pub fn redact(
+    conf: &Config,
+    columns: &[usize],
+    patterns: &[&str],
+    default_patterns: bool,
+    output: Option<&Path>,
+) -> anyhow::Result<Vec<(String, u64)>> {
+    let defaults = if default_patterns {
+        DEFAULT_REDACT_PATTERNS
+    } else {
+        &[]
+    };
+    if defaults.is_empty() && patterns.is_empty() {
+        return Err(anyhow!("redact needs at least one pattern"));
+    }
+    let alternation = defaults
+        .iter()
+        .chain(patterns)
+        .map(|&p| {
+            if p == CARD_NUMBER_PATTERN {
+                format!("(?P<card>{p})")
+            } else {
+                format!("(?:{p})")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("|");
+    let re = regex::bytes::Regex::new(&alternation)
+        .map_err(|e| anyhow!("invalid redact pattern: {e}"))?;
+
+    let mut rdr = conf.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    if let Some(col) = columns.iter().find(|&&col| col >= headers.len()) {
+        return Err(anyhow!(
+            "redact column {col} out of range ({} columns)",
+            headers.len()
+        ));
+    }
+
+    let mut wtr = csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    if !conf.no_headers {
+        wtr.write_byte_record(&headers)?;
+    }
+
+    let mut counts = vec![0_u64; columns.len()];
+    let mut record = ByteRecord::new();
+    let mut out_record = ByteRecord::with_capacity(1024, headers.len());
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        out_record.clear();
+        for (i, field) in record.iter().enumerate() {
+            match columns.iter().position(|&col| col == i) {
+                Some(pos) => {
+                    let count = &mut counts[pos];
+                    let redacted = re.replace_all(field, |caps: &regex::bytes::Captures| {
+                        if let Some(card) = caps.name("card")
+                            && !luhn_valid(card.as_bytes())
+                        {
+                            return caps[0].to_vec();
+                        }
+                        *count += 1;
+                        REDACT_MASK.to_vec()
+                    });
+                    out_record.push_field(&redacted);
+                },
+                None => out_record.push_field(field),
+            }
+        }
+        wtr.write_byte_record(&out_record)?;
+    }
+    wtr.flush()?;
+
+    Ok(columns
+        .iter()
+        .zip(counts)
+        .map(|(&col, count)| {
+            let name = if conf.no_headers {
+                col.to_string()
+            } else {
+                String::from_utf8_lossy(&headers[col]).into_owned()
+            };
+            (name, count)
+        })
+        .collect())
+}
+
+// number of HMAC-SHA256 bytes kept in a pseudonymize token (hex-encoded, so twice as many chars)
+const PSEUDONYM_TOKEN_BYTES: usize = 16;
+
+/// the pseudonymize token of `value`: its HMAC-SHA256 keyed with `salt`, truncated and
+/// hex-encoded
+fn pseudonym_token(salt: &[u8], value: &[u8]) -> String {
+    use std::fmt::Write as _;
+
+    use hmac::{Hmac, Mac};
+
+    let mut mac =
+        Hmac::<sha2::Sha256>::new_from_slice(salt).expect("HMAC accepts keys of any length");
+    mac.update(value);
+    let mac = mac.finalize().into_bytes();
+    let mut token = String::with_capacity(PSEUDONYM_TOKEN_BYTES * 2);
+    for b in &mac[..PSEUDONYM_TOKEN_BYTES] {
+        let _ = write!(token, "{b:02x}");
+    }
+    token
+}
+
+/// replace the values of `columns` with deterministic tokens - the HMAC-SHA256 of the value
+/// keyed with `salt`, truncated to 128 bits and hex-encoded - writing the result to `output`
+/// (stdout if None). The same value always gets the same token for a given salt, whatever
+/// column it's in, but tokens can't be reversed or recomputed without the salt. Empty values
+/// are left empty.
+///
+/// If `mapping_output` is set, a `value,token` CSV of every distinct value pseudonymized is
+/// written there too, for internal re-identification - keep it away from the shared data.
+/// Returns the number of records written.
+pub fn pseudonymize(
+    conf: &Config,
+    columns: &[usize],
+    salt: &[u8],
+    output: Option<&Path>,
+    mapping_output: Option<&Path>,
+) -> anyhow::Result<u64> {
+    if salt.is_empty() {
+        return Err(anyhow!("pseudonymize requires a non-empty salt"));
+    }
+
+    let mut rdr = conf.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    if let Some(col) = columns.iter().find(|&&col| col >= headers.len()) {
+        return Err(anyhow!(
+            "pseudonymize column {col} out of range ({} columns)",
+            headers.len()
+        ));
+    }
+
+    let mut wtr = csv_writer(output, None, csv::QuoteStyle::Necessary)?;
+    if !conf.no_headers {
+        wtr.write_byte_record(&headers)?;
+    }
+
+    // distinct values in first-seen order, with the position of each in `mapping`. Only
+    // tracked when the mapping is written, so memory doesn't grow with the input otherwise.
+    let mut mapping: Vec<(Vec<u8>, String)> = Vec::new();
+    let mut seen: Option<HashMap<Vec<u8>, usize>> = mapping_output.map(|_| HashMap::new());
+    let mut record = ByteRecord::new();
+    let mut out_record = ByteRecord::with_capacity(1024, headers.len());
+    let mut count = 0_u64;
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        out_record.clear();
+        for (i, field) in record.iter().enumerate() {
+            if field.is_empty() || !columns.contains(&i) {
+                out_record.push_field(field);
+                continue;
+            }
+            if let Some(seen) = &mut seen {
+                let pos = *seen.entry(field.to_vec()).or_insert_with(|| {
+                    mapping.push((field.to_vec(), pseudonym_token(salt, field)));
+                    mapping.len() - 1
+                });
+                out_record.push_field(mapping[pos].1.as_bytes());
+            } else {
+                out_record.push_field(pseudonym_token(salt, field).as_bytes());
+            }
+        }
+        wtr.write_byte_record(&out_record)?;
+        count += 1;
+    }
+    wtr.flush()?;
+
+    if let Some(mapping_path) = mapping_output {
+        let mut mapping_wtr = csv_writer(Some(mapping_path), None, csv::QuoteStyle::Necessary)?;
+        mapping_wtr.write_record(["value", "token"])?;
+        for (value, token) in &mapping {
+            mapping_wtr.write_record([value.as_slice(), token.as_bytes()])?;
+        }
+        mapping_wtr.flush()?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_safe_header_names_max_len() {
+        let headers =
+            csv::StringRecord::from(vec!["a rather long column name", "größenänderung_überall"]);
+
+        let options = SafeNameOptions::builder()
+            .check_first_char(true)
+            .max_len(10)
+            .build();
+        let (names, _) = safe_header_names(&headers, &options);
+        assert_eq!(names, vec!["a_rather_l", "gr__en_nde"]);
+
+        let options = SafeNameOptions::builder().check_first_char(true).build();
+        let (names, _) = safe_header_names(&headers, &options);
+        assert_eq!(
+            names,
+            vec!["a_rather_long_column_name", "gr__en_nderung__berall"]
+        );
+    }
+
+    #[test]
+    fn test_camel_to_snake() {
+        assert_eq!(camel_to_snake("SomeColumnName"), "Some_Column_Name");
+        assert_eq!(camel_to_snake("someColumnName"), "some_Column_Name");
+        assert_eq!(camel_to_snake("HTTPServerID"), "HTTP_Server_ID");
+        assert_eq!(camel_to_snake("event2Time"), "event2_Time");
+        assert_eq!(camel_to_snake("already_snake"), "already_snake");
+        assert_eq!(camel_to_snake("ALLCAPS"), "ALLCAPS");
+    }
+
+    #[test]
+    fn test_safe_header_names_snake_case() {
+        let headers = csv::StringRecord::from(vec![
+            "SomeColumnName",
+            "HTTPStatusCode",
+            "plain",
+            "Two Words",
+        ]);
+        let safe_names = |conditional, snake_case| {
+            safe_header_names(
+                &headers,
+                &SafeNameOptions::builder()
+                    .check_first_char(true)
+                    .conditional(conditional)
+                    .snake_case(snake_case)
+                    .build(),
+            )
+        };
+
+        let (names, changed) = safe_names(false, true);
+        assert_eq!(
+            names,
+            vec!["some_column_name", "http_status_code", "plain", "two_words"]
+        );
+        assert_eq!(changed, 3);
+
+        // conditional mode leaves safe CamelCase names alone unless snake_case is set
+        assert_eq!(safe_names(true, false).0[0], "SomeColumnName");
+        assert_eq!(safe_names(true, true).0[0], "some_column_name");
+    }
+
+    #[test]
+    fn test_is_safe_name_max_len() {
+        assert!(is_safe_name("column_name", DEFAULT_SAFE_NAME_MAX_LEN));
+        assert!(!is_safe_name("column_name", 6));
+    }
+
+    #[test]
+    fn test_hash_record() {
+        let record = ByteRecord::from(vec!["a", "b"]);
+        assert_eq!(
+            hash_record(&record, HashAlgo::Sha256),
+            "cf6ab613e3942391f88ed698557e1680f160bd10e88c6b668c50360c10930e2b"
+        );
+        assert_eq!(hash_record(&record, HashAlgo::Blake3).len(), 64);
+
+        // field boundaries are part of the hash
+        let joined = ByteRecord::from(vec!["a,b"]);
+        for algo in [HashAlgo::Sha256, HashAlgo::Blake3] {
+            assert_ne!(hash_record(&record, algo), hash_record(&joined, algo));
+        }
+    }
+
+    #[test]
+    fn test_pseudonym_token() {
+        // RFC 4231 test case 2, truncated to PSEUDONYM_TOKEN_BYTES
+        assert_eq!(
+            pseudonym_token(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c7"
+        );
+    }
+
+    #[test]
+    fn test_pseudonymize() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("logons.csv");
+        fs::write(
+            &input,
+            "user,host,src_user\nalice,ws1,bob\nbob,ws2,\nalice,ws3,alice\n",
+        )
+        .unwrap();
+        let output = dir.path().join("shared.csv");
+        let mapping = dir.path().join("mapping.csv");
+
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        let count =
+            pseudonymize(&conf, &[0, 2], b"case-42", Some(&output), Some(&mapping)).unwrap();
+        assert_eq!(count, 3);
+
+        let mut rdr = csv::Reader::from_path(&output).unwrap();
+        let rows = rdr.records().collect::<Result<Vec<_>, _>>().unwrap();
+        let alice = &rows[0][0];
+        let bob = &rows[0][2];
+        assert_eq!(alice.len(), PSEUDONYM_TOKEN_BYTES * 2);
+        assert_ne!(alice, bob);
+        assert_eq!(&rows[0][1], "ws1");
+        assert_eq!(&rows[1][0], bob);
+        assert_eq!(&rows[1][2], "");
+        assert_eq!(&rows[2][0], alice);
+        assert_eq!(&rows[2][2], alice);
+
+        assert_eq!(
+            fs::read_to_string(&mapping).unwrap(),
+            format!("value,token\nalice,{alice}\nbob,{bob}\n")
+        );
+
+        // the same tokens without a mapping file
+        pseudonymize(&conf, &[0], b"case-42", Some(&output), None).unwrap();
+        let mut rdr = csv::Reader::from_path(&output).unwrap();
+        let first = rdr.records().next().unwrap().unwrap();
+        assert_eq!(&first[0], alice);
+
+        // a different salt gives different tokens
+        pseudonymize(&conf, &[0], b"case-43", Some(&output), None).unwrap();
+        let mut rdr = csv::Reader::from_path(&output).unwrap();
+        let first = rdr.records().next().unwrap().unwrap();
+        assert_ne!(&first[0], alice);
+
+        assert!(pseudonymize(&conf, &[0], b"", Some(&output), None).is_err());
+        assert!(pseudonymize(&conf, &[3], b"case-42", Some(&output), None).is_err());
+    }
+
+    #[test]
+    fn test_redact() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("notes.csv");
+        fs::write(
+            &input,
+            "id,note,contact\n1,mailed jane@example.com re 123-45-6789,jane@example.com\n2,card \
+             4111 1111 1111 1111 used on case X-7 at 1700000000000,none\n",
+        )
+        .unwrap();
+        let output = dir.path().join("redacted.csv");
+
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        let counts = redact(&conf, &[1], &[r"case X-\d+"], true, Some(&output)).unwrap();
+        assert_eq!(counts, vec![("note".to_string(), 4)]);
+        // the epoch-ms timestamp fails the Luhn check, so it is kept
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "id,note,contact\n1,mailed *** re ***,jane@example.com\n2,card *** used on *** at \
+             1700000000000,none\n"
+        );
+
+        // only the given patterns without the defaults
+        let counts = redact(&conf, &[1], &[r"case X-\d+"], false, Some(&output)).unwrap();
+        assert_eq!(counts, vec![("note".to_string(), 1)]);
+        assert!(
+            fs::read_to_string(&output)
+                .unwrap()
+                .contains("card 4111 1111 1111 1111 used on *** at")
+        );
+
+        assert!(redact(&conf, &[1], &["("], true, Some(&output)).is_err());
+        assert!(redact(&conf, &[3], &[], true, Some(&output)).is_err());
+        assert!(redact(&conf, &[1], &[], false, Some(&output)).is_err());
+    }
+
+    #[test]
+    fn test_luhn_valid() {
+        assert!(luhn_valid(b"4111 1111 1111 1111"));
+        assert!(luhn_valid(b"5500-0000-0000-0004"));
+        assert!(!luhn_valid(b"4111 1111 1111 1112"));
+        assert!(!luhn_valid(b"1700000000000"));
+    }
+
+    #[test]
+    fn test_field_template() {
+        let headers = ["host name", "port", "user"];
+        let template = FieldTemplate::new("{Host_Name}:{port} ({2})", &headers).unwrap();
+        let record = csv::ByteRecord::from(vec!["srv01", "8443", "alice"]);
+        assert_eq!(template.render(|c| record.get(c)), "srv01:8443 (alice)");
+        // a short record leaves missing fields empty
+        let record = csv::ByteRecord::from(vec!["srv02"]);
+        assert_eq!(template.render(|c| record.get(c)), "srv02: ()");
+
+        assert_eq!(
+            FieldTemplate::new("constant", &headers)
+                .unwrap()
+                .render(|_| None),
+            "constant"
+        );
+        assert!(FieldTemplate::new("{host}", &headers).is_err());
+        assert!(FieldTemplate::new("{3}", &headers).is_err());
+    }
+
+    #[test]
+    fn test_extract_regex_columns() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("log.csv");
+        fs::write(
+            &input,
+            "id,msg\n1,accepted from 10.0.0.7:51022\n2,service restarted\n3,denied \
+             192.168.1.9:22\n",
+        )
+        .unwrap();
+        let output = dir.path().join("extracted.csv");
+
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        let (names, matched) = extract_regex_columns(
+            &conf,
+            1,
+            r"(?P<ip>\d+\.\d+\.\d+\.\d+):(?P<id>\d+)",
+            Some(&output),
+        )
+        .unwrap();
+        // `id` is taken by an existing column
+        assert_eq!(names, vec!["ip", "id_2"]);
+        assert_eq!(matched, 2);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "id,msg,ip,id_2\n1,accepted from 10.0.0.7:51022,10.0.0.7,51022\n2,service \
+             restarted,,\n3,denied 192.168.1.9:22,192.168.1.9,22\n"
+        );
+
+        assert!(extract_regex_columns(&conf, 1, r"\d+", Some(&output)).is_err());
+        assert!(extract_regex_columns(&conf, 1, "(?P<x>", Some(&output)).is_err());
+        assert!(extract_regex_columns(&conf, 2, "(?P<x>.)", Some(&output)).is_err());
+    }
+
+    #[test]
+    fn test_hash_record_ignores_delimiter() {
+        let read_first = |data: &str, delimiter: u8| {
+            let mut rdr = csv::ReaderBuilder::new()
+                .has_headers(false)
+                .delimiter(delimiter)
+                .from_reader(data.as_bytes());
+            rdr.byte_records().next().unwrap().unwrap()
+        };
+        let comma = read_first("evidence,\"a \"\"quoted\"\" value\",42\n", b',');
+        let tab = read_first("evidence\ta \"quoted\" value\t42\n", b'\t');
+        assert_eq!(
+            hash_record(&comma, HashAlgo::Blake3),
+            hash_record(&tab, HashAlgo::Blake3)
+        );
+    }
+
+    #[test]
+    fn test_hash_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("evidence.bin");
+        fs::write(&path, b"abc").unwrap();
+
+        assert_eq!(
+            hash_file(&path, HashAlgo::Md5, None).unwrap(),
+            "900150983cd24fb0d6963f7d28e17f72"
+        );
+        assert_eq!(
+            hash_file(&path, HashAlgo::Sha1, None).unwrap(),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hash_file(&path, HashAlgo::Sha256, None).unwrap(),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+
+        let progress = ProgressBar::hidden();
+        hash_file(&path, HashAlgo::Sha256, Some(&progress)).unwrap();
+        assert_eq!(progress.position(), 3);
+        assert_eq!(progress.length(), Some(3));
+    }
+
+    #[test]
+    fn test_hash_algo_from_str() {
+        assert_eq!("SHA256".parse::<HashAlgo>().unwrap(), HashAlgo::Sha256);
+        assert_eq!("blake3".parse::<HashAlgo>().unwrap(), HashAlgo::Blake3);
+        assert_eq!("MD5".parse::<HashAlgo>().unwrap(), HashAlgo::Md5);
+        assert_eq!("sha-1".parse::<HashAlgo>().unwrap(), HashAlgo::Sha1);
+        assert!("crc32".parse::<HashAlgo>().is_err());
+    }
+
+    #[test]
+    fn test_process_input_plan_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmpdir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("events.csv"), "a,b\n1,2\n").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a table").unwrap();
+
+        let mut plan = process_input_plan(vec![dir.path().to_path_buf()], &tmpdir).unwrap();
+        plan.sort_by(|a, b| a.0.cmp(&b.0));
+
+        assert_eq!(
+            plan,
+            vec![
+                (dir.path().join("events.csv"), ProcessDecision::Included),
+                (
+                    dir.path().join("notes.txt"),
+                    ProcessDecision::SkippedUnsupported
+                ),
+            ]
+        );
+        // nothing was written to the temp directory
+        assert_eq!(fs::read_dir(tmpdir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_process_input_plan_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let tmpdir = tempfile::tempdir().unwrap();
+        let csv = dir.path().join("events.csv");
+        let snappy = dir.path().join("events2.csv.sz");
+        fs::write(&csv, "a,b\n1,2\n").unwrap();
+        fs::write(&snappy, "").unwrap();
+
+        let plan =
+            process_input_plan(vec![csv.clone(), snappy, PathBuf::from("-")], &tmpdir).unwrap();
+        assert_eq!(
+            plan,
+            vec![
+                (csv, ProcessDecision::Included),
+                (
+                    tmpdir.path().join("events2.csv"),
+                    ProcessDecision::Decompressed
+                ),
+                (PathBuf::from("-"), ProcessDecision::Included),
+            ]
+        );
+
+        let missing = dir.path().join("missing.csv");
+        assert!(process_input_plan(vec![missing], &tmpdir).is_err());
+    }
+
+    #[test]
+    fn test_optimal_batch_size_explained_raised_to_default() {
+        let rconfig = Config::builder().build();
+        let explanation = optimal_batch_size_explained(&rconfig, 100, 4);
+        assert_eq!(
+            explanation,
+            BatchSizeExplanation {
+                num_rows:   None,
+                num_jobs:   4,
+                batch_size: DEFAULT_BATCH_SIZE,
+                branch:     BatchSizeBranch::RaisedToDefault,
+            }
+        );
+        assert_eq!(optimal_batch_size(&rconfig, 100, 4), DEFAULT_BATCH_SIZE);
+    }
+
+    #[test]
+    fn test_par_record_batches_preserves_order() {
+        let data: String = (0..1_000).map(|i| format!("{i}\n")).collect();
+        let mut rdr = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_reader(data.as_bytes());
+
+        let conf = Config::builder().build();
+        let batches: Vec<Vec<usize>> = par_record_batches(&conf, &mut rdr, 64, 3, |record| {
+            record[0].parse::<usize>().unwrap() * 2
+        })
+        .unwrap()
+        .collect::<csv::Result<_>>()
+        .unwrap();
+
+        assert_eq!(batches.len(), 16);
+        assert!(batches[..15].iter().all(|batch| batch.len() == 64));
+        let doubled: Vec<usize> = batches.into_iter().flatten().collect();
+        assert_eq!(doubled, (0..1_000).map(|i| i * 2).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_count_rows_detail() {
+        let dir = tempfile::tempdir().unwrap();
+        let count_detail = |name: &str, contents: &str, no_headers: bool| {
+            let path = dir.path().join(name);
+            fs::write(&path, contents).unwrap();
+            let conf = Config::builder()
+                .path(path.to_string_lossy())
+                .build()
+                .no_headers(no_headers);
+            count_rows_detail(&conf).unwrap()
+        };
+
+        assert_eq!(
+            count_detail("empty.csv", "", false),
+            RowCount {
+                data_rows:  0,
+                has_header: false,
+            }
+        );
+        assert_eq!(
+            count_detail("header_only.csv", "a,b\n", false),
+            RowCount {
+                data_rows:  0,
+                has_header: true,
+            }
+        );
+        assert_eq!(
+            count_detail("rows.csv", "a,b\n1,2\n3,4\n", false),
+            RowCount {
+                data_rows:  2,
+                has_header: true,
+            }
+        );
+        assert_eq!(
+            count_detail("no_headers.csv", "1,2\n3,4\n", true),
+            RowCount {
+                data_rows:  2,
+                has_header: false,
+            }
+        );
+    }
+
+    #[test]
+    fn test_sniff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.csv");
+        let mut contents = String::from("name;age;city\n");
+        for i in 0..20 {
+            contents.push_str(&format!("\"person {i}\";{};town {i}\n", 20 + i));
+        }
+        fs::write(&path, contents).unwrap();
+
+        let dialect = sniff(&path).unwrap();
+        assert!(dialect.sniffed);
+        assert_eq!(dialect.delimiter, b';');
+        assert!(dialect.has_headers);
+        assert_eq!(dialect.preamble_rows, 0);
+        assert_eq!(dialect.record_count, 20);
+        assert_eq!(dialect.column_count, 3);
+        assert!(dialect.is_utf8);
+
+        let latin1 = dir.path().join("latin1.csv");
+        fs::write(&latin1, b"name,city\nJos\xe9,M\xe1laga\n").unwrap();
+        assert!(!sniff(&latin1).unwrap().is_utf8);
+    }
+
+    #[test]
+    fn test_csv_writer_quote_styles() {
+        let dir = tempfile::tempdir().unwrap();
+        let write_with = |quote_style: csv::QuoteStyle| {
+            let path = dir.path().join("out.csv");
+            let mut wtr = csv_writer(Some(&path), Some(Delimiter(b';')), quote_style).unwrap();
+            wtr.write_record(["name", "a;b", "42"]).unwrap();
+            wtr.flush().unwrap();
+            drop(wtr);
+            fs::read_to_string(&path).unwrap()
+        };
+
+        assert_eq!(
+            write_with(csv::QuoteStyle::Always),
+            "\"name\";\"a;b\";\"42\"\n"
+        );
+        assert_eq!(write_with(csv::QuoteStyle::Necessary), "name;\"a;b\";42\n");
+        assert_eq!(
+            write_with(csv::QuoteStyle::NonNumeric),
+            "\"name\";\"a;b\";42\n"
+        );
+        assert_eq!(write_with(csv::QuoteStyle::Never), "name;a;b;42\n");
+    }
+
+    #[test]
+    fn test_tail_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.csv");
+        let data: String = std::iter::once("n\n".to_string())
+            .chain((1..=100).map(|i| format!("{i}\n")))
+            .collect();
+        fs::write(&path, data).unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+
+        let last_values = |n: usize| -> Vec<String> {
+            tail_records(&conf, n)
+                .unwrap()
+                .iter()
+                .map(|r| String::from_utf8_lossy(&r[0]).to_string())
+                .collect()
+        };
+
+        // streaming, no index
+        assert_eq!(last_values(3), vec!["98", "99", "100"]);
+        assert!(last_values(0).is_empty());
+        assert_eq!(last_values(500).len(), 100);
+
+        // indexed
+        crate::index::run(&path.to_string_lossy(), None).unwrap();
+        assert_eq!(last_values(3), vec!["98", "99", "100"]);
+        assert_eq!(last_values(500).len(), 100);
+    }
+
+    #[test]
+    fn test_find_ragged_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ragged.csv");
+        fs::write(&path, "a,b,c\n1,2,3\n4,5\n6,7,8\n9,10,11,12\n").unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        assert_eq!(find_ragged_rows(&conf).unwrap(), vec![(1, 2), (3, 4)]);
+
+        // without headers, the first record sets the expected field count
+        let conf = conf.no_headers(true);
+        assert_eq!(find_ragged_rows(&conf).unwrap(), vec![(2, 2), (4, 4)]);
+
+        let path = dir.path().join("even.csv");
+        fs::write(&path, "a,b\n1,2\n").unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        assert!(find_ragged_rows(&conf).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_duplicate_headers() {
+        let headers = csv::ByteRecord::from(vec!["id", "name", "id", "ts", "name", "id"]);
+        assert_eq!(
+            duplicate_headers(&headers),
+            vec![
+                ("id".to_string(), vec![0, 2, 5]),
+                ("name".to_string(), vec![1, 4]),
+            ]
+        );
+        assert!(duplicate_headers(&csv::ByteRecord::from(vec!["a", "b"])).is_empty());
+
+        assert_eq!(
+            dedup_header_names(["id", "name", "id", "id_2", "id"]),
+            vec!["id", "name", "id_2", "id_2_2", "id_3"]
+        );
+        assert_eq!(dedup_header_names(["a", "b"]), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_validate_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("clean.csv");
+        fs::write(&path, "a,b,c\n1,\"x, \"\"y\"\"\",3\n4,\"multi\nline\",6\n").unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        let report = validate_csv(&conf).unwrap();
+        assert!(report.is_valid(), "{report:?}");
+        assert!(report.utf8_sample);
+        assert_eq!(report.records, 3);
+
+        let path = dir.path().join("dirty.csv");
+        let mut data = b"a,b,c\n1,x\"y,3\n4,sp\nlit,6\n7,8\n9,\"q\"z,10\n".to_vec();
+        data.extend_from_slice(b"11,\xff,12\n13,14,15,16\n");
+        fs::write(&path, data).unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        let report = validate_csv(&conf).unwrap();
+        assert_eq!(
+            report.issues,
+            vec![
+                CsvIssue {
+                    line: 2,
+                    kind: CsvIssueKind::UnescapedQuote,
+                },
+                CsvIssue {
+                    line: 3,
+                    kind: CsvIssueKind::UnquotedNewline,
+                },
+                CsvIssue {
+                    line: 5,
+                    kind: CsvIssueKind::FieldCount {
+                        expected: 3,
+                        found:    2,
+                    },
+                },
+                CsvIssue {
+                    line: 6,
+                    kind: CsvIssueKind::UnescapedQuote,
+                },
+                CsvIssue {
+                    line: 7,
+                    kind: CsvIssueKind::InvalidUtf8,
+                },
+                CsvIssue {
+                    line: 8,
+                    kind: CsvIssueKind::FieldCount {
+                        expected: 3,
+                        found:    4,
+                    },
+                },
+            ]
+        );
+        assert_eq!(report.issue_count(), 6);
+        assert!(!report.utf8_sample);
+        assert!(!report.truncated());
+    }
+
+    #[test]
+    fn test_parse_timestamp_ms() {
+        assert_eq!(
+            parse_timestamp_ms("1970-01-01T00:00:01Z", false),
+            Some(1000)
+        );
+        assert_eq!(
+            parse_timestamp_ms(" 2023-01-15 10:30:00 ", false),
+            parse_timestamp_ms("2023-01-15T10:30:00Z", false)
+        );
+        // 02/03 is March 2nd when preferring day-month-year
+        assert_ne!(
+            parse_timestamp_ms("02/03/2023", false),
+            parse_timestamp_ms("02/03/2023", true)
+        );
+        assert_eq!(parse_timestamp_ms("not a date", false), None);
+        assert_eq!(timestamp_ms_to_rfc3339(1000), "1970-01-01T00:00:01+00:00");
+    }
+
+    #[test]
+    fn test_write_json_null_values() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.json").to_string_lossy().to_string();
+        let headers = csv::ByteRecord::from(vec!["a", "b", "c"]);
+        let records = vec![csv::ByteRecord::from(vec!["NULL", "", "x"])];
+
+        write_json(
+            Some(&out),
+            false,
+            &headers,
+            records.clone().into_iter(),
+            &[],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&out).unwrap().trim(),
+            r#"[{"a":"NULL","b":null,"c":"x"}]"#
+        );
+
+        write_json(
+            Some(&out),
+            false,
+            &headers,
+            records.into_iter(),
+            &[b"NULL".to_vec()],
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            fs::read_to_string(&out).unwrap().trim(),
+            r#"[{"a":null,"b":null,"c":"x"}]"#
+        );
+    }
+
+    #[test]
+    fn test_write_json_pretty() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.json").to_string_lossy().to_string();
+        let headers = csv::ByteRecord::from(vec!["a", "b"]);
+        let records = vec![
+            csv::ByteRecord::from(vec!["1", ""]),
+            csv::ByteRecord::from(vec!["2", "y"]),
+        ];
+
+        write_json(Some(&out), false, &headers, records.into_iter(), &[], true).unwrap();
+        let written = fs::read_to_string(&out).unwrap();
+        assert_eq!(
+            written,
+            "[\n  {\n    \"a\": \"1\",\n    \"b\": null\n  },\n  {\n    \"a\": \"2\",\n    \"b\": \
+             \"y\"\n  }\n]\n"
+        );
+        assert!(serde_json::from_str::<serde_json::Value>(&written).is_ok());
+
+        write_json(Some(&out), false, &headers, std::iter::empty(), &[], true).unwrap();
+        assert_eq!(fs::read_to_string(&out).unwrap(), "[]\n");
+    }
+
+    #[test]
+    fn test_write_json_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("rows.csv");
+        fs::write(&input, "n\n0\n1\n2\n3\n4\n").unwrap();
+        let out = dir.path().join("out.json").to_string_lossy().to_string();
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+
+        // without an index, then seeking through one
+        write_json_range(&conf, Some(&out), 1, 2, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&out).unwrap().trim(),
+            r#"[{"n":"1"},{"n":"2"}]"#
+        );
+
+        crate::index::run(&input.to_string_lossy(), None).unwrap();
+        write_json_range(&conf, Some(&out), 3, 10, false).unwrap();
+        assert_eq!(
+            fs::read_to_string(&out).unwrap().trim(),
+            r#"[{"n":"3"},{"n":"4"}]"#
+        );
+    }
+
+    #[test]
+    fn test_jsonl_to_csv() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("events.jsonl");
+        let output = dir.path().join("events.csv");
+        fs::write(
+            &input,
+            concat!(
+                r#"{"id":1,"proc":{"name":"cmd.exe","pid":4},"tags":["a","b"]}"#,
+                "\n\n",
+                r#"{"id":2,"user":"bob","ok":true,"proc":null}"#,
+                "\n",
+                r#"{"id":3,"late":"dropped"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let rows = jsonl_to_csv(&input, &output, JsonNesting::Flatten, 2).unwrap();
+        assert_eq!(rows, 3);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "id,proc.name,proc.pid,tags,user,ok,proc\n1,cmd.exe,4,\"[\"\"a\"\",\"\"b\"\"]\",,,\n2,\
+             ,,,bob,true,\n3,,,,,,\n"
+        );
+
+        jsonl_to_csv(&input, &output, JsonNesting::Stringify, 10).unwrap();
+        let csv = fs::read_to_string(&output).unwrap();
+        assert!(csv.starts_with("id,proc,tags,user,ok,late\n"));
+        assert!(csv.contains(r#""{""name"":""cmd.exe"",""pid"":4}""#));
+
+        fs::write(&input, "[1,2]\n").unwrap();
+        assert_eq!(
+            jsonl_to_csv(&input, &output, JsonNesting::Flatten, 10)
+                .unwrap_err()
+                .to_string(),
+            "line 1: expected a JSON object"
+        );
+    }
+
+    #[test]
+    fn test_jsonl_opens_through_streaming_converter() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("events.jsonl");
+        fs::write(
+            &input,
+            concat!(
+                r#"{"id":1,"proc":{"name":"cmd.exe","pid":4}}"#,
+                "\n",
+                r#"{"id":2,"user":"bob"}"#,
+                "\n",
+            ),
+        )
+        .unwrap();
+
+        let mut rdr = Config::builder()
+            .path(input.to_string_lossy())
+            .build()
+            .reader()
+            .unwrap();
+        assert_eq!(
+            rdr.headers().unwrap(),
+            vec!["id", "proc.name", "proc.pid", "user"]
+        );
+        assert_eq!(rdr.records().count(), 2);
+    }
+
+    #[test]
+    fn test_sample_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rows.csv");
+        let mut data = String::from("id\n");
+        for i in 0..100 {
+            data.push_str(&format!("{i}\n"));
+        }
+        fs::write(&path, data).unwrap();
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        let ids = |sample: Vec<ByteRecord>| -> Vec<u32> {
+            sample
+                .iter()
+                .map(|r| str::from_utf8(&r[0]).unwrap().parse().unwrap())
+                .collect()
+        };
+
+        let streamed = ids(sample_records(&conf, 10, Some(42)).unwrap());
+        assert_eq!(streamed.len(), 10);
+        assert!(streamed.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(streamed, ids(sample_records(&conf, 10, Some(42)).unwrap()));
+        assert_eq!(
+            ids(sample_records(&conf, 500, None).unwrap()),
+            (0..100).collect::<Vec<_>>()
+        );
+
+        crate::index::run(&path.to_string_lossy(), None).unwrap();
+        let seeked = ids(sample_records(&conf, 10, Some(7)).unwrap());
+        assert_eq!(seeked.len(), 10);
+        assert!(seeked.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(seeked, ids(sample_records(&conf, 10, Some(7)).unwrap()));
+        assert!(sample_records(&conf, 0, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_transpose() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("wide.csv");
+        fs::write(&input, "name,age,city\nalice,30,Paris\nbob,25\n").unwrap();
+        let output = dir.path().join("tall.csv");
+
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        transpose(&conf, Some(&output)).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "name,alice,bob\nage,30,25\ncity,Paris,\n"
+        );
+    }
+
+    #[test]
+    fn test_split_by_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("big.csv");
+        fs::write(&input, "id,name\n1,a\n2,b\n3,c\n4,d\n5,e\n").unwrap();
+        let outdir = dir.path().join("chunks");
+        let template = FilenameTemplate {
+            prefix: "part_".to_string(),
+            suffix: ".csv".to_string(),
+        };
+
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        assert_eq!(split_by_rows(&conf, 2, &template, &outdir).unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(outdir.join("part_0.csv")).unwrap(),
+            "id,name\n1,a\n2,b\n"
+        );
+        assert_eq!(
+            fs::read_to_string(outdir.join("part_2.csv")).unwrap(),
+            "id,name\n3,c\n4,d\n"
+        );
+        assert_eq!(
+            fs::read_to_string(outdir.join("part_4.csv")).unwrap(),
+            "id,name\n5,e\n"
+        );
+        assert!(split_by_rows(&conf, 0, &template, &outdir).is_err());
+    }
+
+    #[test]
+    fn test_split_by_column() {
+        let dir = tempfile::tempdir().unwrap();
+        let input = dir.path().join("hosts.csv");
+        fs::write(
+            &input,
+            "host,event\nWS-01,logon\nsrv/02,boot\nWS-01,logoff\nWS_01,scan\n,orphan\n",
+        )
+        .unwrap();
+        let outdir = dir.path().join("by_host");
+        let template = FilenameTemplate {
+            prefix: "host_".to_string(),
+            suffix: ".csv".to_string(),
+        };
+
+        let conf = Config::builder().path(input.to_string_lossy()).build();
+        assert_eq!(split_by_column(&conf, 0, &template, &outdir).unwrap(), 4);
+        assert_eq!(
+            fs::read_to_string(outdir.join("host_WS_01.csv")).unwrap(),
+            "host,event\nWS-01,logon\nWS-01,logoff\n"
+        );
+        assert_eq!(
+            fs::read_to_string(outdir.join("host_WS_01_2.csv")).unwrap(),
+            "host,event\nWS_01,scan\n"
+        );
+        assert_eq!(
+            fs::read_to_string(outdir.join("host_srv_02.csv")).unwrap(),
+            "host,event\nsrv/02,boot\n"
+        );
+        assert_eq!(
+            fs::read_to_string(outdir.join("host_empty.csv")).unwrap(),
+            "host,event\n,orphan\n"
+        );
+        assert!(split_by_column(&conf, 5, &template, &outdir).is_err());
+    }
+
+    #[test]
+    fn test_inferred_type_from_stats_type() {
+        assert_eq!(InferredType::from_stats_type("Integer"), InferredType::Int);
+        assert_eq!(InferredType::from_stats_type("Float"), InferredType::Float);
+        assert_eq!(InferredType::from_stats_type("Boolean"), InferredType::Bool);
+        assert_eq!(InferredType::from_stats_type("Date"), InferredType::Date);
+        assert_eq!(
+            InferredType::from_stats_type("DateTime"),
+            InferredType::DateTime
+        );
+        assert_eq!(
+            InferredType::from_stats_type("String"),
+            InferredType::String
+        );
+        assert_eq!(InferredType::from_stats_type("NULL"), InferredType::String);
+    }
+
+    #[test]
+    fn test_validate_against_schema() {
+        use polars::{datatypes::DataType, prelude::PlSmallStr};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("evidence.csv");
+        fs::write(
+            &path,
+            "id,size,deleted,seen,note\n1,10.5,true,2023-01-15,ok\nx,,maybe,not a \
+             date,\n3,1e3,FALSE,2023-02-01,fine\n",
+        )
+        .unwrap();
+        let mut schema = Schema::with_capacity(4);
+        schema.insert(PlSmallStr::from_str("id"), DataType::UInt32);
+        schema.insert(PlSmallStr::from_str("size"), DataType::Float64);
+        schema.insert(PlSmallStr::from_str("deleted"), DataType::Boolean);
+        schema.insert(PlSmallStr::from_str("seen"), DataType::Date);
+
+        let conf = Config::builder().path(path.to_string_lossy()).build();
+        let violations = validate_against_schema(&conf, &schema).unwrap();
+        let found: Vec<(u64, &str, &str)> = violations
+            .iter()
+            .map(|v| (v.row, v.column.as_str(), v.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (1, "id", "x"),
+                (1, "deleted", "maybe"),
+                (1, "seen", "not a date")
+            ]
+        );
+        assert_eq!(violations[0].expected, DataType::UInt32.to_string());
+    }
+
+    #[test]
+    fn test_merge_csvs() {
+        let dir = tempfile::tempdir().unwrap();
+        let host1 = dir.path().join("host1.csv");
+        let host2 = dir.path().join("host2.csv");
+        let output = dir.path().join("merged.csv");
+        fs::write(&host1, "time,user,pid\n1,alice,4\n").unwrap();
+        fs::write(&host2, "pid,time,cmd\n8,2,cmd.exe\n9,3,\n").unwrap();
+        let inputs: Vec<Config> = [&host1, &host2]
+            .iter()
+            .map(|p| Config::builder().path(p.to_string_lossy()).build())
+            .collect();
+
+        assert_eq!(merge_csvs(&inputs, &output, None).unwrap(), 3);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "time,user,pid,cmd\n1,alice,4,\n2,,8,cmd.exe\n3,,9,\n"
+        );
+
+        merge_csvs(&inputs, &output, Some("source_file")).unwrap();
+        let merged = fs::read_to_string(&output).unwrap();
+        let mut lines = merged.lines();
+        assert_eq!(lines.next(), Some("time,user,pid,cmd,source_file"));
+        assert_eq!(
+            lines.next(),
+            Some(format!("1,alice,4,,{}", host1.display()).as_str())
+        );
+
+        let err = merge_csvs(&inputs, &output, Some("pid")).unwrap_err();
+        assert!(err.to_string().contains("already has a column"));
+    }
+
+    #[test]
+    fn test_record_transform_in_util_readers() {
+        fn upper(record: &mut ByteRecord) {
+            *record = record.iter().map(<[u8]>::to_ascii_uppercase).collect();
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events.csv");
+        let hosts = dir.path().join("hosts.csv");
+        let output = dir.path().join("out.csv");
+        fs::write(&events, "host,event\nws1,logon\n").unwrap();
+        fs::write(&hosts, "host,owner\nWS1,alice\n").unwrap();
+        let conf = |p: &Path| {
+            Config::builder()
+                .path(p.to_string_lossy())
+                .build()
+                .with_record_transform(upper)
+        };
+
+        transpose(&conf(&events), Some(&output)).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "HOST,WS1\nEVENT,LOGON\n"
+        );
+
+        merge_csvs(&[conf(&events)], &output, None).unwrap();
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "host,event\nWS1,LOGON\n"
+        );
+
+        // the right rows are read back from their positions after the transform
+        let rows = hash_join(
+            &conf(&events),
+            0,
+            &conf(&hosts),
+            0,
+            JoinKind::Inner,
+            &output,
+        );
+        assert_eq!(rows.unwrap(), 1);
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "host,event,owner\nWS1,LOGON,ALICE\n"
+        );
+
+        let events = conf(&events);
+        let mut rdr = events.reader().unwrap();
+        let batches: Vec<Vec<String>> =
+            par_record_batches(&events, &mut rdr, 8, 1, |record| record[1].to_string())
+                .unwrap()
+                .collect::<csv::Result<_>>()
+                .unwrap();
+        assert_eq!(batches, vec![vec!["LOGON".to_string()]]);
+    }
+
+    #[test]
+    fn test_hash_join() {
+        let dir = tempfile::tempdir().unwrap();
+        let events = dir.path().join("events.csv");
+        let hosts = dir.path().join("hosts.csv");
+        let output = dir.path().join("joined.csv");
+        fs::write(
+            &events,
+            "host,event\nws1,logon\nws2,logoff\nws9,boot\n,orphan\n",
+        )
+        .unwrap();
+        fs::write(
+            &hosts,
+            "owner,host,event\nalice,ws1,x\nbob,ws2,y\ncarol,ws2,z\n,,w\n",
+        )
+        .unwrap();
+        let left = Config::builder().path(events.to_string_lossy()).build();
+        let right = Config::builder().path(hosts.to_string_lossy()).build();
+
+        assert_eq!(
+            hash_join(&left, 0, &right, 1, JoinKind::Inner, &output).unwrap(),
+            3
+        );
+        assert_eq!(
+            fs::read_to_string(&output).unwrap(),
+            "host,event,owner,event_right\nws1,logon,alice,x\nws2,logoff,bob,y\nws2,logoff,carol,\
+             z\n"
+        );
+
+        assert_eq!(
+            hash_join(&left, 0, &right, 1, JoinKind::Left, &output).unwrap(),
+            5
+        );
+        let joined = fs::read_to_string(&output).unwrap();
+        assert!(joined.ends_with("ws9,boot,,\n,orphan,,\n"));
+
+        assert!(hash_join(&left, 5, &right, 1, JoinKind::Inner, &output).is_err());
+    }
+}
@@ -1,36 +1,38 @@
 use std::path::PathBuf;
 
 use eframe::egui::{self, ComboBox, Frame, Ui};
-use epaint::{Color32, CornerRadius, Margin, Stroke, StrokeKind};
+use epaint::{CornerRadius, Margin, Stroke, StrokeKind};
 
-use crate::app::table::TableEditor;
+use crate::{
+    app::{table::TableEditor, toast::Notifier},
+    util,
+};
 
 pub struct BasicEditor {
     pub table: TableEditor,
 }
 
 impl BasicEditor {
-    pub fn new() -> Self {
+    pub fn new(notifier: Notifier) -> Self {
         Self {
-            table: TableEditor::default(),
+            table: TableEditor::new(notifier),
         }
     }
 
     pub fn show(&mut self, ui: &mut Ui) {
         self.table.handle_file_drop(ui.ctx());
+        self.table.poll_jobs(ui.ctx());
 
-        Frame::new()
-            .fill(egui::Color32::from_rgb(37, 37, 38))
-            .show(ui, |ui| {
-                ui.vertical(|ui| {
-                    self.show_results_section(ui);
-                });
+        Frame::new().fill(ui.visuals().panel_fill).show(ui, |ui| {
+            ui.vertical(|ui| {
+                self.show_results_section(ui);
             });
+        });
     }
 
     pub fn show_results_section(&mut self, ui: &mut Ui) {
         Frame::new()
-            .fill(Color32::from_rgb(37, 37, 38))
+            .fill(ui.visuals().panel_fill)
             .inner_margin(Margin::symmetric(16, 8))
             .show(ui, |ui| {
                 ui.vertical(|ui| {
@@ -49,19 +51,158 @@ impl BasicEditor {
 
     pub fn show_table_controls(&mut self, ui: &mut egui::Ui) {
         ui.horizontal(|ui| {
+            ui.menu_button("🕘 Recent files", |ui| {
+                if self.table.recent_files.paths.is_empty() {
+                    ui.label("No recent files");
+                    return;
+                }
+                let mut picked = None;
+                for path in &self.table.recent_files.paths {
+                    if ui
+                        .button(util::display_name(&path.to_string_lossy()))
+                        .on_hover_text(path.display().to_string())
+                        .clicked()
+                    {
+                        picked = Some(path.clone());
+                    }
+                }
+                ui.separator();
+                if ui.button("Clear list").clicked() {
+                    self.table.recent_files.clear();
+                    ui.close();
+                }
+                if let Some(path) = picked {
+                    if path.exists() {
+                        self.table.open_file(path);
+                    } else {
+                        self.table.recent_files.remove(&path);
+                    }
+                    ui.close();
+                }
+            });
+
             if self.table.current_fp().is_some() {
                 let export_button = egui::Button::new(
                     egui::RichText::new("📤 Export data")
-                        .color(egui::Color32::WHITE)
+                        .color(ui.visuals().selection.stroke.color)
                         .size(12.0),
                 )
-                .fill(egui::Color32::from_rgb(0, 120, 215))
+                .fill(ui.visuals().selection.bg_fill)
                 .corner_radius(CornerRadius::same(4));
                 let resp = ui.add(export_button);
                 // Open the DataTableArea export popup anchored to this button
                 self.table.show_export_popup(ui, &resp);
             }
 
+            if self.table.current_fp().is_some()
+                && ui
+                    .button("🧬 Generate schema")
+                    .on_hover_text("Infer column types and save them as a .pschema.json file")
+                    .clicked()
+            {
+                self.table.generate_schema_for_current();
+            }
+
+            let reordered = self
+                .table
+                .current_fp()
+                .is_some_and(|fp| fp.has_custom_column_order());
+            if reordered
+                && ui
+                    .button("↺ Reset column order")
+                    .on_hover_text("Restore the file's original column order")
+                    .clicked()
+                && let Some(fp) = self.table.current_fp_mut()
+            {
+                fp.reset_column_order();
+            }
+
+            if self.table.files.len() > 1 {
+                let resp = ui
+                    .button("⊕ Merge open files")
+                    .on_hover_text("Stack all open files into one CSV");
+                self.table.show_merge_popup(ui, &resp);
+
+                let resp = ui
+                    .button("⋈ Join files")
+                    .on_hover_text("Enrich one open file with columns from another by a key");
+                self.table.show_join_popup(ui, &resp);
+            }
+
+            if self.table.current_fp().is_some() {
+                let resp = ui
+                    .button("🎲 Sample")
+                    .on_hover_text("Preview and export N random rows");
+                self.table.show_sample_popup(ui, &resp);
+
+                let resp = ui
+                    .button("🧩 Extract with regex")
+                    .on_hover_text("Pull fields out of a text column into new columns");
+                self.table.show_extract_popup(ui, &resp);
+
+                let resp = ui
+                    .button("ƒ Derive column")
+                    .on_hover_text("Add a column built from others, e.g. {hostname}:{port}");
+                self.table.show_derive_popup(ui, &resp);
+
+                let resp = ui
+                    .button("ℹ File info")
+                    .on_hover_text("Delimiter, quoting, headers, row and column counts");
+                self.table.show_file_info_popup(ui, &resp);
+
+                if self.table.is_materializing() {
+                    ui.spinner();
+                    ui.label("Materializing…");
+                } else if ui
+                    .button("💾 Materialize view")
+                    .on_hover_text(
+                        "Write the filtered, sorted rows to a new CSV, index it and open it, so \
+                         the narrowed view pages fast when reopened",
+                    )
+                    .clicked()
+                {
+                    self.table.materialize_current();
+                }
+            }
+
+            if let Some(fp) = self.table.current_fp()
+                && ui
+                    .button("📝 Copy as Markdown")
+                    .on_hover_text("Copy the rows on this page as a Markdown table")
+                    .clicked()
+            {
+                ui.ctx().copy_text(fp.page_as_markdown());
+                self.table.notify.success(format!(
+                    "Copied {} rows as a Markdown table",
+                    fp.preview_rows.len()
+                ));
+            }
+
+            if self.table.current_fp().is_some() {
+                ui.toggle_value(&mut self.table.wrap_cells, "↩ Wrap cells")
+                    .on_hover_text(
+                        "Wrap long cell values onto several lines instead of truncating them",
+                    );
+                ui.toggle_value(&mut self.table.show_blanks, "∅ Show blanks")
+                    .on_hover_text(
+                        "Mark empty cells with ∅ and make the whitespace of whitespace-only cells \
+                         visible",
+                    );
+            }
+
+            // Progress of $MFT files being parsed for the table
+            self.table.show_mft_progress(ui);
+
+            // Export progress while a worker is running, then its summary
+            self.table.show_export_progress(ui);
+            if let Some(msg) = &self.table.export_status {
+                ui.label(
+                    egui::RichText::new(msg.as_str())
+                        .color(ui.visuals().weak_text_color())
+                        .size(11.0),
+                );
+            }
+
             // File selector if multiple files
             if !self.table.files.is_empty() {
                 let cur_name = self
@@ -99,14 +240,14 @@ impl BasicEditor {
             let files_being_dropped_now = ui.ctx().input(|i| !i.raw.dropped_files.is_empty());
 
             let bg = if files_being_dropped_now {
-                Color32::from_rgb(30, 70, 30)
+                ui.visuals().selection.bg_fill.gamma_multiply(0.5)
             } else if dragging_files_in {
-                Color32::from_rgb(50, 50, 50)
+                ui.visuals().widgets.hovered.bg_fill
             } else {
-                Color32::from_rgb(45, 45, 45)
+                ui.visuals().faint_bg_color
             };
 
-            let stroke = Stroke::new(1.0, Color32::from_gray(90));
+            let stroke = Stroke::new(1.0, ui.visuals().widgets.noninteractive.bg_stroke.color);
             ui.painter().rect(rect, 6.0, bg, stroke, StrokeKind::Inside);
 
             // Show explicit prompt while a file is being dragged in
@@ -115,13 +256,13 @@ impl BasicEditor {
             } else if dragging_files_in {
                 "Drop CSV file(s) to preview"
             } else {
-                "📁 Drag & drop CSV file(s) here"
+                "📁 Drag & drop CSV or $MFT file(s) here"
             };
             ui.put(
                 rect.shrink2(egui::Vec2::new(8.0, 8.0)),
                 egui::Label::new(
                     egui::RichText::new(text)
-                        .color(egui::Color32::from_rgb(180, 180, 180))
+                        .color(ui.visuals().text_color())
                         .size(14.0)
                         .strong(),
                 )
@@ -151,27 +292,27 @@ impl BasicEditor {
 
         if let Some(err) = load_error {
             Frame::new()
-                .fill(egui::Color32::from_rgb(45, 45, 45))
-                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(90, 40, 40)))
+                .fill(ui.visuals().faint_bg_color)
+                .stroke(egui::Stroke::new(1.0, ui.visuals().error_fg_color))
                 .corner_radius(CornerRadius::same(4))
                 .inner_margin(Margin::same(8))
                 .show(ui, |ui| {
-                    ui.colored_label(egui::Color32::RED, format!("Load error: {err}"));
+                    ui.colored_label(ui.visuals().error_fg_color, format!("Load error: {err}"));
                 });
             return;
         }
 
         if no_rows {
             Frame::new()
-                .fill(egui::Color32::from_rgb(45, 45, 45))
-                .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(70, 70, 70)))
+                .fill(ui.visuals().faint_bg_color)
+                .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
                 .corner_radius(CornerRadius::same(4))
                 .inner_margin(Margin::same(8))
                 .show(ui, |ui| {
                     ui.horizontal(|ui| {
                         ui.label(
                             egui::RichText::new("No rows match current filters.")
-                                .color(egui::Color32::from_rgb(200, 180, 150)),
+                                .color(ui.visuals().warn_fg_color),
                         );
                         if ui.button("Clear filters").clicked() {
                             self.table.clear_all_filters_current_file();
@@ -183,8 +324,8 @@ impl BasicEditor {
         }
 
         Frame::new()
-            .fill(egui::Color32::from_rgb(45, 45, 45))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(70, 70, 70)))
+            .fill(ui.visuals().faint_bg_color)
+            .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
             .corner_radius(CornerRadius::same(4))
             .inner_margin(Margin::same(8))
             .show(ui, |ui| {
@@ -200,7 +341,7 @@ impl BasicEditor {
                             current_idx + 1,
                             total_files
                         ))
-                        .color(egui::Color32::WHITE)
+                        .color(ui.visuals().strong_text_color())
                         .size(12.0)
                         .strong(),
                     );
@@ -210,8 +351,15 @@ impl BasicEditor {
                 });
                 ui.add_space(6.0);
 
-                // Pinned header + shared horizontal scroll
-                self.table.show_preview_table(ui);
+                // Status bar pinned to the bottom, table (pinned header + shared horizontal
+                // scroll) filling the space above it
+                ui.with_layout(egui::Layout::bottom_up(egui::Align::LEFT), |ui| {
+                    self.table.show_status_bar(ui);
+                    ui.separator();
+                    ui.with_layout(egui::Layout::top_down(egui::Align::LEFT), |ui| {
+                        self.table.show_preview_table(ui);
+                    });
+                });
             });
 
         if reload_requested
@@ -1,9 +1,81 @@
-use eframe::egui;
+use std::{
+    ops::Range,
+    path::PathBuf,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    time::{Duration, Instant},
+};
+
+use eframe::egui::{self, text::LayoutJob};
+use regex::{Regex, RegexBuilder};
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use waka_core::config::Config;
+
+use crate::{config, util};
+
+/// State file holding the search toggles.
+const FTS_OPTIONS_FILE: &str = "fts_options.json";
+/// Stop keeping hits for a file after this many so the result list stays responsive.
+/// Matches past the cap are still counted.
+const MAX_HITS_PER_FILE: usize = 1_000;
+/// How often (in rows) the search worker reports its running counts.
+const COUNT_UPDATE_ROWS: usize = 10_000;
+
+/// Search toggles, persisted across sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FtsOptions {
+    pub case_insensitive: bool,
+    pub whole_word:       bool,
+}
+
+/// One matching cell.
+pub struct FtsHit {
+    pub file:  usize,
+    pub row:   u64, // 0-based data row
+    pub col:   usize,
+    pub text:  String,
+    pub spans: Vec<Range<usize>>, // byte ranges of the matches within `text`
+}
+
+/// Progress reported by the search worker.
+enum FtsEvent {
+    Headers {
+        file:    usize,
+        headers: Vec<String>,
+    },
+    /// Matching cells per column of `file` so far.
+    Counts {
+        file:       usize,
+        per_column: Vec<usize>,
+    },
+    Hit(FtsHit),
+    Error(String),
+}
+
+/// A search streaming through the sources on a worker thread. The worker stops when `cancel`
+/// is set or the receiver is dropped; a disconnected channel means it has finished.
+struct RunningSearch {
+    rx:      Receiver<FtsEvent>,
+    cancel:  Arc<AtomicBool>,
+    started: Instant,
+}
 
 pub struct FtsEditor {
     query:       String,
     result:      String,
     show_result: bool,
+    sources:     Vec<PathBuf>,
+    headers:     Vec<Vec<String>>, // per source
+    counts:      Vec<Vec<usize>>,  // per source, per column
+    hits:        Vec<FtsHit>,
+    errors:      Vec<String>,
+    focus:       Option<(usize, Option<usize>)>, // (file, column) the hit list is narrowed to
+    running:     Option<RunningSearch>,
+    options:     FtsOptions,
 }
 
 impl FtsEditor {
@@ -12,13 +84,68 @@ impl FtsEditor {
             query:       String::new(),
             result:      String::new(),
             show_result: false,
+            sources:     Vec::new(),
+            headers:     Vec::new(),
+            counts:      Vec::new(),
+            hits:        Vec::new(),
+            errors:      Vec::new(),
+            focus:       None,
+            running:     None,
+            options:     config::load_json(FTS_OPTIONS_FILE),
         }
     }
 
     pub fn show(&mut self, ui: &mut egui::Ui) {
+        self.poll_search(ui.ctx());
+
+        let dropped = ui.ctx().input(|i| i.raw.dropped_files.clone());
+        for f in dropped {
+            if let Some(path) = f.path
+                && !self.sources.contains(&path)
+            {
+                self.sources.push(path);
+            }
+        }
+
         ui.vertical(|ui| {
             ui.heading("Full Text Search QL Query Editor");
 
+            ui.horizontal_wrapped(|ui| {
+                if ui.button("📂 Add files").clicked()
+                    && let Some(paths) = FileDialog::new()
+                        .add_filter("CSV", &["csv", "tsv", "tab", "ssv"])
+                        .pick_files()
+                {
+                    for path in paths {
+                        if !self.sources.contains(&path) {
+                            self.sources.push(path);
+                        }
+                    }
+                }
+                let mut remove: Option<usize> = None;
+                for (i, src) in self.sources.iter().enumerate() {
+                    let name = util::display_name(&src.to_string_lossy());
+                    if ui
+                        .button(format!("{name} ✕"))
+                        .on_hover_text("Remove from search")
+                        .clicked()
+                    {
+                        remove = Some(i);
+                    }
+                }
+                if let Some(i) = remove {
+                    self.sources.remove(i);
+                    // results refer to sources by index
+                    self.clear_results();
+                }
+                if self.sources.is_empty() {
+                    ui.label(
+                        egui::RichText::new("Drop or add CSV files to search")
+                            .color(ui.visuals().weak_text_color()),
+                    );
+                }
+            });
+
             // Query input area
             ui.label("Enter your Full Text Search query:");
             ui.add(
@@ -33,10 +160,33 @@ impl FtsEditor {
                     self.execute_query();
                 }
 
+                if self.running.is_some() {
+                    ui.spinner();
+                    if ui
+                        .button("⏹ Stop")
+                        .on_hover_text("Stop searching; keep the matches found so far")
+                        .clicked()
+                    {
+                        self.stop_search();
+                    }
+                }
+
                 if ui.button("Clear").clicked() {
                     self.query.clear();
-                    self.result.clear();
-                    self.show_result = false;
+                    self.clear_results();
+                }
+
+                ui.add_space(12.0);
+                let mut toggled = ui
+                    .checkbox(&mut self.options.case_insensitive, "Aa")
+                    .on_hover_text("Case-insensitive")
+                    .changed();
+                toggled |= ui
+                    .checkbox(&mut self.options.whole_word, "Whole word")
+                    .on_hover_text("Only match the term at word boundaries")
+                    .changed();
+                if toggled && let Err(e) = config::save_json(FTS_OPTIONS_FILE, &self.options) {
+                    tracing::warn!("failed to save search options: {e}");
                 }
             });
 
@@ -44,33 +194,320 @@ impl FtsEditor {
             if self.show_result {
                 ui.separator();
                 ui.heading("Query Result");
+                if !self.result.is_empty() {
+                    ui.label(self.result.as_str());
+                }
+                for e in &self.errors {
+                    ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {e}"));
+                }
+                self.show_match_summary(ui);
+                ui.separator();
 
                 egui::ScrollArea::vertical()
-                    .max_height(200.0)
+                    .max_height(ui.available_height())
+                    .auto_shrink([false, true])
                     .show(ui, |ui| {
-                        ui.add(
-                            egui::TextEdit::multiline(&mut self.result)
-                                .desired_rows(6)
-                                .desired_width(f32::INFINITY)
-                                .font(egui::TextStyle::Monospace)
-                                .interactive(false),
-                        );
+                        let focused = self.hits.iter().filter(|hit| match self.focus {
+                            Some((file, col)) => {
+                                hit.file == file && col.is_none_or(|c| hit.col == c)
+                            },
+                            None => true,
+                        });
+                        for hit in focused {
+                            ui.horizontal(|ui| {
+                                let file =
+                                    util::display_name(&self.sources[hit.file].to_string_lossy());
+                                ui.label(
+                                    egui::RichText::new(format!(
+                                        "{file}:{} [{}]",
+                                        hit.row + 1,
+                                        self.column_name(hit.file, hit.col)
+                                    ))
+                                    .color(ui.visuals().hyperlink_color)
+                                    .monospace()
+                                    .size(11.0),
+                                );
+                                ui.label(Self::highlighted(ui.visuals(), hit));
+                            });
+                        }
                     });
             }
         });
     }
 
+    /// Total matches and a per-file/per-column breakdown. Clicking an entry narrows the hit
+    /// list to that file or column.
+    fn show_match_summary(&mut self, ui: &mut egui::Ui) {
+        let totals: Vec<usize> = self.counts.iter().map(|c| c.iter().sum()).collect();
+        let total: usize = totals.iter().sum();
+        let files = totals.iter().filter(|&&n| n > 0).count();
+
+        ui.horizontal(|ui| {
+            ui.strong(format!("{total} matches in {files} file(s)"));
+            if let Some(running) = &self.running {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "searching… {:.1}s",
+                        running.started.elapsed().as_secs_f32()
+                    ))
+                    .color(ui.visuals().weak_text_color()),
+                );
+            }
+            if self.focus.is_some() && ui.button("Show all").clicked() {
+                self.focus = None;
+            }
+        });
+
+        let mut clicked = None;
+        for (fi, &file_total) in totals.iter().enumerate() {
+            if file_total == 0 {
+                continue;
+            }
+            let name = util::display_name(&self.sources[fi].to_string_lossy());
+            let shown = self.hits.iter().filter(|hit| hit.file == fi).count();
+            let columns: Vec<(usize, &str, usize)> = self.counts[fi]
+                .iter()
+                .enumerate()
+                .filter(|&(_, &n)| n > 0)
+                .map(|(ci, &n)| (ci, self.column_name(fi, ci), n))
+                .collect();
+            ui.horizontal_wrapped(|ui| {
+                let label = ui
+                    .selectable_label(
+                        self.focus == Some((fi, None)),
+                        format!("{name}: {file_total}"),
+                    )
+                    .on_hover_text(if shown < file_total {
+                        format!("Show matched rows (first {shown} listed)")
+                    } else {
+                        "Show matched rows".to_string()
+                    });
+                if label.clicked() {
+                    clicked = Some((fi, None));
+                }
+                for (ci, column, n) in columns {
+                    let text = egui::RichText::new(format!("{column} {n}")).size(11.0);
+                    if ui
+                        .selectable_label(self.focus == Some((fi, Some(ci))), text)
+                        .clicked()
+                    {
+                        clicked = Some((fi, Some(ci)));
+                    }
+                }
+            });
+        }
+        if clicked.is_some() {
+            self.focus = clicked;
+        }
+    }
+
+    fn column_name(&self, file: usize, col: usize) -> &str {
+        self.headers
+            .get(file)
+            .and_then(|h| h.get(col))
+            .map(String::as_str)
+            .unwrap_or("?")
+    }
+
+    /// Lay out a hit's text with its matched spans highlighted.
+    fn highlighted(visuals: &egui::Visuals, hit: &FtsHit) -> LayoutJob {
+        let plain = egui::TextFormat {
+            font_id: egui::FontId::monospace(12.0),
+            color: visuals.text_color(),
+            ..Default::default()
+        };
+        let marked = egui::TextFormat {
+            background: visuals.selection.bg_fill,
+            color: visuals.selection.stroke.color,
+            ..plain.clone()
+        };
+        let mut job = LayoutJob::default();
+        let mut pos = 0;
+        for span in &hit.spans {
+            job.append(&hit.text[pos..span.start], 0.0, plain.clone());
+            job.append(&hit.text[span.clone()], 0.0, marked.clone());
+            pos = span.end;
+        }
+        job.append(&hit.text[pos..], 0.0, plain);
+        job
+    }
+
+    /// Build the matcher for `term`: the term is matched literally, optionally case-insensitive
+    /// and restricted to word boundaries. A boundary is only required on a side where the term
+    /// starts or ends with a word character, so terms like `C:\`, `.exe` or `-v` still match.
+    fn build_matcher(term: &str, options: &FtsOptions) -> Result<Regex, regex::Error> {
+        let is_word = |c: Option<char>| c.is_some_and(|c| c.is_alphanumeric() || c == '_');
+        let mut pattern = regex::escape(term);
+        if options.whole_word {
+            if is_word(term.chars().next()) {
+                pattern.insert_str(0, r"\b");
+            }
+            if is_word(term.chars().next_back()) {
+                pattern.push_str(r"\b");
+            }
+        }
+        RegexBuilder::new(&pattern)
+            .case_insensitive(options.case_insensitive)
+            .build()
+    }
+
+    fn clear_results(&mut self) {
+        self.stop_search();
+        self.result.clear();
+        self.hits.clear();
+        self.headers.clear();
+        self.counts.clear();
+        self.errors.clear();
+        self.focus = None;
+        self.show_result = false;
+    }
+
+    /// Stop the running search, keeping whatever it has reported so far.
+    fn stop_search(&mut self) {
+        if let Some(running) = self.running.take() {
+            running.cancel.store(true, Ordering::Relaxed);
+            self.result = format!("Stopped after {}ms", running.started.elapsed().as_millis());
+        }
+    }
+
     fn execute_query(&mut self) {
-        // TODO: Implement actual SQL execution logic
-        // For now, just show a placeholder result
-        if self.query.trim().is_empty() {
+        self.clear_results();
+        self.show_result = true;
+
+        let term = self.query.trim();
+        if term.is_empty() {
             self.result = "Error: Empty query".to_string();
-        } else {
-            self.result = format!(
-                "Query executed: {}\n\nResult: [Placeholder - implement SQL execution]",
-                self.query
-            );
+            return;
+        }
+        if self.sources.is_empty() {
+            self.result = "Error: No files to search".to_string();
+            return;
+        }
+        let matcher = match Self::build_matcher(term, &self.options) {
+            Ok(rx) => rx,
+            Err(e) => {
+                self.result = format!("Error: {e}");
+                return;
+            },
+        };
+
+        self.result.clear();
+        self.headers = vec![Vec::new(); self.sources.len()];
+        self.counts = vec![Vec::new(); self.sources.len()];
+
+        // Search on a worker so large inputs don't block the UI; progress is picked up in
+        // `poll_search`
+        let (tx, rx) = mpsc::channel();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let sources = self.sources.clone();
+        let worker_cancel = Arc::clone(&cancel);
+        std::thread::spawn(move || search_sources(&sources, &matcher, &tx, &worker_cancel));
+        self.running = Some(RunningSearch {
+            rx,
+            cancel,
+            started: Instant::now(),
+        });
+    }
+
+    /// Drain the worker's progress into the results.
+    fn poll_search(&mut self, ctx: &egui::Context) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        loop {
+            match running.rx.try_recv() {
+                Ok(FtsEvent::Headers { file, headers }) => self.headers[file] = headers,
+                Ok(FtsEvent::Counts { file, per_column }) => self.counts[file] = per_column,
+                Ok(FtsEvent::Hit(hit)) => self.hits.push(hit),
+                Ok(FtsEvent::Error(e)) => self.errors.push(e),
+                Err(TryRecvError::Empty) => {
+                    // Keep the counts and elapsed-time readout ticking
+                    ctx.request_repaint_after(Duration::from_millis(100));
+                    return;
+                },
+                Err(TryRecvError::Disconnected) => break,
+            }
+        }
+
+        if let Some(running) = self.running.take() {
+            self.result = format!("Finished in {}ms", running.started.elapsed().as_millis());
+        }
+    }
+}
+
+/// Worker side of a search: scan every cell of every source, reporting headers, running
+/// per-column counts and the first `MAX_HITS_PER_FILE` hits of each file.
+fn search_sources(
+    sources: &[PathBuf],
+    matcher: &Regex,
+    tx: &Sender<FtsEvent>,
+    cancel: &AtomicBool,
+) {
+    for (fi, src) in sources.iter().enumerate() {
+        let cfg = Config::builder().path(src.to_string_lossy()).build();
+        let mut rdr = match cfg.reader() {
+            Ok(rdr) => rdr,
+            Err(e) => {
+                let _ = tx.send(FtsEvent::Error(format!("{}: {e}", src.display())));
+                continue;
+            },
+        };
+        let headers: Vec<String> = rdr
+            .headers()
+            .map(|h| h.iter().map(str::to_string).collect())
+            .unwrap_or_default();
+        let mut per_column = vec![0; headers.len()];
+        if tx.send(FtsEvent::Headers { file: fi, headers }).is_err() {
+            return;
+        }
+
+        let mut kept = 0;
+        for (ri, rec) in cfg.records(&mut rdr).enumerate() {
+            if ri % COUNT_UPDATE_ROWS == 0 && ri > 0 {
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+                let counts = FtsEvent::Counts {
+                    file:       fi,
+                    per_column: per_column.clone(),
+                };
+                if tx.send(counts).is_err() {
+                    return;
+                }
+            }
+            let Ok(rec) = rec else {
+                continue;
+            };
+            for (ci, cell) in rec.iter().enumerate() {
+                if !matcher.is_match(cell) {
+                    continue;
+                }
+                if ci >= per_column.len() {
+                    per_column.resize(ci + 1, 0);
+                }
+                per_column[ci] += 1;
+                if kept < MAX_HITS_PER_FILE {
+                    kept += 1;
+                    let hit = FtsHit {
+                        file:  fi,
+                        row:   ri as u64,
+                        col:   ci,
+                        text:  cell.to_string(),
+                        spans: matcher.find_iter(cell).map(|m| m.range()).collect(),
+                    };
+                    if tx.send(FtsEvent::Hit(hit)).is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+
+        let counts = FtsEvent::Counts {
+            file: fi,
+            per_column,
+        };
+        if tx.send(counts).is_err() {
+            return;
         }
-        self.show_result = true;
     }
 }
@@ -0,0 +1,66 @@
+use std::{
+    sync::mpsc::{self, Receiver, TryRecvError},
+    time::Duration,
+};
+
+use anyhow::anyhow;
+use eframe::egui;
+
+/// How soon a frame with a job still running asks to be redrawn, to check on it again.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Work running on a worker thread so the UI keeps drawing; `poll` picks up its result.
+/// Dropping the job detaches the worker, which then discards whatever it produces.
+pub struct BackgroundJob<T> {
+    what: &'static str,
+    rx:   Receiver<anyhow::Result<T>>,
+}
+
+impl<T: Send + 'static> BackgroundJob<T> {
+    /// Run `work` on a new thread. `what` names the job in the error reported when the
+    /// worker dies without a result, e.g. "row count".
+    pub fn spawn(
+        what: &'static str,
+        work: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+    ) -> Self {
+        let (tx, rx) = mpsc::channel();
+        std::thread::spawn(move || {
+            // the receiver is gone if the job was dropped
+            let _ = tx.send(work());
+        });
+        BackgroundJob { what, rx }
+    }
+
+    /// The result once the job is done. While it is running this returns None and asks for
+    /// a repaint, so the next frame checks again.
+    pub fn poll(&self, ctx: &egui::Context) -> Option<anyhow::Result<T>> {
+        match self.rx.try_recv() {
+            Ok(res) => Some(res),
+            Err(TryRecvError::Disconnected) => {
+                Some(Err(anyhow!("{} stopped unexpectedly", self.what)))
+            },
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(POLL_INTERVAL);
+                None
+            },
+        }
+    }
+}
+
+/// Poll every job in `jobs`, removing the finished ones and returning them with their
+/// results. `job` picks the BackgroundJob out of an entry.
+pub fn take_finished<J, T: Send + 'static>(
+    jobs: &mut Vec<J>,
+    ctx: &egui::Context,
+    job: impl Fn(&J) -> &BackgroundJob<T>,
+) -> Vec<(J, anyhow::Result<T>)> {
+    let mut finished = Vec::new();
+    let mut i = 0;
+    while i < jobs.len() {
+        match job(&jobs[i]).poll(ctx) {
+            Some(res) => finished.push((jobs.remove(i), res)),
+            None => i += 1,
+        }
+    }
+    finished
+}
@@ -4,40 +4,195 @@ use epaint::TextureHandle;
 
 use crate::{
     APP_ICON, APP_VERSION,
-    app::{basic::BasicEditor, ftsq::FtsEditor, sqlq::SqlEditor},
+    app::{
+        basic::BasicEditor, ftsq::FtsEditor, sqlq::SqlEditor, timeline::TimelineEditor,
+        toast::Toasts, workflow::WorkflowEditor,
+    },
+    config::Settings,
 };
 
 mod basic;
 mod ftsq;
+mod job;
 mod sqlq;
 mod table;
+mod timeline;
+mod toast;
+mod workflow;
+
+/// I/O buffer sizes offered in the settings window, in KiB; None keeps the core defaults.
+const IO_BUFFER_CHOICES_KIB: [Option<u32>; 6] = [
+    None,
+    Some(256),
+    Some(1024),
+    Some(4096),
+    Some(16384),
+    Some(65536),
+];
 
 pub enum WakaMode {
     Basic,
     FullTextSearch,
     Sql,
+    Timeline,
     Workflow,
 }
 
 pub struct WakaApp {
-    basic_editor: BasicEditor,
-    sql_editor:   SqlEditor,
-    fts_editor:   FtsEditor,
-    current_mode: WakaMode,
-    logo_tex:     Option<TextureHandle>,
+    basic_editor:  BasicEditor,
+    sql_editor:    SqlEditor,
+    fts_editor:    FtsEditor,
+    timeline:      TimelineEditor,
+    workflow:      WorkflowEditor,
+    current_mode:  WakaMode,
+    logo_tex:      Option<TextureHandle>,
+    settings:      Settings,
+    active_jobs:   usize, // size of rayon's global pool, fixed at startup
+    show_settings: bool,
+    toasts:        Toasts,
 }
 
 impl WakaApp {
-    pub fn new() -> Self {
+    pub fn new(settings: Settings, active_jobs: usize) -> Self {
+        let toasts = Toasts::default();
         WakaApp {
-            basic_editor: BasicEditor::new(),
-            sql_editor:   SqlEditor::new(),
+            basic_editor: BasicEditor::new(toasts.notifier()),
+            sql_editor: SqlEditor::new(toasts.notifier()),
             current_mode: WakaMode::Basic,
-            fts_editor:   FtsEditor::new(),
-            logo_tex:     None,
+            fts_editor: FtsEditor::new(),
+            timeline: TimelineEditor::new(toasts.notifier()),
+            workflow: WorkflowEditor::new(toasts.notifier()),
+            logo_tex: None,
+            settings,
+            active_jobs,
+            show_settings: false,
+            toasts,
         }
     }
 
+    fn show_settings_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_settings;
+        egui::Window::new("⚙ Settings")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let max_jobs = waka_core::util::max_jobs();
+                ui.horizontal(|ui| {
+                    ui.label("Worker threads:");
+                    let changed = ui
+                        .add(
+                            egui::DragValue::new(&mut self.settings.jobs)
+                                .range(0..=max_jobs)
+                                .custom_formatter(|n, _| {
+                                    if n == 0.0 {
+                                        "all".to_string()
+                                    } else {
+                                        format!("{n}")
+                                    }
+                                }),
+                        )
+                        .on_hover_text(
+                            "Threads used by parallel operations (0 = all cores). Leaving a core \
+                             free keeps the interface responsive during large jobs.",
+                        )
+                        .changed();
+                    if changed && let Err(e) = self.settings.save() {
+                        tracing::warn!("failed to save settings: {e}");
+                    }
+                });
+                ui.label(
+                    egui::RichText::new(format!(
+                        "Currently using {} of {max_jobs} cores.",
+                        self.active_jobs
+                    ))
+                    .size(11.0)
+                    .color(ui.visuals().weak_text_color()),
+                );
+
+                let wanted = if self.settings.jobs == 0 {
+                    max_jobs
+                } else {
+                    self.settings.jobs
+                };
+                if wanted != self.active_jobs {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "⚠ Restart Waka to apply: the thread pool size is fixed once set.",
+                    );
+                }
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let mut custom = self.settings.memory_headroom_pct.is_some();
+                    let mut changed = ui
+                        .checkbox(&mut custom, "Memory headroom:")
+                        .on_hover_text(
+                            "Share of memory kept free when an operation has to load a whole file \
+                             (sorting, sampling, transposing…). Files that would not fit are \
+                             refused. Unchecked uses QSV_FREEMEMORY_HEADROOM_PCT or the default.",
+                        )
+                        .changed();
+                    let mut pct = self
+                        .settings
+                        .memory_headroom_pct
+                        .unwrap_or_else(waka_core::config::memory_headroom_pct)
+                        .clamp(10, 90);
+                    changed |= ui
+                        .add_enabled(custom, egui::Slider::new(&mut pct, 10..=90).suffix("%"))
+                        .changed();
+                    if changed {
+                        self.settings.memory_headroom_pct = custom.then_some(pct);
+                        self.settings.apply_memory_headroom();
+                        if let Err(e) = self.settings.save() {
+                            tracing::warn!("failed to save settings: {e}");
+                        }
+                    }
+                });
+                let headroom = waka_core::config::memory_headroom_pct();
+                let effective = if headroom == 0 {
+                    "Memory check disabled (QSV_FREEMEMORY_HEADROOM_PCT=0).".to_string()
+                } else {
+                    format!("Effective headroom: {headroom}%.")
+                };
+                ui.label(
+                    egui::RichText::new(effective)
+                        .size(11.0)
+                        .color(ui.visuals().weak_text_color()),
+                );
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label("I/O buffer:").on_hover_text(
+                        "Read and write buffer size for CSV files. Larger buffers speed up \
+                         evidence on network shares (SMB/NFS). Applies to files opened from now \
+                         on.",
+                    );
+                    let label = |kib: Option<u32>| match kib {
+                        None => "Default".to_string(),
+                        Some(kib) if kib >= 1024 => format!("{} MiB", kib / 1024),
+                        Some(kib) => format!("{kib} KiB"),
+                    };
+                    let mut kib = self.settings.io_buffer_kib;
+                    egui::ComboBox::from_id_salt("io_buffer_kib")
+                        .selected_text(label(kib))
+                        .show_ui(ui, |ui| {
+                            for choice in IO_BUFFER_CHOICES_KIB {
+                                ui.selectable_value(&mut kib, choice, label(choice));
+                            }
+                        });
+                    if kib != self.settings.io_buffer_kib {
+                        self.settings.io_buffer_kib = kib;
+                        self.settings.apply_io_buffer();
+                        if let Err(e) = self.settings.save() {
+                            tracing::warn!("failed to save settings: {e}");
+                        }
+                    }
+                });
+            });
+        self.show_settings = open;
+    }
+
     fn ensure_logo(&mut self, ctx: &egui::Context) {
         if self.logo_tex.is_some() {
             return;
@@ -56,9 +211,6 @@ impl eframe::App for WakaApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         self.ensure_logo(ctx);
 
-        // Set dark theme
-        ctx.set_visuals(egui::Visuals::dark());
-
         // Top panel with logo and navigation
         egui::TopBottomPanel::top("top_panel")
             .exact_height(60.0)
@@ -90,7 +242,7 @@ impl eframe::App for WakaApp {
                         ui.painter().circle_filled(
                             logo_response.rect.center(),
                             16.0,
-                            egui::Color32::from_rgb(0, 150, 255),
+                            ui.visuals().selection.bg_fill,
                         );
                     }
 
@@ -101,13 +253,13 @@ impl eframe::App for WakaApp {
                         ui.label(
                             egui::RichText::new("Waka DFIR Suite")
                                 .size(16.0)
-                                .color(egui::Color32::WHITE),
+                                .color(ui.visuals().strong_text_color()),
                         );
                         ui.label(
                             egui::RichText::new(format!("Version v{}", APP_VERSION))
                                 .size(11.0)
                                 .italics()
-                                .color(egui::Color32::GRAY),
+                                .color(ui.visuals().weak_text_color()),
                         );
                     });
 
@@ -117,9 +269,9 @@ impl eframe::App for WakaApp {
                     ui.horizontal(|ui| {
                         // Workflow button
                         let workflow_color = if matches!(self.current_mode, WakaMode::Workflow) {
-                            egui::Color32::from_rgb(0, 150, 255)
+                            ui.visuals().selection.stroke.color
                         } else {
-                            egui::Color32::GRAY
+                            ui.visuals().weak_text_color()
                         };
 
                         if ui
@@ -141,9 +293,9 @@ impl eframe::App for WakaApp {
 
                         // Toolbox button
                         let toolbox_color = if !matches!(self.current_mode, WakaMode::Workflow) {
-                            egui::Color32::from_rgb(0, 150, 255)
+                            ui.visuals().selection.stroke.color
                         } else {
-                            egui::Color32::GRAY
+                            ui.visuals().weak_text_color()
                         };
 
                         if ui
@@ -161,6 +313,80 @@ impl eframe::App for WakaApp {
                             // Show toolbox submenu or switch to default tool
                             self.current_mode = WakaMode::Basic;
                         }
+
+                        ui.add_space(24.0);
+
+                        if ui
+                            .add(
+                                egui::Button::new(
+                                    egui::RichText::new("⚙ Settings")
+                                        .color(if self.show_settings {
+                                            ui.visuals().selection.stroke.color
+                                        } else {
+                                            ui.visuals().weak_text_color()
+                                        })
+                                        .size(14.0),
+                                )
+                                .fill(egui::Color32::TRANSPARENT)
+                                .stroke(egui::Stroke::NONE),
+                            )
+                            .clicked()
+                        {
+                            self.show_settings = !self.show_settings;
+                        }
+
+                        ui.add_space(24.0);
+
+                        let theme_label = if ctx.theme() == egui::Theme::Light {
+                            "🌙 Dark"
+                        } else {
+                            "☀ Light"
+                        };
+                        if ui
+                            .add(
+                                egui::Button::new(
+                                    egui::RichText::new(theme_label)
+                                        .color(ui.visuals().weak_text_color())
+                                        .size(14.0),
+                                )
+                                .fill(egui::Color32::TRANSPARENT)
+                                .stroke(egui::Stroke::NONE),
+                            )
+                            .on_hover_text("Switch between the dark and light themes")
+                            .clicked()
+                        {
+                            let next = match ctx.theme() {
+                                egui::Theme::Dark => egui::Theme::Light,
+                                egui::Theme::Light => egui::Theme::Dark,
+                            };
+                            self.settings.theme = next.into();
+                            ctx.set_theme(self.settings.theme);
+                            if let Err(e) = self.settings.save() {
+                                tracing::warn!("failed to save settings: {e}");
+                            }
+                        }
+
+                        ui.add_space(24.0);
+
+                        if ui
+                            .add(
+                                egui::Button::new(
+                                    egui::RichText::new("🔔 Notifications")
+                                        .color(if self.toasts.show_log {
+                                            ui.visuals().selection.stroke.color
+                                        } else {
+                                            ui.visuals().weak_text_color()
+                                        })
+                                        .size(14.0),
+                                )
+                                .fill(egui::Color32::TRANSPARENT)
+                                .stroke(egui::Stroke::NONE),
+                            )
+                            .on_hover_text("Recent errors and completed jobs")
+                            .clicked()
+                        {
+                            self.toasts.show_log = !self.toasts.show_log;
+                        }
                     });
 
                     // Right side - tool selection (when in toolbox mode)
@@ -175,14 +401,14 @@ impl eframe::App for WakaApp {
                                         egui::Button::new(
                                             egui::RichText::new("SQL Editor").size(12.0).color(
                                                 if matches!(self.current_mode, WakaMode::Sql) {
-                                                    egui::Color32::WHITE
+                                                    ui.visuals().selection.stroke.color
                                                 } else {
-                                                    egui::Color32::GRAY
+                                                    ui.visuals().weak_text_color()
                                                 },
                                             ),
                                         )
                                         .fill(if matches!(self.current_mode, WakaMode::Sql) {
-                                            egui::Color32::from_rgb(0, 150, 255)
+                                            ui.visuals().selection.bg_fill
                                         } else {
                                             egui::Color32::TRANSPARENT
                                         })
@@ -203,16 +429,16 @@ impl eframe::App for WakaApp {
                                                         self.current_mode,
                                                         WakaMode::FullTextSearch
                                                     ) {
-                                                        egui::Color32::WHITE
+                                                        ui.visuals().selection.stroke.color
                                                     } else {
-                                                        egui::Color32::GRAY
+                                                        ui.visuals().weak_text_color()
                                                     },
                                                 ),
                                         )
                                         .fill(
                                             if matches!(self.current_mode, WakaMode::FullTextSearch)
                                             {
-                                                egui::Color32::from_rgb(0, 150, 255)
+                                                ui.visuals().selection.bg_fill
                                             } else {
                                                 egui::Color32::TRANSPARENT
                                             },
@@ -224,19 +450,42 @@ impl eframe::App for WakaApp {
                                     self.current_mode = WakaMode::FullTextSearch;
                                 }
 
+                                if ui
+                                    .add(
+                                        egui::Button::new(
+                                            egui::RichText::new("Timeline").size(12.0).color(
+                                                if matches!(self.current_mode, WakaMode::Timeline) {
+                                                    ui.visuals().selection.stroke.color
+                                                } else {
+                                                    ui.visuals().weak_text_color()
+                                                },
+                                            ),
+                                        )
+                                        .fill(if matches!(self.current_mode, WakaMode::Timeline) {
+                                            ui.visuals().selection.bg_fill
+                                        } else {
+                                            egui::Color32::TRANSPARENT
+                                        })
+                                        .corner_radius(4.0),
+                                    )
+                                    .clicked()
+                                {
+                                    self.current_mode = WakaMode::Timeline;
+                                }
+
                                 if ui
                                     .add(
                                         egui::Button::new(
                                             egui::RichText::new("Filer").size(12.0).color(
                                                 if matches!(self.current_mode, WakaMode::Basic) {
-                                                    egui::Color32::WHITE
+                                                    ui.visuals().selection.stroke.color
                                                 } else {
-                                                    egui::Color32::GRAY
+                                                    ui.visuals().weak_text_color()
                                                 },
                                             ),
                                         )
                                         .fill(if matches!(self.current_mode, WakaMode::Basic) {
-                                            egui::Color32::from_rgb(0, 150, 255)
+                                            ui.visuals().selection.bg_fill
                                         } else {
                                             egui::Color32::TRANSPARENT
                                         })
@@ -265,11 +514,17 @@ impl eframe::App for WakaApp {
                 WakaMode::Sql => {
                     self.sql_editor.show(ui);
                 },
+                WakaMode::Timeline => {
+                    self.timeline.show(ui, &self.basic_editor.table.files);
+                },
                 WakaMode::Workflow => {
-                    ui.label("Workflow Mode");
-                    // TODO: Add workflow UI here
+                    self.workflow.show(ui);
                 },
             }
         });
+
+        self.show_settings_window(ctx);
+        self.toasts.show_log_window(ctx);
+        self.toasts.show(ctx);
     }
 }
@@ -1,14 +1,79 @@
 use std::{
     path::PathBuf,
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use eframe::{egui, egui::Frame};
 use epaint::{CornerRadius, Margin};
 use polars_sql::keywords::{all_functions, all_keywords};
+use serde::{Deserialize, Serialize};
 use sqlparser::{ast::Statement, dialect::GenericDialect, parser::Parser};
 
-use crate::{app::basic::BasicEditor, util};
+use crate::{
+    app::{basic::BasicEditor, job::BackgroundJob, toast::Notifier},
+    config, util,
+};
+
+/// Default per-query timeout in seconds (overridable through `QSV_TIMEOUT`).
+const DEFAULT_QUERY_TIMEOUT_SECS: u16 = 300;
+
+/// State file holding the query history and saved queries.
+const QUERY_STORE_FILE: &str = "sql_queries.json";
+/// How many executed queries are remembered.
+const MAX_HISTORY: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedQuery {
+    pub name: String,
+    pub sql:  String,
+}
+
+/// Executed queries (most recent first) and user-named saved queries, persisted as JSON.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct QueryStore {
+    pub history: Vec<String>,
+    pub saved:   Vec<SavedQuery>,
+}
+
+impl QueryStore {
+    /// Record an executed query, skipping it when it repeats the previous one.
+    pub fn push_history(&mut self, sql: &str) {
+        let sql = sql.trim();
+        if sql.is_empty() || self.history.first().is_some_and(|last| last.trim() == sql) {
+            return;
+        }
+        self.history.insert(0, sql.to_string());
+        self.history.truncate(MAX_HISTORY);
+    }
+
+    /// Save `sql` under `name`, replacing an existing entry with the same name.
+    pub fn save_query(&mut self, name: &str, sql: &str) {
+        let name = name.trim();
+        if let Some(q) = self.saved.iter_mut().find(|q| q.name == name) {
+            q.sql = sql.to_string();
+        } else {
+            self.saved.push(SavedQuery {
+                name: name.to_string(),
+                sql:  sql.to_string(),
+            });
+        }
+    }
+
+    fn persist(&self) {
+        if let Err(e) = config::save_json(QUERY_STORE_FILE, self) {
+            tracing::warn!("failed to save SQL query history: {e}");
+        }
+    }
+}
+
+/// A query running on a worker thread. Polars cannot be interrupted mid-query, so cancelling
+/// or timing out detaches the worker and discards whatever it produces.
+struct RunningQuery {
+    job:      BackgroundJob<util::SqlpLibResult>,
+    started:  Instant,
+    timeout:  Duration,
+    out_path: PathBuf,
+}
 
 pub struct SqlEditor {
     query:               String,
@@ -34,10 +99,23 @@ pub struct SqlEditor {
     execution_time: String,
     row_count:      usize,
     basic_editor:   BasicEditor,
+    timeout_secs:   u16,
+    running:        Option<RunningQuery>,
+    queries:        QueryStore,
+    save_name:      String,
+    result_path:    Option<PathBuf>,
+    notify:         Notifier,
 }
 
 impl SqlEditor {
     fn execute_query(&mut self) {
+        if self.running.is_some() {
+            return;
+        }
+
+        self.queries.push_history(&self.query);
+        self.queries.persist();
+
         // Require at least one file to be present as a table
         if self.basic_editor.table.files.is_empty() {
             self.syntax_error = Some("Load at least one CSV file to query.".to_string());
@@ -80,23 +158,84 @@ impl SqlEditor {
             quiet: true,
         };
 
-        match util::run_sqlp(lib_args) {
+        let timeout = match waka_core::util::timeout_secs(self.timeout_secs) {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(e) => {
+                self.syntax_error = Some(format!("Invalid query timeout: {e}"));
+                return;
+            },
+        };
+
+        // Run on a worker so the UI stays responsive; results are picked up in `poll_running_query`
+        let job = BackgroundJob::spawn("query worker", move || util::run_sqlp(lib_args));
+        self.running = Some(RunningQuery {
+            job,
+            started: Instant::now(),
+            timeout,
+            out_path,
+        });
+        self.syntax_error = None;
+    }
+
+    /// Check on the running query. The current results stay in place until the query
+    /// completes successfully.
+    fn poll_running_query(&mut self, ctx: &egui::Context) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        let (elapsed, timeout) = (running.started.elapsed(), running.timeout);
+        // a query still running also keeps the elapsed-time readout ticking
+        let Some(outcome) = running.job.poll(ctx) else {
+            if elapsed >= timeout {
+                self.cancel_query(format!(
+                    "SQL query timed out after {}s; no results were loaded.",
+                    timeout.as_secs()
+                ));
+            }
+            return;
+        };
+
+        let Some(running) = self.running.take() else {
+            return;
+        };
+        match outcome {
             Ok(res) => {
                 self.execution_time = format!("{}ms", res.elapsed_ms);
                 self.row_count = res.rows;
                 self.show_result = true;
                 self.syntax_error = None;
                 // Load the produced CSV into the results table
-                self.basic_editor.table.load_preview(out_path);
+                self.basic_editor
+                    .table
+                    .load_preview(running.out_path.clone());
+                self.result_path = Some(running.out_path);
             },
             Err(e) => {
-                self.show_result = false;
                 self.syntax_error = Some(format!("SQL execution failed: {}", e));
+                self.notify.error(format!("SQL execution failed: {e}"));
             },
         }
     }
 
-    pub fn new() -> Self {
+    /// Tab index of the latest query result in the results table, if it is still open.
+    fn result_index(&self) -> Option<usize> {
+        let path = self.result_path.as_ref()?.to_string_lossy();
+        self.basic_editor
+            .table
+            .files
+            .iter()
+            .position(|fp| fp.file_path == path)
+    }
+
+    /// Abandon the running query, leaving the previous results untouched.
+    fn cancel_query(&mut self, message: String) {
+        if let Some(running) = self.running.take() {
+            self.execution_time = format!("{}ms (aborted)", running.started.elapsed().as_millis());
+            self.syntax_error = Some(message);
+        }
+    }
+
+    pub fn new(notifier: Notifier) -> Self {
         Self {
             query:               "SELECT * FROM data\nLIMIT 1000".to_string(),
             result:              String::new(),
@@ -119,46 +258,52 @@ impl SqlEditor {
 
             execution_time: "69ms".to_string(),
             row_count:      1000,
-            basic_editor:   BasicEditor::new(),
+            basic_editor:   BasicEditor::new(notifier.clone()),
+            timeout_secs:   DEFAULT_QUERY_TIMEOUT_SECS,
+            running:        None,
+            queries:        config::load_json(QUERY_STORE_FILE),
+            save_name:      String::new(),
+            result_path:    None,
+            notify:         notifier,
         }
     }
 
     pub fn show(&mut self, ui: &mut egui::Ui) {
-        // Main container with VS Code dark theme
+        // Main container, colored by the current theme
         self.basic_editor.table.handle_file_drop(ui.ctx());
+        self.basic_editor.table.poll_jobs(ui.ctx());
+        self.poll_running_query(ui.ctx());
 
-        Frame::new()
-            .fill(egui::Color32::from_rgb(37, 37, 38)) // VS Code background
-            .show(ui, |ui| {
-                ui.vertical(|ui| {
-                    // Header section
-                    self.show_header(ui);
+        Frame::new().fill(ui.visuals().panel_fill).show(ui, |ui| {
+            ui.vertical(|ui| {
+                // Header section
+                self.show_header(ui);
 
-                    // SQL Editor area
-                    self.show_sql_editor_section(ui);
+                // SQL Editor area
+                self.show_sql_editor_section(ui);
 
-                    // Query controls
-                    self.show_query_controls(ui);
+                // Query controls
+                self.show_query_controls(ui);
 
-                    // Execution status
-                    self.show_execution_status(ui);
+                // Execution status
+                self.show_execution_status(ui);
 
-                    // Results area
-                    if self.show_result {
-                        self.show_results_section(ui);
-                    }
+                // Results area
+                if self.show_result {
+                    self.show_results_section(ui);
+                }
 
-                    // Suggestions popup (overlay)
-                    if self.show_suggestions && !self.suggestions.is_empty() {
-                        self.show_suggestions_popup(ui);
-                    }
-                });
+                // Suggestions popup (overlay)
+                if self.show_suggestions && !self.suggestions.is_empty() {
+                    self.show_suggestions_popup(ui);
+                }
             });
+        });
     }
 
     fn show_header(&mut self, ui: &mut egui::Ui) {
         Frame::new()
-            .fill(egui::Color32::from_rgb(45, 45, 45))
+            .fill(ui.visuals().faint_bg_color)
             .inner_margin(Margin::symmetric(0, 12))
             .show(ui, |ui| {
                 ui.vertical(|ui| {
@@ -169,7 +314,7 @@ impl SqlEditor {
                         // Collapsible arrow and title
                         ui.label(
                             egui::RichText::new("▼ Run a Polars SQL query")
-                                .color(egui::Color32::WHITE)
+                                .color(ui.visuals().strong_text_color())
                                 .size(14.0)
                                 .strong(),
                         );
@@ -199,7 +344,7 @@ impl SqlEditor {
                             ui.add_space(16.0);
                             ui.label(
                                 egui::RichText::new(format!("• {}", instruction))
-                                    .color(egui::Color32::from_rgb(200, 200, 200))
+                                    .color(ui.visuals().text_color())
                                     .size(12.0),
                             );
                         });
@@ -212,7 +357,7 @@ impl SqlEditor {
                         ui.add_space(16.0);
                         ui.label(
                             egui::RichText::new("Enter your Polars SQL query:")
-                                .color(egui::Color32::WHITE)
+                                .color(ui.visuals().strong_text_color())
                                 .size(13.0)
                                 .strong(),
                         );
@@ -226,8 +371,8 @@ impl SqlEditor {
         let editor_height = available_height * self.editor_height_ratio;
 
         Frame::new()
-            .fill(egui::Color32::from_rgb(30, 30, 30))
-            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(70, 70, 70)))
+            .fill(ui.visuals().extreme_bg_color)
+            .stroke(ui.visuals().widgets.noninteractive.bg_stroke)
             .corner_radius(CornerRadius::same(4))
             .inner_margin(Margin::symmetric(16, 8))
             .show(ui, |ui| {
@@ -238,22 +383,61 @@ impl SqlEditor {
 
     fn show_query_controls(&mut self, ui: &mut egui::Ui) {
         Frame::new()
-            .fill(egui::Color32::from_rgb(40, 40, 40))
+            .fill(ui.visuals().faint_bg_color)
             .inner_margin(Margin::symmetric(16, 8))
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
                     // Run SQL query button
                     let run_button = egui::Button::new(
                         egui::RichText::new("🛠 Run SQL query")
-                            .color(egui::Color32::WHITE)
+                            .color(ui.visuals().selection.stroke.color)
                             .size(13.0),
                     )
-                    .fill(egui::Color32::from_rgb(0, 120, 215)) // VS Code blue
+                    .fill(ui.visuals().selection.bg_fill)
                     .corner_radius(CornerRadius::same(4));
 
-                    if ui.add(run_button).clicked() {
+                    let is_running = self.running.is_some();
+                    if ui.add_enabled(!is_running, run_button).clicked() {
                         self.execute_query();
                     }
+                    if is_running {
+                        ui.spinner();
+                        if ui
+                            .button("⏹ Cancel")
+                            .on_hover_text("Abort the running query")
+                            .clicked()
+                        {
+                            self.cancel_query(
+                                "SQL query cancelled; no results were loaded.".into(),
+                            );
+                        }
+                    }
+
+                    let export_button = egui::Button::new(
+                        egui::RichText::new("📤 Export results")
+                            .color(ui.visuals().strong_text_color())
+                            .size(13.0),
+                    )
+                    .corner_radius(CornerRadius::same(4));
+                    let has_result = self.result_index().is_some();
+                    let resp = ui
+                        .add_enabled(has_result, export_button)
+                        .on_disabled_hover_text("Run a query first");
+                    if resp.clicked()
+                        && let Some(idx) = self.result_index()
+                    {
+                        // Export acts on the active tab, so bring the result set to the front
+                        self.basic_editor.table.current_file = idx;
+                        self.basic_editor.table.reload_current_preview_page();
+                    }
+                    if has_result {
+                        self.basic_editor.table.show_export_popup(ui, &resp);
+                    }
+                    self.basic_editor.table.show_export_progress(ui);
+
+                    ui.add_space(16.0);
+
+                    self.show_query_library(ui);
 
                     ui.add_space(16.0);
 
@@ -279,7 +463,7 @@ impl SqlEditor {
                     // Inference length
                     ui.label(
                         egui::RichText::new("Inference length:")
-                            .color(egui::Color32::WHITE)
+                            .color(ui.visuals().strong_text_color())
                             .size(12.0),
                     );
 
@@ -288,23 +472,137 @@ impl SqlEditor {
                             .range(1..=10000)
                             .speed(10),
                     );
+
+                    ui.add_space(16.0);
+
+                    ui.label(
+                        egui::RichText::new("Timeout (s):")
+                            .color(ui.visuals().strong_text_color())
+                            .size(12.0),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut self.timeout_secs)
+                            .range(1..=3600)
+                            .speed(5),
+                    );
+                });
+            });
+    }
+
+    /// History and saved-query dropdown. Double-clicking an entry loads it into the editor.
+    fn show_query_library(&mut self, ui: &mut egui::Ui) {
+        let resp = ui
+            .button("🕘 Queries")
+            .on_hover_text("Query history and saved queries");
+        let popup_id = ui.make_persistent_id("sql_query_library");
+        if resp.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+
+        let mut load: Option<String> = None;
+        let mut changed = false;
+        egui::Popup::from_response(&resp)
+            .open_memory(None)
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(360.0);
+
+                ui.label(egui::RichText::new("Saved queries").strong());
+                ui.horizontal(|ui| {
+                    ui.add(
+                        egui::TextEdit::singleline(&mut self.save_name)
+                            .hint_text("Name")
+                            .desired_width(200.0),
+                    );
+                    let can_save = !self.save_name.trim().is_empty() && !self.query.is_empty();
+                    if ui
+                        .add_enabled(can_save, egui::Button::new("💾 Save current"))
+                        .clicked()
+                    {
+                        self.queries.save_query(&self.save_name, &self.query);
+                        self.save_name.clear();
+                        changed = true;
+                    }
                 });
+                let mut remove: Option<usize> = None;
+                egui::ScrollArea::vertical()
+                    .id_salt("sql_saved_queries")
+                    .max_height(140.0)
+                    .show(ui, |ui| {
+                        for (i, q) in self.queries.saved.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("🗑").on_hover_text("Delete").clicked() {
+                                    remove = Some(i);
+                                }
+                                let entry = ui.selectable_label(false, q.name.as_str());
+                                if entry.on_hover_text(q.sql.as_str()).double_clicked() {
+                                    load = Some(q.sql.clone());
+                                }
+                            });
+                        }
+                    });
+                if let Some(i) = remove {
+                    self.queries.saved.remove(i);
+                    changed = true;
+                }
+
+                ui.separator();
+                ui.label(egui::RichText::new("History").strong());
+                egui::ScrollArea::vertical()
+                    .id_salt("sql_query_history")
+                    .max_height(200.0)
+                    .show(ui, |ui| {
+                        if self.queries.history.is_empty() {
+                            ui.label(
+                                egui::RichText::new("No queries run yet")
+                                    .color(ui.visuals().weak_text_color())
+                                    .size(11.0),
+                            );
+                        }
+                        for sql in &self.queries.history {
+                            let first_line = sql.lines().next().unwrap_or_default();
+                            let entry = ui.selectable_label(
+                                false,
+                                egui::RichText::new(first_line).monospace().size(11.0),
+                            );
+                            if entry.on_hover_text(sql.as_str()).double_clicked() {
+                                load = Some(sql.clone());
+                            }
+                        }
+                    });
             });
+
+        if changed {
+            self.queries.persist();
+        }
+        if let Some(sql) = load {
+            self.query = sql;
+            self.validate_syntax();
+            egui::Popup::close_id(ui.ctx(), popup_id);
+        }
     }
 
     fn show_execution_status(&mut self, ui: &mut egui::Ui) {
         Frame::new()
-            .fill(egui::Color32::from_rgb(50, 50, 50))
+            .fill(ui.visuals().faint_bg_color)
             .inner_margin(Margin::symmetric(16, 4))
             .show(ui, |ui| {
                 ui.horizontal(|ui| {
-                    ui.label(
-                        egui::RichText::new(format!(
+                    let text = match &self.running {
+                        Some(running) => format!(
+                            "Running Polars SQL query… elapsed: {:.1}s",
+                            running.started.elapsed().as_secs_f32()
+                        ),
+                        None => format!(
                             "Recent Polars SQL query's estimated elapsed time: {} | Row count: {}",
                             self.execution_time, self.row_count
-                        ))
-                        .color(egui::Color32::from_rgb(180, 180, 180))
-                        .size(11.0),
+                        ),
+                    };
+                    ui.label(
+                        egui::RichText::new(text)
+                            .color(ui.visuals().text_color())
+                            .size(11.0),
                     );
                 });
             });
@@ -317,7 +615,7 @@ impl SqlEditor {
 
     fn show_highlighted_editor(&mut self, ui: &mut egui::Ui, height: f32) {
         Frame::new()
-            .fill(egui::Color32::from_rgb(30, 30, 30))
+            .fill(ui.visuals().extreme_bg_color)
             .show(ui, |ui| {
                 ui.vertical(|ui| {
                     let text_edit_id = egui::Id::new("sql_editor");
@@ -429,8 +727,8 @@ impl SqlEditor {
                     response.clone().on_hover_ui_at_pointer(|ui| {
                         ui.set_max_width(300.0);
                         Frame::new()
-                            .fill(egui::Color32::from_rgb(80, 40, 40))
-                            .stroke(egui::Stroke::new(1.0, egui::Color32::from_rgb(220, 80, 80)))
+                            .fill(ui.visuals().error_fg_color.gamma_multiply(0.15))
+                            .stroke(egui::Stroke::new(1.0, ui.visuals().error_fg_color))
                             .corner_radius(CornerRadius::same(4))
                             .inner_margin(Margin::same(8))
                             .show(ui, |ui| {
@@ -439,13 +737,13 @@ impl SqlEditor {
                                     ui.vertical(|ui| {
                                         ui.label(
                                             egui::RichText::new("Syntax Error:")
-                                                .color(egui::Color32::from_rgb(255, 200, 200))
+                                                .color(ui.visuals().error_fg_color)
                                                 .size(12.0)
                                                 .strong(),
                                         );
                                         ui.label(
                                             egui::RichText::new(error_msg)
-                                                .color(egui::Color32::from_rgb(255, 220, 220))
+                                                .color(ui.visuals().text_color())
                                                 .size(11.0)
                                                 .family(egui::FontFamily::Monospace),
                                         );
@@ -466,7 +764,7 @@ impl SqlEditor {
                 egui::Align2::LEFT_TOP,
                 "-- Enter your SQL query here",
                 egui::FontId::monospace(13.0),
-                egui::Color32::from_rgb(100, 100, 100),
+                painter.ctx().style().visuals.weak_text_color(),
             );
             return;
         }
@@ -510,7 +808,7 @@ impl SqlEditor {
     }
 
     fn paint_error_underline(&self, painter: &egui::Painter, start_x: f32, end_x: f32, y: f32) {
-        let color = egui::Color32::from_rgb(220, 80, 80);
+        let color = painter.ctx().style().visuals.error_fg_color;
         let stroke = egui::Stroke::new(1.5, color);
 
         // Draw a wavy line
@@ -586,6 +884,7 @@ impl SqlEditor {
         char_width: f32,
     ) -> f32 {
         let mut x_pos = start_x;
+        let visuals = painter.ctx().style().visuals.clone();
         let chars: Vec<char> = text.chars().collect();
         let mut i = 0;
 
@@ -638,7 +937,7 @@ impl SqlEditor {
 
             let token: String = chars[word_start..i].iter().collect();
             if !token.is_empty() {
-                let color = self.get_token_color(&token);
+                let color = self.get_token_color(&token, &visuals);
 
                 painter.text(
                     egui::Pos2::new(x_pos, y_pos),
@@ -655,7 +954,7 @@ impl SqlEditor {
         x_pos
     }
 
-    fn get_token_color(&self, word: &str) -> egui::Color32 {
+    fn get_token_color(&self, word: &str, visuals: &egui::Visuals) -> egui::Color32 {
         let clean_word = word.trim_matches(|c: char| !c.is_alphanumeric() && c != '_');
         let upper_word = clean_word.to_uppercase();
 
@@ -672,9 +971,9 @@ impl SqlEditor {
         } else if word.starts_with("--") || word.starts_with("/*") {
             egui::Color32::from_rgb(106, 153, 85) // Green for comments
         } else if "=<>!+-*/%()[]{},.;".contains(clean_word) {
-            egui::Color32::from_rgb(212, 212, 212) // Light gray for operators
+            visuals.text_color() // plain text color for operators
         } else {
-            egui::Color32::WHITE // White for regular text
+            visuals.strong_text_color() // strong text color for regular text
         }
     }
 
@@ -687,7 +986,7 @@ impl SqlEditor {
                 ui.label("🟢");
                 ui.label(
                     egui::RichText::new("Ready")
-                        .color(egui::Color32::WHITE)
+                        .color(ui.visuals().strong_text_color())
                         .size(11.0),
                 );
 
@@ -700,7 +999,7 @@ impl SqlEditor {
                             "Ln {}, Col {}",
                             self.cursor_row, self.cursor_col
                         ))
-                        .color(egui::Color32::from_rgb(170, 170, 170))
+                        .color(ui.visuals().weak_text_color())
                         .size(10.0),
                     );
                 });
@@ -821,49 +1120,43 @@ impl SqlEditor {
         egui::Area::new(egui::Id::new("sql_suggestions"))
             .fixed_pos(popup_pos)
             .show(ui.ctx(), |ui| {
-                egui::Frame::popup(ui.style())
-                    .fill(egui::Color32::from_rgb(60, 60, 60))
-                    .stroke(egui::Stroke::new(
-                        1.0,
-                        egui::Color32::from_rgb(100, 100, 100),
-                    ))
-                    .show(ui, |ui| {
-                        ui.vertical(|ui| {
-                            ui.set_min_width(200.0);
-
-                            for (i, suggestion) in self.suggestions.iter().take(8).enumerate() {
-                                let is_selected = i == self.selected_suggestion;
-
-                                let response = ui.selectable_label(
-                                    is_selected,
-                                    egui::RichText::new(suggestion)
-                                        .color(if is_selected {
-                                            egui::Color32::WHITE
-                                        } else {
-                                            egui::Color32::from_rgb(220, 220, 220)
-                                        })
-                                        .family(egui::FontFamily::Monospace)
-                                        .size(13.0),
-                                );
-
-                                if response.clicked() {
-                                    clicked_suggestion = Some(suggestion.clone());
-                                }
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.vertical(|ui| {
+                        ui.set_min_width(200.0);
+
+                        for (i, suggestion) in self.suggestions.iter().take(8).enumerate() {
+                            let is_selected = i == self.selected_suggestion;
+
+                            let response = ui.selectable_label(
+                                is_selected,
+                                egui::RichText::new(suggestion)
+                                    .color(if is_selected {
+                                        ui.visuals().strong_text_color()
+                                    } else {
+                                        ui.visuals().text_color()
+                                    })
+                                    .family(egui::FontFamily::Monospace)
+                                    .size(13.0),
+                            );
+
+                            if response.clicked() {
+                                clicked_suggestion = Some(suggestion.clone());
                             }
+                        }
 
-                            ui.separator();
-                            ui.horizontal(|ui| {
-                                ui.label("💡");
-                                ui.label(
-                                    egui::RichText::new(
-                                        "Ctrl+Enter to accept  •  Ctrl+Space to toggle",
-                                    )
-                                    .color(egui::Color32::from_rgb(170, 170, 170))
-                                    .size(10.0),
-                                );
-                            });
+                        ui.separator();
+                        ui.horizontal(|ui| {
+                            ui.label("💡");
+                            ui.label(
+                                egui::RichText::new(
+                                    "Ctrl+Enter to accept  •  Ctrl+Space to toggle",
+                                )
+                                .color(ui.visuals().weak_text_color())
+                                .size(10.0),
+                            );
                         });
                     });
+                });
             });
 
         if let Some(suggestion) = clicked_suggestion {
@@ -1,6 +1,11 @@
 use std::{
-    collections::{BTreeSet, HashSet},
+    collections::{BTreeMap, HashMap, HashSet},
     path::{Path, PathBuf},
+    sync::{
+        Arc,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    time::Instant,
 };
 
 use anyhow::anyhow;
@@ -19,9 +24,62 @@ use regex::{Regex, RegexBuilder};
 use rfd::FileDialog;
 use serde::{Deserialize, Serialize};
 use serde_json::{Map as JsonMap, Value as JsonValue};
-use waka_core::{config::Config, count, slice, sort};
+use waka_core::{
+    config::{Config, Delimiter, IndexState},
+    count, index, slice, sort, util as core_util,
+};
+
+use crate::{
+    app::{
+        job::{self, BackgroundJob},
+        toast::{Notifier, ToastKind},
+    },
+    config, util,
+};
+
+/// State file holding the recently opened files.
+const RECENT_FILES_FILE: &str = "recent_files.json";
+/// How many recently opened files are remembered.
+const MAX_RECENT_FILES: usize = 10;
+
+/// Files opened in the table (most recent first), persisted as JSON.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct RecentFiles {
+    pub paths: Vec<PathBuf>,
+}
+
+impl RecentFiles {
+    /// Load the list, dropping files that no longer exist.
+    pub fn load() -> Self {
+        let mut recent: RecentFiles = config::load_json(RECENT_FILES_FILE);
+        recent.paths.retain(|p| p.exists());
+        recent
+    }
 
-use crate::util;
+    /// Move `path` to the front of the list.
+    pub fn push(&mut self, path: &Path) {
+        self.paths.retain(|p| p != path);
+        self.paths.insert(0, path.to_path_buf());
+        self.paths.truncate(MAX_RECENT_FILES);
+        self.persist();
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.paths.retain(|p| p != path);
+        self.persist();
+    }
+
+    pub fn clear(&mut self) {
+        self.paths.clear();
+        self.persist();
+    }
+
+    fn persist(&self) {
+        if let Err(e) = config::save_json(RECENT_FILES_FILE, self) {
+            tracing::warn!("failed to save recent files: {e}");
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ExportFormat {
@@ -30,16 +88,30 @@ pub enum ExportFormat {
     Ods,
     Parquet,
     Json,
+    Ndjson,
+}
+
+/// Which rows of the current file an export writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportScope {
+    AllRows,
+    FilteredRows, // falls back to all rows when no filters are active
+    CurrentPage,  // only the rows shown on the current page
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct ColumnFilter {
     pub enabled:          bool,
     pub include:          bool, // include selected values when true; else exclude them
     pub case_insensitive: bool, // Aa toggle
     pub selected:         Vec<String>, // chosen values in this column
     #[serde(skip)]
-    pub distinct_cache:   Option<Vec<String>>, // lazily populated (sampled)
+    pub distinct_cache:   Option<Vec<String>>, // lazily populated (sampled unless all_distinct)
+    #[serde(skip)]
+    pub all_distinct:     bool, // scan the whole column instead of sampling distinct values
+    #[serde(skip)]
+    pub value_counts:     Option<ValueCounts>, // top values, filled with distinct_cache
     #[serde(skip)]
     pub search:           String, // search within the dropdown
     // Regex filtering
@@ -49,6 +121,14 @@ pub struct ColumnFilter {
     pub regex_error:      Option<String>, // last regex compile error (ui only)
     #[serde(skip)]
     pub compiled_regex:   Option<Regex>, // cached compiled regex (ui/runtime only)
+    // Numeric range filtering
+    pub use_range:        bool, // enable range filter (numeric columns only)
+    pub range_min:        String, // lower bound text, empty = unbounded
+    pub range_max:        String, // upper bound text, empty = unbounded
+    pub min_inclusive:    bool,
+    pub max_inclusive:    bool,
+    #[serde(skip)]
+    pub is_numeric:       Option<bool>, // detected lazily from a sample of the column
 }
 
 impl Default for ColumnFilter {
@@ -59,16 +139,115 @@ impl Default for ColumnFilter {
             case_insensitive: false,
             selected:         Vec::new(),
             distinct_cache:   None,
+            all_distinct:     false,
+            value_counts:     None,
             search:           String::new(),
             use_regex:        false,
             regex_text:       String::new(),
             regex_error:      None,
             compiled_regex:   None,
+            use_range:        false,
+            range_min:        String::new(),
+            range_max:        String::new(),
+            min_inclusive:    true,
+            max_inclusive:    true,
+            is_numeric:       None,
         }
     }
 }
 
+/// Numeric bounds of a range filter. Cells that don't parse as numbers never match.
+#[derive(Debug, Clone, Copy)]
+pub struct NumRange {
+    min:           Option<f64>,
+    max:           Option<f64>,
+    min_inclusive: bool,
+    max_inclusive: bool,
+}
+
+impl NumRange {
+    fn contains(&self, v: f64) -> bool {
+        let above_min = self.min.is_none_or(|min| {
+            if self.min_inclusive {
+                v >= min
+            } else {
+                v > min
+            }
+        });
+        let below_max = self.max.is_none_or(|max| {
+            if self.max_inclusive {
+                v <= max
+            } else {
+                v < max
+            }
+        });
+        above_min && below_max
+    }
+
+    fn matches(&self, cell: &str) -> bool {
+        cell.trim().parse::<f64>().is_ok_and(|v| self.contains(v))
+    }
+
+    fn matches_bytes(&self, cell: &[u8]) -> bool {
+        std::str::from_utf8(cell).is_ok_and(|s| self.matches(s))
+    }
+}
+
+/// Most frequent values of a column, counted while sampling its distinct values.
+#[derive(Debug, Clone, Default)]
+pub struct ValueCounts {
+    pub top:      Vec<(String, u64)>, // by count descending, then value
+    pub complete: bool,               // false if sampling stopped before the end of the file
+}
+
+impl ValueCounts {
+    const TOP_N: usize = 10;
+
+    fn from_counts(counts: BTreeMap<String, u64>, complete: bool) -> Self {
+        let mut top: Vec<(String, u64)> = counts.into_iter().collect();
+        // stable sort keeps equal counts in value order
+        top.sort_by(|a, b| b.1.cmp(&a.1));
+        top.truncate(Self::TOP_N);
+        Self { top, complete }
+    }
+}
+
 impl ColumnFilter {
+    /// Whether this filter restricts rows at all.
+    pub fn is_active(&self) -> bool {
+        !self.selected.is_empty() || self.use_regex || self.range().is_some()
+    }
+
+    /// The range to filter by, if range mode is on and the bounds are valid numbers
+    /// (at least one of them set).
+    pub fn range(&self) -> Option<NumRange> {
+        if !self.use_range || self.range_error().is_some() {
+            return None;
+        }
+        let parse = |s: &str| s.trim().parse::<f64>().ok();
+        let (min, max) = (parse(&self.range_min), parse(&self.range_max));
+        if min.is_none() && max.is_none() {
+            return None;
+        }
+        Some(NumRange {
+            min,
+            max,
+            min_inclusive: self.min_inclusive,
+            max_inclusive: self.max_inclusive,
+        })
+    }
+
+    /// Why the range bounds can't be used, if they can't.
+    pub fn range_error(&self) -> Option<String> {
+        for (name, bound) in [("minimum", &self.range_min), ("maximum", &self.range_max)] {
+            let bound = bound.trim();
+            if !bound.is_empty() && bound.parse::<f64>().is_err() {
+                return Some(format!("Invalid {name}: {bound}"));
+            }
+        }
+        None
+    }
+
     pub fn rebuild_regex(&mut self) {
         if self.use_regex && !self.regex_text.is_empty() {
             let mut b = RegexBuilder::new(self.regex_text.as_str());
@@ -104,32 +283,405 @@ pub struct FilePreview {
     pub sorted_indices:   Option<Vec<u64>>,
     pub sort_col:         Option<usize>,
     pub sort_desc:        bool,
+    pub column_order:     Vec<usize>, // display position -> source column index
+    pub cursor_row:       Option<usize>, // keyboard cursor, row within the current page
+    pub delimiter:        Option<u8>, // manual delimiter override, None = auto-detect
+    pub link_columns:     HashMap<usize, bool>, // "treat as link" overrides, absent = auto
+    pub json_columns:     HashSet<usize>, // columns emitted as nested JSON on JSON export
+    pub index_state:      IndexState, // state of the `.idx` sidecar, refreshed on reload
+    pub sample_rows:      Option<Vec<Vec<String>>>, // random sample shown instead of pages
+    pub renamed_headers:  Vec<Option<String>>, // header overrides by source column, None = original
+    /// sniffed the first time "File info" is opened
+    pub dialect:          Option<Result<core_util::CsvDialect, String>>,
+    /// RFC 4180 conformance, checked in the background when asked for from the tab menu
+    pub health:           Option<Result<core_util::CsvReport, String>>,
+    /// computed from templates over the row, shown and exported after the file's columns
+    pub derived_columns:  Vec<DerivedColumn>,
+    /// scratch directory holding the CSV parsed from an `$MFT`, removed with the last clone
+    pub mft_workdir:      Option<Arc<tempfile::TempDir>>,
+}
+
+/// A column filled from a template such as `{hostname}:{port}` over the other columns.
+#[derive(Debug, Clone)]
+pub struct DerivedColumn {
+    pub name:     String,
+    pub template: String,
+    compiled:     core_util::FieldTemplate,
+}
+
+impl FilePreview {
+    /// Reader config for this file, honoring the manual delimiter override.
+    pub fn config(&self) -> Config {
+        Config::builder()
+            .path(&self.file_path)
+            .build()
+            .delimiter(self.delimiter.map(Delimiter))
+    }
+
+    /// Header shown and exported for a source column: its rename, if any, else the original.
+    pub fn header_name(&self, col: usize) -> &str {
+        match self.renamed_headers.get(col) {
+            Some(Some(name)) => name.as_str(),
+            _ => self.headers.get(col).map_or("", String::as_str),
+        }
+    }
+
+    /// Rename repeated header names to `name_2`, `name_3`, ... (unless already renamed) so
+    /// filters, exports and JSON keys can tell the columns apart. Returns the repeated names
+    /// with the columns they occur in.
+    pub fn disambiguate_headers(&mut self) -> Vec<(String, Vec<usize>)> {
+        let dupes = core_util::duplicate_headers(&csv::ByteRecord::from(self.headers.as_slice()));
+        if !dupes.is_empty() {
+            let names = core_util::dedup_header_names(self.headers.iter().map(String::as_str));
+            for (col, name) in names.iter().enumerate() {
+                if self.renamed_headers.get(col).is_none_or(Option::is_none) {
+                    self.rename_header(col, name);
+                }
+            }
+        }
+        dupes
+    }
+
+    /// Relabel a source column. An empty name or the original header clears the rename.
+    pub fn rename_header(&mut self, col: usize, name: &str) {
+        if col >= self.headers.len() {
+            return;
+        }
+        let name = name.trim();
+        if self.renamed_headers.len() < self.headers.len() {
+            self.renamed_headers.resize(self.headers.len(), None);
+        }
+        self.renamed_headers[col] =
+            (!name.is_empty() && name != self.headers[col]).then(|| name.to_string());
+    }
+
+    /// Add a derived column named `name` filled from `template`, whose `{key}`s are resolved
+    /// against the headers as currently shown.
+    pub fn add_derived_column(&mut self, name: &str, template: &str) -> anyhow::Result<()> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(anyhow!("The new column needs a name"));
+        }
+        let shown: Vec<&str> = (0..self.headers.len())
+            .map(|ci| self.header_name(ci))
+            .collect();
+        if shown.contains(&name) || self.derived_columns.iter().any(|d| d.name == name) {
+            return Err(anyhow!("A column named \"{name}\" already exists"));
+        }
+        let compiled = core_util::FieldTemplate::new(template, &shown)?;
+        self.derived_columns.push(DerivedColumn {
+            name: name.to_string(),
+            template: template.to_string(),
+            compiled,
+        });
+        Ok(())
+    }
+
+    /// The derived column values of a row, `field` giving its source column values.
+    pub fn derived_values<'a>(&self, field: impl Fn(usize) -> Option<&'a [u8]>) -> Vec<String> {
+        self.derived_columns
+            .iter()
+            .map(|d| d.compiled.render(&field))
+            .collect()
+    }
+
+    /// Exported header row: the columns in `order`, then the derived ones.
+    fn output_headers(&self, order: &[usize]) -> Vec<&str> {
+        order
+            .iter()
+            .map(|&ci| self.header_name(ci))
+            .chain(self.derived_columns.iter().map(|d| d.name.as_str()))
+            .collect()
+    }
+
+    /// Write one exported row: the fields of the columns in `order`, then the derived ones.
+    fn write_output_row<'a, W: std::io::Write>(
+        &self,
+        wtr: &mut Writer<W>,
+        order: &[usize],
+        field: impl Fn(usize) -> Option<&'a [u8]>,
+    ) -> csv::Result<()> {
+        wtr.write_record(
+            order
+                .iter()
+                .map(|&ci| field(ci).unwrap_or_default())
+                .chain(self.derived_values(&field).iter().map(String::as_bytes)),
+        )
+    }
+
+    /// Source column indices in display order. Falls back to file order when the stored
+    /// order does not cover the current headers (e.g. the file changed on reload).
+    pub fn display_columns(&self) -> Vec<usize> {
+        if self.column_order.len() == self.headers.len() {
+            self.column_order.clone()
+        } else {
+            (0..self.headers.len()).collect()
+        }
+    }
+
+    /// The rows on the current page as a Markdown table, in display column order.
+    pub fn page_as_markdown(&self) -> String {
+        let order = self.display_columns();
+        let headers = self.output_headers(&order);
+        let derived: Vec<Vec<String>> = self
+            .preview_rows
+            .iter()
+            .map(|row| self.derived_values(|ci| row.get(ci).map(String::as_bytes)))
+            .collect();
+        let rows: Vec<Vec<&str>> = self
+            .preview_rows
+            .iter()
+            .zip(&derived)
+            .map(|(row, derived)| {
+                order
+                    .iter()
+                    .map(|&ci| row.get(ci).map_or("", String::as_str))
+                    .chain(derived.iter().map(String::as_str))
+                    .collect()
+            })
+            .collect();
+        util::markdown_table(&headers, &rows)
+    }
+
+    /// Whether the user moved any column away from its original position.
+    pub fn has_custom_column_order(&self) -> bool {
+        self.column_order.len() == self.headers.len()
+            && self
+                .column_order
+                .iter()
+                .enumerate()
+                .any(|(pos, &ci)| pos != ci)
+    }
+
+    /// Move the column shown at display position `from` to display position `to`.
+    pub fn move_column(&mut self, from: usize, to: usize) {
+        let mut order = self.display_columns();
+        if from >= order.len() || to >= order.len() || from == to {
+            return;
+        }
+        let ci = order.remove(from);
+        order.insert(to, ci);
+        self.column_order = order;
+    }
+
+    pub fn reset_column_order(&mut self) {
+        self.column_order = (0..self.headers.len()).collect();
+    }
+
+    /// What a cell of source column `col` links to, honoring the column's override.
+    /// A column forced on treats any non-empty value that isn't a URL as a path.
+    pub fn cell_link(&self, col: usize, cell: &str) -> Option<util::CellLink> {
+        match self.link_columns.get(&col) {
+            Some(false) => None,
+            Some(true) if !cell.trim().is_empty() => util::detect_cell_link(cell)
+                .or_else(|| Some(util::CellLink::Path(PathBuf::from(cell.trim())))),
+            Some(true) => None,
+            None => util::detect_cell_link(cell),
+        }
+    }
+}
+
+/// Rows written so far by an export worker, and the flag the UI sets to cancel it.
+#[derive(Clone, Default)]
+struct ExportProgress {
+    rows_written: Arc<AtomicU64>,
+    cancel:       Arc<AtomicBool>,
+}
+
+impl ExportProgress {
+    /// Count a written row, bailing out if the export was cancelled.
+    fn tick(&self) -> anyhow::Result<()> {
+        if self.cancel.load(Ordering::Relaxed) {
+            return Err(anyhow!("Export cancelled"));
+        }
+        self.rows_written.fetch_add(1, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+/// An export running on a worker thread.
+struct RunningExport {
+    job:        BackgroundJob<()>,
+    progress:   ExportProgress,
+    total_rows: u64,
+    started:    Instant,
+}
+
+/// An `$MFT` being parsed into a temporary CSV on a worker thread.
+struct RunningMftParse {
+    source:        String,
+    job:           BackgroundJob<(tempfile::TempDir, PathBuf)>,
+    entries_done:  Arc<AtomicU64>,
+    cancel:        Arc<AtomicBool>,
+    total_entries: u64, // estimated from the file size (1 KiB records)
+}
+
+/// A `.idx` sidecar being built for an open file on a worker thread.
+struct RunningIndexBuild {
+    file_path: String,
+    job:       BackgroundJob<()>,
+}
+
+/// A random sample of a file being drawn on a worker thread.
+struct RunningSample {
+    file_path: String,
+    job:       BackgroundJob<Vec<csv::ByteRecord>>,
+}
+
+/// Open files being stacked into one CSV on a worker thread.
+struct RunningMerge {
+    dest:  PathBuf,
+    files: usize,
+    job:   BackgroundJob<u64>,
+}
+
+/// Two open files being joined on a worker thread.
+struct RunningJoin {
+    dest: PathBuf,
+    job:  BackgroundJob<u64>,
+}
+
+/// The effective rows of a file being written to a new CSV and indexed on a worker thread.
+struct RunningMaterialize {
+    dest: PathBuf,
+    job:  BackgroundJob<u64>,
+}
+
+/// A regex's capture groups being extracted into new columns on a worker thread.
+struct RunningExtract {
+    dest: PathBuf,
+    job:  BackgroundJob<(Vec<String>, u64)>,
+}
+
+/// The data rows of an open file being counted on a worker thread.
+struct RunningCount {
+    file_path: String,
+    job:       BackgroundJob<u64>,
+}
+
+/// A file being checked for RFC 4180 conformance on a worker thread.
+struct RunningValidation {
+    file_path: String,
+    job:       BackgroundJob<core_util::CsvReport>,
+}
+
+/// The dialect of a file being sniffed for the file info popup on a worker thread.
+struct RunningSniff {
+    file_path: String,
+    job:       BackgroundJob<core_util::CsvDialect>,
+}
+
+/// Every distinct value of a filter column being counted on a worker thread.
+struct RunningDistinct {
+    file_path: String,
+    col:       usize,
+    job:       BackgroundJob<(BTreeMap<String, u64>, bool)>,
 }
 
+/// Drag payload carried by a header cell while it is being reordered (display position).
+#[derive(Debug, Clone, Copy)]
+struct HeaderDrag(usize);
+
 pub struct TableEditor {
-    pub files:                Vec<FilePreview>,
-    pub current_file:         usize,
-    pub toal_rows:            usize, // kept for backward-compat
-    pub rows_per_page:        usize,
-    pub page:                 usize, // 0-based
-    pub export_format:        ExportFormat,
-    pub export_only_filtered: bool,
-    pub export_status:        Option<String>,
-    pub pending_reload:       bool,
+    pub files:          Vec<FilePreview>,
+    pub current_file:   usize,
+    pub toal_rows:      usize, // kept for backward-compat
+    pub rows_per_page:  usize,
+    pub page:           usize, // 0-based
+    pub export_format:  ExportFormat,
+    pub export_scope:   ExportScope,
+    pub export_status:  Option<String>,
+    pub pending_reload: bool,
+    pub wrap_cells:     bool, // wrap long body cells instead of truncating them
+    pub show_blanks:    bool, // mark empty and whitespace-only cells
+    pub fit_to_window:  bool, // derive rows_per_page from the table's height
+    pub goto_row:       String, // "Go to row" input, 1-based data row number
+    pub goto_error:     Option<String>,
+    pub mft_error:      Option<String>, // last failed $MFT parse
+    pub index_error:    Option<String>, // last failed index build
+    pub sample_size:    usize,
+    pub sample_seed:    String, // empty = a new random sample each time
+    pub sample_error:   Option<String>,
+    pub merge_source:   bool, // add a `source_file` provenance column when merging
+    pub merge_status:   Option<String>,
+    pub join_left:      (usize, usize), // (file, key column)
+    pub join_right:     (usize, usize), // (file, key column)
+    pub join_kind:      core_util::JoinKind,
+    pub join_status:    Option<String>,
+    pub extract_column: usize, // source column the extract regex runs on
+    pub extract_regex:  String,
+    pub extract_status: Option<String>,
+    pub derive_name:    String,
+    pub derive_format:  String,
+    pub recent_files:   RecentFiles,
+    pub notify:         Notifier, // raises toasts for the app to show
+    export_job:         Option<RunningExport>,
+    mft_jobs:           Vec<RunningMftParse>,
+    index_jobs:         Vec<RunningIndexBuild>,
+    sample_job:         Option<RunningSample>,
+    merge_job:          Option<RunningMerge>,
+    join_job:           Option<RunningJoin>,
+    materialize_job:    Option<RunningMaterialize>,
+    extract_job:        Option<RunningExtract>,
+    count_jobs:         Vec<RunningCount>,
+    validation_jobs:    Vec<RunningValidation>,
+    sniff_jobs:         Vec<RunningSniff>,
+    distinct_jobs:      Vec<RunningDistinct>,
+    renaming_header:    Option<(usize, String)>, // source column being renamed and its edit buffer
+    scroll_to_cursor:   bool,                    // reveal the cursor row next frame
+    mft_csvs:           HashMap<String, PathBuf>, // parsed $MFT source -> its CSV
 }
 
-impl Default for TableEditor {
-    fn default() -> Self {
+impl TableEditor {
+    pub fn new(notifier: Notifier) -> Self {
         Self {
-            files:                Vec::new(),
-            current_file:         0,
-            toal_rows:            0,
-            rows_per_page:        50,
-            page:                 0,
-            export_format:        ExportFormat::Csv,
-            export_only_filtered: true,
-            export_status:        None,
-            pending_reload:       false,
+            files:            Vec::new(),
+            current_file:     0,
+            toal_rows:        0,
+            rows_per_page:    50,
+            page:             0,
+            export_format:    ExportFormat::Csv,
+            export_scope:     ExportScope::FilteredRows,
+            export_status:    None,
+            pending_reload:   false,
+            wrap_cells:       false,
+            show_blanks:      false,
+            fit_to_window:    false,
+            goto_row:         String::new(),
+            goto_error:       None,
+            mft_error:        None,
+            index_error:      None,
+            sample_size:      100,
+            sample_seed:      String::new(),
+            sample_error:     None,
+            merge_source:     true,
+            merge_status:     None,
+            join_left:        (0, 0),
+            join_right:       (1, 0),
+            join_kind:        core_util::JoinKind::Inner,
+            join_status:      None,
+            extract_column:   0,
+            extract_regex:    String::new(),
+            extract_status:   None,
+            derive_name:      String::new(),
+            derive_format:    String::new(),
+            recent_files:     RecentFiles::load(),
+            notify:           notifier,
+            export_job:       None,
+            mft_jobs:         Vec::new(),
+            index_jobs:       Vec::new(),
+            sample_job:       None,
+            merge_job:        None,
+            join_job:         None,
+            materialize_job:  None,
+            extract_job:      None,
+            count_jobs:       Vec::new(),
+            validation_jobs:  Vec::new(),
+            sniff_jobs:       Vec::new(),
+            distinct_jobs:    Vec::new(),
+            renaming_header:  None,
+            scroll_to_cursor: false,
+            mft_csvs:         HashMap::new(),
         }
     }
 }
@@ -140,9 +692,18 @@ impl TableEditor {
     // UI constants (single source of truth)
     // -------------------------
     const DEFAULT_COL_WIDTH: f32 = 180.0;
+    // Delimiter choices offered per file; None lets Config detect it
+    const DELIMITER_CHOICES: [(&'static str, Option<u8>); 5] = [
+        ("Auto", None),
+        ("Comma", Some(b',')),
+        ("Tab", Some(b'\t')),
+        ("Semicolon", Some(b';')),
+        ("Pipe", Some(b'|')),
+    ];
     const FILTER_CONTROLS_WIDTH: f32 = 56.0;
     const HEADER_FONT_SIZE: f32 = 12.0;
     const HEADER_HEIGHT: f32 = 22.0;
+    const ROWS_PER_PAGE_PRESETS: [usize; 5] = [25, 50, 100, 500, 1000];
     const ROW_HEIGHT: f32 = 20.0;
 
     // -------------------------
@@ -170,6 +731,73 @@ impl TableEditor {
         }
     }
 
+    /// Jump to a 1-based data row of the active file (file order, clamped to the row
+    /// count): open the page that shows it in the current sort and put the cursor on it.
+    pub fn go_to_row(&mut self, row: u64) -> Result<(), String> {
+        let rows_per_page = self.rows_per_page.max(1);
+        let Some(fp) = self.current_fp_mut() else {
+            return Err("No file open".to_string());
+        };
+        let Some(total) = fp.total_rows else {
+            return Err("Still counting rows, try again in a moment".to_string());
+        };
+        if total == 0 {
+            return Err("The file has no rows".to_string());
+        }
+        let target = row.clamp(1, total) - 1;
+        let pos = match Self::compose_indices(fp) {
+            Some(indices) => indices
+                .iter()
+                .position(|&i| i == target)
+                .ok_or_else(|| format!("Row {} is hidden by the active filters", target + 1))?,
+            None => target as usize,
+        };
+        let page = pos / rows_per_page;
+        fp.sample_rows = None;
+        fp.page = page;
+        fp.cursor_row = Some(pos % rows_per_page);
+        self.page = page;
+        self.scroll_to_cursor = true;
+        self.reload_current_preview_page();
+        Ok(())
+    }
+
+    /// Widget id of the inline rename editor for a source column.
+    fn rename_id(col: usize) -> egui::Id {
+        egui::Id::new(("rename_header", col))
+    }
+
+    /// Open the inline editor on a header, prefilled with its current name.
+    fn start_header_rename(&mut self, ctx: &egui::Context, col: usize, current: &str) {
+        self.renaming_header = Some((col, current.to_string()));
+        ctx.memory_mut(|m| m.request_focus(Self::rename_id(col)));
+    }
+
+    /// Count the data rows of a file for paging. Uses a valid index when present, then the
+    /// polars memory-mapped count, and only iterates byte records when neither applies
+    /// (snappy files, no-headers configs, or polars reporting zero rows). Unlike
+    /// `core_util::count_rows` nothing is cached globally, so every opened file gets its own count.
+    fn count_rows_for_path(cfg: &Config) -> anyhow::Result<u64> {
+        if let Some(idx) = cfg.indexed().unwrap_or(None) {
+            return Ok(idx.count());
+        }
+        if !cfg.no_headers
+            && !cfg.is_snappy()
+            && let Ok(cnt) = count::polars_count_input(cfg, false)
+            && cnt > 0
+        {
+            return Ok(cnt);
+        }
+
+        let mut rdr = cfg.clone().skip_format_check(true).reader()?;
+        let mut record = csv::ByteRecord::new();
+        let mut cnt = 0_u64;
+        while rdr.read_byte_record(&mut record)? {
+            cnt += 1;
+        }
+        Ok(cnt)
+    }
+
     /// Clamp a page index to valid bounds for a given `total_rows` and `rows_per_page`.
     #[inline]
     fn clamp_page(page: usize, rows_per_page: usize, total_rows: usize) -> usize {
@@ -184,18 +812,172 @@ impl TableEditor {
         }
     }
 
+    /// Rows visible through the current filters, falling back to the file's row count.
+    fn current_visible_rows(&self) -> Option<usize> {
+        let fp = self.current_fp()?;
+        let filtered = fp.filtered_indices.as_ref().map(|v| v.len());
+        let base_total = match fp.total_rows {
+            Some(total) => total as usize,
+            // still counting: the rows up to this page, and one more page if this one is full
+            None => {
+                let shown = fp.preview_rows.len();
+                fp.page * self.rows_per_page + shown + usize::from(shown >= self.rows_per_page)
+            },
+        };
+        Some(filtered.unwrap_or(base_total))
+    }
+
+    /// Whether the pager knows how many rows there are, i.e. the active file is not
+    /// being counted (filters count their matches themselves).
+    fn rows_known(&self) -> bool {
+        self.current_fp()
+            .is_some_and(|fp| fp.total_rows.is_some() || fp.filtered_indices.is_some())
+    }
+
+    /// In fit-to-window mode, size pages to the rows that fit in `height` (header and
+    /// horizontal scrollbar excluded), keeping the first visible row on screen.
+    fn fit_rows_to_height(&mut self, height: f32) {
+        const SCROLLBAR_ALLOWANCE: f32 = 14.0;
+        let body = height - Self::HEADER_HEIGHT - SCROLLBAR_ALLOWANCE;
+        let rows = ((body / Self::ROW_HEIGHT).floor().max(0.0) as usize).clamp(5, 5000);
+        if rows == self.rows_per_page {
+            return;
+        }
+        let old = self.rows_per_page;
+        self.rows_per_page = rows;
+        if let Some(fp) = self.current_fp_mut() {
+            fp.page = fp.page * old / rows;
+        }
+        self.reload_current_preview_page();
+    }
+
+    /// One-line summary of the active file under the table: path, row counts and paging.
+    pub fn show_status_bar(&self, ui: &mut Ui) {
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        let visible = self.current_visible_rows().unwrap_or(0);
+        let total_pages = if self.rows_per_page == 0 {
+            0
+        } else {
+            visible.div_ceil(self.rows_per_page)
+        };
+        let mut parts = vec![format!("📄 {}", fp.file_path)];
+        match fp.total_rows {
+            Some(total) => parts.push(format!("{total} rows")),
+            None => parts.push("counting rows…".to_string()),
+        }
+        if let Some(filtered) = &fp.filtered_indices {
+            parts.push(format!("{} filtered", filtered.len()));
+        }
+        if let Some(sample) = &fp.sample_rows {
+            parts.push(format!("sample of {}", sample.len()));
+        } else {
+            parts.push(format!("{} rows/page", self.rows_per_page));
+            parts.push(format!(
+                "page {}/{}",
+                if total_pages == 0 { 0 } else { fp.page + 1 },
+                total_pages.max(1)
+            ));
+        }
+        ui.add_space(2.0);
+        ui.add(
+            egui::Label::new(
+                RichText::new(parts.join("  •  "))
+                    .size(11.0)
+                    .color(ui.visuals().weak_text_color()),
+            )
+            .truncate(),
+        )
+        .on_hover_text(&fp.file_path);
+    }
+
+    /// Keyboard navigation for the preview: PageUp/PageDown flip pages, Home/End jump to the
+    /// first/last page and the arrow keys move the row cursor (crossing page boundaries).
+    /// Keys are left alone while a text field (filter search, regex, SQL editor) has focus.
+    pub fn handle_keyboard_navigation(&mut self, ctx: &egui::Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        let Some(total_rows) = self.current_visible_rows() else {
+            return;
+        };
+        let rows_per_page = self.rows_per_page;
+        let total_pages = if rows_per_page == 0 {
+            0
+        } else {
+            total_rows.div_ceil(rows_per_page)
+        };
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        let (page, page_len, cursor) = (fp.page, fp.preview_rows.len(), fp.cursor_row);
+
+        let (page_up, page_down, home, end, up, down) = ctx.input_mut(|i| {
+            (
+                i.consume_key(egui::Modifiers::NONE, egui::Key::PageUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::PageDown),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::Home),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::End),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowUp),
+                i.consume_key(egui::Modifiers::NONE, egui::Key::ArrowDown),
+            )
+        });
+
+        let last_page = total_pages.saturating_sub(1);
+        let mut new_page = page;
+        let mut new_cursor = cursor;
+        if page_up {
+            new_page = page.saturating_sub(1);
+        } else if page_down {
+            new_page = (page + 1).min(last_page);
+        } else if home {
+            new_page = 0;
+        } else if end && self.rows_known() {
+            new_page = last_page;
+        } else if up {
+            match cursor {
+                Some(0) if page > 0 => {
+                    new_page = page - 1;
+                    new_cursor = Some(rows_per_page.saturating_sub(1));
+                },
+                Some(c) => new_cursor = Some(c.saturating_sub(1)),
+                None if page_len > 0 => new_cursor = Some(page_len - 1),
+                None => {},
+            }
+        } else if down {
+            match cursor {
+                Some(c) if c + 1 >= page_len && page < last_page => {
+                    new_page = page + 1;
+                    new_cursor = Some(0);
+                },
+                Some(c) => new_cursor = Some((c + 1).min(page_len.saturating_sub(1))),
+                None if page_len > 0 => new_cursor = Some(0),
+                None => {},
+            }
+        }
+
+        if let Some(fp) = self.current_fp_mut() {
+            fp.cursor_row = new_cursor;
+            fp.page = new_page;
+        }
+        if new_page != page {
+            self.page = new_page;
+            self.reload_current_preview_page();
+        }
+    }
+
     /// Heuristically infer sort preferences (numeric/natural/ignore_case) for a column
     /// by sampling up to 256 data rows. Numeric wins if >=90% are parseable as numbers.
     /// Otherwise, we prefer natural when ~40%+ contain digits; we default to case-insensitive.
-    fn infer_sort_prefs_for_col(&self, path: &str, col: usize) -> (bool, bool, bool) {
-        let cfg = Config::builder().path(path).build();
+    fn infer_sort_prefs_for_col(&self, cfg: &Config, col: usize) -> (bool, bool, bool) {
         let mut numeric_hits = 0usize;
         let mut has_digit_hits = 0usize;
         let mut sample = 0usize;
         if let Ok(mut rdr) = cfg.reader() {
             // ensure header consumed so `records()` yields data rows
             let _ = rdr.headers();
-            for rec_res in rdr.records().take(256) {
+            for rec_res in cfg.records(&mut rdr).take(256) {
                 if let Ok(rec) = rec_res {
                     sample += 1;
                     let s = rec.get(col).unwrap_or("").trim();
@@ -220,18 +1002,34 @@ impl TableEditor {
     /// Render the preview table with a header that stays pinned vertically
     /// while sharing the same horizontal scroll as the body.
     pub fn show_preview_table(&mut self, ui: &mut Ui) {
-        let (headers, file_id) = match self.current_fp() {
+        if self.fit_to_window {
+            self.fit_rows_to_height(ui.available_height());
+        }
+        let (headers, originals, order, file_id, derived) = match self.current_fp() {
             Some(fp) => (
-                fp.headers
-                    .iter()
-                    .map(|u| u.as_str().to_owned())
+                (0..fp.headers.len())
+                    .map(|ci| fp.header_name(ci).to_owned())
                     .collect::<Vec<String>>(),
+                fp.headers.clone(),
+                fp.display_columns(),
                 fp.file_path.clone(),
+                fp.derived_columns
+                    .iter()
+                    .map(|d| (d.name.clone(), d.template.clone()))
+                    .collect::<Vec<(String, String)>>(),
             ),
             None => return,
         };
+        self.handle_keyboard_navigation(ui.ctx());
         let col_width: f32 = Self::DEFAULT_COL_WIDTH;
-        let ncols = headers.len().max(1);
+        // derived columns trail the file's own columns
+        let ncols = (headers.len() + derived.len()).max(1);
+        // (from, to) display positions of a header dropped this frame
+        let mut column_move: Option<(usize, usize)> = None;
+        // (source column, new name) of a header rename finished this frame; None = cancelled
+        let mut header_rename: Option<(usize, Option<String>)> = None;
+        // position of a derived column removed this frame
+        let mut derived_removal: Option<usize> = None;
 
         // One table with header + scrollable body so column widths stay in sync
         ScrollArea::horizontal()
@@ -243,17 +1041,24 @@ impl TableEditor {
                     let mut tbl = TableBuilder::new(ui)
                         .id_salt(("dt_preview_shared", file_id.as_str()))
                         .striped(true)
+                        .sense(egui::Sense::click())
                         .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
                         .resizable(true)
                         .min_scrolled_height(0.0) // allow small tables
                         .max_scroll_height(max_h); // fill remaining vertical space
+                    if std::mem::take(&mut self.scroll_to_cursor)
+                        && let Some(c) = self.current_fp().and_then(|fp| fp.cursor_row)
+                    {
+                        tbl = tbl.scroll_to_row(c, Some(Align::Center));
+                    }
                     for _ in 0..ncols {
                         tbl = tbl.column(Column::initial(col_width).clip(true));
                     }
 
                     // Header (pinned)
                     let table = tbl.header(Self::HEADER_HEIGHT, |mut header| {
-                        for (ci, h) in headers.iter().enumerate() {
+                        for (pos, &ci) in order.iter().enumerate() {
+                            let h = &headers[ci];
                             header.col(|ui| {
                                 // Allocate a single row area and split into: [label        |   controls]
                                 let avail = ui.available_width().max(0.0);
@@ -263,15 +1068,95 @@ impl TableEditor {
                                     egui::vec2(avail, 20.0),
                                     egui::Layout::left_to_right(egui::Align::Center),
                                     |ui| {
-                                        // --- Left: header label (clipped/truncated)
-                                        let header_label = egui::Label::new(
-                                            RichText::new(h.as_str())
+                                        let renaming = self
+                                            .renaming_header
+                                            .as_mut()
+                                            .filter(|(col, _)| *col == ci);
+                                        if let Some((_, buf)) = renaming {
+                                            // --- Left: inline rename editor (Enter commits, Esc cancels)
+                                            let edit = ui.add_sized(
+                                                egui::vec2(label_w, 20.0),
+                                                TextEdit::singleline(buf)
+                                                    .id(Self::rename_id(ci))
+                                                    .font(egui::FontId::proportional(Self::HEADER_FONT_SIZE)),
+                                            );
+                                            if edit.lost_focus() {
+                                                let cancelled = ui.input(|i| i.key_pressed(egui::Key::Escape));
+                                                header_rename = Some((ci, (!cancelled).then(|| buf.clone())));
+                                            }
+                                        } else {
+                                            // --- Left: header label (clipped/truncated), draggable to reorder
+                                            let renamed = *h != originals[ci];
+                                            let mut header_text = RichText::new(h.as_str())
                                                 .strong()
                                                 .size(Self::HEADER_FONT_SIZE)
-                                                .color(Color32::WHITE),
-                                        )
-                                        .truncate();
-                                        ui.add_sized(egui::vec2(label_w, 20.0), header_label);
+                                                .color(ui.visuals().strong_text_color());
+                                            if renamed {
+                                                header_text = header_text.italics();
+                                            }
+                                            let header_label = egui::Label::new(header_text)
+                                                .truncate()
+                                                .sense(egui::Sense::click_and_drag());
+                                            let mut label_resp = ui
+                                                .add_sized(egui::vec2(label_w, 20.0), header_label)
+                                                .on_hover_cursor(egui::CursorIcon::Grab);
+                                            if renamed {
+                                                label_resp = label_resp.on_hover_text(format!("Original name: {}", originals[ci]));
+                                            }
+                                            if label_resp.double_clicked() {
+                                                self.start_header_rename(ui.ctx(), ci, h);
+                                            }
+                                            label_resp.dnd_set_drag_payload(HeaderDrag(pos));
+                                            if label_resp.dnd_hover_payload::<HeaderDrag>().is_some() {
+                                                let r = label_resp.rect;
+                                                ui.painter().line_segment(
+                                                    [r.left_top(), r.left_bottom()],
+                                                    Stroke::new(2.0, ui.visuals().selection.stroke.color),
+                                                );
+                                            }
+                                            if let Some(drag) = label_resp.dnd_release_payload::<HeaderDrag>() {
+                                                column_move = Some((drag.0, pos));
+                                            }
+                                            label_resp.context_menu(|ui| {
+                                                if ui.button("✏ Rename…").clicked() {
+                                                    self.start_header_rename(ui.ctx(), ci, h);
+                                                    ui.close();
+                                                }
+                                                if renamed && ui.button("Restore original name").clicked() {
+                                                    header_rename = Some((ci, Some(String::new())));
+                                                    ui.close();
+                                                }
+                                                ui.separator();
+                                                ui.label(RichText::new("Treat as link").strong());
+                                                let mut mode = self
+                                                    .current_fp()
+                                                    .and_then(|fp| fp.link_columns.get(&ci).copied());
+                                                let mut changed = ui.radio_value(&mut mode, None, "Auto-detect").changed();
+                                                changed |= ui.radio_value(&mut mode, Some(true), "Always").changed();
+                                                changed |= ui.radio_value(&mut mode, Some(false), "Never").changed();
+                                                if changed && let Some(fp) = self.current_fp_mut() {
+                                                    match mode {
+                                                        Some(on) => fp.link_columns.insert(ci, on),
+                                                        None => fp.link_columns.remove(&ci),
+                                                    };
+                                                }
+                                                ui.separator();
+                                                let mut as_json =
+                                                    self.current_fp().is_some_and(|fp| fp.json_columns.contains(&ci));
+                                                if ui
+                                                    .checkbox(&mut as_json, "Parse as JSON on export")
+                                                    .on_hover_text("Emit object/array values as nested JSON instead of strings")
+                                                    .changed()
+                                                    && let Some(fp) = self.current_fp_mut()
+                                                {
+                                                    if as_json {
+                                                        fp.json_columns.insert(ci);
+                                                    } else {
+                                                        fp.json_columns.remove(&ci);
+                                                    }
+                                                }
+                                            });
+                                        }
 
                                         // --- Right: controls (Filter ▾ button + ▲ ▼ sort buttons)
                                         ui.scope(|ui| {
@@ -286,8 +1171,7 @@ impl TableEditor {
                                                 let active = self
                                                     .current_fp()
                                                     .and_then(|fp| fp.filters.get(ci))
-                                                    .map(|f| !f.selected.is_empty())
-                                                    .unwrap_or(false);
+                                                    .is_some_and(ColumnFilter::is_active);
                                                 let btn_resp = util::filter_icon_button(ui, active).on_hover_text("Filter");
                                                 let popup_id = ui.make_persistent_id(("col_filter_popup", ci));
                                                 if btn_resp.clicked() {
@@ -314,6 +1198,7 @@ impl TableEditor {
                                                 // Filter popup anchored to `btn_resp`
                                                 let mut apply_now = false;
                                                 let mut clear_now = false;
+                                                let mut load_all_col = None;
                                                 Popup::from_response(&btn_resp)
                                                     .layout(Layout::top_down_justified(Align::LEFT))
                                                     .open_memory(None)
@@ -324,6 +1209,7 @@ impl TableEditor {
                                                     .show(|ui: &mut Ui| {
                                                         ui.set_min_width(ui.available_width());
                                                         self.ensure_distinct_for_col(ci);
+                                                        self.ensure_numeric_for_col(ci);
                                                         if let Some(fp) = self.current_fp_mut() {
                                                             let f = &mut fp.filters[ci];
 
@@ -345,13 +1231,57 @@ impl TableEditor {
                                                                 if let Some(err) = &f.regex_error {
                                                                     ui.label(
                                                                         RichText::new(format!("⚠ Invalid regex: {}", err))
-                                                                            .color(Color32::from_rgb(220, 90, 90))
+                                                                            .color(ui.visuals().error_fg_color)
                                                                             .size(11.0),
                                                                     );
                                                                 }
                                                                 ui.add_space(4.0);
                                                             }
 
+                                                            if f.is_numeric == Some(true) {
+                                                                let mut range_changed =
+                                                                    ui.checkbox(&mut f.use_range, "Numeric range").changed();
+                                                                if f.use_range {
+                                                                    let min_op = if f.min_inclusive { "≥" } else { ">" };
+                                                                    let max_op = if f.max_inclusive { "≤" } else { "<" };
+                                                                    ui.horizontal(|ui| {
+                                                                        range_changed |= ui
+                                                                            .toggle_value(&mut f.min_inclusive, min_op)
+                                                                            .on_hover_text("Include the minimum")
+                                                                            .changed();
+                                                                        range_changed |= ui
+                                                                            .add(
+                                                                                TextEdit::singleline(&mut f.range_min)
+                                                                                    .hint_text("min")
+                                                                                    .desired_width(70.0),
+                                                                            )
+                                                                            .changed();
+                                                                        range_changed |= ui
+                                                                            .toggle_value(&mut f.max_inclusive, max_op)
+                                                                            .on_hover_text("Include the maximum")
+                                                                            .changed();
+                                                                        range_changed |= ui
+                                                                            .add(
+                                                                                TextEdit::singleline(&mut f.range_max)
+                                                                                    .hint_text("max")
+                                                                                    .desired_width(70.0),
+                                                                            )
+                                                                            .changed();
+                                                                    });
+                                                                    if let Some(err) = f.range_error() {
+                                                                        ui.label(
+                                                                            RichText::new(format!("⚠ {err}"))
+                                                                                .color(ui.visuals().error_fg_color)
+                                                                                .size(11.0),
+                                                                        );
+                                                                    }
+                                                                }
+                                                                if range_changed && f.range_error().is_none() {
+                                                                    apply_now = true;
+                                                                }
+                                                                ui.add_space(4.0);
+                                                            }
+
                                                             let mut buf = f.search.to_string();
                                                             if ui
                                                                 .add(TextEdit::singleline(&mut buf).hint_text("Search values..."))
@@ -361,6 +1291,41 @@ impl TableEditor {
                                                             }
                                                             ui.add_space(4.0);
 
+                                                            if let Some(vc) = &f.value_counts {
+                                                                let n_values = f.distinct_cache.as_ref().map_or(0, Vec::len);
+                                                                let loading = f.all_distinct && !vc.complete;
+                                                                let mut load_all = false;
+                                                                ui.horizontal(|ui| {
+                                                                    if vc.complete {
+                                                                        ui.label(
+                                                                            RichText::new(format!("✔ Complete: {n_values} values"))
+                                                                                .size(11.0),
+                                                                        );
+                                                                    } else {
+                                                                        ui.label(
+                                                                            RichText::new(format!("⚠ Sampled: first {n_values} values"))
+                                                                                .color(ui.visuals().warn_fg_color)
+                                                                                .size(11.0),
+                                                                        );
+                                                                    }
+                                                                    if loading {
+                                                                        ui.spinner();
+                                                                    } else if !vc.complete {
+                                                                        load_all = ui
+                                                                            .small_button("Load all")
+                                                                            .on_hover_text("Scan the whole column (may be slow on large files)")
+                                                                            .clicked();
+                                                                    }
+                                                                });
+                                                                Self::show_value_counts(ui, ci, vc);
+                                                                ui.add_space(4.0);
+                                                                if load_all {
+                                                                    // the sample stays listed until poll_distincts swaps in every value
+                                                                    f.all_distinct = true;
+                                                                    load_all_col = Some(ci);
+                                                                }
+                                                            }
+
                                                             let values_slice: &[String] = f
                                                                 .distinct_cache.as_deref()
                                                                 .unwrap_or(&[]);
@@ -415,8 +1380,36 @@ impl TableEditor {
                                                                     }
                                                                     apply_now = true;
                                                                 }
+                                                                if ui
+                                                                    .button("Invert")
+                                                                    .on_hover_text("Toggle every value's selection")
+                                                                    .clicked()
+                                                                {
+                                                                    if let Some(all) = &f.distinct_cache {
+                                                                        let current: HashSet<&String> = f.selected.iter().collect();
+                                                                        f.selected =
+                                                                            all.iter().filter(|v| !current.contains(v)).cloned().collect();
+                                                                    }
+                                                                    apply_now = true;
+                                                                }
+                                                                if ui
+                                                                    .add_enabled(!f.search.is_empty(), egui::Button::new("Select filtered"))
+                                                                    .on_hover_text("Select only the values matching the search")
+                                                                    .clicked()
+                                                                {
+                                                                    if let Some(all) = &f.distinct_cache {
+                                                                        let search_lower = f.search.to_ascii_lowercase();
+                                                                        f.selected = all
+                                                                            .iter()
+                                                                            .filter(|v| v.to_ascii_lowercase().contains(&search_lower))
+                                                                            .cloned()
+                                                                            .collect();
+                                                                    }
+                                                                    apply_now = true;
+                                                                }
                                                                 if ui.button("Clear").clicked() {
                                                                     f.selected.clear();
+                                                                    f.use_range = false;
                                                                     clear_now = true;
                                                                 }
                                                                 if ui.button("Apply").clicked() {
@@ -426,6 +1419,9 @@ impl TableEditor {
                                                         }
                                                     });
 
+                                                if let Some(col) = load_all_col {
+                                                    self.start_distinct(col);
+                                                }
                                                 if apply_now || clear_now {
                                                     self.apply_filters_for_current_file();
                                                     self.reload_current_preview_page();
@@ -437,29 +1433,190 @@ impl TableEditor {
                                 );
                             });
                         }
+                        for (i, (name, template)) in derived.iter().enumerate() {
+                            header.col(|ui| {
+                                let label = egui::Label::new(
+                                    RichText::new(format!("ƒ {name}"))
+                                        .strong()
+                                        .italics()
+                                        .size(Self::HEADER_FONT_SIZE)
+                                        .color(ui.visuals().strong_text_color()),
+                                )
+                                .truncate()
+                                .sense(egui::Sense::click());
+                                let resp = ui
+                                    .add_sized(egui::vec2(ui.available_width(), 20.0), label)
+                                    .on_hover_text(format!("Derived from {template}"));
+                                resp.context_menu(|ui| {
+                                    if ui.button("🗑 Remove column").clicked() {
+                                        derived_removal = Some(i);
+                                        ui.close();
+                                    }
+                                });
+                            });
+                        }
                     });
 
-                    // Body (scrolls under the pinned header; widths stay in sync with header)
+                    if let Some(i) = derived_removal
+                        && let Some(fp) = self.current_fp_mut()
+                    {
+                        fp.derived_columns.remove(i);
+                    }
+
+                    if let Some((from, to)) = column_move
+                        && let Some(fp) = self.current_fp_mut()
+                    {
+                        fp.move_column(from, to);
+                    }
+
+                    if let Some((ci, name)) = header_rename {
+                        self.renaming_header = None;
+                        if let Some(name) = name
+                            && let Some(fp) = self.current_fp_mut()
+                        {
+                            fp.rename_header(ci, &name);
+                        }
+                    }
+
+                    // Body (scrolls under the pinned header; widths stay in sync with header)
                     let row_h = Self::ROW_HEIGHT;
-                    table.body(|body| {
+                    let wrap = self.wrap_cells;
+                    let show_blanks = self.show_blanks;
+                    let mut clicked_row: Option<usize> = None;
+                    table.body(|mut body| {
                         if let Some(fp_ref) = self.current_fp() {
                             let rows_ref = &fp_ref.preview_rows;
-                            body.rows(row_h, rows_ref.len(), |mut row| {
-                                let r = &rows_ref[row.index()];
-                                for ci in 0..ncols {
+                            let cursor = fp_ref.cursor_row;
+                            let add_row = |mut row: egui_extras::TableRow<'_, '_>| {
+                                let ri = row.index();
+                                let r = &rows_ref[ri];
+                                row.set_selected(cursor == Some(ri));
+                                for &ci in order.iter() {
                                     row.col(|ui| {
                                         let txt = r.get(ci).map(|s| s.as_str()).unwrap_or("");
-                                        let label = egui::Label::new(RichText::new(txt).size(Self::CELL_FONT_SIZE))
-                                            .truncate();
-                                        ui.add_sized(
-                                            egui::vec2(ui.available_width(), row_h - 2.0),
-                                            label,
-                                        );
+                                        let blank = if show_blanks {
+                                            util::blank_cell_marker(txt)
+                                        } else {
+                                            None
+                                        };
+                                        let link = fp_ref.cell_link(ci, txt);
+                                        let mut text = match &blank {
+                                            Some((marker, _)) => RichText::new(marker.as_str())
+                                                .size(Self::CELL_FONT_SIZE)
+                                                .color(ui.visuals().weak_text_color()),
+                                            None => RichText::new(txt).size(Self::CELL_FONT_SIZE),
+                                        };
+                                        if link.is_some() {
+                                            text = text.color(ui.visuals().hyperlink_color).underline();
+                                        }
+                                        let mut label = egui::Label::new(text);
+                                        if link.is_some() {
+                                            label = label.sense(egui::Sense::click());
+                                        }
+                                        let resp = if wrap {
+                                            ui.add(label.wrap())
+                                        } else {
+                                            ui.add_sized(
+                                                egui::vec2(ui.available_width(), row_h - 2.0),
+                                                label.truncate(),
+                                            )
+                                        };
+                                        let resp = match blank {
+                                            Some((_, hint)) => resp.on_hover_text(hint),
+                                            None => resp,
+                                        };
+                                        match link {
+                                            Some(util::CellLink::Url(url)) => {
+                                                let resp = resp
+                                                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                    .on_hover_text("Open link");
+                                                if resp.clicked() {
+                                                    ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                                                }
+                                            },
+                                            Some(util::CellLink::Path(path)) => {
+                                                let resp = resp
+                                                    .on_hover_cursor(egui::CursorIcon::PointingHand)
+                                                    .on_hover_text("Show in file manager");
+                                                if resp.clicked()
+                                                    && let Err(e) = util::reveal_in_file_manager(&path)
+                                                {
+                                                    tracing::warn!("failed to reveal {}: {e}", path.display());
+                                                }
+                                            },
+                                            None => {},
+                                        }
                                     });
                                 }
-                            });
+                                for value in fp_ref.derived_values(|ci| r.get(ci).map(String::as_bytes)) {
+                                    row.col(|ui| {
+                                        let blank = if show_blanks {
+                                            util::blank_cell_marker(&value)
+                                        } else {
+                                            None
+                                        };
+                                        let text = match &blank {
+                                            Some((marker, _)) => RichText::new(marker.as_str())
+                                                .size(Self::CELL_FONT_SIZE)
+                                                .color(ui.visuals().weak_text_color()),
+                                            None => RichText::new(value).size(Self::CELL_FONT_SIZE),
+                                        };
+                                        let label = egui::Label::new(text);
+                                        let resp = if wrap {
+                                            ui.add(label.wrap())
+                                        } else {
+                                            ui.add_sized(
+                                                egui::vec2(ui.available_width(), row_h - 2.0),
+                                                label.truncate(),
+                                            )
+                                        };
+                                        if let Some((_, hint)) = blank {
+                                            resp.on_hover_text(hint);
+                                        }
+                                    });
+                                }
+                                if row.response().clicked() {
+                                    clicked_row = Some(ri);
+                                }
+                            };
+                            if wrap {
+                                // Each row is as tall as its tallest wrapped cell
+                                let widths = body.widths().to_vec();
+                                let font = egui::FontId::proportional(Self::CELL_FONT_SIZE);
+                                let heights: Vec<f32> = rows_ref
+                                    .iter()
+                                    .map(|r| {
+                                        let derived_values =
+                                            fp_ref.derived_values(|ci| r.get(ci).map(String::as_bytes));
+                                        order
+                                            .iter()
+                                            .map(|&ci| r.get(ci).cloned().unwrap_or_default())
+                                            .chain(derived_values)
+                                            .enumerate()
+                                            .map(|(pos, txt)| {
+                                                let txt = match show_blanks.then(|| util::blank_cell_marker(&txt)) {
+                                                    Some(Some((marker, _))) => marker,
+                                                    _ => txt,
+                                                };
+                                                let width = widths.get(pos).copied().unwrap_or(col_width);
+                                                body.ui_mut().fonts(|f| {
+                                                    f.layout(txt, font.clone(), Color32::PLACEHOLDER, width).size().y
+                                                }) + 4.0
+                                            })
+                                            .fold(row_h, f32::max)
+                                    })
+                                    .collect();
+                                body.heterogeneous_rows(heights.into_iter(), add_row);
+                            } else {
+                                body.rows(row_h, rows_ref.len(), add_row);
+                            }
                         }
                     });
+                    if let Some(ri) = clicked_row
+                        && let Some(fp) = self.current_fp_mut()
+                    {
+                        fp.cursor_row = Some(ri);
+                    }
 
                     // If something else scheduled a reload, run it even while the popup is open.
                     if self.pending_reload {
@@ -477,9 +1634,15 @@ impl TableEditor {
         let Some(fp) = self.current_fp_mut() else {
             return;
         };
+        // A drawn sample replaces paging until it is dismissed
+        if let Some(rows) = &fp.sample_rows {
+            fp.preview_rows = rows.clone();
+            fp.cursor_row = fp.cursor_row.filter(|&c| c < rows.len());
+            return;
+        }
+
         let mut new_page = fp.page;
-        let path_str = fp.file_path.to_string();
-        let cfg = Config::builder().path(&path_str).build();
+        let cfg = fp.config();
 
         // Prepare rows buffer up front
         fp.preview_rows.clear();
@@ -487,6 +1650,7 @@ impl TableEditor {
 
         // Track legacy total rows update for self after we drop the fp borrow.
         let mut new_total_rows: Option<usize> = None;
+        let mut needs_count = false;
 
         // Prefer using the qsv index for fast paging; fallback to streaming reader
         if let Ok(Some(mut idx)) = cfg.indexed() {
@@ -502,22 +1666,10 @@ impl TableEditor {
                 }
             }
 
-            // count rows once per file (if not already counted)
-            if fp.total_rows.is_none() {
-                let total = match count::run(count::Args::builder().arg_input(&path_str).build()) {
-                    Ok((cnt, _)) => cnt,
-                    Err(e) => {
-                        fp.load_error = Some(format!("Count error: {e}"));
-                        0
-                    },
-                };
-                fp.total_rows = Some(total);
-                new_total_rows = Some(total as usize); // update self after dropping fp
-                // Clamp page within new total
-                new_page = Self::clamp_page(new_page, rows_per_page, total as usize);
-            } else {
-                new_total_rows = Some(fp.total_rows.unwrap_or(0) as usize);
-            }
+            // the index knows the row count, no need to count in the background
+            let total = *fp.total_rows.get_or_insert_with(|| idx.count());
+            new_total_rows = Some(total as usize); // update self after dropping fp
+            new_page = Self::clamp_page(new_page, rows_per_page, total as usize);
 
             // Compose effective indices from overlay sort and filters (if any).
             let eff_slice_vec = Self::compose_indices(fp);
@@ -528,37 +1680,7 @@ impl TableEditor {
 
                 let start_idx = new_page.saturating_mul(rows_per_page);
                 let end_idx = (start_idx + rows_per_page).min(total);
-                let slice = &slice_all[start_idx..end_idx];
-
-                // seek in contiguous chunks to minimize random seeks
-                let mut i = 0usize;
-                while i < slice.len() {
-                    let base = slice[i];
-                    let mut len = 1usize;
-                    while i + len < slice.len() && slice[i + len] == base + len as u64 {
-                        len += 1;
-                    }
-                    if let Err(e) = idx.seek(base) {
-                        fp.load_error = Some(format!("Index seek error: {e}"));
-                        break;
-                    }
-                    for rec_res in idx.byte_records().take(len) {
-                        match rec_res {
-                            Ok(brec) => {
-                                let mut row = Vec::with_capacity(fp.headers.len().max(brec.len()));
-                                row.extend(
-                                    brec.iter().map(|b| String::from_utf8_lossy(b).to_string()),
-                                );
-                                fp.preview_rows.push(row);
-                            },
-                            Err(e) => {
-                                fp.load_error = Some(format!("Row read error: {e}"));
-                                break;
-                            },
-                        }
-                    }
-                    i += len;
-                }
+                Self::push_preview_rows(fp, &cfg, &slice_all[start_idx..end_idx]);
             } else {
                 // Unsorted & unfiltered fast page-seek via library slice helper
                 let start = new_page.saturating_mul(rows_per_page);
@@ -588,22 +1710,11 @@ impl TableEditor {
                         let _ = rdr.headers();
                     }
 
-                    // count rows once per file (if not already counted)
-                    if fp.total_rows.is_none() {
-                        let total =
-                            match count::run(count::Args::builder().arg_input(&path_str).build()) {
-                                Ok((cnt, _)) => cnt,
-                                Err(e) => {
-                                    fp.load_error = Some(format!("Count error: {e}"));
-                                    0
-                                },
-                            };
-                        fp.total_rows = Some(total);
-                        new_total_rows = Some(total as usize); // update self after dropping fp
-                        // Clamp page within new total
-                        new_page = Self::clamp_page(new_page, rows_per_page, total as usize);
-                    } else {
-                        new_total_rows = Some(fp.total_rows.unwrap_or(0) as usize);
+                    // the page is read straight away; an uncounted file gets counted in the
+                    // background and `poll_counts` updates the pager when that is done
+                    match fp.total_rows {
+                        Some(total) => new_total_rows = Some(total as usize),
+                        None => needs_count = true,
                     }
 
                     // Compose effective indices from overlay sort and filters (if any).
@@ -615,31 +1726,7 @@ impl TableEditor {
 
                         let start_idx = new_page.saturating_mul(rows_per_page);
                         let end_idx = (start_idx + rows_per_page).min(total);
-                        let mut wanted_iter = slice_all[start_idx..end_idx].iter().copied();
-                        let mut next = wanted_iter.next();
-
-                        for (ri, rec_res) in rdr.records().enumerate() {
-                            match next {
-                                Some(want) if ri as u64 == want => {
-                                    if let Ok(rec) = rec_res {
-                                        let mut row =
-                                            Vec::with_capacity(fp.headers.len().max(rec.len()));
-                                        row.extend(rec.iter().map(|s| s.to_string()));
-                                        fp.preview_rows.push(row);
-                                    }
-                                    next = wanted_iter.next();
-                                    if next.is_none() {
-                                        break;
-                                    }
-                                },
-                                Some(want) if (ri as u64) < want => continue,
-                                _ => {
-                                    if next.is_none() {
-                                        break;
-                                    }
-                                },
-                            }
-                        }
+                        Self::push_preview_rows(fp, &cfg, &slice_all[start_idx..end_idx]);
                     } else {
                         // Unsorted & unfiltered: use the library slice helper to keep logic
                         // centralized
@@ -667,6 +1754,18 @@ impl TableEditor {
             }
         }
 
+        // Reading through `indexed()` may have rebuilt a stale index
+        fp.index_state = cfg.index_state();
+
+        // Keep the keyboard cursor on a row that exists on this page
+        if let Some(c) = fp.cursor_row {
+            fp.cursor_row = if fp.preview_rows.is_empty() {
+                None
+            } else {
+                Some(c.min(fp.preview_rows.len() - 1))
+            };
+        }
+
         // End the mutable borrow of the file before mutating other fields on self.
         fp.page = new_page;
         let _ = fp;
@@ -674,6 +1773,9 @@ impl TableEditor {
         if let Some(tr) = new_total_rows {
             self.toal_rows = tr; // keep legacy field updated
         }
+        if needs_count {
+            self.start_count(self.current_file);
+        }
     }
 
     pub fn handle_file_drop(&mut self, ctx: &egui::Context) {
@@ -684,11 +1786,1078 @@ impl TableEditor {
         }
         for f in dropped {
             if let Some(path) = f.path {
-                self.load_preview(path);
+                self.open_file(path);
             }
         }
     }
 
+    /// Open a file the user picked, remembering it in the recent files list.
+    pub fn open_file(&mut self, path: PathBuf) {
+        self.recent_files.push(&path);
+        self.load_preview(path);
+    }
+
+    /// Parse an `$MFT` on a worker thread; `poll_mft_parses` opens the result once done.
+    fn start_mft_parse(&mut self, path: PathBuf) {
+        let source = path.to_string_lossy().to_string();
+        if self.mft_jobs.iter().any(|job| job.source == source) {
+            return;
+        }
+        let total_entries = std::fs::metadata(&path).map_or(0, |m| m.len() / 1024);
+        let entries_done = Arc::new(AtomicU64::new(0));
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (worker_done, worker_cancel) = (entries_done.clone(), cancel.clone());
+        let job = BackgroundJob::spawn("MFT parser", move || {
+            let dir = tempfile::Builder::new().prefix("waka-mft-").tempdir()?;
+            let dest = dir.path().join(format!(
+                "{}.csv",
+                util::display_name(&path.to_string_lossy())
+            ));
+            let rows = util::mft_to_csv(&path, &dest, &worker_done, &worker_cancel)?;
+            tracing::info!("parsed {rows} MFT entries from {}", path.display());
+            Ok((dir, dest))
+        });
+        self.mft_error = None;
+        self.mft_jobs.push(RunningMftParse {
+            source,
+            job,
+            entries_done,
+            cancel,
+            total_entries,
+        });
+    }
+
+    /// Pick up the results of all background jobs. Call once per frame.
+    pub fn poll_jobs(&mut self, ctx: &egui::Context) {
+        self.poll_mft_parses(ctx);
+        self.poll_index_builds(ctx);
+        self.poll_sample(ctx);
+        self.poll_merge(ctx);
+        self.poll_join(ctx);
+        self.poll_materialize(ctx);
+        self.poll_extract(ctx);
+        self.poll_counts(ctx);
+        self.poll_validations(ctx);
+        self.poll_sniffs(ctx);
+        self.poll_distincts(ctx);
+    }
+
+    /// Open the CSVs of finished `$MFT` parses and record failures in `mft_error`.
+    pub fn poll_mft_parses(&mut self, ctx: &egui::Context) {
+        for (parse, res) in job::take_finished(&mut self.mft_jobs, ctx, |parse| &parse.job) {
+            let source = parse.source;
+            match res {
+                Ok((dir, csv)) => {
+                    self.mft_csvs.insert(source, csv.clone());
+                    let file_path = csv.to_string_lossy().to_string();
+                    self.load_preview(csv);
+                    // the CSV lives as long as the file stays open
+                    if let Some(fp) = self.files.iter_mut().find(|fp| fp.file_path == file_path) {
+                        fp.mft_workdir = Some(Arc::new(dir));
+                    }
+                },
+                Err(e) => {
+                    let msg = format!("Failed to parse {}: {e}", util::display_name(&source));
+                    self.mft_error = Some(format!("⚠ {msg}"));
+                    self.notify.error(msg);
+                },
+            }
+        }
+    }
+
+    /// Write the `.idx` sidecar of an open file on a worker thread so paging can seek
+    /// through the index; `poll_index_builds` reloads the file once it is done.
+    pub fn start_index_build(&mut self, file_path: String) {
+        if self.index_jobs.iter().any(|job| job.file_path == file_path) {
+            return;
+        }
+        let path = file_path.clone();
+        let job = BackgroundJob::spawn("index builder", move || index::run(&path, None));
+        self.index_error = None;
+        self.index_jobs.push(RunningIndexBuild { file_path, job });
+    }
+
+    /// Whether an index is currently being built for the given file.
+    pub fn is_indexing(&self, file_path: &str) -> bool {
+        self.index_jobs.iter().any(|job| job.file_path == file_path)
+    }
+
+    /// Refresh the index state of files whose index build finished, reloading the active
+    /// one so it switches to indexed paging, and record failures in `index_error`.
+    pub fn poll_index_builds(&mut self, ctx: &egui::Context) {
+        for (build, res) in job::take_finished(&mut self.index_jobs, ctx, |build| &build.job) {
+            let file_path = build.file_path;
+            if let Err(e) = res {
+                let msg = format!("Failed to index {}: {e}", util::display_name(&file_path));
+                self.index_error = Some(format!("⚠ {msg}"));
+                self.notify.error(msg);
+            }
+            let Some(idx) = self.files.iter().position(|fp| fp.file_path == file_path) else {
+                continue;
+            };
+            self.files[idx].index_state = self.files[idx].config().index_state();
+            if idx == self.current_file {
+                self.reload_current_preview_page();
+            }
+        }
+    }
+
+    /// Count the data rows of an open file on a worker thread unless it is counted or being
+    /// counted already; `poll_counts` stores the total and fixes up the pager.
+    pub fn start_count(&mut self, idx: usize) {
+        let Some(fp) = self.files.get(idx) else {
+            return;
+        };
+        if fp.total_rows.is_some() || self.is_counting(&fp.file_path) {
+            return;
+        }
+        let file_path = fp.file_path.clone();
+        let conf = fp.config();
+        let job = BackgroundJob::spawn("row count", move || Self::count_rows_for_path(&conf));
+        self.count_jobs.push(RunningCount { file_path, job });
+    }
+
+    /// Whether the rows of the given file are still being counted.
+    pub fn is_counting(&self, file_path: &str) -> bool {
+        self.count_jobs.iter().any(|job| job.file_path == file_path)
+    }
+
+    /// Store the totals of finished row counts. The active file's page is clamped to the
+    /// new page count, reloading it if it was past the end.
+    pub fn poll_counts(&mut self, ctx: &egui::Context) {
+        let rows_per_page = self.rows_per_page;
+        for (count, res) in job::take_finished(&mut self.count_jobs, ctx, |count| &count.job) {
+            let file_path = count.file_path;
+            let Some(idx) = self.files.iter().position(|fp| fp.file_path == file_path) else {
+                continue;
+            };
+            let fp = &mut self.files[idx];
+            let total = match res {
+                Ok(total) => total,
+                Err(e) => {
+                    let msg = format!("Count error: {e}");
+                    self.notify
+                        .error(format!("{}: {msg}", util::display_name(&file_path)));
+                    fp.load_error = Some(msg);
+                    0
+                },
+            };
+            fp.total_rows = Some(total);
+            if idx != self.current_file {
+                continue;
+            }
+            let page = if fp.filtered_indices.is_none() {
+                Self::clamp_page(fp.page, rows_per_page, total as usize)
+            } else {
+                fp.page
+            };
+            let moved = page != fp.page;
+            fp.page = page;
+            self.toal_rows = total as usize;
+            if moved {
+                self.page = page;
+                self.reload_current_preview_page();
+            }
+        }
+    }
+
+    /// Check an open file for RFC 4180 conformance on a worker thread, when the user asks
+    /// for it from the tab's context menu, as it reads the whole file;
+    /// `poll_validations` stores the report on the file for its tab badge.
+    pub fn start_validation(&mut self, idx: usize) {
+        self.clear_validation(idx);
+        let Some(fp) = self.files.get(idx) else {
+            return;
+        };
+        let file_path = fp.file_path.clone();
+        let conf = fp.config();
+        let job = BackgroundJob::spawn("validation", move || core_util::validate_csv(&conf));
+        self.validation_jobs
+            .push(RunningValidation { file_path, job });
+    }
+
+    /// Drop the conformance report of an open file along with a check still running for it,
+    /// whose result would be discarded.
+    fn clear_validation(&mut self, idx: usize) {
+        let Some(fp) = self.files.get_mut(idx) else {
+            return;
+        };
+        fp.health = None;
+        let file_path = fp.file_path.clone();
+        self.validation_jobs
+            .retain(|job| job.file_path != file_path);
+    }
+
+    /// Whether the given file is currently being checked.
+    pub fn is_validating(&self, file_path: &str) -> bool {
+        self.validation_jobs
+            .iter()
+            .any(|job| job.file_path == file_path)
+    }
+
+    /// Store the reports of finished conformance checks on their files.
+    pub fn poll_validations(&mut self, ctx: &egui::Context) {
+        for (check, res) in job::take_finished(&mut self.validation_jobs, ctx, |check| &check.job) {
+            if let Some(fp) = self
+                .files
+                .iter_mut()
+                .find(|fp| fp.file_path == check.file_path)
+            {
+                fp.health = Some(res.map_err(|e| e.to_string()));
+            }
+        }
+    }
+
+    /// Sniff the dialect of an open file on a worker thread unless it is known or being
+    /// sniffed already; `poll_sniffs` stores it for the file info popup.
+    fn start_sniff(&mut self, idx: usize) {
+        let Some(fp) = self.files.get(idx) else {
+            return;
+        };
+        if fp.dialect.is_some()
+            || self
+                .sniff_jobs
+                .iter()
+                .any(|job| job.file_path == fp.file_path)
+        {
+            return;
+        }
+        let file_path = fp.file_path.clone();
+        let path = PathBuf::from(&file_path);
+        let job = BackgroundJob::spawn("sniffer", move || core_util::sniff(&path));
+        self.sniff_jobs.push(RunningSniff { file_path, job });
+    }
+
+    /// Store the dialects of finished sniffs on their files.
+    pub fn poll_sniffs(&mut self, ctx: &egui::Context) {
+        for (sniff, res) in job::take_finished(&mut self.sniff_jobs, ctx, |sniff| &sniff.job) {
+            if let Some(fp) = self
+                .files
+                .iter_mut()
+                .find(|fp| fp.file_path == sniff.file_path)
+            {
+                fp.dialect = Some(res.map_err(|e| e.to_string()));
+            }
+        }
+    }
+
+    /// Draw `sample_size` random rows of the active file on a worker thread;
+    /// `poll_sample` shows them in place of the current page once done.
+    pub fn start_sample(&mut self) {
+        if self.sample_job.is_some() {
+            return;
+        }
+        let seed = match self.sample_seed.trim() {
+            "" => None,
+            s => match s.parse::<u64>() {
+                Ok(seed) => Some(seed),
+                Err(_) => {
+                    self.sample_error =
+                        Some(format!("Invalid seed \"{s}\": expected a whole number"));
+                    return;
+                },
+            },
+        };
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        let (file_path, cfg, n) = (fp.file_path.clone(), fp.config(), self.sample_size);
+        let job = BackgroundJob::spawn("sampler", move || core_util::sample_records(&cfg, n, seed));
+        self.sample_error = None;
+        self.sample_job = Some(RunningSample { file_path, job });
+    }
+
+    /// Show a finished sample in its file's preview, or record why drawing it failed.
+    pub fn poll_sample(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.sample_job else {
+            return;
+        };
+        let Some(res) = job.job.poll(ctx) else {
+            return;
+        };
+        let file_path = job.file_path.clone();
+        self.sample_job = None;
+        match res {
+            Ok(records) => {
+                let Some(idx) = self.files.iter().position(|fp| fp.file_path == file_path) else {
+                    return;
+                };
+                let rows = records
+                    .iter()
+                    .map(|r| {
+                        r.iter()
+                            .map(|b| String::from_utf8_lossy(b).to_string())
+                            .collect()
+                    })
+                    .collect();
+                self.files[idx].sample_rows = Some(rows);
+                self.files[idx].cursor_row = None;
+                if idx == self.current_file {
+                    self.reload_current_preview_page();
+                }
+            },
+            Err(e) => {
+                self.sample_error = Some(format!("⚠ Sampling failed: {e}"));
+                self.notify.error(format!("Sampling failed: {e}"));
+            },
+        }
+    }
+
+    /// Leave sample mode on the active file and go back to regular paging.
+    pub fn clear_sample(&mut self) {
+        if let Some(fp) = self.current_fp_mut() {
+            fp.sample_rows = None;
+        }
+        self.reload_current_preview_page();
+    }
+
+    /// Popup to draw a random sample of the active file, anchored to `anchor`.
+    pub fn show_sample_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
+        let popup_id = ui.make_persistent_id("sample_popup");
+        if anchor.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+        egui::Popup::from_response(anchor)
+            .open_memory(None)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(220.0);
+                ui.label(RichText::new("Random sample").strong());
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    ui.label("Rows:");
+                    ui.add(
+                        DragValue::new(&mut self.sample_size)
+                            .range(1..=100_000)
+                            .speed(10),
+                    );
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Seed:");
+                    ui.add(
+                        TextEdit::singleline(&mut self.sample_seed)
+                            .hint_text("random")
+                            .desired_width(120.0),
+                    )
+                    .on_hover_text("Use a fixed seed to draw the same sample again");
+                });
+                if let Some(err) = &self.sample_error {
+                    ui.label(RichText::new(err.as_str()).color(ui.visuals().error_fg_color));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if self.sample_job.is_some() {
+                        ui.spinner();
+                        ui.label("Sampling…");
+                    } else if ui.button("Draw sample").clicked() {
+                        self.start_sample();
+                    }
+                    if ui.button("Close").clicked() {
+                        egui::Popup::close_id(ui.ctx(), popup_id);
+                    }
+                });
+            });
+    }
+
+    /// Dialect report of the current file. Sniffing counts all rows, so it runs the first time
+    /// the popup is opened and is kept on the file afterwards.
+    pub fn show_file_info_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
+        let popup_id = ui.make_persistent_id("file_info_popup");
+        if anchor.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+        if !egui::Popup::is_id_open(ui.ctx(), popup_id) {
+            return;
+        }
+        self.start_sniff(self.current_file);
+        let Some(fp) = self.files.get(self.current_file) else {
+            return;
+        };
+        let dialect = fp.dialect.as_ref();
+
+        egui::Popup::from_response(anchor)
+            .open_memory(None)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(220.0);
+                ui.label(RichText::new("File info").strong());
+                ui.add_space(6.0);
+                let dialect = match dialect {
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Sniffing…");
+                        });
+                        return;
+                    },
+                    Some(Ok(dialect)) => dialect,
+                    Some(Err(e)) => {
+                        ui.label(RichText::new(e.as_str()).color(ui.visuals().error_fg_color));
+                        return;
+                    },
+                };
+                let delimiter = Self::DELIMITER_CHOICES
+                    .iter()
+                    .find(|(_, d)| *d == Some(dialect.delimiter))
+                    .map_or_else(
+                        || format!("{:?}", char::from(dialect.delimiter)),
+                        |(label, _)| (*label).to_string(),
+                    );
+                let quote = dialect
+                    .quote
+                    .map_or_else(|| "none".to_string(), |q| char::from(q).to_string());
+                egui::Grid::new("file_info_grid")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        let mut row = |name: &str, value: String| {
+                            ui.label(name);
+                            ui.label(value);
+                            ui.end_row();
+                        };
+                        row("Delimiter:", delimiter);
+                        row("Quote:", quote);
+                        row(
+                            "Headers:",
+                            if dialect.has_headers { "yes" } else { "no" }.into(),
+                        );
+                        row("Preamble rows:", dialect.preamble_rows.to_string());
+                        row("Records:", dialect.record_count.to_string());
+                        row("Columns:", dialect.column_count.to_string());
+                        row("UTF-8:", if dialect.is_utf8 { "yes" } else { "no" }.into());
+                    });
+                if !dialect.sniffed {
+                    ui.add_space(4.0);
+                    ui.label(
+                        RichText::new("Could not sniff the dialect, showing defaults")
+                            .color(ui.visuals().warn_fg_color)
+                            .size(11.0),
+                    );
+                }
+            });
+    }
+
+    /// Stack all open files into `dest` on a worker thread; `poll_merge` opens the result.
+    fn start_merge(&mut self, dest: PathBuf) {
+        if self.merge_job.is_some() {
+            return;
+        }
+        let inputs: Vec<Config> = self.files.iter().map(FilePreview::config).collect();
+        let source = self.merge_source.then_some("source_file");
+        let out = dest.clone();
+        let job = BackgroundJob::spawn("merge", move || {
+            core_util::merge_csvs(&inputs, &out, source)
+        });
+        self.merge_status = None;
+        self.merge_job = Some(RunningMerge {
+            dest,
+            files: self.files.len(),
+            job,
+        });
+    }
+
+    /// Open the output of a finished merge as a new tab, or record why it failed.
+    pub fn poll_merge(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.merge_job else {
+            return;
+        };
+        let Some(res) = job.job.poll(ctx) else {
+            return;
+        };
+        let (dest, files) = (job.dest.clone(), job.files);
+        self.merge_job = None;
+        match res {
+            Ok(rows) => {
+                let msg = format!("Merged {rows} rows from {files} files");
+                self.notify.success(msg.clone());
+                self.merge_status = Some(msg);
+                self.load_preview(dest);
+            },
+            Err(e) => {
+                self.merge_status = Some(format!("⚠ Merge failed: {e}"));
+                self.notify.error(format!("Merge failed: {e}"));
+            },
+        }
+    }
+
+    /// Popup to stack all open files into one CSV, anchored to `anchor`.
+    pub fn show_merge_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
+        let popup_id = ui.make_persistent_id("merge_popup");
+        if anchor.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+        egui::Popup::from_response(anchor)
+            .open_memory(None)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(260.0);
+                ui.label(RichText::new("Merge open files").strong());
+                ui.add_space(6.0);
+                ui.label(format!(
+                    "Stacks all {} open files into one CSV. Headers are unioned in first-seen \
+                     order; missing columns are left empty.",
+                    self.files.len()
+                ));
+                ui.checkbox(&mut self.merge_source, "Add source_file column")
+                    .on_hover_text("Record the path of the file each row came from");
+                if let Some(msg) = &self.merge_status {
+                    ui.label(RichText::new(msg.as_str()).color(ui.visuals().weak_text_color()));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if self.merge_job.is_some() {
+                        ui.spinner();
+                        ui.label("Merging…");
+                    } else if ui.button("Save As…").clicked()
+                        && let Some(dest) = FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("merged.csv")
+                            .save_file()
+                    {
+                        self.start_merge(dest);
+                    }
+                    if ui.button("Close").clicked() {
+                        egui::Popup::close_id(ui.ctx(), popup_id);
+                    }
+                });
+            });
+    }
+
+    /// Join the two files picked in the join popup into `dest` on a worker thread;
+    /// `poll_join` opens the result.
+    fn start_join(&mut self, dest: PathBuf) {
+        if self.join_job.is_some() {
+            return;
+        }
+        let ((lf, lk), (rf, rk)) = (self.join_left, self.join_right);
+        let (Some(left), Some(right)) = (self.files.get(lf), self.files.get(rf)) else {
+            self.join_status = Some("⚠ Join failed: pick two open files".to_string());
+            return;
+        };
+        let (left, right, kind) = (left.config(), right.config(), self.join_kind);
+        let out = dest.clone();
+        let job = BackgroundJob::spawn("join", move || {
+            core_util::hash_join(&left, lk, &right, rk, kind, &out)
+        });
+        self.join_status = None;
+        self.join_job = Some(RunningJoin { dest, job });
+    }
+
+    /// Open the output of a finished join as a new tab, or record why it failed.
+    pub fn poll_join(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.join_job else {
+            return;
+        };
+        let Some(res) = job.job.poll(ctx) else {
+            return;
+        };
+        let dest = job.dest.clone();
+        self.join_job = None;
+        match res {
+            Ok(rows) => {
+                let msg = format!("Joined {rows} rows");
+                self.notify.success(msg.clone());
+                self.join_status = Some(msg);
+                self.load_preview(dest);
+            },
+            Err(e) => {
+                self.join_status = Some(format!("⚠ Join failed: {e}"));
+                self.notify.error(format!("Join failed: {e}"));
+            },
+        }
+    }
+
+    /// Pick a column and a regex with named groups; saving writes the active file with a new
+    /// column per group and opens it.
+    pub fn show_extract_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
+        let popup_id = ui.make_persistent_id("extract_popup");
+        if anchor.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+        let Some(fp) = self.files.get(self.current_file) else {
+            return;
+        };
+        egui::Popup::from_response(anchor)
+            .open_memory(None)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(320.0);
+                ui.label(RichText::new("Extract columns with a regex").strong());
+                ui.add_space(6.0);
+                ui.label(
+                    "Each named group, e.g. (?P<port>\\d+), becomes a new column filled from the \
+                     first match in the source column. The whole file is written out.",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Column:");
+                    egui::ComboBox::from_id_salt("extract_column")
+                        .selected_text(fp.header_name(self.extract_column))
+                        .width(160.0)
+                        .show_ui(ui, |ui| {
+                            for ci in 0..fp.headers.len() {
+                                ui.selectable_value(
+                                    &mut self.extract_column,
+                                    ci,
+                                    fp.header_name(ci),
+                                );
+                            }
+                        });
+                });
+                ui.add(
+                    TextEdit::singleline(&mut self.extract_regex)
+                        .hint_text(r"(?P<ip>[\d.]+):(?P<port>\d+)")
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(300.0),
+                );
+                // the new column names, or why the regex can't be used (empty: nothing typed yet)
+                let groups = if self.extract_regex.is_empty() {
+                    Err(String::new())
+                } else {
+                    match Regex::new(&self.extract_regex) {
+                        Ok(re) => {
+                            let names: Vec<&str> = re.capture_names().flatten().collect();
+                            if names.is_empty() {
+                                Err("The regex has no named groups".to_string())
+                            } else {
+                                Ok(names.join(", "))
+                            }
+                        },
+                        Err(e) => Err(e.to_string()),
+                    }
+                };
+                match &groups {
+                    Ok(names) => {
+                        ui.label(RichText::new(format!("New columns: {names}")).size(11.0));
+                    },
+                    Err(e) if !e.is_empty() => {
+                        ui.label(
+                            RichText::new(e.as_str())
+                                .color(ui.visuals().error_fg_color)
+                                .size(11.0),
+                        );
+                    },
+                    Err(_) => {},
+                }
+                if let Some(msg) = &self.extract_status {
+                    ui.label(RichText::new(msg.as_str()).color(ui.visuals().weak_text_color()));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if self.extract_job.is_some() {
+                        ui.spinner();
+                        ui.label("Extracting…");
+                    } else if ui
+                        .add_enabled(groups.is_ok(), Button::new("Save As…"))
+                        .clicked()
+                        && let Some(dest) = FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name(format!(
+                                "{}_extracted.csv",
+                                util::display_name(&fp.file_path)
+                            ))
+                            .save_file()
+                    {
+                        let (cfg, column, pattern) =
+                            (fp.config(), self.extract_column, self.extract_regex.clone());
+                        let out = dest.clone();
+                        let job = BackgroundJob::spawn("extraction", move || {
+                            core_util::extract_regex_columns(&cfg, column, &pattern, Some(&out))
+                        });
+                        self.extract_status = None;
+                        self.extract_job = Some(RunningExtract { dest, job });
+                    }
+                    if ui.button("Close").clicked() {
+                        egui::Popup::close_id(ui.ctx(), popup_id);
+                    }
+                });
+            });
+    }
+
+    /// Name a new column and give the template it is filled from; it is added to the active
+    /// file's preview and exports without rewriting the file.
+    pub fn show_derive_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
+        let popup_id = ui.make_persistent_id("derive_popup");
+        if anchor.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+        let Some(fp) = self.files.get_mut(self.current_file) else {
+            return;
+        };
+        egui::Popup::from_response(anchor)
+            .open_memory(None)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(320.0);
+                ui.label(RichText::new("Derive a column").strong());
+                ui.add_space(6.0);
+                ui.label(
+                    "Each {column} in the template is replaced by that column's value, e.g. \
+                     {first} {last}. Columns can also be named by their position, from {0}.",
+                );
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.add(
+                        TextEdit::singleline(&mut self.derive_name)
+                            .hint_text("endpoint")
+                            .desired_width(200.0),
+                    );
+                });
+                ui.add(
+                    TextEdit::singleline(&mut self.derive_format)
+                        .hint_text("{hostname}:{port}")
+                        .font(egui::TextStyle::Monospace)
+                        .desired_width(300.0),
+                );
+                // a preview of the first row, or why the template can't be used
+                let shown: Vec<&str> = (0..fp.headers.len()).map(|ci| fp.header_name(ci)).collect();
+                let preview = if self.derive_format.is_empty() {
+                    Err(String::new())
+                } else {
+                    core_util::FieldTemplate::new(&self.derive_format, &shown)
+                        .map(|template| {
+                            fp.preview_rows
+                                .first()
+                                .map(|row| template.render(|ci| row.get(ci).map(String::as_bytes)))
+                        })
+                        .map_err(|e| e.to_string())
+                };
+                match &preview {
+                    Ok(Some(value)) => {
+                        ui.label(RichText::new(format!("First row: {value}")).size(11.0));
+                    },
+                    Ok(None) => {},
+                    Err(e) if !e.is_empty() => {
+                        ui.label(
+                            RichText::new(e.as_str())
+                                .color(ui.visuals().error_fg_color)
+                                .size(11.0),
+                        );
+                    },
+                    Err(_) => {},
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui
+                        .add_enabled(preview.is_ok(), Button::new("Add column"))
+                        .clicked()
+                    {
+                        match fp.add_derived_column(&self.derive_name, &self.derive_format) {
+                            Ok(()) => {
+                                self.notify
+                                    .success(format!("Added column {}", self.derive_name.trim()));
+                                self.derive_name.clear();
+                                self.derive_format.clear();
+                            },
+                            Err(e) => self.notify.error(format!("Can't add the column: {e}")),
+                        }
+                    }
+                    if ui.button("Close").clicked() {
+                        egui::Popup::close_id(ui.ctx(), popup_id);
+                    }
+                });
+            });
+    }
+
+    /// Open the output of a finished regex extraction as a new tab, or record why it failed.
+    pub fn poll_extract(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.extract_job else {
+            return;
+        };
+        let Some(res) = job.job.poll(ctx) else {
+            return;
+        };
+        let dest = job.dest.clone();
+        self.extract_job = None;
+        match res {
+            Ok((names, matched)) => {
+                let msg = format!(
+                    "Extracted {} from {matched} matching rows",
+                    names.join(", ")
+                );
+                self.notify.success(msg.clone());
+                self.extract_status = Some(msg);
+                self.load_preview(dest);
+            },
+            Err(e) => {
+                self.extract_status = Some(format!("⚠ Extraction failed: {e}"));
+                self.notify.error(format!("Extraction failed: {e}"));
+            },
+        }
+    }
+
+    /// Ask where to write the active file's current view, then materialize it there.
+    pub fn materialize_current(&mut self) {
+        if self.materialize_job.is_some() {
+            return;
+        }
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        let Some(dest) = FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name(format!("{}_view.csv", util::display_name(&fp.file_path)))
+            .save_file()
+        else {
+            return;
+        };
+        self.start_materialize(dest);
+    }
+
+    /// Write the active file's effective rows to `dest` and build its index on a worker
+    /// thread; `poll_materialize` opens the result.
+    fn start_materialize(&mut self, dest: PathBuf) {
+        let Some(fp) = self.current_fp().cloned() else {
+            return;
+        };
+        let out = dest.clone();
+        let job = BackgroundJob::spawn("materialize", move || {
+            let rows = Self::write_effective_rows(&fp, &out)?;
+            index::run(&out.to_string_lossy(), None)?;
+            Ok(rows)
+        });
+        self.materialize_job = Some(RunningMaterialize { dest, job });
+    }
+
+    pub fn is_materializing(&self) -> bool {
+        self.materialize_job.is_some()
+    }
+
+    /// Open a finished materialized view as a new tab, or report why it failed.
+    pub fn poll_materialize(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.materialize_job else {
+            return;
+        };
+        let Some(res) = job.job.poll(ctx) else {
+            return;
+        };
+        let dest = job.dest.clone();
+        self.materialize_job = None;
+        match res {
+            Ok(rows) => {
+                self.notify.success(format!(
+                    "Materialized {rows} rows to {} with an index",
+                    dest.display()
+                ));
+                self.load_preview(dest);
+            },
+            Err(e) => self.notify.error(format!("Materialize view failed: {e}")),
+        }
+    }
+
+    /// Write the rows of `fp` as currently shown - filtered, sorted, in the user's column
+    /// order and with renamed headers - to a new CSV at `dest`. Returns the number of rows.
+    fn write_effective_rows(fp: &FilePreview, dest: &Path) -> anyhow::Result<u64> {
+        let cfg = fp.config();
+        let order = fp.display_columns();
+        let mut wtr = core_util::csv_writer(Some(dest), None, csv::QuoteStyle::Necessary)?;
+        wtr.write_record(fp.output_headers(&order))?;
+
+        let rows = Self::compose_indices(fp);
+        // sorted without an index, the wanted rows are held in memory to reorder them
+        if let Some(rows) = &rows
+            && !rows.is_sorted()
+            && !matches!(cfg.indexed(), Ok(Some(_)))
+            && let Some(path) = &cfg.path
+        {
+            core_util::mem_file_check(path, false, false)?;
+        }
+        let mut written = 0_u64;
+        Self::for_each_row(&cfg, rows.as_deref(), |brec| {
+            fp.write_output_row(&mut wtr, &order, |ci| brec.get(ci))?;
+            written += 1;
+            Ok(())
+        })?;
+        wtr.flush()?;
+        Ok(written)
+    }
+
+    /// Call `each` with the data rows numbered in `rows`, in that order, or with every row in
+    /// file order when `rows` is None. Seeks through the index when the file has one;
+    /// without it, rows in file order are picked in a single pass and any other order holds
+    /// the picked rows to reorder them.
+    fn for_each_row(
+        cfg: &Config,
+        rows: Option<&[u64]>,
+        mut each: impl FnMut(&csv::ByteRecord) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut rec = csv::ByteRecord::new();
+        let Some(rows) = rows else {
+            let mut rdr = cfg.reader()?;
+            while cfg.read_byte_record(&mut rdr, &mut rec)? {
+                each(&rec)?;
+            }
+            return Ok(());
+        };
+        if let Ok(Some(mut idx)) = cfg.indexed() {
+            // seek once per run of consecutive rows
+            let mut i = 0usize;
+            while i < rows.len() {
+                let base = rows[i];
+                let mut len = 1usize;
+                while i + len < rows.len() && rows[i + len] == base + len as u64 {
+                    len += 1;
+                }
+                idx.seek(base)
+                    .map_err(|e| anyhow!("Index seek error: {e}"))?;
+                for brec in cfg.byte_records(&mut idx).take(len) {
+                    each(&brec.map_err(|e| anyhow!("Row read error: {e}"))?)?;
+                }
+                i += len;
+            }
+        } else if rows.is_sorted() {
+            let mut rdr = cfg.reader()?;
+            let mut wanted = rows.iter().copied().peekable();
+            let mut ri = 0u64;
+            while wanted.peek().is_some() && cfg.read_byte_record(&mut rdr, &mut rec)? {
+                if wanted.next_if_eq(&ri).is_some() {
+                    each(&rec)?;
+                }
+                ri += 1;
+            }
+        } else {
+            let positions: HashMap<u64, usize> =
+                rows.iter().enumerate().map(|(pos, &r)| (r, pos)).collect();
+            let mut picked: Vec<Option<csv::ByteRecord>> = vec![None; rows.len()];
+            let mut rdr = cfg.reader()?;
+            let mut ri = 0u64;
+            while cfg.read_byte_record(&mut rdr, &mut rec)? {
+                if let Some(&pos) = positions.get(&ri) {
+                    picked[pos] = Some(rec.clone());
+                }
+                ri += 1;
+            }
+            for brec in picked.iter().flatten() {
+                each(brec)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Append the data rows numbered in `rows` to the preview of `fp`, noting a failed read
+    /// in `load_error`.
+    fn push_preview_rows(fp: &mut FilePreview, cfg: &Config, rows: &[u64]) {
+        let width = fp.headers.len();
+        let preview = &mut fp.preview_rows;
+        let res = Self::for_each_row(cfg, Some(rows), |brec| {
+            let mut row = Vec::with_capacity(width.max(brec.len()));
+            row.extend(brec.iter().map(|b| String::from_utf8_lossy(b).to_string()));
+            preview.push(row);
+            Ok(())
+        });
+        if let Err(e) = res {
+            fp.load_error = Some(e.to_string());
+        }
+    }
+
+    /// File and key column pickers for one side of a join.
+    fn join_side_ui(ui: &mut Ui, id: &str, files: &[FilePreview], side: &mut (usize, usize)) {
+        let file_name = |i: usize| {
+            files
+                .get(i)
+                .map_or(String::new(), |fp| util::display_name(&fp.file_path))
+        };
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt((id, "file"))
+                .selected_text(file_name(side.0))
+                .width(150.0)
+                .show_ui(ui, |ui| {
+                    for i in 0..files.len() {
+                        if ui.selectable_label(side.0 == i, file_name(i)).clicked() {
+                            *side = (i, 0);
+                        }
+                    }
+                });
+            let headers = files
+                .get(side.0)
+                .map_or(&[][..], |fp| fp.headers.as_slice());
+            egui::ComboBox::from_id_salt((id, "key"))
+                .selected_text(headers.get(side.1).map_or("", String::as_str))
+                .width(120.0)
+                .show_ui(ui, |ui| {
+                    for (ci, h) in headers.iter().enumerate() {
+                        ui.selectable_value(&mut side.1, ci, h);
+                    }
+                });
+        });
+    }
+
+    /// Popup to join two open files on a key column, anchored to `anchor`.
+    pub fn show_join_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
+        let popup_id = ui.make_persistent_id("join_popup");
+        if anchor.clicked() {
+            egui::Popup::toggle_id(ui.ctx(), popup_id);
+        }
+        egui::Popup::from_response(anchor)
+            .open_memory(None)
+            .close_behavior(PopupCloseBehavior::CloseOnClickOutside)
+            .id(popup_id)
+            .show(|ui| {
+                ui.set_min_width(300.0);
+                ui.label(RichText::new("Join files on a key").strong());
+                ui.add_space(6.0);
+                ui.label("Left file and key:");
+                Self::join_side_ui(ui, "join_left", &self.files, &mut self.join_left);
+                ui.label("Right file and key:");
+                Self::join_side_ui(ui, "join_right", &self.files, &mut self.join_right);
+                ui.horizontal(|ui| {
+                    ui.label("Type:");
+                    ui.radio_value(&mut self.join_kind, core_util::JoinKind::Inner, "Inner");
+                    ui.radio_value(&mut self.join_kind, core_util::JoinKind::Left, "Left");
+                });
+                if let Some(msg) = &self.join_status {
+                    ui.label(RichText::new(msg.as_str()).color(ui.visuals().weak_text_color()));
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if self.join_job.is_some() {
+                        ui.spinner();
+                        ui.label("Joining…");
+                    } else if ui.button("Save As…").clicked()
+                        && let Some(dest) = FileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("joined.csv")
+                            .save_file()
+                    {
+                        self.start_join(dest);
+                    }
+                    if ui.button("Close").clicked() {
+                        egui::Popup::close_id(ui.ctx(), popup_id);
+                    }
+                });
+            });
+    }
+
+    /// Progress of running `$MFT` parses, with a cancel button each, or the last failure.
+    pub fn show_mft_progress(&self, ui: &mut Ui) {
+        for job in &self.mft_jobs {
+            let done = job.entries_done.load(Ordering::Relaxed);
+            let name = util::display_name(&job.source);
+            let bar = if job.total_entries > 0 {
+                egui::ProgressBar::new((done as f32 / job.total_entries as f32).min(1.0))
+                    .text(format!("{name}: {done} / {} entries", job.total_entries))
+            } else {
+                egui::ProgressBar::new(0.0)
+                    .animate(true)
+                    .text(format!("{name}: {done} entries"))
+            };
+            ui.add_sized(egui::vec2(220.0, 16.0), bar);
+            if ui.button("⏹ Cancel").clicked() {
+                job.cancel.store(true, Ordering::Relaxed);
+            }
+        }
+        if let Some(err) = &self.mft_error {
+            ui.label(
+                RichText::new(err.as_str())
+                    .color(ui.visuals().error_fg_color)
+                    .size(11.0),
+            );
+        }
+    }
+
     pub fn current_fp(&self) -> Option<&FilePreview> {
         self.files.get(self.current_file)
     }
@@ -699,6 +2868,16 @@ impl TableEditor {
 
     pub fn load_preview(&mut self, path: PathBuf) {
         let file_path = path.to_string_lossy().to_string();
+        // A raw $MFT is parsed into a CSV first; the table then works on that, until
+        // closing it removes the CSV and the next open parses the $MFT again
+        if let Some(csv) = self.mft_csvs.get(&file_path).filter(|csv| csv.exists()) {
+            let csv = csv.clone();
+            return self.load_preview(csv);
+        }
+        if util::is_mft_file(&path) {
+            self.start_mft_parse(path);
+            return;
+        }
         // Avoid reloading same file
         if let Some(idx) = self.files.iter().position(|fp| fp.file_path == file_path) {
             self.current_file = idx;
@@ -720,26 +2899,26 @@ impl TableEditor {
             sorted_indices: None,
             sort_col: None,
             sort_desc: false,
+            column_order: Vec::new(),
+            cursor_row: None,
+            delimiter: None,
+            link_columns: HashMap::new(),
+            json_columns: HashSet::new(),
+            index_state: IndexState::None,
+            sample_rows: None,
+            renamed_headers: Vec::new(),
+            dialect: None,
+            health: None,
+            derived_columns: Vec::new(),
+            mft_workdir: None,
         };
 
-        // Count first so we can clamp paging appropriately (byte_records for speed)
-        let total = match count::run(
-            count::Args::builder()
-                .arg_input(fp.file_path.to_string())
-                .build(),
-        ) {
-            Ok((cnt, _)) => cnt,
-            Err(e) => {
-                fp.load_error = Some(format!("Count error: {e}"));
-                0
-            },
-        };
-
-        fp.total_rows = Some(total);
-        self.toal_rows = total as usize;
+        // Show the first page straight from the reader; the row count, which can take a
+        // while on a large file, follows from `start_count`
         self.page = 0;
 
-        let cfg = Config::builder().path(&fp.file_path).build();
+        let cfg = fp.config();
+        fp.index_state = cfg.index_state();
 
         match cfg.reader() {
             Ok(mut rdr) => {
@@ -750,12 +2929,18 @@ impl TableEditor {
                 }
                 if fp.load_error.is_none() {
                     fp.filters = vec![ColumnFilter::default(); fp.headers.len()];
+                    fp.reset_column_order();
+                    Self::warn_duplicate_headers(
+                        &self.notify,
+                        &fp.file_path,
+                        &fp.disambiguate_headers(),
+                    );
                 }
 
                 if fp.load_error.is_none() {
                     let rows_per_page = self.rows_per_page;
                     fp.preview_rows.reserve(rows_per_page);
-                    for rec_res in rdr.records().take(rows_per_page) {
+                    for rec_res in cfg.records(&mut rdr).take(rows_per_page) {
                         match rec_res {
                             Ok(rec) => {
                                 let mut row = Vec::with_capacity(fp.headers.len().max(rec.len()));
@@ -769,8 +2954,12 @@ impl TableEditor {
                         }
                     }
 
-                    if fp.preview_rows.is_empty() && fp.load_error.is_none() {
-                        fp.load_error = Some("No data rows found.".to_string());
+                    // a header-only file just has 0 rows, only a truly empty file is an error
+                    if fp.preview_rows.is_empty()
+                        && fp.load_error.is_none()
+                        && !core_util::count_rows_detail(&cfg).is_ok_and(|rc| rc.has_header)
+                    {
+                        fp.load_error = Some("File is empty.".to_string());
                     }
                 }
             },
@@ -778,9 +2967,17 @@ impl TableEditor {
                 fp.load_error = Some(format!("Unable to open file: {e}"));
             },
         }
+        if let Some(err) = &fp.load_error {
+            self.notify
+                .error(format!("{}: {err}", util::display_name(&fp.file_path)));
+        }
 
+        let failed = fp.load_error.is_some();
         self.files.push(fp);
         self.current_file = self.files.len() - 1;
+        if !failed {
+            self.start_count(self.current_file);
+        }
     }
 
     pub fn show_file_tabs(&mut self, ui: &mut Ui) {
@@ -789,7 +2986,7 @@ impl TableEditor {
         }
 
         Frame::new()
-            .fill(Color32::from_rgb(45, 45, 45))
+            .fill(ui.visuals().faint_bg_color)
             .inner_margin(Margin::symmetric(8, 6))
             .show(ui, |ui| {
                 ScrollArea::horizontal()
@@ -798,6 +2995,11 @@ impl TableEditor {
                     .show(ui, |ui| {
                         let mut clicked_idx: Option<usize> = None;
                         let mut close_idx: Option<usize> = None;
+                        // (tab, keep it) for "Close others" / "Close all"
+                        let mut close_many: Option<(usize, bool)> = None;
+                        let mut new_delimiter: Option<Option<u8>> = None;
+                        let mut build_index: Option<String> = None;
+                        let mut recheck: Option<usize> = None;
 
                         ui.horizontal(|ui| {
                             for (idx, fp) in self.files.iter().enumerate() {
@@ -805,18 +3007,18 @@ impl TableEditor {
                                 let name = util::display_name(&fp.file_path);
 
                                 let tab_fill = if selected {
-                                    Color32::from_rgb(60, 60, 60)
+                                    ui.visuals().widgets.active.weak_bg_fill
                                 } else {
-                                    Color32::from_rgb(40, 40, 40)
+                                    ui.visuals().widgets.inactive.weak_bg_fill
                                 };
                                 let tab_stroke = if selected {
-                                    Stroke::new(1.5, Color32::from_rgb(100, 100, 100))
+                                    Stroke::new(1.5, ui.visuals().widgets.hovered.bg_stroke.color)
                                 } else {
-                                    Stroke::new(1.0, Color32::from_rgb(70, 70, 70))
+                                    ui.visuals().widgets.noninteractive.bg_stroke
                                 };
 
                                 // Compact tab: no icon, tighter paddings, same colors
-                                let accent = Color32::from_rgb(0, 120, 215);
+                                let accent = ui.visuals().selection.bg_fill;
                                 let ir = Frame::new()
                                     .fill(tab_fill)
                                     .stroke(tab_stroke)
@@ -834,9 +3036,9 @@ impl TableEditor {
                                         ui.horizontal(|ui| {
                                             // filename label (smaller font, narrow height)
                                             let text_color = if selected {
-                                                Color32::WHITE
+                                                ui.visuals().strong_text_color()
                                             } else {
-                                                Color32::from_rgb(210, 210, 210)
+                                                ui.visuals().text_color()
                                             };
                                             let label = egui::Label::new(
                                                 RichText::new(name).size(11.0).color(text_color),
@@ -849,6 +3051,37 @@ impl TableEditor {
                                                 clicked_idx = Some(idx);
                                             }
 
+                                            // Index state badge
+                                            let (color, hint) = match fp.index_state {
+                                                IndexState::Fresh => (
+                                                    Color32::from_rgb(120, 200, 120),
+                                                    "Indexed: paging seeks through the .idx",
+                                                ),
+                                                IndexState::Stale => (
+                                                    ui.visuals().warn_fg_color,
+                                                    "Index is stale: the file changed after it \
+                                                     was indexed",
+                                                ),
+                                                IndexState::None => {
+                                                    (ui.visuals().weak_text_color(), "Not indexed")
+                                                },
+                                            };
+                                            ui.label(RichText::new("⚡").size(10.0).color(color))
+                                                .on_hover_text(hint);
+
+                                            // CSV health badge
+                                            let validating = self.is_validating(&fp.file_path);
+                                            if let Some((icon, color, hint)) = Self::health_badge(
+                                                ui.visuals(),
+                                                fp.health.as_ref(),
+                                                validating,
+                                            ) {
+                                                ui.label(
+                                                    RichText::new(icon).size(10.0).color(color),
+                                                )
+                                                .on_hover_text(hint);
+                                            }
+
                                             // Close button at far right (keep small spacing)
                                             ui.add_space(6.0);
                                             let show_close = selected || resp.hovered();
@@ -882,6 +3115,41 @@ impl TableEditor {
                                         clicked_idx = Some(idx);
                                     }
                                 }
+                                tab_hit.context_menu(|ui| {
+                                    let path = Path::new(&fp.file_path);
+                                    if ui.button("📂 Reveal in file manager").clicked() {
+                                        if let Err(e) = util::reveal_in_file_manager(path) {
+                                            tracing::warn!(
+                                                "failed to reveal {}: {e}",
+                                                path.display()
+                                            );
+                                        }
+                                        ui.close();
+                                    }
+                                    if ui.button("📋 Copy full path").clicked() {
+                                        let full = std::path::absolute(path)
+                                            .unwrap_or_else(|_| path.to_path_buf());
+                                        ui.ctx().copy_text(full.display().to_string());
+                                        ui.close();
+                                    }
+                                    if ui.button("🩺 Check CSV health").clicked() {
+                                        recheck = Some(idx);
+                                        ui.close();
+                                    }
+                                    ui.separator();
+                                    if ui.button("Close").clicked() {
+                                        close_idx = Some(idx);
+                                        ui.close();
+                                    }
+                                    if ui.button("Close others").clicked() {
+                                        close_many = Some((idx, true));
+                                        ui.close();
+                                    }
+                                    if ui.button("Close all").clicked() {
+                                        close_many = Some((idx, false));
+                                        ui.close();
+                                    }
+                                });
 
                                 // Draw top accent underline for the active tab
                                 if selected {
@@ -898,29 +3166,233 @@ impl TableEditor {
 
                                 ui.add_space(4.0);
                             }
+
+                            // Delimiter override for the active file
+                            if let Some(fp) = self.files.get(self.current_file) {
+                                ui.separator();
+                                let current = fp.delimiter;
+                                let selected_text = Self::DELIMITER_CHOICES
+                                    .iter()
+                                    .find(|(_, d)| *d == current)
+                                    .map_or("Auto", |(label, _)| *label);
+                                egui::ComboBox::from_id_salt("file_delimiter")
+                                    .selected_text(RichText::new(selected_text).size(11.0))
+                                    .width(90.0)
+                                    .show_ui(ui, |ui| {
+                                        for (label, delim) in Self::DELIMITER_CHOICES {
+                                            if ui
+                                                .selectable_label(current == delim, label)
+                                                .clicked()
+                                                && current != delim
+                                            {
+                                                new_delimiter = Some(delim);
+                                            }
+                                        }
+                                    })
+                                    .response
+                                    .on_hover_text("Field delimiter");
+
+                                // On-demand index build for the active file
+                                let snappy = fp.file_path.to_lowercase().ends_with(".sz");
+                                if self.is_indexing(&fp.file_path) {
+                                    ui.spinner();
+                                    ui.label(RichText::new("Indexing…").size(11.0));
+                                } else {
+                                    let label = if fp.index_state == IndexState::None {
+                                        "Build index"
+                                    } else {
+                                        "Rebuild index"
+                                    };
+                                    let resp = ui
+                                        .add_enabled(
+                                            !snappy,
+                                            Button::new(RichText::new(label).size(11.0)),
+                                        )
+                                        .on_hover_text(
+                                            "Write a .idx next to the file for fast paging",
+                                        )
+                                        .on_disabled_hover_text(
+                                            "Snappy-compressed files cannot be indexed",
+                                        );
+                                    if resp.clicked() {
+                                        build_index = Some(fp.file_path.clone());
+                                    }
+                                }
+                                if fp.index_state == IndexState::Stale {
+                                    ui.label(
+                                        RichText::new(
+                                            "⚠ Index is stale, paging is slow until it is rebuilt",
+                                        )
+                                        .color(ui.visuals().warn_fg_color)
+                                        .size(11.0),
+                                    );
+                                }
+                                if let Some(err) = &self.index_error {
+                                    ui.label(
+                                        RichText::new(err.as_str())
+                                            .color(ui.visuals().error_fg_color)
+                                            .size(11.0),
+                                    );
+                                }
+                            }
                         });
 
-                        if let Some(i) = clicked_idx {
-                            self.current_file = i;
-                            self.reload_current_preview_page();
-                        }
+                        if let Some(delimiter) = new_delimiter {
+                            self.set_current_delimiter(delimiter);
+                        }
+
+                        if let Some(path) = build_index {
+                            self.start_index_build(path);
+                        }
+
+                        if let Some(i) = recheck {
+                            self.start_validation(i);
+                        }
+
+                        if let Some(i) = clicked_idx {
+                            self.current_file = i;
+                            self.reload_current_preview_page();
+                        }
+
+                        if let Some(i) = close_idx {
+                            self.close_files(|idx| idx == i);
+                        }
+                        if let Some((i, keep)) = close_many {
+                            self.close_files(|idx| !keep || idx != i);
+                        }
+                    });
+            });
+
+        ui.add_space(4.0);
+    }
+
+    /// Close every file tab for which `close` returns true, keeping the active tab if it
+    /// stays open and otherwise moving to the tab that took its place.
+    pub fn close_files(&mut self, close: impl Fn(usize) -> bool) {
+        let current = self.current_file;
+        let mut closed_before_current = 0;
+        let mut idx = 0;
+        self.files.retain(|_| {
+            let closing = close(idx);
+            if closing && idx < current {
+                closed_before_current += 1;
+            }
+            idx += 1;
+            !closing
+        });
+        if self.files.is_empty() {
+            self.current_file = 0;
+            self.toal_rows = 0;
+            self.page = 0;
+            self.renaming_header = None;
+            return;
+        }
+        self.current_file = (current - closed_before_current).min(self.files.len() - 1);
+        self.reload_current_preview_page();
+    }
+
+    /// Change the delimiter of the active file and reload it. Headers, filters, sorting
+    /// and column order all depend on how the rows are split, so they are reset.
+    pub fn set_current_delimiter(&mut self, delimiter: Option<u8>) {
+        let Some(fp) = self.current_fp_mut() else {
+            return;
+        };
+        fp.delimiter = delimiter;
+        fp.headers.clear();
+        fp.link_columns.clear();
+        fp.json_columns.clear();
+        fp.sample_rows = None;
+        fp.renamed_headers.clear();
+        fp.derived_columns.clear();
+        fp.filters.clear();
+        fp.filtered_indices = None;
+        fp.sorted_indices = None;
+        fp.sort_col = None;
+        fp.sort_desc = false;
+        fp.page = 0;
+        fp.cursor_row = None;
+        fp.load_error = None;
+
+        self.reload_current_preview_page();
+
+        if let Some(fp) = self.files.get_mut(self.current_file) {
+            fp.filters = vec![ColumnFilter::default(); fp.headers.len()];
+            fp.reset_column_order();
+            Self::warn_duplicate_headers(&self.notify, &fp.file_path, &fp.disambiguate_headers());
+        }
+        // a report on the old delimiter no longer applies
+        self.clear_validation(self.current_file);
+    }
 
-                        if let Some(i) = close_idx {
-                            // Remove the file and adjust current index safely
-                            self.files.remove(i);
-                            if self.files.is_empty() {
-                                self.current_file = 0;
-                            } else if self.current_file >= self.files.len() {
-                                self.current_file = self.files.len() - 1;
-                            } else if self.current_file > i {
-                                self.current_file -= 1;
-                            }
-                            self.reload_current_preview_page();
-                        }
-                    });
-            });
+    /// Tell the user which header names of a file repeat and what they are shown as instead.
+    fn warn_duplicate_headers(notify: &Notifier, file_path: &str, dupes: &[(String, Vec<usize>)]) {
+        if dupes.is_empty() {
+            return;
+        }
+        let names = dupes
+            .iter()
+            .map(|(name, cols)| {
+                let cols: Vec<String> = cols.iter().map(|c| (c + 1).to_string()).collect();
+                format!("\"{name}\" (columns {})", cols.join(", "))
+            })
+            .collect::<Vec<_>>()
+            .join("; ");
+        notify.warning(format!(
+            "{} has duplicate column names: {names}. Later occurrences are renamed with a _2, _3, \
+             ... suffix.",
+            util::display_name(file_path)
+        ));
+    }
 
-        ui.add_space(4.0);
+    /// Icon, color and hover text of a file tab's CSV health badge, or `None` when the file
+    /// has not been checked.
+    fn health_badge(
+        visuals: &egui::Visuals,
+        health: Option<&Result<core_util::CsvReport, String>>,
+        validating: bool,
+    ) -> Option<(&'static str, Color32, String)> {
+        use std::fmt::Write as _;
+
+        const SHOWN_ISSUES: usize = 5;
+        if validating {
+            return Some((
+                "🩺",
+                visuals.weak_text_color(),
+                "Checking CSV health…".into(),
+            ));
+        }
+        match health? {
+            Err(e) => Some((
+                "⚠",
+                visuals.error_fg_color,
+                format!("CSV health check failed: {e}"),
+            )),
+            Ok(report) if report.is_valid() => Some((
+                "✔",
+                Color32::from_rgb(120, 200, 120),
+                format!("Conforms to RFC 4180 ({} records)", report.records),
+            )),
+            Ok(report) => {
+                let mut hint = format!("{} conformance issue(s):", report.issue_count());
+                for (count, what) in [
+                    (report.unescaped_quotes, "unescaped quotes"),
+                    (report.unquoted_newlines, "line breaks in unquoted fields"),
+                    (report.field_count_mismatches, "inconsistent field counts"),
+                    (report.invalid_utf8, "invalid UTF-8 records"),
+                ] {
+                    if count > 0 {
+                        let _ = write!(hint, "\n  {count} {what}");
+                    }
+                }
+                for issue in report.issues.iter().take(SHOWN_ISSUES) {
+                    let _ = write!(hint, "\nline {}: {}", issue.line, issue.kind);
+                }
+                if report.issue_count() > SHOWN_ISSUES as u64 {
+                    hint.push_str("\n…");
+                }
+                Some(("⚠", visuals.warn_fg_color, hint))
+            },
+        }
     }
 
     pub fn clear_all_filters_current_file(&mut self) {
@@ -931,103 +3403,59 @@ impl TableEditor {
                 f.use_regex = false;
                 f.regex_text = String::new();
                 f.regex_error = None;
+                f.use_range = false;
+                f.range_min.clear();
+                f.range_max.clear();
             }
             fp.filtered_indices = None;
             fp.page = 0;
         }
     }
 
-    /// Write rows (filtered or all) of the current file to a CSV writer.
+    /// Write the rows in `scope` of a file to a CSV writer.
     fn write_rows_to_csv_writer<W: std::io::Write>(
-        &self,
         fp: &FilePreview,
         mut wtr: Writer<W>,
-        only_filtered: bool,
+        scope: ExportScope,
+        progress: &ExportProgress,
     ) -> anyhow::Result<()> {
-        // Write headers that we cache in-memory
-        wtr.write_record(fp.headers.iter().map(|u| u.as_str()))?;
-
-        let path_str = fp.file_path.to_string();
-        let cfg = Config::builder().path(&path_str).build();
-
-        // If we have filtered indices and only_filtered is true, restrict to them; else stream all
-        // rows.
-        if only_filtered && let Some(ref filt) = fp.filtered_indices {
-            // Fast-path using the qsv index when available
-            if let Ok(Some(mut idx)) = cfg.indexed() {
-                // Iterate contiguous chunks to minimize seeking, mirroring reload logic
-                let slice = &filt[..];
-                let mut i = 0usize;
-                while i < slice.len() {
-                    let base = slice[i];
-                    let mut len = 1usize;
-                    while i + len < slice.len() && slice[i + len] == base + len as u64 {
-                        len += 1;
-                    }
-                    idx.seek(base)
-                        .map_err(|e| anyhow!("Index seek error: {e}"))?;
-                    for rec_res in idx.byte_records().take(len) {
-                        let brec = rec_res?;
-                        wtr.write_byte_record(&brec)?;
-                    }
-                    i += len;
-                }
-                wtr.flush().map_err(|e| anyhow!("Flush failed: {e}"))?;
-                return Ok(());
-            }
+        // Write headers that we cache in-memory, in the user's column order
+        let order = fp.display_columns();
+        wtr.write_record(fp.output_headers(&order))?;
 
-            // Fallback: stream from reader and pick wanted rows
-            if let Ok(mut rdr) = cfg.reader() {
-                // Ensure header row is consumed before records()
-                let _ = rdr.headers();
-                let mut wanted_iter = filt.iter().copied();
-                let mut next = wanted_iter.next();
-                for (ri, rec_res) in rdr.records().enumerate() {
-                    match next {
-                        Some(want) if ri as u64 == want => {
-                            let rec = rec_res.map_err(|e| anyhow!("Row read error: {e}"))?;
-                            wtr.write_record(rec.iter())
-                                .map_err(|e| anyhow!("Write row failed: {e}"))?;
-                            next = wanted_iter.next();
-                            if next.is_none() {
-                                break;
-                            }
-                        },
-                        Some(want) if (ri as u64) < want => continue,
-                        _ => {
-                            if next.is_none() {
-                                break;
-                            }
-                        },
-                    }
-                }
-                wtr.flush().map_err(|e| anyhow!("Flush failed: {e}"))?;
-                return Ok(());
-            } else {
-                return Err(anyhow!("Unable to open CSV reader for filtered export"));
+        // The current page is already in memory; no need to touch the file
+        if scope == ExportScope::CurrentPage {
+            for row in &fp.preview_rows {
+                fp.write_output_row(&mut wtr, &order, |ci| row.get(ci).map(String::as_bytes))?;
+                progress.tick()?;
             }
+            wtr.flush().map_err(|e| anyhow!("Flush failed: {e}"))?;
+            return Ok(());
         }
-        // If only_filtered is requested but no filters are active, fall through to export all rows.
 
-        // No active filters (or exporting all rows): stream everything
-        if let Ok(mut rdr) = cfg.reader() {
-            // Write remaining rows as-is
-            for rec_res in rdr.records() {
-                let rec = rec_res.map_err(|e| anyhow!("Row read error: {e}"))?;
-                wtr.write_record(rec.iter())?;
-            }
-            wtr.flush().map_err(|e| anyhow!("Flush failed: {e}"))?;
-            Ok(())
-        } else {
-            Err(anyhow!("Unable to open CSV reader"))
+        Self::for_each_row(&fp.config(), Self::export_rows(fp, scope), |brec| {
+            fp.write_output_row(&mut wtr, &order, |ci| brec.get(ci))?;
+            progress.tick()
+        })?;
+        wtr.flush().map_err(|e| anyhow!("Flush failed: {e}"))?;
+        Ok(())
+    }
+
+    /// Rows to export from the file for `scope`, None for all of them: the filtered rows
+    /// when asked for and a filter is active, else every row.
+    fn export_rows(fp: &FilePreview, scope: ExportScope) -> Option<&[u64]> {
+        match scope {
+            ExportScope::FilteredRows => fp.filtered_indices.as_deref(),
+            _ => None,
         }
     }
 
-    /// Create a temporary CSV (UTF-8, comma-delimited) with the current (optionally filtered) rows.
-    fn make_temp_csv_for_current(&self, only_filtered: bool) -> anyhow::Result<PathBuf> {
-        let Some(fp) = self.current_fp() else {
-            return Err(anyhow!("Cannot create temp CSV: no file selected"));
-        };
+    /// Create a temporary CSV (UTF-8, comma-delimited) with the file's rows in `scope`.
+    fn make_temp_csv(
+        fp: &FilePreview,
+        scope: ExportScope,
+        progress: &ExportProgress,
+    ) -> anyhow::Result<PathBuf> {
         // Build a temp filename that carries a human-readable stem and .csv suffix,
         // so downstream converters have a resource name/title.
         let base = crate::util::display_name(&fp.file_path);
@@ -1047,8 +3475,13 @@ impl TableEditor {
             .suffix(".csv")
             .tempfile()?;
         {
-            let wtr = Writer::from_writer(&mut tmp);
-            self.write_rows_to_csv_writer(fp, wtr, only_filtered)?;
+            // always comma-delimited, as the xlsx/ods/parquet converters expect
+            let wtr = core_util::csv_writer(
+                Some(tmp.path()),
+                Some(Delimiter(b',')),
+                csv::QuoteStyle::Necessary,
+            )?;
+            Self::write_rows_to_csv_writer(fp, wtr, scope, progress)?;
         }
         let path = tmp.into_temp_path();
         // Persist the file so it survives once the NamedTempFile is dropped.
@@ -1056,138 +3489,110 @@ impl TableEditor {
         Ok(kept)
     }
 
-    /// Write rows (filtered or all) of the current file to a JSON writer as an array of objects.
+    /// Write the rows in `scope` of a file to a JSON writer as an array of objects,
+    /// or as one object per line with no enclosing array when `ndjson` is set.
     fn write_rows_to_json_writer<W: std::io::Write>(
-        &self,
         fp: &FilePreview,
         mut out: W,
-        only_filtered: bool,
+        scope: ExportScope,
+        ndjson: bool,
+        progress: &ExportProgress,
     ) -> anyhow::Result<()> {
-        let headers: Vec<&str> = fp.headers.iter().map(|u| u.as_str()).collect();
-        let path_str = fp.file_path.to_string();
-        let cfg = Config::builder().path(&path_str).build();
+        let order = fp.display_columns();
+        let headers = fp.output_headers(&order);
+        // derived columns are always plain strings
+        let as_json: Vec<bool> = order
+            .iter()
+            .map(|ci| fp.json_columns.contains(ci))
+            .chain(fp.derived_columns.iter().map(|_| false))
+            .collect();
+        let cfg = fp.config();
 
         // helper to emit one object
         let mut first = true;
-        write!(&mut out, "[")?;
+        if !ndjson {
+            write!(&mut out, "[")?;
+        }
         let mut emit_obj = |vals: &[String]| -> anyhow::Result<()> {
-            if !first {
+            progress.tick()?;
+            if !first && !ndjson {
                 write!(&mut out, ",")?;
             }
             first = false;
             let mut obj = JsonMap::with_capacity(headers.len());
             for (i, key) in headers.iter().enumerate() {
                 let v = vals.get(i).map(|s| s.as_str()).unwrap_or("");
-                obj.insert((*key).to_string(), JsonValue::String(v.to_string()));
+                obj.insert((*key).to_string(), Self::export_json_value(v, as_json[i]));
             }
             serde_json::to_writer(&mut out, &JsonValue::Object(obj))?;
+            if ndjson {
+                writeln!(&mut out)?;
+            }
             Ok(())
         };
 
-        // If we have filtered indices and only_filtered is true, restrict to them; else stream all
-        // rows.
-        if only_filtered && let Some(ref filt) = fp.filtered_indices {
-            if let Ok(Some(mut idx)) = cfg.indexed() {
-                // iterate contiguous chunks (mirrors paging logic)
-                let slice = &filt[..];
-                let mut i = 0usize;
-                while i < slice.len() {
-                    let base = slice[i];
-                    let mut len = 1usize;
-                    while i + len < slice.len() && slice[i + len] == base + len as u64 {
-                        len += 1;
-                    }
-                    idx.seek(base)
-                        .map_err(|e| anyhow!("Index seek error: {e}"))?;
-                    for rec_res in idx.byte_records().take(len) {
-                        let brec = rec_res?;
-                        let mut vals: Vec<String> =
-                            Vec::with_capacity(headers.len().max(brec.len()));
-                        vals.extend((0..headers.len()).map(|ci| {
-                            brec.get(ci)
-                                .map(|b| String::from_utf8_lossy(b).into_owned())
-                                .unwrap_or_default()
-                        }));
-                        emit_obj(&vals)?;
-                    }
-                    i += len;
-                }
-                write!(&mut out, "]")?;
-                out.flush()?;
-                return Ok(());
+        // The current page is already in memory
+        if scope == ExportScope::CurrentPage {
+            for row in &fp.preview_rows {
+                let mut vals: Vec<String> = order
+                    .iter()
+                    .map(|&ci| row.get(ci).cloned().unwrap_or_default())
+                    .collect();
+                vals.extend(fp.derived_values(|ci| row.get(ci).map(String::as_bytes)));
+                emit_obj(&vals)?;
             }
-            // Fallback: stream from reader and pick wanted rows
-            if let Ok(mut rdr) = cfg.reader() {
-                // Ensure header is consumed
-                let _ = rdr.headers();
-                let mut wanted_iter = filt.iter().copied();
-                let mut next = wanted_iter.next();
-                for (ri, rec_res) in rdr.records().enumerate() {
-                    match next {
-                        Some(want) if ri as u64 == want => {
-                            let rec = rec_res?;
-                            let mut vals: Vec<String> =
-                                Vec::with_capacity(headers.len().max(rec.len()));
-                            vals.extend(
-                                (0..headers.len()).map(|ci| rec.get(ci).unwrap_or("").to_string()),
-                            );
-                            emit_obj(&vals)?;
-                            next = wanted_iter.next();
-                            if next.is_none() {
-                                break;
-                            }
-                        },
-                        Some(want) if (ri as u64) < want => continue,
-                        _ => {
-                            if next.is_none() {
-                                break;
-                            }
-                        },
-                    }
-                }
+            if !ndjson {
                 write!(&mut out, "]")?;
-                out.flush()?;
-                return Ok(());
-            } else {
-                return Err(anyhow!("Unable to open CSV reader for filtered export"));
             }
+            out.flush()?;
+            return Ok(());
         }
 
-        // No active filters (or exporting all rows): stream everything
-        if let Ok(mut rdr) = cfg.reader() {
-            for rec_res in rdr.records() {
-                let rec = rec_res?;
-                let mut vals: Vec<String> = Vec::with_capacity(headers.len().max(rec.len()));
-                vals.extend((0..headers.len()).map(|ci| rec.get(ci).unwrap_or("").to_string()));
-                emit_obj(&vals)?;
-            }
+        Self::for_each_row(&cfg, Self::export_rows(fp, scope), |brec| {
+            let mut vals: Vec<String> = Vec::with_capacity(headers.len());
+            vals.extend(order.iter().map(|&ci| {
+                brec.get(ci)
+                    .map(|b| String::from_utf8_lossy(b).into_owned())
+                    .unwrap_or_default()
+            }));
+            vals.extend(fp.derived_values(|ci| brec.get(ci)));
+            emit_obj(&vals)
+        })?;
+        if !ndjson {
             write!(&mut out, "]")?;
-            out.flush()?;
-            Ok(())
-        } else {
-            Err(anyhow!("Unable to open CSV reader"))
         }
+        out.flush()?;
+        Ok(())
     }
 
-    fn export_current_to_json_path(&self, dest: &Path, only_filtered: bool) -> anyhow::Result<()> {
-        let Some(fp) = self.current_fp() else {
-            return Err(anyhow!("Cannot export JSON: no file selected"));
-        };
-        let mut file = std::fs::File::create(dest)?;
-        self.write_rows_to_json_writer(fp, &mut file, only_filtered)
+    fn export_to_json_path(
+        fp: &FilePreview,
+        dest: &Path,
+        scope: ExportScope,
+        ndjson: bool,
+        progress: &ExportProgress,
+    ) -> anyhow::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(dest)?);
+        Self::write_rows_to_json_writer(fp, file, scope, ndjson, progress)
     }
 
-    fn export_current_to_csv_path(&self, dest: &Path, only_filtered: bool) -> anyhow::Result<()> {
-        let Some(fp) = self.current_fp() else {
-            return Err(anyhow!("Cannot export CSV: no file selected"));
-        };
-        let file = std::fs::File::create(dest)?;
-        let wtr = Writer::from_writer(file);
-        self.write_rows_to_csv_writer(fp, wtr, only_filtered)
+    fn export_to_csv_path(
+        fp: &FilePreview,
+        dest: &Path,
+        scope: ExportScope,
+        progress: &ExportProgress,
+    ) -> anyhow::Result<()> {
+        let wtr = core_util::csv_writer(Some(dest), None, csv::QuoteStyle::Necessary)?;
+        Self::write_rows_to_csv_writer(fp, wtr, scope, progress)
     }
 
-    fn export_current_to_xlsx_path(&self, dest: &Path, only_filtered: bool) -> anyhow::Result<()> {
-        let temp_csv = self.make_temp_csv_for_current(only_filtered)?;
+    fn export_to_xlsx_path(
+        fp: &FilePreview,
+        dest: &Path,
+        scope: ExportScope,
+        progress: &ExportProgress,
+    ) -> anyhow::Result<()> {
+        let temp_csv = Self::make_temp_csv(fp, scope, progress)?;
         let options = Options::builder()
             .delimiter(Some(b',')) // we wrote comma-delimited temp CSV
             .threads(1)
@@ -1198,8 +3603,13 @@ impl TableEditor {
         Ok(())
     }
 
-    fn export_current_to_ods_path(&self, dest: &Path, only_filtered: bool) -> anyhow::Result<()> {
-        let temp_csv = self.make_temp_csv_for_current(only_filtered)?;
+    fn export_to_ods_path(
+        fp: &FilePreview,
+        dest: &Path,
+        scope: ExportScope,
+        progress: &ExportProgress,
+    ) -> anyhow::Result<()> {
+        let temp_csv = Self::make_temp_csv(fp, scope, progress)?;
         let options = Options::builder()
             .delimiter(Some(b',')) // we wrote comma-delimited temp CSV
             .threads(1)
@@ -1210,12 +3620,13 @@ impl TableEditor {
         Ok(())
     }
 
-    fn export_current_to_parquet_dir(
-        &self,
+    fn export_to_parquet_dir(
+        fp: &FilePreview,
         dest_dir: &Path,
-        only_filtered: bool,
+        scope: ExportScope,
+        progress: &ExportProgress,
     ) -> anyhow::Result<()> {
-        let temp_csv = self.make_temp_csv_for_current(only_filtered)?;
+        let temp_csv = Self::make_temp_csv(fp, scope, progress)?;
         let options = Options::builder()
             .delimiter(Some(b',')) // we wrote comma-delimited temp CSV
             .threads(1)
@@ -1228,6 +3639,147 @@ impl TableEditor {
         Ok(())
     }
 
+    /// Export `fp` in `format` to `dest` (a directory for Parquet). Runs on the export worker.
+    fn export_to(
+        fp: &FilePreview,
+        format: ExportFormat,
+        dest: &Path,
+        scope: ExportScope,
+        progress: &ExportProgress,
+    ) -> anyhow::Result<()> {
+        // Parquet files only reach the folder once the rows are all read
+        if format == ExportFormat::Parquet {
+            return Self::export_to_parquet_dir(fp, dest, scope, progress);
+        }
+        // write beside `dest` and move it into place when done, so a cancelled or failed
+        // export leaves no truncated file (the temp file is removed when dropped)
+        let dir = dest
+            .parent()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .unwrap_or(Path::new("."));
+        let suffix = dest
+            .extension()
+            .map(|ext| format!(".{}", ext.to_string_lossy()))
+            .unwrap_or_default();
+        let tmp = tempfile::Builder::new()
+            .prefix(".waka-export-")
+            .suffix(&suffix)
+            .tempfile_in(dir)?;
+        let part = tmp.path();
+        match format {
+            ExportFormat::Csv => Self::export_to_csv_path(fp, part, scope, progress),
+            ExportFormat::Xlsx => Self::export_to_xlsx_path(fp, part, scope, progress),
+            ExportFormat::Ods => Self::export_to_ods_path(fp, part, scope, progress),
+            ExportFormat::Json => Self::export_to_json_path(fp, part, scope, false, progress),
+            ExportFormat::Ndjson => Self::export_to_json_path(fp, part, scope, true, progress),
+            ExportFormat::Parquet => unreachable!("exported to a folder above"),
+        }?;
+        tmp.persist(dest)?;
+        Ok(())
+    }
+
+    /// Ask the user where to export to in the selected format (a folder for Parquet).
+    fn pick_export_destination(&self) -> Option<PathBuf> {
+        let (filter_name, extensions, ext): (&str, &[&str], &str) = match self.export_format {
+            ExportFormat::Csv => ("CSV", &["csv"], "csv"),
+            ExportFormat::Xlsx => ("Excel Workbook", &["xlsx"], "xlsx"),
+            ExportFormat::Ods => ("OpenDocument Spreadsheet", &["ods"], "ods"),
+            ExportFormat::Parquet => return FileDialog::new().pick_folder(),
+            ExportFormat::Json => ("JSON", &["json"], "json"),
+            ExportFormat::Ndjson => ("NDJSON", &["ndjson", "jsonl"], "ndjson"),
+        };
+        FileDialog::new()
+            .add_filter(filter_name, extensions)
+            .set_file_name(self.default_export_filename(ext))
+            .save_file()
+    }
+
+    /// Export the current file to `dest` on a worker thread. Progress is picked up
+    /// by `show_export_progress`.
+    fn start_export(&mut self, dest: PathBuf) {
+        if self.export_job.is_some() {
+            return;
+        }
+        let Some(fp) = self.current_fp().cloned() else {
+            self.export_status = Some("⚠ Export failed: no file selected".to_string());
+            return;
+        };
+        let (format, scope) = (self.export_format, self.export_scope);
+        let total_rows = match (scope, &fp.filtered_indices) {
+            (ExportScope::CurrentPage, _) => fp.preview_rows.len() as u64,
+            (ExportScope::FilteredRows, Some(filt)) => filt.len() as u64,
+            _ => fp.total_rows.unwrap_or(0),
+        };
+
+        let progress = ExportProgress::default();
+        let worker_progress = progress.clone();
+        let job = BackgroundJob::spawn("export worker", move || {
+            Self::export_to(&fp, format, &dest, scope, &worker_progress)
+        });
+        self.export_status = None;
+        self.export_job = Some(RunningExport {
+            job,
+            progress,
+            total_rows,
+            started: Instant::now(),
+        });
+    }
+
+    /// Pick up the result of a finished export and record its summary in `export_status`.
+    fn poll_export(&mut self, ctx: &egui::Context) {
+        let Some(job) = &self.export_job else {
+            return;
+        };
+        let Some(outcome) = job.job.poll(ctx) else {
+            return;
+        };
+
+        let Some(job) = self.export_job.take() else {
+            return;
+        };
+        let rows = job.progress.rows_written.load(Ordering::Relaxed);
+        let (kind, msg) = if job.progress.cancel.load(Ordering::Relaxed) {
+            (
+                ToastKind::Info,
+                format!("Export cancelled after {rows} rows"),
+            )
+        } else {
+            match outcome {
+                Ok(()) => (
+                    ToastKind::Success,
+                    format!(
+                        "Exported {rows} rows in {:.1}s",
+                        job.started.elapsed().as_secs_f32()
+                    ),
+                ),
+                Err(e) => (ToastKind::Error, format!("Export failed: {e}")),
+            }
+        };
+        self.export_status = Some(format!("{} {msg}", kind.icon()));
+        self.notify.notify(kind, msg);
+    }
+
+    /// Show a progress bar and a cancel button while an export is running.
+    pub fn show_export_progress(&mut self, ui: &mut Ui) {
+        self.poll_export(ui.ctx());
+        let Some(job) = &self.export_job else {
+            return;
+        };
+        let rows = job.progress.rows_written.load(Ordering::Relaxed);
+        let bar = if job.total_rows > 0 {
+            egui::ProgressBar::new((rows as f32 / job.total_rows as f32).min(1.0))
+                .text(format!("{rows} / {} rows", job.total_rows))
+        } else {
+            egui::ProgressBar::new(0.0)
+                .animate(true)
+                .text(format!("{rows} rows"))
+        };
+        ui.add_sized(egui::vec2(180.0, 16.0), bar);
+        if ui.button("⏹ Cancel").clicked() {
+            job.progress.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
     fn default_export_filename(&self, ext: &str) -> String {
         if let Some(fp) = self.current_fp() {
             let base = crate::util::display_name(&fp.file_path);
@@ -1237,6 +3789,30 @@ impl TableEditor {
         }
     }
 
+    /// Infer a Polars schema for the current file from its stats and save it next to the file
+    /// as `<prefix>.pschema.json`, where polars-backed loads of the file pick it up.
+    pub fn generate_schema_for_current(&mut self) {
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        let table = PathBuf::from(&fp.file_path);
+        let schema_file = core_util::pschema_path(&table);
+        let result = core_util::infer_polars_schema(None, false, &table, &schema_file);
+        let (kind, msg) = match result {
+            Ok(true) => (
+                ToastKind::Success,
+                format!("Schema saved to {}", schema_file.display()),
+            ),
+            Ok(false) => (
+                ToastKind::Error,
+                "Schema inference produced no schema".to_string(),
+            ),
+            Err(e) => (ToastKind::Error, format!("Schema inference failed: {e}")),
+        };
+        self.export_status = Some(format!("{} {msg}", kind.icon()));
+        self.notify.notify(kind, msg);
+    }
+
     /// Render the export popup anchored to `anchor`. Call this from the existing
     /// blue "Export data" button instead of creating another button here.
     pub fn show_export_popup(&mut self, ui: &mut Ui, anchor: &egui::Response) {
@@ -1259,92 +3835,67 @@ impl TableEditor {
                     ui.radio_value(&mut self.export_format, ExportFormat::Ods, "ODS");
                     ui.radio_value(&mut self.export_format, ExportFormat::Parquet, "Parquet");
                     ui.radio_value(&mut self.export_format, ExportFormat::Json, "JSON");
+                    ui.radio_value(&mut self.export_format, ExportFormat::Ndjson, "NDJSON");
                 });
                 ui.add_space(4.0);
-                ui.checkbox(&mut self.export_only_filtered, "Only export filtered rows");
+                ui.horizontal(|ui| {
+                    ui.label("Rows:");
+                    ui.radio_value(&mut self.export_scope, ExportScope::AllRows, "All");
+                    ui.radio_value(
+                        &mut self.export_scope,
+                        ExportScope::FilteredRows,
+                        "Filtered",
+                    );
+                    let sampled = self.current_fp().is_some_and(|fp| fp.sample_rows.is_some());
+                    ui.radio_value(
+                        &mut self.export_scope,
+                        ExportScope::CurrentPage,
+                        if sampled { "Sample" } else { "Current page" },
+                    );
+                });
+                if matches!(
+                    self.export_format,
+                    ExportFormat::Json | ExportFormat::Ndjson
+                ) {
+                    ui.add_space(4.0);
+                    ui.horizontal(|ui| {
+                        let n = self.current_fp().map_or(0, |fp| fp.json_columns.len());
+                        ui.label(format!("Nested JSON columns: {n}"));
+                        if ui
+                            .small_button("Detect")
+                            .on_hover_text(
+                                "Mark columns whose values on this page are JSON objects/arrays",
+                            )
+                            .clicked()
+                            && let Some(fp) = self.current_fp_mut()
+                        {
+                            let added = Self::detect_json_columns(fp);
+                            self.export_status = Some(format!("Detected {added} JSON column(s)"));
+                        }
+                        if n > 0
+                            && ui.small_button("Clear").clicked()
+                            && let Some(fp) = self.current_fp_mut()
+                        {
+                            fp.json_columns.clear();
+                        }
+                    });
+                }
                 ui.add_space(6.0);
 
-                if let Some(msg) = &self.export_status {
-                    ui.label(RichText::new(msg.as_str()).color(Color32::from_rgb(160, 200, 160)));
+                if self.export_job.is_some() {
+                    ui.horizontal(|ui| self.show_export_progress(ui));
+                    ui.add_space(6.0);
+                } else if let Some(msg) = &self.export_status {
+                    ui.label(RichText::new(msg.as_str()).color(ui.visuals().weak_text_color()));
                     ui.add_space(6.0);
                 }
 
                 ui.horizontal(|ui| {
-                    if ui.button("Save As…").clicked() {
-                        // Dispatch per selected format
-                        let result: anyhow::Result<()> = match self.export_format {
-                            ExportFormat::Csv => {
-                                if let Some(path) = FileDialog::new()
-                                    .add_filter("CSV", &["csv"])
-                                    .set_file_name(self.default_export_filename("csv"))
-                                    .save_file()
-                                {
-                                    self.export_current_to_csv_path(
-                                        &path,
-                                        self.export_only_filtered,
-                                    )
-                                } else {
-                                    Ok(())
-                                }
-                            },
-                            ExportFormat::Xlsx => {
-                                if let Some(path) = FileDialog::new()
-                                    .add_filter("Excel Workbook", &["xlsx"])
-                                    .set_file_name(self.default_export_filename("xlsx"))
-                                    .save_file()
-                                {
-                                    self.export_current_to_xlsx_path(
-                                        &path,
-                                        self.export_only_filtered,
-                                    )
-                                } else {
-                                    Ok(())
-                                }
-                            },
-                            ExportFormat::Ods => {
-                                if let Some(path) = FileDialog::new()
-                                    .add_filter("OpenDocument Spreadsheet", &["ods"])
-                                    .set_file_name(self.default_export_filename("ods"))
-                                    .save_file()
-                                {
-                                    self.export_current_to_ods_path(
-                                        &path,
-                                        self.export_only_filtered,
-                                    )
-                                } else {
-                                    Ok(())
-                                }
-                            },
-                            ExportFormat::Parquet => {
-                                if let Some(dir) = FileDialog::new().pick_folder() {
-                                    self.export_current_to_parquet_dir(
-                                        &dir,
-                                        self.export_only_filtered,
-                                    )
-                                } else {
-                                    Ok(())
-                                }
-                            },
-                            ExportFormat::Json => {
-                                if let Some(path) = FileDialog::new()
-                                    .add_filter("JSON", &["json"])
-                                    .set_file_name(self.default_export_filename("json"))
-                                    .save_file()
-                                {
-                                    self.export_current_to_json_path(
-                                        &path,
-                                        self.export_only_filtered,
-                                    )
-                                } else {
-                                    Ok(())
-                                }
-                            },
-                        };
-
-                        self.export_status = Some(match result {
-                            Ok(()) => "✅ Export complete".to_string(),
-                            Err(e) => format!("⚠ Export failed: {e}"),
-                        });
+                    let idle = self.export_job.is_none();
+                    if ui.add_enabled(idle, Button::new("Save As…")).clicked()
+                        && let Some(dest) = self.pick_export_destination()
+                    {
+                        self.start_export(dest);
                     }
                     if ui.button("Close").clicked() {
                         egui::Popup::close_id(ui.ctx(), popup_id);
@@ -1353,12 +3904,53 @@ impl TableEditor {
             });
     }
 
+    /// Value for one exported cell: nested JSON for object/array text in a "parse as JSON"
+    /// column, a plain string otherwise (including malformed JSON).
+    fn export_json_value(v: &str, parse: bool) -> JsonValue {
+        if parse {
+            let t = v.trim_start();
+            if (t.starts_with('{') || t.starts_with('['))
+                && let Ok(val @ (JsonValue::Object(_) | JsonValue::Array(_))) =
+                    serde_json::from_str(v)
+            {
+                return val;
+            }
+        }
+        JsonValue::String(v.to_string())
+    }
+
+    /// Mark every column whose non-empty values on the current page all parse as JSON
+    /// objects or arrays for nested export. Returns how many columns were added.
+    fn detect_json_columns(fp: &mut FilePreview) -> usize {
+        let mut added = 0;
+        for ci in 0..fp.headers.len() {
+            let mut seen = false;
+            let all_json = fp.preview_rows.iter().all(|row| {
+                let v = row.get(ci).map(|s| s.trim()).unwrap_or("");
+                if v.is_empty() {
+                    return true;
+                }
+                seen = true;
+                matches!(
+                    Self::export_json_value(v, true),
+                    JsonValue::Object(_) | JsonValue::Array(_)
+                )
+            });
+            if seen && all_json && fp.json_columns.insert(ci) {
+                added += 1;
+            }
+        }
+        added
+    }
+
     /// Handle a sort click for the given column and sort direction.
     fn on_sort_click(&mut self, col: usize, desc: bool) {
         // Record chosen sort in the active file and compute indices using waka_core::sort
-        let (path, file_idx);
+        let (path, delimiter, cfg, file_idx);
         if let Some(fp) = self.current_fp() {
             path = fp.file_path.clone();
+            delimiter = fp.delimiter.map(Delimiter);
+            cfg = fp.config();
             file_idx = self.current_file;
         } else {
             return;
@@ -1366,16 +3958,18 @@ impl TableEditor {
 
         // Update flags on the active FilePreview first
         if let Some(fp_mut) = self.current_fp_mut() {
+            fp_mut.sample_rows = None;
             fp_mut.sort_col = Some(col);
             fp_mut.sort_desc = desc;
         }
 
         // Infer sensible defaults for comparator style
-        let (numeric, natural, ignore_case) = self.infer_sort_prefs_for_col(&path, col);
+        let (numeric, natural, ignore_case) = self.infer_sort_prefs_for_col(&cfg, col);
 
         // Use the core library sorter to compute a permutation of data-row indices
         match sort::sort_indices_single_col(
             path.as_str(),
+            delimiter,
             col,
             numeric,
             natural,
@@ -1400,15 +3994,28 @@ impl TableEditor {
     }
 
     pub fn show_pagination_controls(&mut self, ui: &mut Ui) {
+        if let Some(n) = self
+            .current_fp()
+            .and_then(|fp| fp.sample_rows.as_ref())
+            .map(Vec::len)
+        {
+            ui.horizontal(|ui| {
+                ui.label(format!("🎲 Random sample of {n} rows"));
+                if ui
+                    .button("✕ Back to pages")
+                    .on_hover_text("Leave sample mode")
+                    .clicked()
+                {
+                    self.clear_sample();
+                }
+            });
+            return;
+        }
         {
             // Pull current values without holding a mutable borrow during UI
-            let (mut page, total_rows) = match self.current_fp() {
-                Some(fp) => {
-                    let filtered = fp.filtered_indices.as_ref().map(|v| v.len());
-                    let base_total = fp.total_rows.unwrap_or(self.toal_rows as u64) as usize;
-                    (fp.page, filtered.unwrap_or(base_total))
-                },
-                None => return,
+            let (mut page, total_rows) = match (self.current_fp(), self.current_visible_rows()) {
+                (Some(fp), Some(total_rows)) => (fp.page, total_rows),
+                _ => return,
             };
             let rows_per_page = self.rows_per_page;
             let total_pages = if rows_per_page == 0 {
@@ -1416,6 +4023,7 @@ impl TableEditor {
             } else {
                 total_rows.div_ceil(rows_per_page)
             };
+            let rows_known = self.rows_known();
 
             let mut reload_needed = false;
 
@@ -1429,11 +4037,16 @@ impl TableEditor {
                     page = page.saturating_sub(1);
                     reload_needed = true;
                 }
-                ui.label(format!(
-                    "Page {}/{}",
-                    if total_pages == 0 { 0 } else { page + 1 },
-                    total_pages.max(1)
-                ));
+                if rows_known {
+                    ui.label(format!(
+                        "Page {}/{}",
+                        if total_pages == 0 { 0 } else { page + 1 },
+                        total_pages.max(1)
+                    ));
+                } else {
+                    ui.label(format!("Page {}/…", page + 1))
+                        .on_hover_text("Counting rows in the background");
+                }
                 if ui
                     .add_enabled(page + 1 < total_pages, Button::new("Next ▶"))
                     .clicked()
@@ -1442,7 +4055,7 @@ impl TableEditor {
                     reload_needed = true;
                 }
                 if ui
-                    .add_enabled(page + 1 < total_pages, Button::new("Last ⏭"))
+                    .add_enabled(rows_known && page + 1 < total_pages, Button::new("Last ⏭"))
                     .clicked()
                 {
                     if total_pages > 0 {
@@ -1453,23 +4066,39 @@ impl TableEditor {
 
                 ui.separator();
 
-                // Rows per page controls
+                // Rows per page controls (read-only while fitting to the window)
                 ui.label("Rows/page:");
                 let mut rpp_changed = false;
-                if ui.button("–").clicked() && self.rows_per_page > 5 {
+                let manual = !self.fit_to_window;
+                if ui.add_enabled(manual, Button::new("–")).clicked() && self.rows_per_page > 5 {
                     self.rows_per_page = (self.rows_per_page - 5).max(5);
                     rpp_changed = true;
                 }
                 let mut rpp = self.rows_per_page as i64;
-                let r = ui.add(DragValue::new(&mut rpp).range(5..=5000).speed(1));
+                let r = ui.add_enabled(manual, DragValue::new(&mut rpp).range(5..=5000).speed(1));
                 if r.changed() {
                     self.rows_per_page = rpp as usize;
                     rpp_changed = true;
                 }
-                if ui.button("+").clicked() {
+                if ui.add_enabled(manual, Button::new("+")).clicked() {
                     self.rows_per_page = (self.rows_per_page + 5).min(5000);
                     rpp_changed = true;
                 }
+                for preset in Self::ROWS_PER_PAGE_PRESETS {
+                    if ui
+                        .selectable_label(
+                            manual && self.rows_per_page == preset,
+                            preset.to_string(),
+                        )
+                        .clicked()
+                    {
+                        self.fit_to_window = false;
+                        self.rows_per_page = preset;
+                        rpp_changed = true;
+                    }
+                }
+                ui.toggle_value(&mut self.fit_to_window, "⇕ Fit")
+                    .on_hover_text("Show as many rows per page as fit in the window");
 
                 if rpp_changed {
                     page = 0;
@@ -1477,8 +4106,44 @@ impl TableEditor {
                 }
 
                 ui.separator();
-                ui.label(format!("Rows: {}", total_rows));
+                if rows_known {
+                    ui.label(format!("Rows: {}", total_rows));
+                } else {
+                    ui.spinner();
+                    ui.label("Counting rows…");
+                }
                 ui.separator();
+
+                // Go to row: accepts digit grouping like 482,931
+                ui.label("Go to row:");
+                let input = ui.add(
+                    TextEdit::singleline(&mut self.goto_row)
+                        .hint_text("row #")
+                        .desired_width(80.0),
+                );
+                let submitted = input.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                if (ui.button("Go").clicked() || submitted) && !self.goto_row.trim().is_empty() {
+                    let digits: String = self
+                        .goto_row
+                        .chars()
+                        .filter(|c| !matches!(c, ',' | '_' | ' ' | '.'))
+                        .collect();
+                    self.goto_error = match digits.parse::<u64>() {
+                        Ok(row) => self.go_to_row(row).err(),
+                        Err(_) => Some(format!("\"{}\" is not a row number", self.goto_row.trim())),
+                    };
+                    // go_to_row reloaded the page itself
+                    if self.goto_error.is_none() {
+                        page = self.current_fp().map_or(0, |fp| fp.page);
+                    }
+                }
+                if let Some(err) = &self.goto_error {
+                    ui.label(
+                        RichText::new(err.as_str())
+                            .color(ui.visuals().error_fg_color)
+                            .size(11.0),
+                    );
+                }
                 // Removed: self.show_export_controls(ui);
             });
 
@@ -1493,6 +4158,63 @@ impl TableEditor {
         }
     }
 
+    /// Compact bar chart of a column's most frequent values.
+    fn show_value_counts(ui: &mut Ui, col: usize, vc: &ValueCounts) {
+        if vc.top.is_empty() {
+            return;
+        }
+        let title = if vc.complete {
+            "📊 Top values"
+        } else {
+            "📊 Top values (sampled)"
+        };
+        egui::CollapsingHeader::new(RichText::new(title).size(11.0))
+            .id_salt(("top_values", col))
+            .default_open(true)
+            .show(ui, |ui| {
+                let max = vc.top.first().map_or(1, |(_, n)| *n).max(1);
+                let bar_color = ui.visuals().selection.bg_fill;
+                for (val, n) in &vc.top {
+                    ui.horizontal(|ui| {
+                        let label = if val.is_empty() {
+                            "(empty)"
+                        } else {
+                            val.as_str()
+                        };
+                        ui.add_sized(
+                            egui::vec2(90.0, 14.0),
+                            egui::Label::new(RichText::new(label).size(11.0)).truncate(),
+                        )
+                        .on_hover_text(label);
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(80.0, 10.0), egui::Sense::hover());
+                        let width = rect.width() * (*n as f32 / max as f32);
+                        ui.painter().rect_filled(
+                            egui::Rect::from_min_size(rect.min, egui::vec2(width, rect.height())),
+                            CornerRadius::same(2),
+                            bar_color,
+                        );
+                        ui.label(RichText::new(n.to_string()).size(11.0));
+                    });
+                }
+            });
+    }
+
+    // Detect whether a column is numeric (sampled), to offer the range filter
+    fn ensure_numeric_for_col(&mut self, col: usize) {
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        if fp.filters.get(col).is_none_or(|f| f.is_numeric.is_some()) {
+            return;
+        }
+        let cfg = fp.config();
+        let (numeric, _, _) = self.infer_sort_prefs_for_col(&cfg, col);
+        if let Some(f) = self.current_fp_mut().and_then(|fp| fp.filters.get_mut(col)) {
+            f.is_numeric = Some(numeric);
+        }
+    }
+
     // Populate the unique values cache for a column (uses index if available)
     fn ensure_distinct_for_col(&mut self, col: usize) {
         let Some(fp) = self.current_fp_mut() else {
@@ -1510,39 +4232,103 @@ impl TableEditor {
             return;
         }
 
-        let path_str = fp.file_path.to_string();
-        let cfg = Config::builder().path(&path_str).build();
-        let mut set: BTreeSet<String> = BTreeSet::new();
-        let limit = 2_000usize; // keep menus snappy
+        // sample to keep menus snappy; a full scan runs on a worker via start_distinct
+        let (counts, complete) = Self::count_distinct(&fp.config(), col, 2_000);
+        let all_distinct = fp.filters.get(col).is_some_and(|f| f.all_distinct);
+        let distinct: Vec<String> = counts.keys().cloned().collect();
+        if let Some(f) = fp.filters.get_mut(col) {
+            f.distinct_cache = Some(distinct);
+            f.value_counts = Some(ValueCounts::from_counts(counts, complete));
+        }
+        if all_distinct && !complete {
+            self.start_distinct(col);
+        }
+    }
+
+    /// Count every distinct value of a column of the active file on a worker thread;
+    /// `poll_distincts` replaces the sampled values with them once done.
+    fn start_distinct(&mut self, col: usize) {
+        let Some(fp) = self.current_fp() else {
+            return;
+        };
+        if self
+            .distinct_jobs
+            .iter()
+            .any(|job| job.file_path == fp.file_path && job.col == col)
+        {
+            return;
+        }
+        let file_path = fp.file_path.clone();
+        let cfg = fp.config();
+        let job = BackgroundJob::spawn("distinct values", move || {
+            Ok(Self::count_distinct(&cfg, col, usize::MAX))
+        });
+        self.distinct_jobs.push(RunningDistinct {
+            file_path,
+            col,
+            job,
+        });
+    }
+
+    /// Swap the full distinct values of finished scans into their column filters.
+    pub fn poll_distincts(&mut self, ctx: &egui::Context) {
+        for (scan, res) in job::take_finished(&mut self.distinct_jobs, ctx, |scan| &scan.job) {
+            let Some(f) = self
+                .files
+                .iter_mut()
+                .find(|fp| fp.file_path == scan.file_path)
+                .and_then(|fp| fp.filters.get_mut(scan.col))
+            else {
+                continue;
+            };
+            match res {
+                Ok((counts, complete)) => {
+                    f.distinct_cache = Some(counts.keys().cloned().collect());
+                    f.value_counts = Some(ValueCounts::from_counts(counts, complete));
+                },
+                Err(e) => {
+                    f.all_distinct = false;
+                    self.notify.error(format!("Failed to load all values: {e}"));
+                },
+            }
+        }
+    }
+
+    /// Count the values of column `col` until `limit` distinct ones are seen (uses the index
+    /// if available). The flag is false when the limit cut the scan short.
+    fn count_distinct(cfg: &Config, col: usize, limit: usize) -> (BTreeMap<String, u64>, bool) {
+        // count values as we go, so the top values chart needs no second pass
+        let mut counts: BTreeMap<String, u64> = BTreeMap::new();
+        let mut complete = true;
 
         if let Ok(Some(mut idx)) = cfg.indexed() {
-            for rec_res in idx.byte_records() {
+            for rec_res in cfg.byte_records(&mut idx) {
                 if let Ok(brec) = rec_res
                     && let Some(val) = brec.get(col)
                 {
-                    set.insert(String::from_utf8_lossy(val).to_string());
-                    if set.len() >= limit {
+                    *counts
+                        .entry(String::from_utf8_lossy(val).to_string())
+                        .or_default() += 1;
+                    if counts.len() >= limit {
+                        complete = false;
                         break;
                     }
                 }
             }
         } else if let Ok(mut rdr) = cfg.reader() {
-            for rec_res in rdr.records() {
+            for rec_res in cfg.records(&mut rdr) {
                 if let Ok(rec) = rec_res
                     && let Some(val) = rec.get(col)
                 {
-                    set.insert(val.into());
-                    if set.len() >= limit {
+                    *counts.entry(val.into()).or_default() += 1;
+                    if counts.len() >= limit {
+                        complete = false;
                         break;
                     }
                 }
             }
         }
-
-        let distinct: Vec<String> = set.into_iter().collect();
-        if let Some(f) = fp.filters.get_mut(col) {
-            f.distinct_cache = Some(distinct);
-        }
+        (counts, complete)
     }
 
     pub fn apply_filters_for_current_file(&mut self) {
@@ -1552,6 +4338,7 @@ impl TableEditor {
         if fp.filters.is_empty() {
             return;
         }
+        fp.sample_rows = None;
 
         // Build active filters once (avoid per-row allocations)
         // We'll prepare two variants: one optimized for byte records (index),
@@ -1561,20 +4348,17 @@ impl TableEditor {
             set:   Option<HashSet<Vec<u8>>>, // normalized per case-insensitive flag
             casei: bool,
             regex: Option<Regex>,
+            range: Option<NumRange>,
         }
 
-        let active_any = fp
-            .filters
-            .iter()
-            .any(|f| !f.selected.is_empty() || f.use_regex);
+        let active_any = fp.filters.iter().any(ColumnFilter::is_active);
         if !active_any {
             fp.filtered_indices = None;
             fp.page = 0;
             return;
         }
 
-        let path_str = fp.file_path.to_string();
-        let cfg = Config::builder().path(&path_str).build();
+        let cfg = fp.config();
         let mut out: Vec<u64> = Vec::new();
 
         // Try fast byte-indexed path
@@ -1582,7 +4366,7 @@ impl TableEditor {
             // Prepare ActiveBytes using byte-normalized sets
             let mut active_b: Vec<ActiveBytes> = Vec::with_capacity(fp.filters.len());
             for (i, f) in fp.filters.iter().enumerate() {
-                if f.selected.is_empty() && !f.use_regex {
+                if !f.is_active() {
                     continue;
                 }
                 let set = if !f.selected.is_empty() {
@@ -1600,6 +4384,7 @@ impl TableEditor {
                     set,
                     casei: f.case_insensitive,
                     regex: rx,
+                    range: f.range(),
                 });
             }
 
@@ -1611,7 +4396,7 @@ impl TableEditor {
 
             // Scan using byte records; do membership checks on &[u8] to avoid String allocations.
             let mut scratch: Vec<u8> = Vec::new();
-            for (ri, rec_res) in idx.byte_records().enumerate() {
+            for (ri, rec_res) in cfg.byte_records(&mut idx).enumerate() {
                 let Ok(brec) = rec_res else { continue };
                 let mut keep = true;
                 for af in active_b.iter() {
@@ -1644,6 +4429,14 @@ impl TableEditor {
                             break;
                         }
                     }
+
+                    // Numeric range
+                    if let Some(range) = af.range.as_ref()
+                        && !range.matches_bytes(val_bytes)
+                    {
+                        keep = false;
+                        break;
+                    }
                 }
                 if keep {
                     out.push(ri as u64);
@@ -1657,9 +4450,15 @@ impl TableEditor {
 
         // Fallback: stream with CSV reader (string records).
         // Keep the existing normalization logic for correctness (Unicode-aware via util::norm).
-        let mut active: Vec<(usize, HashSet<String>, bool /* casei */, Option<Regex>)> = Vec::new();
+        let mut active: Vec<(
+            usize,
+            HashSet<String>,
+            bool, // casei
+            Option<Regex>,
+            Option<NumRange>,
+        )> = Vec::new();
         for (i, f) in fp.filters.iter().enumerate() {
-            if f.selected.is_empty() && !f.use_regex {
+            if !f.is_active() {
                 continue;
             }
             let casei = f.case_insensitive;
@@ -1678,7 +4477,7 @@ impl TableEditor {
                 None
             };
 
-            active.push((i, set, casei, rx));
+            active.push((i, set, casei, rx, f.range()));
         }
 
         if active.is_empty() {
@@ -1688,10 +4487,10 @@ impl TableEditor {
         }
 
         if let Ok(mut rdr) = cfg.reader() {
-            for (ri, rec_res) in rdr.records().enumerate() {
+            for (ri, rec_res) in cfg.records(&mut rdr).enumerate() {
                 if let Ok(rec) = rec_res {
                     let mut keep = true;
-                    for (col, set, casei, rx) in active.iter() {
+                    for (col, set, casei, rx, range) in active.iter() {
                         let val = rec.get(*col).unwrap_or("");
                         // If there are selected values, enforce membership
                         if !set.is_empty() {
@@ -1708,6 +4507,13 @@ impl TableEditor {
                             keep = false;
                             break;
                         }
+                        // If a numeric range is active, the value must parse and fall within it
+                        if let Some(range) = range
+                            && !range.matches(val)
+                        {
+                            keep = false;
+                            break;
+                        }
                     }
                     if keep {
                         out.push(ri as u64);
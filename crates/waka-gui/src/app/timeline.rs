@@ -0,0 +1,426 @@
+use std::{
+    path::Path,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use eframe::egui::{self, ComboBox, RichText};
+use egui_extras::{Column, TableBuilder};
+use epaint::Color32;
+use rfd::FileDialog;
+use waka_core::{
+    config::{Config, Delimiter},
+    util as core_util,
+};
+
+use crate::{
+    app::{job::BackgroundJob, table::FilePreview, toast::Notifier},
+    util,
+};
+
+/// Colors cycled through for the sources of a timeline.
+const SOURCE_COLORS: [Color32; 8] = [
+    Color32::from_rgb(86, 156, 214),
+    Color32::from_rgb(206, 145, 120),
+    Color32::from_rgb(106, 153, 85),
+    Color32::from_rgb(197, 134, 192),
+    Color32::from_rgb(220, 220, 170),
+    Color32::from_rgb(78, 201, 176),
+    Color32::from_rgb(244, 71, 71),
+    Color32::from_rgb(181, 206, 168),
+];
+
+const ROW_HEIGHT: f32 = 20.0;
+
+fn source_color(source: usize) -> Color32 {
+    SOURCE_COLORS[source % SOURCE_COLORS.len()]
+}
+
+/// Timestamp and label columns picked for one open file.
+struct TimelineSource {
+    file_path: String,
+    headers:   Vec<String>,
+    delimiter: Option<u8>,
+    enabled:   bool,
+    ts_col:    Option<usize>,
+    label_col: Option<usize>, // None = use the timestamp column's name
+}
+
+impl TimelineSource {
+    fn new(fp: &FilePreview) -> Self {
+        // Preselect the first column that looks like it holds timestamps
+        let ts_col = fp.headers.iter().position(|h| {
+            let h = h.to_ascii_lowercase();
+            h.contains("time") || h.contains("date")
+        });
+        Self {
+            file_path: fp.file_path.clone(),
+            headers: fp.headers.clone(),
+            delimiter: fp.delimiter,
+            enabled: ts_col.is_some(),
+            ts_col,
+            label_col: None,
+        }
+    }
+
+    fn column_name(&self, col: Option<usize>) -> &str {
+        col.and_then(|c| self.headers.get(c))
+            .map_or("(none)", String::as_str)
+    }
+}
+
+/// One row of the merged timeline.
+struct TimelineEvent {
+    ts_ms:  i64,
+    source: usize, // index into `TimelineEditor::built_names`
+    label:  String,
+    raw:    String, // the timestamp as written in the source file
+}
+
+/// Events read from the sources, merged and sorted.
+struct BuiltTimeline {
+    events:   Vec<TimelineEvent>,
+    unparsed: u64, // non-empty timestamps the date parser did not recognize
+}
+
+/// A timeline being built on a worker thread.
+struct RunningBuild {
+    job:     BackgroundJob<BuiltTimeline>,
+    cancel:  Arc<AtomicBool>,
+    started: Instant,
+}
+
+/// Merges timestamp columns of the open files into one chronological event list.
+pub struct TimelineEditor {
+    sources:     Vec<TimelineSource>,
+    prefer_dmy:  bool,
+    events:      Vec<TimelineEvent>,
+    built_names: Vec<String>, // source names at build time, indexed by `TimelineEvent::source`
+    status:      Option<String>,
+    running:     Option<RunningBuild>,
+    notify:      Notifier,
+}
+
+impl TimelineEditor {
+    pub fn new(notifier: Notifier) -> Self {
+        Self {
+            sources:     Vec::new(),
+            prefer_dmy:  false,
+            events:      Vec::new(),
+            built_names: Vec::new(),
+            status:      None,
+            running:     None,
+            notify:      notifier,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, files: &[FilePreview]) {
+        self.poll_build(ui.ctx());
+        self.sync_sources(files);
+
+        ui.vertical(|ui| {
+            ui.heading("Timeline");
+            if self.sources.is_empty() {
+                ui.label(
+                    RichText::new("Open CSV files in the Filer to merge their timestamps here")
+                        .color(ui.visuals().weak_text_color()),
+                );
+                return;
+            }
+
+            self.show_sources(ui);
+            ui.add_space(6.0);
+
+            ui.horizontal(|ui| {
+                let can_build = self.running.is_none()
+                    && self.sources.iter().any(|s| s.enabled && s.ts_col.is_some());
+                if ui
+                    .add_enabled(can_build, egui::Button::new("🕒 Build timeline"))
+                    .clicked()
+                {
+                    self.start_build();
+                }
+                if let Some(running) = &self.running {
+                    ui.spinner();
+                    ui.label(format!("{:.1}s", running.started.elapsed().as_secs_f32()));
+                    if ui.button("⏹ Stop").clicked() {
+                        running.cancel.store(true, Ordering::Relaxed);
+                    }
+                }
+                ui.checkbox(&mut self.prefer_dmy, "Prefer DMY dates")
+                    .on_hover_text("Read ambiguous dates like 02/03/2023 as day/month/year");
+                if ui
+                    .add_enabled(!self.events.is_empty(), egui::Button::new("📤 Export CSV"))
+                    .clicked()
+                {
+                    self.export_csv();
+                }
+            });
+            if let Some(msg) = &self.status {
+                ui.label(
+                    RichText::new(msg.as_str())
+                        .size(11.0)
+                        .color(ui.visuals().weak_text_color()),
+                );
+            }
+
+            ui.separator();
+            self.show_events(ui);
+        });
+    }
+
+    /// Keep one source per open file, remembering the picks for files that stay open.
+    fn sync_sources(&mut self, files: &[FilePreview]) {
+        let mut sources = Vec::with_capacity(files.len());
+        for fp in files.iter().filter(|fp| !fp.headers.is_empty()) {
+            let source = match self
+                .sources
+                .iter()
+                .position(|s| s.file_path == fp.file_path)
+            {
+                Some(i) if self.sources[i].headers == fp.headers => self.sources.swap_remove(i),
+                _ => TimelineSource::new(fp),
+            };
+            sources.push(source);
+        }
+        self.sources = sources;
+    }
+
+    fn show_sources(&mut self, ui: &mut egui::Ui) {
+        egui::Grid::new("timeline_sources")
+            .num_columns(3)
+            .spacing([12.0, 4.0])
+            .show(ui, |ui| {
+                ui.label(RichText::new("File").strong());
+                ui.label(RichText::new("Timestamp column").strong());
+                ui.label(RichText::new("Label column").strong());
+                ui.end_row();
+
+                for (i, src) in self.sources.iter_mut().enumerate() {
+                    ui.horizontal(|ui| {
+                        let (rect, _) =
+                            ui.allocate_exact_size(egui::vec2(10.0, 10.0), egui::Sense::hover());
+                        ui.painter().rect_filled(rect, 2.0, source_color(i));
+                        ui.checkbox(&mut src.enabled, util::display_name(&src.file_path));
+                    });
+
+                    let ts_text = src.column_name(src.ts_col).to_string();
+                    ComboBox::from_id_salt(("timeline_ts_col", i))
+                        .selected_text(ts_text)
+                        .width(180.0)
+                        .show_ui(ui, |ui| {
+                            for (ci, h) in src.headers.iter().enumerate() {
+                                ui.selectable_value(&mut src.ts_col, Some(ci), h.as_str());
+                            }
+                        });
+
+                    let label_text = src.column_name(src.label_col).to_string();
+                    ComboBox::from_id_salt(("timeline_label_col", i))
+                        .selected_text(label_text)
+                        .width(180.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut src.label_col, None, "(none)")
+                                .on_hover_text("Label events with the timestamp column's name");
+                            for (ci, h) in src.headers.iter().enumerate() {
+                                ui.selectable_value(&mut src.label_col, Some(ci), h.as_str());
+                            }
+                        });
+                    ui.end_row();
+                }
+            });
+    }
+
+    fn show_events(&self, ui: &mut egui::Ui) {
+        if self.events.is_empty() {
+            return;
+        }
+        TableBuilder::new(ui)
+            .id_salt("timeline_events")
+            .striped(true)
+            .resizable(true)
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::initial(230.0))
+            .column(Column::initial(180.0).clip(true))
+            .column(Column::remainder().clip(true))
+            .min_scrolled_height(0.0)
+            .header(ROW_HEIGHT, |mut header| {
+                for title in ["Timestamp (UTC)", "Source", "Label"] {
+                    header.col(|ui| {
+                        ui.strong(title);
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(ROW_HEIGHT, self.events.len(), |mut row| {
+                    let ev = &self.events[row.index()];
+                    let color = source_color(ev.source);
+                    row.col(|ui| {
+                        ui.label(core_util::timestamp_ms_to_rfc3339(ev.ts_ms))
+                            .on_hover_text(ev.raw.as_str());
+                    });
+                    row.col(|ui| {
+                        let name = self.built_names.get(ev.source).map_or("", String::as_str);
+                        ui.add(egui::Label::new(RichText::new(name).color(color)).truncate());
+                    });
+                    row.col(|ui| {
+                        ui.add(egui::Label::new(ev.label.as_str()).truncate());
+                    });
+                });
+            });
+    }
+
+    fn start_build(&mut self) {
+        if self.running.is_some() {
+            return;
+        }
+        let picks: Vec<(usize, Config, usize, Option<usize>, String)> = self
+            .sources
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| s.enabled)
+            .filter_map(|(i, s)| {
+                let ts_col = s.ts_col?;
+                let cfg = Config::builder()
+                    .path(&s.file_path)
+                    .build()
+                    .delimiter(s.delimiter.map(Delimiter));
+                let fallback_label = s.headers.get(ts_col).cloned().unwrap_or_default();
+                Some((i, cfg, ts_col, s.label_col, fallback_label))
+            })
+            .collect();
+        self.built_names = self
+            .sources
+            .iter()
+            .map(|s| util::display_name(&s.file_path))
+            .collect();
+
+        let prefer_dmy = self.prefer_dmy;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = cancel.clone();
+        let job = BackgroundJob::spawn("timeline worker", move || {
+            build_timeline(&picks, prefer_dmy, &worker_cancel)
+        });
+        self.status = None;
+        self.running = Some(RunningBuild {
+            job,
+            cancel,
+            started: Instant::now(),
+        });
+    }
+
+    fn poll_build(&mut self, ctx: &egui::Context) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        let Some(outcome) = running.job.poll(ctx) else {
+            return;
+        };
+        let elapsed = running.started.elapsed();
+        self.running = None;
+        match outcome {
+            Ok(built) => {
+                let mut msg = format!(
+                    "{} events in {:.1}s",
+                    built.events.len(),
+                    elapsed.as_secs_f32()
+                );
+                if built.unparsed > 0 {
+                    msg.push_str(&format!(
+                        "; skipped {} unrecognized timestamps",
+                        built.unparsed
+                    ));
+                }
+                self.status = Some(msg);
+                self.events = built.events;
+            },
+            Err(e) => {
+                self.status = Some(format!("⚠ Timeline build failed: {e}"));
+                self.notify.error(format!("Timeline build failed: {e}"));
+            },
+        }
+    }
+
+    fn export_csv(&mut self) {
+        let Some(dest) = FileDialog::new()
+            .add_filter("CSV", &["csv"])
+            .set_file_name("timeline.csv")
+            .save_file()
+        else {
+            return;
+        };
+        self.status = Some(match self.write_csv(&dest) {
+            Ok(()) => {
+                let msg = format!(
+                    "Exported {} events to {}",
+                    self.events.len(),
+                    dest.display()
+                );
+                self.notify.success(msg.clone());
+                msg
+            },
+            Err(e) => {
+                self.notify.error(format!("Timeline export failed: {e}"));
+                format!("⚠ Export failed: {e}")
+            },
+        });
+    }
+
+    fn write_csv(&self, dest: &Path) -> anyhow::Result<()> {
+        let mut wtr = core_util::csv_writer(Some(dest), None, csv::QuoteStyle::Necessary)?;
+        wtr.write_record(["Timestamp", "Source", "Label", "RawTimestamp"])?;
+        for ev in &self.events {
+            let source = self.built_names.get(ev.source).map_or("", String::as_str);
+            wtr.write_record([
+                core_util::timestamp_ms_to_rfc3339(ev.ts_ms).as_str(),
+                source,
+                ev.label.as_str(),
+                ev.raw.as_str(),
+            ])?;
+        }
+        wtr.flush()?;
+        Ok(())
+    }
+}
+
+/// Read the picked timestamp/label columns of every source and merge them chronologically.
+/// Ties keep source order, then file order.
+fn build_timeline(
+    picks: &[(usize, Config, usize, Option<usize>, String)],
+    prefer_dmy: bool,
+    cancel: &AtomicBool,
+) -> anyhow::Result<BuiltTimeline> {
+    let mut events = Vec::new();
+    let mut unparsed = 0u64;
+    for (source, cfg, ts_col, label_col, fallback_label) in picks {
+        let mut rdr = cfg.reader()?;
+        for rec in cfg.records(&mut rdr) {
+            if cancel.load(Ordering::Relaxed) {
+                return Err(anyhow::anyhow!("cancelled"));
+            }
+            let rec = rec?;
+            let raw = rec.get(*ts_col).unwrap_or("").trim();
+            if raw.is_empty() {
+                continue;
+            }
+            let Some(ts_ms) = core_util::parse_timestamp_ms(raw, prefer_dmy) else {
+                unparsed += 1;
+                continue;
+            };
+            let label = match label_col {
+                Some(c) => rec.get(*c).unwrap_or("").to_string(),
+                None => fallback_label.clone(),
+            };
+            events.push(TimelineEvent {
+                ts_ms,
+                source: *source,
+                label,
+                raw: raw.to_string(),
+            });
+        }
+    }
+    events.sort_by_key(|ev| ev.ts_ms);
+    Ok(BuiltTimeline { events, unparsed })
+}
@@ -0,0 +1,224 @@
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant, SystemTime},
+};
+
+use eframe::egui::{self, RichText};
+use epaint::{Color32, CornerRadius, Margin};
+use waka_core::util as core_util;
+
+/// How long a success or info toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+/// Warnings and errors stay up longer so they are not missed.
+const ERROR_TOAST_DURATION: Duration = Duration::from_secs(10);
+/// How many notifications the log keeps.
+const MAX_LOG: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ToastKind {
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastKind {
+    /// Background of a toast: the window color, tinted by kind so the text stays readable
+    /// in either theme.
+    fn fill(self, visuals: &egui::Visuals) -> Color32 {
+        let tint = match self {
+            ToastKind::Info => return visuals.window_fill,
+            ToastKind::Success => Color32::from_rgb(60, 170, 90),
+            ToastKind::Warning => visuals.warn_fg_color,
+            ToastKind::Error => visuals.error_fg_color,
+        };
+        visuals.window_fill.lerp_to_gamma(tint, 0.3)
+    }
+
+    pub fn icon(self) -> &'static str {
+        match self {
+            ToastKind::Info => "ℹ",
+            ToastKind::Success => "✅",
+            ToastKind::Warning => "⚠",
+            ToastKind::Error => "❌",
+        }
+    }
+}
+
+/// Raises notifications for the app's `Toasts` to show. Each panel keeps a clone, handed
+/// out by `Toasts::notifier`.
+#[derive(Clone)]
+pub struct Notifier {
+    tx: Sender<(ToastKind, String)>,
+}
+
+impl Notifier {
+    /// Queue a notification for the next frame.
+    pub fn notify(&self, kind: ToastKind, message: impl Into<String>) {
+        let message = message.into();
+        if matches!(kind, ToastKind::Warning | ToastKind::Error) {
+            tracing::warn!("{message}");
+        }
+        // the receiver lives in the app, as long as the panels
+        let _ = self.tx.send((kind, message));
+    }
+
+    pub fn error(&self, message: impl Into<String>) {
+        self.notify(ToastKind::Error, message);
+    }
+
+    pub fn warning(&self, message: impl Into<String>) {
+        self.notify(ToastKind::Warning, message);
+    }
+
+    pub fn success(&self, message: impl Into<String>) {
+        self.notify(ToastKind::Success, message);
+    }
+}
+
+#[derive(Clone)]
+struct Toast {
+    id:      u64,
+    kind:    ToastKind,
+    message: String,
+    shown:   Instant,
+    time:    SystemTime,
+}
+
+impl Toast {
+    fn expired(&self) -> bool {
+        let duration = if matches!(self.kind, ToastKind::Warning | ToastKind::Error) {
+            ERROR_TOAST_DURATION
+        } else {
+            TOAST_DURATION
+        };
+        self.shown.elapsed() >= duration
+    }
+}
+
+/// Toasts currently on screen plus a log of recent notifications.
+pub struct Toasts {
+    active:       Vec<Toast>,
+    log:          VecDeque<Toast>, // most recent first
+    next_id:      u64,
+    pub show_log: bool,
+    notifier:     Notifier,
+    pending:      Receiver<(ToastKind, String)>, // raised since the last frame
+}
+
+impl Default for Toasts {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::channel();
+        Self {
+            active:   Vec::new(),
+            log:      VecDeque::new(),
+            next_id:  0,
+            show_log: false,
+            notifier: Notifier { tx },
+            pending:  rx,
+        }
+    }
+}
+
+impl Toasts {
+    /// A handle for raising notifications from a panel.
+    pub fn notifier(&self) -> Notifier {
+        self.notifier.clone()
+    }
+
+    /// Pick up pending notifications and draw the active toasts in the bottom-right corner.
+    /// Clicking a toast dismisses it. Call once per frame.
+    pub fn show(&mut self, ctx: &egui::Context) {
+        let pending: Vec<_> = self.pending.try_iter().collect();
+        for (kind, message) in pending {
+            self.next_id += 1;
+            let toast = Toast {
+                id: self.next_id,
+                kind,
+                message,
+                shown: Instant::now(),
+                time: SystemTime::now(),
+            };
+            self.log.push_front(toast.clone());
+            self.active.push(toast);
+        }
+        self.log.truncate(MAX_LOG);
+        self.active.retain(|t| !t.expired());
+        if self.active.is_empty() {
+            return;
+        }
+
+        let mut dismissed = None;
+        egui::Area::new(egui::Id::new("toasts"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-16.0, -16.0])
+            .order(egui::Order::Foreground)
+            .show(ctx, |ui| {
+                ui.set_max_width(360.0);
+                for toast in &self.active {
+                    let resp = egui::Frame::new()
+                        .fill(toast.kind.fill(ui.visuals()))
+                        .corner_radius(CornerRadius::same(6))
+                        .inner_margin(Margin::symmetric(12, 8))
+                        .show(ui, |ui| {
+                            ui.label(
+                                RichText::new(format!("{} {}", toast.kind.icon(), toast.message))
+                                    .color(ui.visuals().strong_text_color()),
+                            );
+                        })
+                        .response
+                        .interact(egui::Sense::click())
+                        .on_hover_text("Click to dismiss");
+                    if resp.clicked() {
+                        dismissed = Some(toast.id);
+                    }
+                    ui.add_space(6.0);
+                }
+            });
+        if let Some(id) = dismissed {
+            self.active.retain(|t| t.id != id);
+        }
+        ctx.request_repaint_after(Duration::from_millis(250));
+    }
+
+    /// The window listing recent notifications, toggled by `show_log`.
+    pub fn show_log_window(&mut self, ctx: &egui::Context) {
+        let mut open = self.show_log;
+        let mut clear = false;
+        egui::Window::new("🔔 Notifications")
+            .open(&mut open)
+            .collapsible(false)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                if self.log.is_empty() {
+                    ui.label(
+                        RichText::new("No notifications yet").color(ui.visuals().weak_text_color()),
+                    );
+                    return;
+                }
+                if ui.button("Clear").clicked() {
+                    clear = true;
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        for toast in &self.log {
+                            ui.horizontal_wrapped(|ui| {
+                                ui.label(
+                                    RichText::new(core_util::format_systemtime(
+                                        toast.time, "%H:%M:%S",
+                                    ))
+                                    .monospace()
+                                    .color(ui.visuals().weak_text_color()),
+                                );
+                                ui.label(format!("{} {}", toast.kind.icon(), toast.message));
+                            });
+                        }
+                    });
+            });
+        if clear {
+            self.log.clear();
+        }
+        self.show_log = open;
+    }
+}
@@ -0,0 +1,568 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::Instant,
+};
+
+use eframe::egui::{self, ComboBox, RichText, TextEdit};
+use regex::bytes::RegexBuilder;
+use rfd::FileDialog;
+use serde::{Deserialize, Serialize};
+use waka_core::{config::Config, select::SelectColumns, sort, util as core_util};
+
+use crate::{
+    app::{job::BackgroundJob, toast::Notifier},
+    util,
+};
+
+/// Output format of an export step.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WorkflowExportFormat {
+    #[default]
+    Csv,
+    Json,
+}
+
+/// One step of a pipeline. Column arguments take qsv-style selections, e.g. `1-3,name`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "step", rename_all = "lowercase")]
+pub enum WorkflowStep {
+    /// Keep rows where any selected column matches `pattern` (or none does, with `invert`).
+    Filter {
+        columns:          String,
+        pattern:          String,
+        case_insensitive: bool,
+        invert:           bool,
+    },
+    /// Keep only the selected columns, in selection order.
+    Select { columns: String },
+    /// Sort rows by the selected columns.
+    Sort {
+        columns: String,
+        numeric: bool,
+        reverse: bool,
+    },
+    /// Write the rows produced so far to `path`; later steps keep working on them.
+    Export {
+        path:   String,
+        format: WorkflowExportFormat,
+    },
+}
+
+impl WorkflowStep {
+    fn title(&self) -> &'static str {
+        match self {
+            WorkflowStep::Filter { .. } => "Filter",
+            WorkflowStep::Select { .. } => "Select",
+            WorkflowStep::Sort { .. } => "Sort",
+            WorkflowStep::Export { .. } => "Export",
+        }
+    }
+}
+
+/// An input file and the steps run over it in order, saved and loaded as JSON.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Pipeline {
+    pub input: Option<PathBuf>,
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// A pipeline running on a worker thread. Each finished step adds a line to `lines`, which
+/// the UI moves into its log every frame.
+struct RunningPipeline {
+    job:     BackgroundJob<()>,
+    lines:   Arc<Mutex<Vec<String>>>,
+    cancel:  Arc<AtomicBool>,
+    started: Instant,
+}
+
+/// Builds, saves and runs linear pipelines of filter/select/sort/export steps.
+pub struct WorkflowEditor {
+    pipeline: Pipeline,
+    log:      Vec<String>,
+    status:   Option<String>,
+    running:  Option<RunningPipeline>,
+    notify:   Notifier,
+}
+
+impl WorkflowEditor {
+    pub fn new(notifier: Notifier) -> Self {
+        Self {
+            pipeline: Pipeline::default(),
+            log:      Vec::new(),
+            status:   None,
+            running:  None,
+            notify:   notifier,
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        self.poll_run(ui.ctx());
+
+        ui.vertical(|ui| {
+            ui.heading("Workflow");
+
+            ui.horizontal(|ui| {
+                if ui.button("📂 Load pipeline").clicked() {
+                    self.load_pipeline();
+                }
+                if ui.button("💾 Save pipeline").clicked() {
+                    self.save_pipeline();
+                }
+            });
+            ui.add_space(6.0);
+
+            ui.horizontal(|ui| {
+                ui.label("Input:");
+                let input = self
+                    .pipeline
+                    .input
+                    .as_ref()
+                    .map_or("(none)".to_string(), |p| {
+                        util::display_name(&p.to_string_lossy())
+                    });
+                ui.label(RichText::new(input).strong());
+                if ui.button("Choose…").clicked()
+                    && let Some(path) = FileDialog::new()
+                        .add_filter("CSV", &["csv", "tsv", "tab", "ssv"])
+                        .pick_file()
+                {
+                    self.pipeline.input = Some(path);
+                }
+            });
+            ui.separator();
+
+            self.show_steps(ui);
+
+            ui.horizontal(|ui| {
+                if ui.button("+ Filter").clicked() {
+                    self.pipeline.steps.push(WorkflowStep::Filter {
+                        columns:          String::new(),
+                        pattern:          String::new(),
+                        case_insensitive: false,
+                        invert:           false,
+                    });
+                }
+                if ui.button("+ Select").clicked() {
+                    self.pipeline.steps.push(WorkflowStep::Select {
+                        columns: String::new(),
+                    });
+                }
+                if ui.button("+ Sort").clicked() {
+                    self.pipeline.steps.push(WorkflowStep::Sort {
+                        columns: String::new(),
+                        numeric: false,
+                        reverse: false,
+                    });
+                }
+                if ui.button("+ Export").clicked() {
+                    self.pipeline.steps.push(WorkflowStep::Export {
+                        path:   String::new(),
+                        format: WorkflowExportFormat::Csv,
+                    });
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let has_export = self
+                    .pipeline
+                    .steps
+                    .iter()
+                    .any(|s| matches!(s, WorkflowStep::Export { .. }));
+                let can_run = self.running.is_none() && self.pipeline.input.is_some() && has_export;
+                if ui
+                    .add_enabled(can_run, egui::Button::new("▶ Run pipeline"))
+                    .on_disabled_hover_text("Choose an input file and add at least one export step")
+                    .clicked()
+                {
+                    self.start_run();
+                }
+                if let Some(running) = &self.running {
+                    ui.spinner();
+                    ui.label(format!("{:.1}s", running.started.elapsed().as_secs_f32()));
+                    if ui.button("⏹ Stop").clicked() {
+                        running.cancel.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+            for line in &self.log {
+                ui.label(RichText::new(line.as_str()).size(11.0));
+            }
+            if let Some(msg) = &self.status {
+                ui.label(
+                    RichText::new(msg.as_str())
+                        .size(11.0)
+                        .color(ui.visuals().weak_text_color()),
+                );
+            }
+        });
+    }
+
+    fn show_steps(&mut self, ui: &mut egui::Ui) {
+        if self.pipeline.steps.is_empty() {
+            ui.label(
+                RichText::new("Add steps below to build a pipeline")
+                    .color(ui.visuals().weak_text_color()),
+            );
+            return;
+        }
+
+        let mut move_up = None;
+        let mut remove = None;
+        let nsteps = self.pipeline.steps.len();
+        for (i, step) in self.pipeline.steps.iter_mut().enumerate() {
+            ui.push_id(("workflow_step", i), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(RichText::new(format!("{}. {}", i + 1, step.title())).strong());
+                    if ui.add_enabled(i > 0, egui::Button::new("↑")).clicked() {
+                        move_up = Some(i);
+                    }
+                    if ui
+                        .add_enabled(i + 1 < nsteps, egui::Button::new("↓"))
+                        .clicked()
+                    {
+                        move_up = Some(i + 1);
+                    }
+                    if ui.button("✖").on_hover_text("Remove this step").clicked() {
+                        remove = Some(i);
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.add_space(16.0);
+                    show_step_fields(ui, step);
+                });
+            });
+            ui.add_space(4.0);
+        }
+        if let Some(i) = move_up {
+            self.pipeline.steps.swap(i - 1, i);
+        }
+        if let Some(i) = remove {
+            self.pipeline.steps.remove(i);
+        }
+    }
+
+    fn load_pipeline(&mut self) {
+        let Some(path) = FileDialog::new().add_filter("JSON", &["json"]).pick_file() else {
+            return;
+        };
+        let loaded = std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|bytes| Ok(serde_json::from_slice::<Pipeline>(&bytes)?));
+        self.status = Some(match loaded {
+            Ok(pipeline) => {
+                self.pipeline = pipeline;
+                self.log.clear();
+                format!("Loaded pipeline from {}", path.display())
+            },
+            Err(e) => format!("⚠ Cannot load pipeline: {e}"),
+        });
+    }
+
+    fn save_pipeline(&mut self) {
+        let Some(path) = FileDialog::new()
+            .add_filter("JSON", &["json"])
+            .set_file_name("pipeline.json")
+            .save_file()
+        else {
+            return;
+        };
+        let saved = serde_json::to_vec_pretty(&self.pipeline)
+            .map_err(anyhow::Error::from)
+            .and_then(|json| Ok(std::fs::write(&path, json)?));
+        self.status = Some(match saved {
+            Ok(()) => format!("Saved pipeline to {}", path.display()),
+            Err(e) => format!("⚠ Cannot save pipeline: {e}"),
+        });
+    }
+
+    fn start_run(&mut self) {
+        let Some(input) = self.pipeline.input.clone() else {
+            return;
+        };
+        if self.running.is_some() {
+            return;
+        }
+        let steps = self.pipeline.steps.clone();
+        let cancel = Arc::new(AtomicBool::new(false));
+        let worker_cancel = cancel.clone();
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let worker_lines = lines.clone();
+        let job = BackgroundJob::spawn("pipeline", move || {
+            run_pipeline(&input, &steps, &worker_cancel, |line| {
+                if let Ok(mut lines) = worker_lines.lock() {
+                    lines.push(line);
+                }
+            })
+        });
+        self.log.clear();
+        self.status = None;
+        self.running = Some(RunningPipeline {
+            job,
+            lines,
+            cancel,
+            started: Instant::now(),
+        });
+    }
+
+    fn poll_run(&mut self, ctx: &egui::Context) {
+        let Some(running) = &self.running else {
+            return;
+        };
+        // polled before draining, so the lines of a run that just finished are all in
+        let outcome = running.job.poll(ctx);
+        if let Ok(mut lines) = running.lines.lock() {
+            self.log.append(&mut lines);
+        }
+        let Some(outcome) = outcome else {
+            return;
+        };
+        match outcome {
+            Ok(()) => {
+                let msg = format!(
+                    "Pipeline finished in {:.1}s",
+                    running.started.elapsed().as_secs_f32()
+                );
+                self.notify.success(msg.clone());
+                self.status = Some(msg);
+            },
+            Err(e) => {
+                self.status = Some(format!("⚠ Pipeline failed: {e}"));
+                self.notify.error(format!("Pipeline failed: {e}"));
+            },
+        }
+        self.running = None;
+    }
+}
+
+fn show_step_fields(ui: &mut egui::Ui, step: &mut WorkflowStep) {
+    let columns_hint = "columns, e.g. 1-3,name";
+    match step {
+        WorkflowStep::Filter {
+            columns,
+            pattern,
+            case_insensitive,
+            invert,
+        } => {
+            ui.add(
+                TextEdit::singleline(columns)
+                    .hint_text(columns_hint)
+                    .desired_width(160.0),
+            );
+            ui.add(
+                TextEdit::singleline(pattern)
+                    .hint_text("regex")
+                    .desired_width(200.0),
+            );
+            ui.checkbox(case_insensitive, "Aa")
+                .on_hover_text("Ignore case");
+            ui.checkbox(invert, "Invert")
+                .on_hover_text("Keep the rows that do not match");
+        },
+        WorkflowStep::Select { columns } => {
+            ui.add(
+                TextEdit::singleline(columns)
+                    .hint_text(columns_hint)
+                    .desired_width(240.0),
+            );
+        },
+        WorkflowStep::Sort {
+            columns,
+            numeric,
+            reverse,
+        } => {
+            ui.add(
+                TextEdit::singleline(columns)
+                    .hint_text(columns_hint)
+                    .desired_width(160.0),
+            );
+            ui.checkbox(numeric, "Numeric");
+            ui.checkbox(reverse, "Descending");
+        },
+        WorkflowStep::Export { path, format } => {
+            ComboBox::from_id_salt("export_format")
+                .selected_text(match format {
+                    WorkflowExportFormat::Csv => "CSV",
+                    WorkflowExportFormat::Json => "JSON",
+                })
+                .width(70.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(format, WorkflowExportFormat::Csv, "CSV");
+                    ui.selectable_value(format, WorkflowExportFormat::Json, "JSON");
+                });
+            ui.add(
+                TextEdit::singleline(path)
+                    .hint_text("output file")
+                    .desired_width(280.0),
+            );
+            let ext = match format {
+                WorkflowExportFormat::Csv => "csv",
+                WorkflowExportFormat::Json => "json",
+            };
+            if ui.button("…").clicked()
+                && let Some(dest) = FileDialog::new()
+                    .add_filter(ext.to_uppercase(), &[ext])
+                    .save_file()
+            {
+                *path = dest.to_string_lossy().to_string();
+            }
+        },
+    }
+}
+
+/// Run `steps` over `input`, each step reading the previous step's output from a temporary
+/// directory. `log` gets one line per finished step.
+fn run_pipeline(
+    input: &Path,
+    steps: &[WorkflowStep],
+    cancel: &AtomicBool,
+    mut log: impl FnMut(String),
+) -> anyhow::Result<()> {
+    let workdir = tempfile::Builder::new()
+        .prefix("waka-workflow-")
+        .tempdir()?;
+    let mut current = input.to_path_buf();
+    for (i, step) in steps.iter().enumerate() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(anyhow::anyhow!("cancelled"));
+        }
+        let next = workdir.path().join(format!("step_{i}.csv"));
+        let conf = Config::builder().path(current.to_string_lossy()).build();
+        let line = match step {
+            WorkflowStep::Filter {
+                columns,
+                pattern,
+                case_insensitive,
+                invert,
+            } => {
+                let kept = filter_rows(
+                    &conf,
+                    &next,
+                    columns,
+                    pattern,
+                    *case_insensitive,
+                    *invert,
+                    cancel,
+                )?;
+                current = next;
+                format!("Filter: kept {kept} rows")
+            },
+            WorkflowStep::Select { columns } => {
+                let ncols = select_columns(&conf, &next, columns)?;
+                current = next;
+                format!("Select: kept {ncols} columns")
+            },
+            WorkflowStep::Sort {
+                columns,
+                numeric,
+                reverse,
+            } => {
+                sort::run(sort::Args {
+                    arg_input:        Some(current.to_string_lossy().to_string()),
+                    flag_select:      SelectColumns::parse(columns)?,
+                    flag_numeric:     *numeric,
+                    flag_natural:     false,
+                    flag_reverse:     *reverse,
+                    flag_ignore_case: false,
+                    flag_unique:      false,
+                    flag_random:      false,
+                    flag_seed:        None,
+                    flag_rng:         "standard".to_string(),
+                    flag_jobs:        None,
+                    flag_faster:      false,
+                    flag_output:      Some(next.to_string_lossy().to_string()),
+                    flag_no_headers:  false,
+                    flag_delimiter:   None,
+                    flag_memcheck:    false,
+                })?;
+                current = next;
+                format!("Sort: sorted by {columns}")
+            },
+            WorkflowStep::Export { path, format } => {
+                if path.trim().is_empty() {
+                    return Err(anyhow::anyhow!("step {} has no output file", i + 1));
+                }
+                match format {
+                    WorkflowExportFormat::Csv => {
+                        let mut rdr = conf.reader()?;
+                        let mut wtr = core_util::csv_writer(
+                            Some(Path::new(path)),
+                            None,
+                            csv::QuoteStyle::Necessary,
+                        )?;
+                        conf.write_headers(&mut rdr, &mut wtr)?;
+                        for rec in conf.byte_records(&mut rdr) {
+                            wtr.write_byte_record(&rec?)?;
+                        }
+                        wtr.flush()?;
+                    },
+                    WorkflowExportFormat::Json => {
+                        core_util::write_json_range(&conf, Some(path), 0, usize::MAX, false)?;
+                    },
+                }
+                format!("Export: wrote {path}")
+            },
+        };
+        log(format!("{}. {line}", i + 1));
+    }
+    Ok(())
+}
+
+/// Copy the rows of `conf` where any column of `columns` matches `pattern` to `output`,
+/// returning how many were kept.
+fn filter_rows(
+    conf: &Config,
+    output: &Path,
+    columns: &str,
+    pattern: &str,
+    case_insensitive: bool,
+    invert: bool,
+    cancel: &AtomicBool,
+) -> anyhow::Result<u64> {
+    let re = RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .build()?;
+    let conf = conf.clone().select(SelectColumns::parse(columns)?);
+    let mut rdr = conf.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = conf.selection(&headers)?;
+
+    let mut wtr = core_util::csv_writer(Some(output), None, csv::QuoteStyle::Necessary)?;
+    wtr.write_byte_record(&headers)?;
+    let mut kept = 0_u64;
+    let mut record = csv::ByteRecord::new();
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(anyhow::anyhow!("cancelled"));
+        }
+        if sel.select(&record).any(|field| re.is_match(field)) != invert {
+            wtr.write_byte_record(&record)?;
+            kept += 1;
+        }
+    }
+    wtr.flush()?;
+    Ok(kept)
+}
+
+/// Write only the `columns` of `conf` to `output`, returning how many were selected.
+fn select_columns(conf: &Config, output: &Path, columns: &str) -> anyhow::Result<usize> {
+    let conf = conf.clone().select(SelectColumns::parse(columns)?);
+    let mut rdr = conf.reader()?;
+    let headers = rdr.byte_headers()?.clone();
+    let sel = conf.selection(&headers)?;
+
+    let mut wtr = core_util::csv_writer(Some(output), None, csv::QuoteStyle::Necessary)?;
+    wtr.write_record(sel.select(&headers))?;
+    let mut record = csv::ByteRecord::new();
+    while conf.read_byte_record(&mut rdr, &mut record)? {
+        wtr.write_record(sel.select(&record))?;
+    }
+    wtr.flush()?;
+    Ok(sel.len())
+}
@@ -1 +1,74 @@
+use std::{fs, path::PathBuf};
 
+use eframe::egui::ThemePreference;
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+
+/// Per-user directory where the GUI keeps its small JSON state files.
+pub fn config_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "waka").map(|d| d.config_dir().to_path_buf())
+}
+
+/// Load `file_name` from the config directory, falling back to the default value when the file
+/// is missing or cannot be parsed (state files are a convenience, never a reason to fail).
+pub fn load_json<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let Some(path) = config_dir().map(|d| d.join(file_name)) else {
+        return T::default();
+    };
+    match fs::read(&path) {
+        Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_else(|e| {
+            tracing::warn!("ignoring unreadable state file {}: {e}", path.display());
+            T::default()
+        }),
+        Err(_) => T::default(),
+    }
+}
+
+/// Save `value` as pretty JSON to `file_name` in the config directory.
+pub fn save_json<T: Serialize>(file_name: &str, value: &T) -> anyhow::Result<()> {
+    let dir = config_dir().ok_or_else(|| anyhow::anyhow!("no config directory available"))?;
+    fs::create_dir_all(&dir)?;
+    let json = serde_json::to_vec_pretty(value)?;
+    fs::write(dir.join(file_name), json)?;
+    Ok(())
+}
+
+/// State file holding the application settings.
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Application-wide settings, persisted across sessions.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Worker threads for parallel operations; 0 uses every available core.
+    pub jobs:                usize,
+    /// Dark, light, or following the system theme (the default).
+    pub theme:               ThemePreference,
+    /// Percent of memory non-streaming operations leave free (10-90); None defers to
+    /// QSV_FREEMEMORY_HEADROOM_PCT or the core default.
+    pub memory_headroom_pct: Option<u8>,
+    /// Read/write buffer size in KiB for CSV files; None keeps the core defaults. Larger
+    /// buffers mean fewer round trips for evidence on SMB/NFS shares.
+    pub io_buffer_kib:       Option<u32>,
+}
+
+impl Settings {
+    pub fn load() -> Self {
+        load_json(SETTINGS_FILE)
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        save_json(SETTINGS_FILE, self)
+    }
+
+    /// Push the memory headroom override, if any, to the core crate.
+    pub fn apply_memory_headroom(&self) {
+        waka_core::config::set_memory_headroom_pct(self.memory_headroom_pct.unwrap_or(0));
+    }
+
+    /// Push the I/O buffer size, if any, to the core crate; it applies to files opened from
+    /// then on.
+    pub fn apply_io_buffer(&self) {
+        let bytes = self.io_buffer_kib.map_or(0, |kib| kib as usize * 1024);
+        waka_core::config::set_default_buffer_capacities(bytes, bytes);
+    }
+}
@@ -1,7 +1,4 @@
-use eframe::{
-    NativeOptions,
-    egui::{ViewportBuilder, Visuals},
-};
+use eframe::{NativeOptions, egui::ViewportBuilder};
 
 mod app;
 mod config;
@@ -15,6 +12,12 @@ const APP_TITLE: &str = "Waka Forensics Suite";
 const APP_ICON: &[u8] = include_bytes!("../../../assets/logo.png");
 
 fn main() -> eframe::Result<()> {
+    // Size rayon's global pool before anything parallel runs; it cannot be resized later
+    let settings = config::Settings::load();
+    let jobs = waka_core::util::njobs(Some(settings.jobs));
+    settings.apply_memory_headroom();
+    settings.apply_io_buffer();
+
     let native_options = NativeOptions {
         viewport: ViewportBuilder::default()
             .with_title(APP_TITLE)
@@ -29,10 +32,9 @@ fn main() -> eframe::Result<()> {
         APP_TITLE,
         native_options,
         Box::new(|cc| {
-            // Configure egui style here if needed
-            cc.egui_ctx.set_visuals(Visuals::dark());
+            cc.egui_ctx.set_theme(settings.theme);
 
-            Ok(Box::new(app::WakaApp::new()))
+            Ok(Box::new(app::WakaApp::new(settings, jobs)))
         }),
     )
 }
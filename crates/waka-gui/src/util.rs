@@ -2,6 +2,7 @@ use std::{
     collections::HashSet,
     io::{self, BufRead, Write},
     path::{Path, PathBuf},
+    sync::atomic::{AtomicBool, AtomicU64, Ordering},
     time::Instant,
 };
 
@@ -9,6 +10,7 @@ use anyhow::anyhow;
 use eframe::egui::{self, Ui};
 use epaint::{Color32, Shape, Stroke};
 use ext_sort::{ExternalSorter, ExternalSorterBuilder, LimitedBufferBuilder};
+use mft::{MftParser, csv::FlatMftEntryWithName};
 use num_cpus;
 use polars_sql::SQLContext;
 use waka_core::{
@@ -82,6 +84,111 @@ pub fn display_name(path: &str) -> String {
         })
 }
 
+/// Render rows as a GitHub-flavored Markdown table. Pipes are escaped and line breaks become
+/// `<br>` so every row stays on one line. Columns whose non-empty cells are all numbers are
+/// right-aligned.
+pub fn markdown_table(headers: &[&str], rows: &[Vec<&str>]) -> String {
+    fn cell(s: &str) -> String {
+        s.replace('|', "\\|")
+            .replace("\r\n", "<br>")
+            .replace(['\n', '\r'], "<br>")
+    }
+
+    let numeric: Vec<bool> = (0..headers.len())
+        .map(|ci| {
+            let mut values = rows
+                .iter()
+                .filter_map(|r| r.get(ci).map(|v| v.trim()))
+                .filter(|v| !v.is_empty())
+                .peekable();
+            values.peek().is_some() && values.all(|v| v.parse::<f64>().is_ok())
+        })
+        .collect();
+
+    let mut out = String::new();
+    out.push('|');
+    for h in headers {
+        out.push_str(&format!(" {} |", cell(h)));
+    }
+    out.push_str("\n|");
+    for &num in &numeric {
+        out.push_str(if num { " ---: |" } else { " --- |" });
+    }
+    out.push('\n');
+    for row in rows {
+        out.push('|');
+        for ci in 0..headers.len() {
+            out.push_str(&format!(" {} |", cell(row.get(ci).copied().unwrap_or(""))));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// What a blank cell shows when blanks are marked, with its hover text: `∅` for an empty
+/// cell, its whitespace made visible for a whitespace-only one. None for any other cell.
+pub fn blank_cell_marker(cell: &str) -> Option<(String, &'static str)> {
+    if cell.is_empty() {
+        return Some(("∅".to_string(), "Empty cell"));
+    }
+    cell.chars()
+        .all(|c| c.is_whitespace() || c == '\u{200B}')
+        .then(|| {
+            (
+                waka_core::util::visualize_whitespace(cell),
+                "Whitespace only",
+            )
+        })
+}
+
+/// Something a table cell points at that can be opened from the UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CellLink {
+    Url(String),
+    Path(PathBuf),
+}
+
+/// Recognize `http(s)://` and `file://` URLs and absolute Windows/Unix paths.
+pub fn detect_cell_link(cell: &str) -> Option<CellLink> {
+    let s = cell.trim();
+    let lower = s.get(..8).unwrap_or(s).to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") || lower.starts_with("file://")
+    {
+        return (!s.contains(char::is_whitespace)).then(|| CellLink::Url(s.to_string()));
+    }
+    let b = s.as_bytes();
+    let windows_drive = b.len() > 3
+        && b[0].is_ascii_alphabetic()
+        && b[1] == b':'
+        && (b[2] == b'\\' || b[2] == b'/');
+    let unc = s.starts_with("\\\\") && s.len() > 2;
+    let unix = b.len() > 1 && b[0] == b'/' && b[1] != b'/' && !b[1].is_ascii_whitespace();
+    (windows_drive || unc || unix).then(|| CellLink::Path(PathBuf::from(s)))
+}
+
+/// Show `path` in the OS file manager, selecting it where the platform supports that.
+/// Falls back to opening the closest existing parent directory.
+pub fn reveal_in_file_manager(path: &Path) -> io::Result<()> {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut c = std::process::Command::new("explorer");
+        c.arg(format!("/select,{}", path.display()));
+        c
+    } else if cfg!(target_os = "macos") {
+        let mut c = std::process::Command::new("open");
+        c.arg("-R").arg(path);
+        c
+    } else {
+        let dir = path
+            .ancestors()
+            .find(|p| p.is_dir())
+            .unwrap_or_else(|| Path::new("/"));
+        let mut c = std::process::Command::new("xdg-open");
+        c.arg(dir);
+        c
+    };
+    cmd.spawn().map(|_| ())
+}
+
 pub fn norm<'a>(s: &'a str, casei: bool) -> std::borrow::Cow<'a, str> {
     if casei {
         std::borrow::Cow::Owned(s.to_ascii_lowercase())
@@ -245,12 +352,10 @@ pub fn sort_triangle_button(ui: &mut Ui, up: bool, active: bool) -> egui::Respon
     };
     let fill = if active {
         Color32::from_rgb(0, 200, 120)
-    } else if response.hovered() {
-        Color32::from_gray(210)
     } else {
-        Color32::from_gray(170)
+        ui.style().interact(&response).fg_stroke.color
     };
-    let stroke = Stroke::new(0.9, Color32::from_gray(60));
+    let stroke = Stroke::new(0.9, ui.visuals().widgets.noninteractive.bg_stroke.color);
     ui.painter()
         .add(Shape::convex_polygon(vec![p1, p2, p3], fill, stroke));
     response
@@ -263,12 +368,10 @@ pub fn filter_icon_button(ui: &mut Ui, active: bool) -> egui::Response {
 
     let fill = if active {
         Color32::from_rgb(0, 200, 120)
-    } else if response.hovered() {
-        Color32::from_gray(210)
     } else {
-        Color32::from_gray(170)
+        ui.style().interact(&response).fg_stroke.color
     };
-    let stroke = Stroke::new(1.0, Color32::from_gray(60));
+    let stroke = Stroke::new(1.0, ui.visuals().widgets.noninteractive.bg_stroke.color);
 
     // Top trapezoid
     let top_h = (r.height() * 0.55).clamp(6.0, 9.0);
@@ -302,9 +405,9 @@ pub fn close_button(ui: &mut Ui, emphasize: bool) -> egui::Response {
     let (rect, response) = ui.allocate_exact_size(desired, egui::Sense::click());
 
     let color = if emphasize {
-        Color32::from_white_alpha(230)
+        ui.visuals().strong_text_color()
     } else {
-        Color32::from_white_alpha(110)
+        ui.visuals().weak_text_color()
     };
     let stroke = Stroke::new(1.6, color);
 
@@ -395,3 +498,87 @@ fn parse_output_mode(s: &str) -> OutputMode {
 pub fn run_sqlp(lib_args: SqlpLibArgs) -> anyhow::Result<SqlpLibResult> {
     todo!()
 }
+
+// ---- $MFT triage: parse a Master File Table into a CSV the table view can open ----
+
+/// Does `path` start with an MFT record signature (`FILE`)?
+pub fn is_mft_file(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    std::fs::File::open(path)
+        .and_then(|mut f| io::Read::read_exact(&mut f, &mut magic))
+        .is_ok_and(|()| &magic == b"FILE")
+}
+
+/// The subset of an MFT entry shown in the table view.
+#[derive(serde::Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct MftRow {
+    entry_id:                    u64,
+    signature:                   String,
+    sequence:                    u16,
+    full_path:                   PathBuf,
+    is_a_directory:              bool,
+    is_deleted:                  bool,
+    has_alternate_data_streams:  bool,
+    standard_info_attributes:    Option<String>,
+    file_size:                   u64,
+    logical_size:                Option<u64>,
+    physical_size:               Option<u64>,
+    standard_info_created:       Option<String>,
+    standard_info_last_modified: Option<String>,
+    standard_info_last_access:   Option<String>,
+    file_name_created:           Option<String>,
+    file_name_last_modified:     Option<String>,
+    file_name_last_access:       Option<String>,
+}
+
+/// Parse the `$MFT` at `src` and write one CSV row per entry to `dest`.
+/// `entries_done` is bumped per entry so the UI can show progress; setting `cancel`
+/// stops the parse. Returns the number of rows written.
+pub fn mft_to_csv(
+    src: &Path,
+    dest: &Path,
+    entries_done: &AtomicU64,
+    cancel: &AtomicBool,
+) -> anyhow::Result<u64> {
+    let mut parser = MftParser::from_path(src)?;
+    let mut wtr = csv::Writer::from_path(dest)?;
+    let mut written = 0u64;
+    for i in 0..parser.get_entry_count() {
+        if cancel.load(Ordering::Relaxed) {
+            return Err(anyhow!("MFT parsing cancelled"));
+        }
+        entries_done.fetch_add(1, Ordering::Relaxed);
+        let entry = match parser.get_entry(i) {
+            Ok(entry) if entry.header.is_unused() => continue,
+            Ok(entry) => entry,
+            Err(e) => {
+                tracing::debug!("skipping MFT entry {i}: {e}");
+                continue;
+            },
+        };
+        let flat = FlatMftEntryWithName::from_entry(&entry, &mut parser);
+        wtr.serialize(MftRow {
+            entry_id:                    flat.entry_id,
+            signature:                   flat.signature,
+            sequence:                    flat.sequence,
+            full_path:                   flat.full_path,
+            is_a_directory:              flat.is_a_directory,
+            is_deleted:                  flat.is_deleted,
+            has_alternate_data_streams:  flat.has_alternate_data_streams,
+            standard_info_attributes:    flat.standard_info_attributes,
+            file_size:                   flat.file_size,
+            logical_size:                flat.logical_size,
+            physical_size:               flat.physical_size,
+            standard_info_created:       flat.standard_info_created.map(|t| t.to_rfc3339()),
+            standard_info_last_modified: flat.standard_info_last_modified.map(|t| t.to_rfc3339()),
+            standard_info_last_access:   flat.standard_info_last_access.map(|t| t.to_rfc3339()),
+            file_name_created:           flat.file_name_created.map(|t| t.to_rfc3339()),
+            file_name_last_modified:     flat.file_name_last_modified.map(|t| t.to_rfc3339()),
+            file_name_last_access:       flat.file_name_last_access.map(|t| t.to_rfc3339()),
+        })?;
+        written += 1;
+    }
+    wtr.flush()?;
+    Ok(written)
+}